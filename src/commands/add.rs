@@ -0,0 +1,209 @@
+use std::path::{Component, Path};
+
+use colored::Colorize;
+use itertools::Itertools;
+use sea_orm::{prelude::*, ActiveValue, TransactionTrait};
+use tracing::info;
+
+use crate::commands::tags::TagDefaults;
+use crate::commands::{JournalAction, SourceType};
+use crate::entities::{prelude::*, *};
+
+/// A mod to register without prompts
+#[derive(clap::Args, Debug)]
+pub struct AddOptions {
+    /// Mod folder, relative to its root
+    #[arg(short, long)]
+    pub dir: String,
+
+    /// Mod name. Defaults to the folder's name.
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Source URL
+    #[arg(short, long)]
+    pub url: Option<String>,
+
+    /// Tags to give the mod
+    #[arg(short, long, value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// Version to set. Defaults to today's date.
+    #[arg(short = 'v', long)]
+    pub mod_version: Option<String>,
+
+    /// Root holding the folder, by name. Defaults to the main Mods folder.
+    #[arg(short, long)]
+    pub root: Option<String>,
+
+    /// Protect the mod from accidental removal. Script frameworks are always protected.
+    #[arg(short, long)]
+    pub protected: bool,
+}
+
+fn invalid_input(message: String) -> crate::Result<()> {
+    eprintln!("{}", message);
+    Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into())
+}
+
+/// Registers one mod folder from the command line, hashing its files, without asking
+/// anything, so folders can be added from scripts
+pub async fn add(options: AddOptions) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let roots = super::util::ModRoots::load(&db).await?;
+
+    let root_id = match options.root.as_deref() {
+        Some(root_name) => match roots
+            .iter()
+            .find(|(_, name, _)| name.eq_ignore_ascii_case(root_name))
+        {
+            Some((root_id, _, _)) => root_id,
+            None => return invalid_input(format!("No root named {} found!", root_name)),
+        },
+        None => None,
+    };
+    let directory = options.dir.trim().trim_end_matches(['/', '\\']).to_string();
+    let path = Path::new(&directory);
+    if directory.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return invalid_input(format!(
+            "{} is not a folder inside the root; give it relative to the root.",
+            options.dir
+        ));
+    }
+    let mod_dir = roots.dir(root_id)?.join(path);
+    if !mod_dir.is_dir() {
+        eprintln!("{} is not a folder!", mod_dir.display());
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    }
+    if let Some(existing) = SimsMod::find()
+        .filter(sims_mod::Column::Directory.eq(&directory))
+        .one(&db)
+        .await?
+    {
+        return invalid_input(format!(
+            "{} is already registered as {} (ID {}){}.",
+            directory,
+            existing.name,
+            existing.id,
+            if existing.deleted_at.is_some() {
+                ", which was deleted; bring it back with restore-entry"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    let name = options
+        .name
+        .map(|name| name.trim().to_string())
+        .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    if name.is_empty() {
+        return invalid_input("Give the mod a name with --name.".to_string());
+    }
+    if SimsMod::find()
+        .filter(sims_mod::Column::Name.eq(&name))
+        .one(&db)
+        .await?
+        .is_some()
+    {
+        return invalid_input(format!(
+            "A mod named {} already exists; pick another with --name.",
+            name
+        ));
+    }
+    if let Some(url) = &options.url {
+        if url::Url::parse(url).is_err() {
+            return invalid_input(format!("{} is not a valid URL!", url));
+        }
+    }
+
+    let tags: Vec<String> = options
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .unique()
+        .collect();
+    let tags = super::tag_typos::check_tags(&db, tags).await?;
+    let defaults = TagDefaults::for_tags(&db, &tags).await?;
+    defaults.print_preview(&tags);
+    let source_url = options
+        .url
+        .or_else(|| defaults.source_url.clone())
+        .unwrap_or_default();
+
+    info!("Hashing {}", mod_dir.display());
+    let (files, mut hashes) = super::util::get_file_hashes_with(
+        roots.dir(root_id)?,
+        path,
+        &crate::config::get().tag_extensions(&tags),
+    )?;
+    if files.is_empty() {
+        eprintln!("{} has no mod files; adding it anyway.", directory);
+    }
+    // Files another mod already tracks are left untracked, as `adopt` does
+    let file_count = hashes.len();
+    super::collision::resolve_collisions(&db, &roots, &name, None, &mod_dir, &mut hashes, false)
+        .await?;
+    if hashes.len() < file_count {
+        println!(
+            "{} files duplicate another mod's; resolve them with {}.",
+            file_count - hashes.len(),
+            "scan --verify --fix".bold()
+        );
+    }
+
+    let protected = options.protected || super::util::is_framework_mod(path, &files);
+
+    let now = chrono::Utc::now();
+    let mut new_mod = sims_mod::ActiveModel {
+        name: ActiveValue::Set(name),
+        directory: ActiveValue::Set(directory),
+        root_id: ActiveValue::Set(root_id),
+        source_type: ActiveValue::Set(SourceType::name_for_url(&source_url).to_string()),
+        source_url: ActiveValue::Set(source_url),
+        version: ActiveValue::Set(
+            options
+                .mod_version
+                .unwrap_or_else(|| crate::datetime::today().format("%d%m%y").to_string()),
+        ),
+        updated: ActiveValue::Set(now),
+        added_at: ActiveValue::Set(Some(now)),
+        protected: ActiveValue::Set(protected),
+        ..Default::default()
+    };
+    TagDefaults {
+        source_url: None,
+        ..defaults
+    }
+    .apply(&mut new_mod);
+    super::composition::set_file_counts(&mut new_mod, hashes.keys());
+    let tracked = hashes.len();
+    let sims_mod = db
+        .transaction::<_, sims_mod::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let new_mod = SimsMod::insert(new_mod).exec_with_returning(txn).await?;
+                super::log::record(txn, JournalAction::Added, &new_mod).await?;
+                for tag in tags.iter() {
+                    let relation = mod_tag_relation::ActiveModel {
+                        mod_id: ActiveValue::Set(new_mod.id),
+                        tag_id: ActiveValue::Set(
+                            super::util::get_or_create_tag_id(txn, tag).await?,
+                        ),
+                    };
+                    ModTagRelation::insert(relation).exec(txn).await?;
+                }
+                super::hash_changes::insert_hashes(txn, new_mod.id, hashes).await?;
+                Ok(new_mod)
+            })
+        })
+        .await?;
+    println!(
+        "Added {} (ID {}) with {} files.",
+        sims_mod.name.bold().green(),
+        sims_mod.id,
+        tracked
+    );
+    Ok(())
+}
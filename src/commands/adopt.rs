@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, TransactionTrait};
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+use tracing_unwrap::OptionExt;
+
+use crate::commands::{JournalAction, SourceType};
+use crate::entities::{prelude::*, *};
+
+/// Width of the progress bar, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Tag given to mods with script files
+const SCRIPT_TAG: &str = "Script";
+/// Tag given to large frameworks that other mods depend on
+const FRAMEWORK_TAG: &str = "Framework";
+
+/// Redraws a progress bar of the folders hashed so far, when showing on a terminal
+fn show_progress(done: usize, total: usize, current: &str) {
+    let mut stderr = std::io::stderr();
+    if !stderr.is_terminal() {
+        return;
+    }
+    let filled = BAR_WIDTH * done / total.max(1);
+    let current: String = current.chars().take(40).collect();
+    let _ = write!(
+        stderr,
+        "\x1b[2K\r[{}{}] {}/{} {}",
+        "#".repeat(filled),
+        " ".repeat(BAR_WIDTH - filled),
+        done,
+        total,
+        current
+    );
+    let _ = stderr.flush();
+}
+
+fn clear_progress() {
+    let mut stderr = std::io::stderr();
+    if stderr.is_terminal() {
+        let _ = write!(stderr, "\x1b[2K\r");
+        let _ = stderr.flush();
+    }
+}
+
+/// Tags an adopted mod gets from its folder and files
+fn auto_tags(mod_path: &Path, files: &HashSet<PathBuf>) -> Vec<&'static str> {
+    let mut tags = Vec::new();
+    if files.iter().any(|file| {
+        file.extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ts4script"))
+    }) {
+        tags.push(SCRIPT_TAG);
+    }
+    if super::util::is_framework_mod(mod_path, files) {
+        tags.push(FRAMEWORK_TAG);
+    }
+    tags
+}
+
+/// A name for a mod from its folder name, numbered if another mod has it already
+fn unique_name(path: &Path, used_names: &HashSet<String>) -> String {
+    let base = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let mut name = base.clone();
+    let mut number = 2;
+    while used_names.contains(&name) {
+        name = format!("{} {}", base, number);
+        number += 1;
+    }
+    name
+}
+
+/// An adopted mod and what to look at by hand, besides its source URL and version
+struct Adopted {
+    sims_mod: sims_mod::Model,
+    tags: Vec<&'static str>,
+    file_count: usize,
+    issues: Vec<String>,
+}
+
+/// Registers a folder as a mod without asking anything
+async fn adopt_folder(
+    db: &DatabaseConnection,
+    roots: &super::util::ModRoots,
+    root_id: Option<i32>,
+    path: &Path,
+    name: String,
+) -> crate::Result<Adopted> {
+    let (files, mut hashes) = super::util::get_file_hashes(roots.dir(root_id)?, path)?;
+    let file_count = hashes.len();
+    let mut issues = Vec::new();
+    if files.is_empty() {
+        issues.push("has no mod files".to_string());
+    }
+    let folder_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+    if folder_name.as_deref() != Some(name.as_str()) {
+        issues.push("name is numbered, another mod had the folder's name".to_string());
+    }
+    // Files another mod already tracks are left untracked
+    super::collision::resolve_collisions(
+        db,
+        roots,
+        &name,
+        None,
+        &roots.dir(root_id)?.join(path),
+        &mut hashes,
+        false,
+    )
+    .await?;
+    if hashes.len() < file_count {
+        issues.push(format!(
+            "{} files duplicate another mod's, resolve them with scan --verify --fix",
+            file_count - hashes.len()
+        ));
+    }
+    let tags = auto_tags(path, &files);
+    let protected = tags.contains(&FRAMEWORK_TAG);
+
+    let now = chrono::Utc::now();
+    let directory = path
+        .to_str()
+        .expect_or_log("Failed to convert path to UTF-8")
+        .to_string();
+    let mut new_mod = sims_mod::ActiveModel {
+        name: ActiveValue::Set(name),
+        directory: ActiveValue::Set(directory),
+        root_id: ActiveValue::Set(root_id),
+        source_type: ActiveValue::Set(SourceType::UNKNOWN.to_string()),
+        source_url: ActiveValue::Set(String::new()),
+        version: ActiveValue::Set(crate::datetime::today().format("%d%m%y").to_string()),
+        updated: ActiveValue::Set(now),
+        added_at: ActiveValue::Set(Some(now)),
+        protected: ActiveValue::Set(protected),
+        ..Default::default()
+    };
+    super::composition::set_file_counts(&mut new_mod, hashes.keys());
+    let tags_to_add = tags.clone();
+    let sims_mod = db
+        .transaction::<_, sims_mod::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let new_mod = SimsMod::insert(new_mod).exec_with_returning(txn).await?;
+                super::log::record(txn, JournalAction::Added, &new_mod).await?;
+                for tag in tags_to_add.into_iter() {
+                    let tag_id = super::util::get_or_create_tag_id(txn, tag).await?;
+                    let relation = mod_tag_relation::ActiveModel {
+                        mod_id: ActiveValue::Set(new_mod.id),
+                        tag_id: ActiveValue::Set(tag_id),
+                    };
+                    ModTagRelation::insert(relation).exec(txn).await?;
+                }
+                super::hash_changes::store_hashes(txn, new_mod.id, &hashes).await?;
+                Ok(new_mod)
+            })
+        })
+        .await?;
+    Ok(Adopted {
+        sims_mod,
+        tags,
+        file_count,
+        issues,
+    })
+}
+
+/// Renders what adopted mods still need, and the folders that failed, as JSON
+fn render_report(adopted: &[Adopted], failed: &[(PathBuf, String)]) -> crate::Result<String> {
+    let value = json!({
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "mods": adopted.iter().map(|adopted| json!({
+            "id": adopted.sims_mod.id,
+            "name": adopted.sims_mod.name,
+            "directory": adopted.sims_mod.directory,
+            "files": adopted.file_count,
+            "tags": adopted.tags,
+            "protected": adopted.sims_mod.protected,
+            "missing": ["source_url", "version"],
+            "issues": adopted.issues,
+        })).collect::<Vec<Value>>(),
+        "failed": failed.iter().map(|(path, error)| json!({
+            "directory": path.to_string_lossy(),
+            "error": error,
+        })).collect::<Vec<Value>>(),
+    });
+    Ok(serde_json::to_string_pretty(&value)? + "\n")
+}
+
+/// Registers every unregistered mod folder in one go, named after its folder, for
+/// first-time setup over an existing Mods folder. Metadata that can't be guessed
+/// is listed at the end, and optionally written to a JSON report.
+pub async fn adopt(report: Option<PathBuf>) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let roots = super::util::ModRoots::load(&db).await?;
+    let mods = SimsMod::find().all(&db).await?;
+    let mut used_names: HashSet<String> = mods.iter().map(|m| m.name.clone()).collect();
+    let used_directories: HashSet<&str> = mods.iter().map(|m| m.directory.as_str()).collect();
+
+    let canonical_roots: Vec<(Option<i32>, PathBuf)> = roots
+        .iter()
+        .filter_map(|(id, _, path)| Some((id, path.canonicalize().ok()?)))
+        .collect();
+    let mut folders = Vec::new();
+    for (root_id, root_name, root_dir) in roots.iter() {
+        if !root_dir.is_dir() {
+            warn!("Skipping unavailable mod root {}", root_name);
+            continue;
+        }
+        let known_dirs: Vec<PathBuf> = mods
+            .iter()
+            .filter(|sims_mod| sims_mod.root_id == root_id)
+            .map(|sims_mod| PathBuf::from(&sims_mod.directory))
+            .collect();
+        let other_roots: Vec<PathBuf> = canonical_roots
+            .iter()
+            .filter(|(id, _)| *id != root_id)
+            .map(|(_, path)| path.clone())
+            .collect();
+        let mut found: Vec<PathBuf> =
+            crate::scanner::find_mod_directories(root_dir, &known_dirs, &other_roots)?
+                .into_iter()
+                .filter(|dir| !used_directories.contains(dir.to_string_lossy().as_ref()))
+                .collect();
+        found.sort();
+        folders.extend(found.into_iter().map(|dir| (root_id, dir)));
+    }
+    if folders.is_empty() {
+        println!("Every mod folder is registered already.");
+        return Ok(());
+    }
+    println!(
+        "Adopting {} unregistered folders.",
+        folders.len().to_string().bold()
+    );
+
+    let mut adopted = Vec::new();
+    let mut failed = Vec::new();
+    for (done, (root_id, path)) in folders.iter().enumerate() {
+        show_progress(done, folders.len(), &path.to_string_lossy());
+        let name = unique_name(path, &used_names);
+        debug!("Adopting {} as {}", path.display(), name);
+        match adopt_folder(&db, &roots, *root_id, path, name.clone()).await {
+            Ok(mod_adopted) => {
+                used_names.insert(name);
+                adopted.push(mod_adopted);
+            }
+            Err(e) => {
+                warn!("Failed to adopt {}: {}", path.display(), e);
+                failed.push((path.clone(), e.to_string()));
+            }
+        }
+    }
+    clear_progress();
+    info!("Adopted {} mods", adopted.len());
+
+    println!(
+        "Adopted {} mods, {} of them tagged automatically.",
+        adopted.len().to_string().green().bold(),
+        adopted.iter().filter(|a| !a.tags.is_empty()).count()
+    );
+    for adopted in adopted.iter().filter(|a| !a.issues.is_empty()) {
+        println!(
+            "  {} ({}): {}",
+            adopted.sims_mod.name.bold(),
+            adopted.sims_mod.id,
+            adopted.issues.join("; ")
+        );
+    }
+    for (path, error) in failed.iter() {
+        println!("  {} {}: {}", "Failed".red().bold(), path.display(), error);
+    }
+    if !adopted.is_empty() {
+        println!(
+            "Adopted mods have no source URL, and their version is today's date. Fill them in with {} or the \"No source URL\" view of {}.",
+            "edit --mod-id N --source-url URL --mod-version V".bold(),
+            "edit --interactive".bold()
+        );
+    }
+    if let Some(report) = report {
+        crate::fileio::write(&report, render_report(&adopted, &failed)?)?;
+        println!(
+            "Wrote the report to {}",
+            report.display().to_string().blue()
+        );
+    }
+    Ok(())
+}
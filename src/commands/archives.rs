@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
+use tracing::{debug, info, warn};
+
+use crate::entities::{prelude::*, *};
+use crate::style::{self, Status};
+
+/// Archive formats the game ignores. Only zips can be extracted.
+const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "rar", "7z"];
+
+/// Files worth extracting from an archive
+const MOD_EXTENSIONS: &[&str] = &["package", "ts4script"];
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .map(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+        .unwrap_or(false)
+}
+
+/// Archives directly inside a folder
+fn find_archives(dir: &Path) -> crate::Result<Vec<PathBuf>> {
+    let mut archives = Vec::new();
+    for entry in crate::fileio::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && has_extension(&path, ARCHIVE_EXTENSIONS) {
+            archives.push(path);
+        }
+    }
+    archives.sort();
+    Ok(archives)
+}
+
+/// The mod an archive with this hash was last installed for
+pub async fn find_installed_archive<C>(
+    db: &C,
+    archive_hash: &str,
+) -> Result<Option<sims_mod::Model>, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    super::util::active_mods()
+        .filter(sims_mod::Column::ArchiveHash.eq(archive_hash))
+        .one(db)
+        .await
+}
+
+/// Remembers the archive a mod was installed from, so installing it again is noticed
+pub async fn record_installed_archive<C>(
+    db: &C,
+    sims_mod: &sims_mod::Model,
+    archive_hash: &str,
+) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    debug!("Recording archive {} for {}", archive_hash, sims_mod.name);
+    let mut model = sims_mod.clone().into_active_model();
+    model.archive_hash = ActiveValue::Set(Some(archive_hash.to_string()));
+    model.archive_installed_at = ActiveValue::Set(Some(chrono::Utc::now()));
+    SimsMod::update(model).exec(db).await?;
+    Ok(())
+}
+
+/// Says that an archive was already installed, and for which mod and when
+pub fn report_installed_archive(name: &str, sims_mod: &sims_mod::Model) {
+    println!(
+        "You already installed this exact archive ({}) for {}{}.",
+        name.bold(),
+        sims_mod.name.bold(),
+        sims_mod
+            .archive_installed_at
+            .map(|installed_at| format!(" on {}", crate::datetime::format_date(&installed_at)))
+            .unwrap_or_default()
+    );
+}
+
+/// Extracts the mod files of a zip directly into a folder, dropping the archive's
+/// own folders so scripts end up where the game loads them. Returns the extracted names.
+fn extract_flat(archive_path: &Path, target_dir: &Path) -> crate::Result<Vec<PathBuf>> {
+    let data = crate::fileio::read(archive_path)?;
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+    let mut extracted = Vec::new();
+    let mut seen = HashSet::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        // Only the file name is kept, so entries cannot point outside the target folder
+        let Some(name) = Path::new(file.name()).file_name().map(PathBuf::from) else {
+            continue;
+        };
+        if !has_extension(&name, MOD_EXTENSIONS) {
+            debug!("Skipping {} in {}", file.name(), archive_path.display());
+            continue;
+        }
+        if !seen.insert(name.to_string_lossy().to_lowercase()) || target_dir.join(&name).exists() {
+            warn!(
+                "Skipping {}, a file with that name already exists",
+                file.name()
+            );
+            eprintln!(
+                "Skipped {}: a file named {} is already there.",
+                file.name(),
+                name.display()
+            );
+            continue;
+        }
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        crate::fileio::write(target_dir.join(&name), contents)?;
+        extracted.push(name);
+    }
+    Ok(extracted)
+}
+
+/// Offers to extract one archive, moving it to the trash afterwards. Extracting into
+/// a mod's folder records the archive for that mod.
+async fn offer_extract(
+    db: &DatabaseConnection,
+    archive: &Path,
+    target_dir: &Path,
+    target_mod: Option<&sims_mod::Model>,
+) -> crate::Result<()> {
+    let archive_hash = crate::hasher::hash_file(archive)?;
+    if let Some(installed) = find_installed_archive(db, &archive_hash).await? {
+        report_installed_archive(
+            &archive.file_name().unwrap_or_default().to_string_lossy(),
+            &installed,
+        );
+        if Confirm::new("Move the archive to the trash instead of extracting it again?")
+            .with_default(true)
+            .prompt()?
+        {
+            super::trashed::remove_path(db, archive, None, false).await?;
+        }
+        return Ok(());
+    }
+    if !has_extension(archive, &["zip"]) {
+        println!(
+            "Extract {} by hand; only zip archives can be extracted.",
+            archive.display()
+        );
+        return Ok(());
+    }
+    if !Confirm::new(
+        format!(
+            "Extract the mod files of {} into {}?",
+            archive.display(),
+            target_dir.display()
+        )
+        .as_str(),
+    )
+    .with_default(true)
+    .prompt()?
+    {
+        return Ok(());
+    }
+    if !target_dir.is_dir() {
+        crate::fileio::create_dir_all(target_dir)?;
+    }
+    let extracted = extract_flat(archive, target_dir)?;
+    if extracted.is_empty() {
+        println!("{} contains no mod files, keeping it.", archive.display());
+        return Ok(());
+    }
+    info!(
+        "Extracted {} files from {}",
+        extracted.len(),
+        archive.display()
+    );
+    if let Some(target_mod) = target_mod {
+        record_installed_archive(db, target_mod, &archive_hash).await?;
+    }
+    super::trashed::remove_path(db, archive, None, false).await?;
+    println!(
+        "Extracted {} files and moved {} to the trash.",
+        extracted.len().to_string().green().bold(),
+        archive.display()
+    );
+    Ok(())
+}
+
+/// Reports archives dropped into mod roots or mod folders, where the game ignores them.
+/// With `fix`, zips are extracted in place: an archive in a mod root becomes a new mod
+/// folder named after it, and one inside a mod folder adds its files to that mod.
+/// Runs before the scan lists folders, so extracted mods are registered by the same scan.
+pub async fn check_archives(db: &DatabaseConnection, fix: bool) -> crate::Result<()> {
+    let roots = super::util::ModRoots::load(db).await?;
+    let mut found: Vec<(PathBuf, PathBuf, Option<sims_mod::Model>)> = Vec::new();
+    for (_, _, root_dir) in roots.iter() {
+        if !root_dir.is_dir() {
+            continue;
+        }
+        for archive in find_archives(root_dir)? {
+            let stem = archive.file_stem().unwrap_or_default().to_os_string();
+            found.push((archive, root_dir.join(stem), None));
+        }
+    }
+    // Archives loose in the main Mods folder were found with the roots above
+    for sims_mod in super::util::active_mods()
+        .all(db)
+        .await?
+        .iter()
+        .filter(|sims_mod| !super::util::is_root_entry(sims_mod))
+    {
+        let Ok(mod_dir) = roots.mod_dir(sims_mod) else {
+            continue;
+        };
+        if !mod_dir.is_dir() {
+            continue;
+        }
+        for archive in find_archives(&mod_dir)? {
+            found.push((archive, mod_dir.clone(), Some(sims_mod.clone())));
+        }
+    }
+    if found.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        style::mark(
+            Status::Warning,
+            &format!("Found {} archives, which the game ignores:", found.len())
+        )
+    );
+    for (archive, _, _) in found.iter() {
+        println!("  {}", archive.display());
+    }
+    if !fix {
+        println!("Run {} to extract them.", "scan --fix".bold());
+        return Ok(());
+    }
+    for (archive, target_dir, target_mod) in found.iter() {
+        offer_extract(db, archive, target_dir, target_mod.as_ref()).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use colored::Colorize;
+use inquire::Confirm;
+use tracing::info;
+
+use crate::util::{list_backups, verify_checksum, ChecksumStatus};
+
+/// Actions for managing copies of the database
+#[derive(clap::Subcommand, Debug)]
+pub enum BackupAction {
+    /// Copies the database into the backup folder and stores its checksum
+    Create,
+    /// Lists backups and whether they still match their checksums
+    List,
+    /// Replaces the database with a backup, after verifying its checksum
+    Restore {
+        /// Name of the backup, as shown by `backup list`
+        name: String,
+    },
+}
+
+pub async fn backup(action: BackupAction) -> crate::Result<()> {
+    match action {
+        BackupAction::Create => {
+            let db = crate::util::open_database().await?;
+            let backup_path = crate::util::create_backup(&db).await?;
+            println!(
+                "Created backup {}",
+                backup_path.display().to_string().bold()
+            );
+        }
+        BackupAction::List => {
+            crate::util::ensure_default_database()?;
+            let backups = list_backups()?;
+            if backups.is_empty() {
+                println!("No backups.");
+            }
+            for backup in backups.iter() {
+                let status = match verify_checksum(backup)? {
+                    ChecksumStatus::Valid => "ok".green(),
+                    ChecksumStatus::Mismatch => "corrupted".red(),
+                    ChecksumStatus::Missing => "no checksum".yellow(),
+                };
+                let size = std::fs::metadata(backup)?.len();
+                println!(
+                    "{} {} ({})",
+                    backup
+                        .file_stem()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .bold(),
+                    format!("{} KiB", size / 1024).dimmed(),
+                    status
+                );
+            }
+        }
+        BackupAction::Restore { name } => {
+            crate::util::ensure_default_database()?;
+            let Some(backup) = list_backups()?.into_iter().find(|backup| {
+                backup.file_stem().is_some_and(|stem| *stem == *name)
+                    || backup.file_name().is_some_and(|file| *file == *name)
+            }) else {
+                eprintln!("No backup named {} found!", name);
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            };
+            if !Confirm::new(
+                format!(
+                    "Replace the current database with backup {}? Changes made since then will be lost.",
+                    name.bold()
+                )
+                .as_str(),
+            )
+            .with_default(false)
+            .prompt()?
+            {
+                println!("Not restoring.");
+                return Ok(());
+            }
+            info!("Restoring backup {}", backup.display());
+            crate::util::restore_backup(&backup)?;
+            println!("Restored the database from backup {}", name.bold());
+        }
+    }
+    Ok(())
+}
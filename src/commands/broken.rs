@@ -0,0 +1,55 @@
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
+use tracing::info;
+
+use crate::commands::JournalAction;
+use crate::entities::*;
+
+async fn find_mod(db: &DatabaseConnection, mod_id: i32) -> crate::Result<sims_mod::Model> {
+    match super::util::active_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(db)
+        .await?
+    {
+        Some(sims_mod) => Ok(sims_mod),
+        None => {
+            eprintln!("No mod with mod ID {} found!", mod_id);
+            Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+        }
+    }
+}
+
+pub async fn mark_broken(mod_id: i32, reason: Option<String>) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let sims_mod = find_mod(&db, mod_id).await?;
+
+    info!("Marking {} as broken", sims_mod.name);
+    let mut active_model = sims_mod.into_active_model();
+    active_model.broken_at = ActiveValue::set(Some(chrono::Utc::now()));
+    active_model.broken_reason = ActiveValue::set(reason);
+    let saved_model = active_model.update(&db).await?;
+    super::log::record(&db, JournalAction::Broken, &saved_model).await?;
+
+    println!("Marked {} as broken", saved_model.name.bold().red());
+    Ok(())
+}
+
+pub async fn mark_fixed(mod_id: i32) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let sims_mod = find_mod(&db, mod_id).await?;
+
+    if sims_mod.broken_at.is_none() {
+        println!("{} is not marked as broken", sims_mod.name.bold());
+        return Ok(());
+    }
+
+    info!("Marking {} as fixed", sims_mod.name);
+    let mut active_model = sims_mod.into_active_model();
+    active_model.broken_at = ActiveValue::set(None);
+    active_model.broken_reason = ActiveValue::set(None);
+    let saved_model = active_model.update(&db).await?;
+    super::log::record(&db, JournalAction::Fixed, &saved_model).await?;
+
+    println!("Marked {} as fixed", saved_model.name.bold().green());
+    Ok(())
+}
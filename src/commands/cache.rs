@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Confirm;
+use tracing::{debug, info};
+
+/// Cache files and folders in the game's user folder, next to Mods. The game
+/// rebuilds them on its next start, and stale ones keep showing removed or
+/// changed mods. The user folder is laid out the same on Windows and macOS, and
+/// on Linux inside the Wine or Proton prefix, so only its location differs.
+const CACHE_ENTRIES: &[&str] = &[
+    "localthumbcache.package",
+    "avatarcache.package",
+    "cache",
+    "cachestr",
+    "lotcachedData",
+    "onlinethumbnailcache",
+];
+
+/// A cache file or folder found in the game's user folder, with its size
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+}
+
+/// Finds the game's cache files and folders that exist
+fn find_caches(game_dir: &Path) -> Vec<CacheEntry> {
+    CACHE_ENTRIES
+        .iter()
+        .map(|name| game_dir.join(name))
+        .filter_map(|path| {
+            let metadata = path.symlink_metadata().ok()?;
+            let size = if metadata.is_dir() {
+                super::stale::folder_stats(&path, &HashSet::new()).0
+            } else {
+                metadata.len()
+            };
+            Some(CacheEntry { path, size })
+        })
+        .collect()
+}
+
+fn delete_caches(caches: &[CacheEntry]) -> crate::Result<()> {
+    for cache in caches.iter() {
+        info!("Deleting cache {}", cache.path.display());
+        crate::util::remove_path(&cache.path, true)?;
+    }
+    Ok(())
+}
+
+/// Shows the game's caches with their sizes, and deletes them after confirmation
+pub fn clear_cache() -> crate::Result<()> {
+    let Some(game_dir) = crate::util::get_game_dir() else {
+        eprintln!("Could not locate the game's user folder.");
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+    debug!("Looking for caches in {}", game_dir.display());
+    let caches = find_caches(&game_dir);
+    if caches.is_empty() {
+        println!("No cache files found in {}.", game_dir.display());
+        return Ok(());
+    }
+    for cache in caches.iter() {
+        println!(
+            "  {} {}",
+            cache
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+                .bold(),
+            super::util::format_size(cache.size).dimmed()
+        );
+    }
+    let total: u64 = caches.iter().map(|cache| cache.size).sum();
+    if !Confirm::new(&format!(
+        "Delete {} cache files and folders ({})? The game rebuilds them on its next start.",
+        caches.len(),
+        super::util::format_size(total)
+    ))
+    .with_default(true)
+    .prompt()?
+    {
+        return Ok(());
+    }
+    delete_caches(&caches)?;
+    println!(
+        "Cleared {} of cache.",
+        super::util::format_size(total).green().bold()
+    );
+    Ok(())
+}
+
+/// Offers to clear the game's caches after mod files were turned on or off, unless
+/// turned off with the `offer_clear_cache` setting or the game is running
+pub fn offer_clear_cache() -> crate::Result<()> {
+    if !crate::config::get().offer_clear_cache()
+        || crate::fileio::is_read_only()
+        || crate::util::is_game_running()
+    {
+        return Ok(());
+    }
+    let Some(caches) = crate::util::get_game_dir()
+        .map(|game_dir| find_caches(&game_dir))
+        .filter(|caches| !caches.is_empty())
+    else {
+        return Ok(());
+    };
+    let total: u64 = caches.iter().map(|cache| cache.size).sum();
+    if Confirm::new(&format!(
+        "Clear the game's cache ({}) so it picks up the changed mods?",
+        super::util::format_size(total)
+    ))
+    .with_default(true)
+    .prompt()?
+    {
+        delete_caches(&caches)?;
+        println!("Cleared the game's cache.");
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use sea_orm::{prelude::*, QueryOrder};
+use serde_json::{json, Value};
+
+use crate::entities::{prelude::*, *};
+
+/// Identifies catalog bundles written by `sync export`
+pub const BUNDLE_FORMAT: &str = "sims4modorganizer-sync";
+pub const BUNDLE_VERSION: u64 = 1;
+
+/// A mod as recorded in a catalog: the live database, a sync bundle, or a JSON export.
+/// Fields missing from the source are `None` and are not compared.
+#[derive(Clone, Debug)]
+pub struct CatalogMod {
+    pub name: String,
+    pub version: Option<String>,
+    pub source_url: Option<String>,
+    pub directory: Option<String>,
+    pub files: Option<BTreeMap<String, String>>,
+    pub tags: Option<BTreeSet<String>>,
+}
+
+impl CatalogMod {
+    fn hashes(&self) -> HashSet<&str> {
+        self.files
+            .iter()
+            .flat_map(|files| files.values().map(|h| h.as_str()))
+            .collect()
+    }
+}
+
+/// Mods of two catalogs paired up with each other
+pub struct CatalogPairs<'a> {
+    pub matched: Vec<(&'a CatalogMod, &'a CatalogMod)>,
+    pub only_left: Vec<&'a CatalogMod>,
+    pub only_right: Vec<&'a CatalogMod>,
+}
+
+/// Reads every active mod with its file hashes and tags from the database
+pub async fn load_catalog<C>(db: &C) -> crate::Result<Vec<CatalogMod>>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let mods = SimsMod::find()
+        .filter(sims_mod::Column::DeletedAt.is_null())
+        .order_by_asc(sims_mod::Column::Name)
+        .find_with_related(ModHash)
+        .all(db)
+        .await?;
+    let mut catalog = Vec::new();
+    for (sims_mod, hashes) in mods.into_iter() {
+        let tags = super::util::get_tags_for_mod(db, sims_mod.id).await?;
+        catalog.push(CatalogMod {
+            name: sims_mod.name,
+            version: Some(sims_mod.version),
+            source_url: Some(sims_mod.source_url),
+            directory: Some(sims_mod.directory),
+            files: Some(
+                hashes
+                    .into_iter()
+                    .map(|h| (h.file, h.hash.trim().to_string()))
+                    .collect(),
+            ),
+            tags: Some(tags.into_iter().collect()),
+        });
+    }
+    Ok(catalog)
+}
+
+/// Builds a sync bundle from catalog mods
+pub fn catalog_to_bundle(mods: &[CatalogMod]) -> Value {
+    let entries: Vec<Value> = mods
+        .iter()
+        .map(|m| {
+            json!({
+                "name": m.name,
+                "version": m.version,
+                "source": m.source_url,
+                "directory": m.directory,
+                "files": m.files,
+                "tags": m.tags,
+            })
+        })
+        .collect();
+    json!({
+        "format": BUNDLE_FORMAT,
+        "version": BUNDLE_VERSION,
+        "exported": chrono::Utc::now().to_rfc3339(),
+        "mods": entries,
+    })
+}
+
+/// Reads a sync bundle, or a JSON file written by `export --format json`
+pub fn parse_catalog(contents: &str) -> Option<Vec<CatalogMod>> {
+    let value: Value = serde_json::from_str(contents).ok()?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(bundle) => {
+            if bundle.get("format")?.as_str()? != BUNDLE_FORMAT
+                || bundle.get("version")?.as_u64()? > BUNDLE_VERSION
+            {
+                return None;
+            }
+            bundle.get("mods")?.as_array()?
+        }
+        _ => return None,
+    };
+    let optional_string = |entry: &Value, key: &str| -> Option<Option<String>> {
+        match entry.get(key) {
+            None | Some(Value::Null) => Some(None),
+            Some(Value::String(s)) => Some(Some(s.clone())),
+            Some(_) => None,
+        }
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            let files = match entry.get("files") {
+                None | Some(Value::Null) => None,
+                Some(files) => Some(
+                    files
+                        .as_object()?
+                        .iter()
+                        .map(|(file, hash)| Some((file.clone(), hash.as_str()?.trim().to_string())))
+                        .collect::<Option<_>>()?,
+                ),
+            };
+            let tags = match entry.get("tags") {
+                None | Some(Value::Null) => None,
+                Some(tags) => Some(
+                    tags.as_array()?
+                        .iter()
+                        .map(|tag| tag.as_str().map(|t| t.to_string()))
+                        .collect::<Option<_>>()?,
+                ),
+            };
+            Some(CatalogMod {
+                name: entry.get("name")?.as_str()?.to_string(),
+                version: optional_string(entry, "version")?,
+                source_url: optional_string(entry, "source")?,
+                directory: optional_string(entry, "directory")?,
+                files,
+                tags,
+            })
+        })
+        .collect()
+}
+
+/// Pairs up mods of two catalogs by name, then directory, then source URL, then shared files
+pub fn pair_catalogs<'a>(left: &'a [CatalogMod], right: &'a [CatalogMod]) -> CatalogPairs<'a> {
+    let mut taken = HashSet::new();
+    let mut matched = Vec::new();
+    let mut only_left = Vec::new();
+    for left_mod in left.iter() {
+        let available = || (0..right.len()).filter(|i| !taken.contains(i));
+        let found = available()
+            .find(|i| right[*i].name.eq_ignore_ascii_case(&left_mod.name))
+            .or_else(|| {
+                available().find(|i| {
+                    left_mod.directory.is_some() && right[*i].directory == left_mod.directory
+                })
+            })
+            .or_else(|| {
+                available().find(|i| {
+                    left_mod
+                        .source_url
+                        .as_deref()
+                        .is_some_and(|s| !s.is_empty())
+                        && right[*i].source_url == left_mod.source_url
+                })
+            })
+            .or_else(|| {
+                let hashes = left_mod.hashes();
+                available().find(|i| !right[*i].hashes().is_disjoint(&hashes))
+            });
+        match found {
+            Some(i) => {
+                taken.insert(i);
+                matched.push((left_mod, &right[i]));
+            }
+            None => only_left.push(left_mod),
+        }
+    }
+    let only_right = right
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !taken.contains(i))
+        .map(|(_, m)| m)
+        .collect();
+    CatalogPairs {
+        matched,
+        only_left,
+        only_right,
+    }
+}
+
+/// Compares a field present in both catalogs, ignoring fields only one side records
+pub fn field_changed<T: PartialEq>(left: &Option<T>, right: &Option<T>) -> bool {
+    matches!((left, right), (Some(l), Some(r)) if l != r)
+}
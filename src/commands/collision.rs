@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Select;
+use sea_orm::{prelude::*, ActiveValue};
+use tracing::{debug, info};
+use tracing_unwrap::OptionExt;
+
+use crate::entities::{prelude::*, *};
+
+use super::util::ModRoots;
+
+/// Ways to resolve a file whose hash is already stored for another mod
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum CollisionAction {
+    TrashNew,
+    TrashExisting,
+    KeepBoth,
+}
+
+impl std::fmt::Display for CollisionAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CollisionAction::TrashNew => write!(f, "Move the new copy to the trash"),
+            CollisionAction::TrashExisting => {
+                write!(f, "Move the existing mod's copy to the trash")
+            }
+            CollisionAction::KeepBoth => {
+                write!(f, "Keep both, and stop tracking the new copy")
+            }
+        }
+    }
+}
+
+/// Asks how to resolve files of a mod being saved whose hashes are already stored for another mod.
+/// Files that are not to be tracked are removed from `hashes`; the ones kept as duplicates are
+/// returned so the decision can be recorded with [`record_kept_duplicates`] once the mod is saved.
+/// Without `interactive`, colliding files are left untracked for now and nothing is recorded.
+pub async fn resolve_collisions(
+    db: &DatabaseConnection,
+    roots: &ModRoots,
+    name: &str,
+    mod_id: Option<i32>,
+    mod_dir: &Path,
+    hashes: &mut HashMap<PathBuf, String>,
+    interactive: bool,
+) -> crate::Result<Vec<(PathBuf, String)>> {
+    let mut kept = Vec::new();
+    let files: Vec<(PathBuf, String)> = hashes
+        .iter()
+        .map(|(file, hash)| (file.clone(), hash.clone()))
+        .collect();
+    for (file, hash) in files.into_iter() {
+        let Some(collision) = super::util::active_mod_hashes()
+            .filter(mod_hash::Column::Hash.eq(&hash))
+            .one(db)
+            .await?
+        else {
+            continue;
+        };
+        if Some(collision.mod_id) == mod_id {
+            continue;
+        }
+        let file_name = file.to_string_lossy().to_string();
+        if let Some(mod_id) = mod_id {
+            let already_kept = KeptDuplicate::find()
+                .filter(kept_duplicate::Column::ModId.eq(mod_id))
+                .filter(kept_duplicate::Column::File.eq(&file_name))
+                .filter(kept_duplicate::Column::Hash.eq(&hash))
+                .one(db)
+                .await?
+                .is_some();
+            if already_kept {
+                debug!("{} was kept as a duplicate before", file.display());
+                hashes.remove(&file);
+                continue;
+            }
+        }
+        let existing_mod = collision
+            .find_related(SimsMod)
+            .one(db)
+            .await?
+            .expect_or_log("Failed to find existing mod for collision");
+
+        eprintln!("{}", "!!! HASH COLLISION DETECTED !!!".bold().red());
+        eprintln!("Hash:           {}", collision.hash.bold().yellow());
+        eprintln!("Colliding mod:  {}", name.red().bold());
+        eprintln!("Colliding file: {}", file.display().to_string().red());
+        eprintln!("Existing mod:   {}", existing_mod.name.blue().bold());
+        eprintln!("Existing file:  {}", collision.file.blue());
+        if !interactive {
+            eprintln!(
+                "Not tracking {} for now. Run {} to resolve this.",
+                file.display(),
+                "scan --verify --fix".bold()
+            );
+            hashes.remove(&file);
+            continue;
+        }
+
+        let Some(action) = Select::new(
+            "How do you want to resolve this?",
+            vec![
+                CollisionAction::TrashNew,
+                CollisionAction::TrashExisting,
+                CollisionAction::KeepBoth,
+            ],
+        )
+        .with_help_message("Press ESC to cancel saving this mod")
+        .prompt_skippable()?
+        else {
+            eprintln!("Cancelled saving {}", name);
+            return Err(std::io::Error::from(std::io::ErrorKind::Interrupted).into());
+        };
+        match action {
+            CollisionAction::TrashNew => {
+                info!("Trashing duplicate {}", file.display());
+                super::trashed::remove_path(db, &mod_dir.join(&file), None, false).await?;
+                hashes.remove(&file);
+            }
+            CollisionAction::TrashExisting => {
+                let existing_path = roots.mod_dir(&existing_mod)?.join(&collision.file);
+                info!("Trashing duplicate {}", existing_path.display());
+                super::trashed::remove_path(db, &existing_path, Some(&existing_mod), false).await?;
+                ModHash::delete_by_id(collision.id).exec(db).await?;
+                super::composition::recount_files(db, existing_mod.id).await?;
+            }
+            CollisionAction::KeepBoth => {
+                hashes.remove(&file);
+                kept.push((file, hash));
+            }
+        }
+    }
+    Ok(kept)
+}
+
+/// Records files kept as duplicates so verification skips them and scans don't ask again
+pub async fn record_kept_duplicates<C>(
+    db: &C,
+    mod_id: i32,
+    kept: Vec<(PathBuf, String)>,
+) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    for (file, hash) in kept.into_iter() {
+        debug!("Recording kept duplicate {}", file.display());
+        let kept_duplicate = kept_duplicate::ActiveModel {
+            mod_id: ActiveValue::Set(mod_id),
+            file: ActiveValue::Set(file.to_string_lossy().to_string()),
+            hash: ActiveValue::Set(hash),
+            kept_at: ActiveValue::Set(chrono::Utc::now()),
+            ..Default::default()
+        };
+        KeptDuplicate::insert(kept_duplicate).exec(db).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use sea_orm::{prelude::*, ActiveValue};
+
+use crate::entities::{prelude::*, *};
+
+/// What kinds of files a mod is made of
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Composition {
+    /// Only script files
+    Script,
+    /// Script and package files
+    Mixed,
+    /// Only package files
+    CcOnly,
+    /// Neither script nor package files
+    Empty,
+}
+
+impl Composition {
+    pub fn of(sims_mod: &sims_mod::Model) -> Composition {
+        match (sims_mod.script_count > 0, sims_mod.package_count > 0) {
+            (true, false) => Composition::Script,
+            (true, true) => Composition::Mixed,
+            (false, true) => Composition::CcOnly,
+            (false, false) => Composition::Empty,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Composition::Script => "script mod",
+            Composition::Mixed => "mixed",
+            Composition::CcOnly => "CC only",
+            Composition::Empty => "no game files",
+        }
+    }
+}
+
+impl std::fmt::Display for Composition {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A mod's composition with its file counts, e.g. `mixed (1 script, 12 packages)`
+pub fn format_counts(sims_mod: &sims_mod::Model) -> String {
+    format!(
+        "{} ({} script{}, {} package{})",
+        Composition::of(sims_mod),
+        sims_mod.script_count,
+        if sims_mod.script_count == 1 { "" } else { "s" },
+        sims_mod.package_count,
+        if sims_mod.package_count == 1 { "" } else { "s" }
+    )
+}
+
+/// Whether a mod has script files, which break most often after a patch
+pub fn is_script_mod(sims_mod: &sims_mod::Model) -> bool {
+    sims_mod.script_count > 0
+}
+
+/// Stores how many files a mod has, and how many of them are packages and scripts
+pub fn set_file_counts<'a, I>(model: &mut sims_mod::ActiveModel, files: I)
+where
+    I: IntoIterator<Item = &'a PathBuf>,
+{
+    let (mut total, mut packages, mut scripts) = (0, 0, 0);
+    for file in files.into_iter() {
+        total += 1;
+        let extension = file.extension().map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("package") => packages += 1,
+            Some("ts4script") => scripts += 1,
+            _ => {}
+        }
+    }
+    model.file_count = ActiveValue::Set(total);
+    model.package_count = ActiveValue::Set(packages);
+    model.script_count = ActiveValue::Set(scripts);
+}
+
+/// Counts a mod's files again from its stored hashes, after hashes were removed
+/// outside of a scan
+pub async fn recount_files<C>(db: &C, mod_id: i32) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let files: Vec<PathBuf> = ModHash::find()
+        .filter(mod_hash::Column::ModId.eq(mod_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|hash| PathBuf::from(hash.file))
+        .collect();
+    let mut model = sims_mod::ActiveModel {
+        id: ActiveValue::Unchanged(mod_id),
+        ..Default::default()
+    };
+    set_file_counts(&mut model, files.iter());
+    SimsMod::update(model).exec(db).await?;
+    Ok(())
+}
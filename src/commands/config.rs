@@ -0,0 +1,100 @@
+use colored::Colorize;
+
+use crate::config::{display_value, read_config_file, write_config_file, ConfigKey, ConfigSource};
+
+/// Actions for viewing and changing settings
+#[derive(clap::Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Shows the effective value of a setting
+    Get {
+        /// Setting to show
+        #[arg(value_enum)]
+        key: ConfigKey,
+    },
+    /// Stores a setting in the config file
+    Set {
+        /// Setting to change
+        #[arg(value_enum)]
+        key: ConfigKey,
+
+        /// New value
+        value: String,
+    },
+    /// Lists every setting with its effective value and where it came from
+    List,
+    /// Removes a setting from the config file, restoring its default
+    Unset {
+        /// Setting to remove
+        #[arg(value_enum)]
+        key: ConfigKey,
+    },
+}
+
+/// Warns when a value in the config file is hidden by a higher-precedence source
+fn warn_if_overridden(key: ConfigKey) {
+    match crate::config::get().source(key) {
+        ConfigSource::Environment => eprintln!(
+            "{}",
+            format!("Note: {} overrides the config file", key.env_var()).yellow()
+        ),
+        ConfigSource::Flag => eprintln!(
+            "{}",
+            "Note: a command line flag overrides the config file".yellow()
+        ),
+        _ => {}
+    }
+}
+
+pub async fn config(action: ConfigAction) -> crate::Result<()> {
+    let config = crate::config::get();
+
+    match action {
+        ConfigAction::Get { key } => {
+            println!("{}", display_value(config.value(key)));
+        }
+        ConfigAction::Set { key, value } => {
+            let parsed = match key.parse(&value) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Invalid value for {}: {}", key.name(), e);
+                    return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+                }
+            };
+            let mut values = read_config_file()?;
+            println!("{} = {}", key.name().bold(), display_value(&parsed));
+            values.insert(key.name().to_string(), parsed);
+            write_config_file(&values)?;
+            warn_if_overridden(key);
+        }
+        ConfigAction::List => {
+            println!(
+                "{}",
+                format!(
+                    "Config file: {}",
+                    crate::config::get_config_path()?.display()
+                )
+                .dimmed()
+            );
+            for key in ConfigKey::all() {
+                println!(
+                    "{} = {} ({})",
+                    key.name().bold(),
+                    display_value(config.value(*key)),
+                    config.source(*key)
+                );
+                println!("    {}", key.description().dimmed());
+            }
+        }
+        ConfigAction::Unset { key } => {
+            let mut values = read_config_file()?;
+            if values.remove(key.name()).is_none() {
+                eprintln!("{} is not set in the config file", key.name());
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            }
+            write_config_file(&values)?;
+            println!("Unset {}", key.name().bold());
+            warn_if_overridden(key);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use regex::Regex;
+use sea_orm::DatabaseConnection;
+use tracing::{debug, info};
+use tracing_unwrap::ResultExt;
+
+use super::util::{get_file_hashes, get_hashes_for_mod, ModRoots};
+use crate::entities::sims_mod;
+
+/// A new folder named like a copy Windows Explorer makes of a registered mod's folder,
+/// such as `CuteHair - Copy` or `CuteHair (1)`
+pub struct CopyOf<'a> {
+    pub original: &'a sims_mod::Model,
+    /// Files in the copy with the same contents as one of the original's
+    pub matching_files: usize,
+    /// Mod files in the copy
+    pub files: usize,
+    /// Files stored for the original
+    pub original_files: usize,
+}
+
+impl CopyOf<'_> {
+    /// Whether the copy holds exactly the original's files, so nothing is lost by
+    /// deleting it
+    pub fn is_identical(&self) -> bool {
+        self.matching_files == self.files && self.files == self.original_files
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} of {} files match", self.matching_files, self.files)
+    }
+}
+
+/// What to do about a copy of a registered mod's folder
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CopyAction {
+    Delete,
+    Merge,
+    AddAsNew,
+    Skip,
+}
+
+impl std::fmt::Display for CopyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CopyAction::Delete => write!(f, "Move the copy to the trash"),
+            CopyAction::Merge => write!(f, "Merge the copy into the original's folder"),
+            CopyAction::AddAsNew => write!(f, "Add it as a separate mod"),
+            CopyAction::Skip => write!(f, "Skip this folder"),
+        }
+    }
+}
+
+/// The folder name a copy was made from, for the names Windows Explorer gives copies:
+/// `Name - Copy`, `Name - Copy (2)`, `Copy of Name`, `Copy (2) of Name` and `Name (1)`
+pub fn original_name(name: &str) -> Option<&str> {
+    let pattern = Regex::new(
+        r"^(?:(?P<suffixed>.+?)(?: - Copy(?: \(\d+\))?| \(\d+\))|Copy(?: \(\d+\))? of (?P<prefixed>.+))$",
+    )
+    .expect_or_log("Invalid copy name pattern");
+    let captures = pattern.captures(name)?;
+    captures
+        .name("suffixed")
+        .or_else(|| captures.name("prefixed"))
+        .map(|m| m.as_str().trim())
+        .filter(|original| !original.is_empty())
+}
+
+/// Finds the registered mod a new folder is a copy of, going by the folder's name, and
+/// compares their files
+pub async fn find_copy_of<'a, F>(
+    db: &DatabaseConnection,
+    root: &Path,
+    path: &Path,
+    find_mod: F,
+) -> crate::Result<Option<CopyOf<'a>>>
+where
+    F: Fn(&Path) -> Option<&'a sims_mod::Model>,
+{
+    let Some(original_dir) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(original_name)
+        .map(|original| path.with_file_name(original))
+    else {
+        return Ok(None);
+    };
+    let Some(original) = find_mod(&original_dir) else {
+        return Ok(None);
+    };
+    debug!(
+        "{} is named like a copy of {}",
+        path.display(),
+        original.directory
+    );
+    let original_hashes: HashSet<String> = get_hashes_for_mod(db, original.id)
+        .await?
+        .into_values()
+        .collect();
+    let (_, hashes) = get_file_hashes(root, path)?;
+    Ok(Some(CopyOf {
+        original,
+        matching_files: hashes
+            .values()
+            .filter(|hash| original_hashes.contains(*hash))
+            .count(),
+        files: hashes.len(),
+        original_files: original_hashes.len(),
+    }))
+}
+
+/// Every file under a folder, relative to it
+fn files_under(dir: &Path) -> crate::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut to_read = vec![PathBuf::new()];
+    while let Some(relative_dir) = to_read.pop() {
+        for entry in crate::fileio::read_dir(dir.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                to_read.push(relative_path);
+            } else {
+                files.push(relative_path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Copies a copy's files over its original's folder, replacing files with the same name,
+/// then moves the copy to the trash
+pub async fn merge_copy(
+    db: &DatabaseConnection,
+    roots: &ModRoots,
+    root_id: Option<i32>,
+    path: &Path,
+    original: &sims_mod::Model,
+) -> crate::Result<()> {
+    let copy_dir = roots.dir(root_id)?.join(path);
+    let original_dir = roots.mod_dir(original)?;
+    let files = files_under(&copy_dir)?;
+    info!(
+        "Merging {} files from {} into {}",
+        files.len(),
+        copy_dir.display(),
+        original_dir.display()
+    );
+    crate::transfer::run("merge", |transfer| {
+        for file in files.iter() {
+            transfer.copy_file(copy_dir.join(file), original_dir.join(file))?;
+        }
+        Ok(())
+    })?;
+    super::trashed::remove_path(db, &copy_dir, Some(original), false).await?;
+    println!(
+        "Merged {} files into {}.",
+        files.len().to_string().bold(),
+        original.name.bold()
+    );
+    Ok(())
+}
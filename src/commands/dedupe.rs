@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
+use tracing::{debug, info};
+
+use crate::entities::{prelude::*, *};
+use crate::style::{self, Status};
+
+/// A file kept as a duplicate of another mod's file
+struct Duplicate {
+    kept: kept_duplicate::Model,
+    mod_name: String,
+    path: PathBuf,
+    original_mod_id: i32,
+    original_name: String,
+    original_file: String,
+    original_path: PathBuf,
+    size: u64,
+}
+
+impl Duplicate {
+    fn is_linked(&self) -> bool {
+        self.kept.linked_at.is_some()
+    }
+}
+
+/// Kept duplicates of active mods whose original is still installed
+async fn find_duplicates(db: &DatabaseConnection) -> crate::Result<Vec<Duplicate>> {
+    let roots = super::util::ModRoots::load(db).await?;
+    let mods: HashMap<i32, sims_mod::Model> = super::util::active_mods()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|sims_mod| (sims_mod.id, sims_mod))
+        .collect();
+
+    let mut duplicates = Vec::new();
+    for kept in KeptDuplicate::find().all(db).await?.into_iter() {
+        let Some(sims_mod) = mods.get(&kept.mod_id) else {
+            continue;
+        };
+        // Linked files stay tied to the file they were linked to, even after it changed
+        let original = match (kept.linked_mod_id, &kept.linked_file) {
+            (Some(linked_mod_id), Some(linked_file)) => {
+                ModHash::find()
+                    .filter(mod_hash::Column::ModId.eq(linked_mod_id))
+                    .filter(mod_hash::Column::File.eq(linked_file))
+                    .one(db)
+                    .await?
+            }
+            _ => {
+                super::util::active_mod_hashes()
+                    .filter(mod_hash::Column::Hash.eq(&kept.hash))
+                    .one(db)
+                    .await?
+            }
+        };
+        let Some(original) = original else {
+            debug!("The original of {} is gone", kept.file);
+            continue;
+        };
+        let Some(original_mod) = mods.get(&original.mod_id) else {
+            continue;
+        };
+        let path = roots.mod_dir(sims_mod)?.join(&kept.file);
+        let original_path = roots.mod_dir(original_mod)?.join(&original.file);
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            debug!("{} is missing", path.display());
+            continue;
+        };
+        if !original_path.is_file() {
+            continue;
+        }
+        duplicates.push(Duplicate {
+            mod_name: sims_mod.name.clone(),
+            path,
+            original_mod_id: original_mod.id,
+            original_name: original_mod.name.clone(),
+            original_file: original.file,
+            original_path,
+            size: metadata.len(),
+            kept,
+        });
+    }
+    duplicates.sort_by(|a, b| {
+        a.original_name
+            .cmp(&b.original_name)
+            .then_with(|| a.mod_name.cmp(&b.mod_name))
+    });
+    Ok(duplicates)
+}
+
+/// Paths of hardlinked duplicates and of the files they are linked to.
+/// Deleting one of them frees no space while the other remains.
+pub async fn linked_paths(db: &DatabaseConnection) -> crate::Result<HashSet<PathBuf>> {
+    Ok(find_duplicates(db)
+        .await?
+        .into_iter()
+        .filter(|duplicate| duplicate.is_linked())
+        .flat_map(|duplicate| [duplicate.path, duplicate.original_path])
+        .collect())
+}
+
+/// Disk space saved by hardlinked duplicates
+pub async fn hardlink_savings(db: &DatabaseConnection) -> crate::Result<u64> {
+    Ok(find_duplicates(db)
+        .await?
+        .iter()
+        .filter(|duplicate| duplicate.is_linked())
+        .map(|duplicate| duplicate.size)
+        .sum())
+}
+
+/// Replaces a duplicate with a hardlink to its original. The link is made next to the
+/// duplicate and moved over it, so the duplicate is never missing.
+fn link_duplicate(duplicate: &Duplicate) -> crate::Result<()> {
+    if crate::hasher::hash_file(&duplicate.path)? != duplicate.kept.hash
+        || crate::hasher::hash_file(&duplicate.original_path)? != duplicate.kept.hash
+    {
+        eprintln!(
+            "{} no longer matches {}, run a scan first.",
+            duplicate.path.display(),
+            duplicate.original_path.display()
+        );
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    }
+    let mut temp_name = duplicate.path.as_os_str().to_os_string();
+    temp_name.push(".link");
+    let temp_path = PathBuf::from(temp_name);
+    crate::fileio::hard_link(&duplicate.original_path, &temp_path)?;
+    if let Err(e) = crate::fileio::rename(&temp_path, &duplicate.path) {
+        crate::fileio::remove_file(&temp_path)?;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Lists files kept as duplicates of other mods' files and the space they take up.
+/// With `hardlink`, each duplicate is replaced by a hardlink to its original after
+/// confirmation, so both mods keep their file while it is stored once.
+pub async fn dedupe(hardlink: bool) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let duplicates = find_duplicates(&db).await?;
+    if duplicates.is_empty() {
+        println!("No duplicate files found.");
+        return Ok(());
+    }
+
+    for duplicate in duplicates.iter() {
+        let state = if duplicate.is_linked() {
+            style::mark(Status::Passed, "linked")
+        } else {
+            style::mark(Status::Warning, "copy")
+        };
+        println!(
+            "{} {} ({}) = {} {} [{}] {}",
+            duplicate.mod_name.bold(),
+            duplicate.kept.file,
+            duplicate.kept.mod_id,
+            duplicate.original_name.bold(),
+            duplicate.original_file,
+            super::util::format_size(duplicate.size),
+            state
+        );
+    }
+    let (linked, copies): (Vec<&Duplicate>, Vec<&Duplicate>) = duplicates
+        .iter()
+        .partition(|duplicate| duplicate.is_linked());
+    let wasted: u64 = copies.iter().map(|duplicate| duplicate.size).sum();
+    let saved: u64 = linked.iter().map(|duplicate| duplicate.size).sum();
+    println!(
+        "{} duplicate files use {}, {} hardlinked files save {}.",
+        copies.len().to_string().yellow().bold(),
+        super::util::format_size(wasted).bold(),
+        linked.len().to_string().green().bold(),
+        super::util::format_size(saved).bold()
+    );
+    if copies.is_empty() {
+        return Ok(());
+    }
+    if !hardlink {
+        println!(
+            "Run {} to store each duplicate once.",
+            "dedupe --hardlink".bold()
+        );
+        return Ok(());
+    }
+
+    if !Confirm::new(&format!(
+        "Replace {} duplicate files with hardlinks to their originals, saving {}?",
+        copies.len(),
+        super::util::format_size(wasted)
+    ))
+    .with_default(false)
+    .prompt()?
+    {
+        return Ok(());
+    }
+    let mut linked_count = 0;
+    let mut saved = 0;
+    for duplicate in copies.into_iter() {
+        info!(
+            "Linking {} to {}",
+            duplicate.path.display(),
+            duplicate.original_path.display()
+        );
+        // Hardlinks only work within one drive, so a failure leaves the copy in place
+        if let Err(e) = link_duplicate(duplicate) {
+            if crate::util::read_only_refusal(e.as_ref()).is_some() {
+                return Err(e);
+            }
+            eprintln!("Could not link {}: {}", duplicate.path.display(), e);
+            continue;
+        }
+        let mut kept = duplicate.kept.clone().into_active_model();
+        kept.linked_mod_id = ActiveValue::Set(Some(duplicate.original_mod_id));
+        kept.linked_file = ActiveValue::Set(Some(duplicate.original_file.clone()));
+        kept.linked_at = ActiveValue::Set(Some(chrono::Utc::now()));
+        KeptDuplicate::update(kept).exec(&db).await?;
+        linked_count += 1;
+        saved += duplicate.size;
+    }
+    println!(
+        "Linked {} files, saving {}.",
+        linked_count.to_string().green().bold(),
+        super::util::format_size(saved).bold()
+    );
+    Ok(())
+}
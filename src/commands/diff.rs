@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use super::catalog::{field_changed, load_catalog, pair_catalogs, parse_catalog, CatalogMod};
+
+async fn read_catalog(path: &Path) -> crate::Result<Vec<CatalogMod>> {
+    let contents = crate::fileio::read_to_string(path)?;
+    match parse_catalog(&contents) {
+        Some(catalog) => Ok(catalog),
+        None => {
+            eprintln!("{} is not a sync bundle or JSON export", path.display());
+            Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into())
+        }
+    }
+}
+
+/// Lists the differences between two versions of one mod, empty if there are none
+fn describe_changes(old: &CatalogMod, new: &CatalogMod) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.name != new.name {
+        changes.push(format!("name: {} -> {}", old.name, new.name));
+    }
+    for (label, old_value, new_value) in [
+        ("version", &old.version, &new.version),
+        ("source", &old.source_url, &new.source_url),
+        ("directory", &old.directory, &new.directory),
+    ] {
+        if field_changed(old_value, new_value) {
+            changes.push(format!(
+                "{}: {} -> {}",
+                label,
+                old_value.as_deref().unwrap_or_default(),
+                new_value.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+    if let (Some(old_tags), Some(new_tags)) = (&old.tags, &new.tags) {
+        let added = new_tags.difference(old_tags).map(|t| format!("+{}", t));
+        let removed = old_tags.difference(new_tags).map(|t| format!("-{}", t));
+        let tag_changes: Vec<String> = added.chain(removed).collect();
+        if !tag_changes.is_empty() {
+            changes.push(format!("tags: {}", tag_changes.join(" ")));
+        }
+    }
+    if let (Some(old_files), Some(new_files)) = (&old.files, &new.files) {
+        for (file, hash) in new_files.iter() {
+            match old_files.get(file) {
+                None => changes.push(format!("{} {}", "+".green(), file)),
+                Some(old_hash) if old_hash != hash => {
+                    changes.push(format!("{} {}", "~".yellow(), file))
+                }
+                Some(_) => {}
+            }
+        }
+        for file in old_files.keys().filter(|f| !new_files.contains_key(*f)) {
+            changes.push(format!("{} {}", "-".red(), file));
+        }
+    }
+    changes
+}
+
+/// Prints mods added, removed and changed between two catalog states
+pub fn print_catalog_diff(old: &[CatalogMod], new: &[CatalogMod]) {
+    let pairs = pair_catalogs(old, new);
+    let describe = |m: &CatalogMod| match &m.version {
+        Some(version) => format!("{} ({})", m.name.bold(), version),
+        None => m.name.bold().to_string(),
+    };
+
+    if !pairs.only_right.is_empty() {
+        println!("{}", "Added:".bold());
+        for added in pairs.only_right.iter() {
+            println!("{} {}", "+".green(), describe(added));
+        }
+    }
+    if !pairs.only_left.is_empty() {
+        println!("{}", "Removed:".bold());
+        for removed in pairs.only_left.iter() {
+            println!("{} {}", "-".red(), describe(removed));
+        }
+    }
+    let mut changed = 0;
+    for (old_mod, new_mod) in pairs.matched.iter() {
+        let changes = describe_changes(old_mod, new_mod);
+        if changes.is_empty() {
+            continue;
+        }
+        if changed == 0 {
+            println!("{}", "Changed:".bold());
+        }
+        changed += 1;
+        println!("{} {}", "~".yellow(), describe(new_mod));
+        for change in changes.iter() {
+            println!("    {}", change);
+        }
+    }
+    println!(
+        "{} added, {} removed, {} changed, {} unchanged",
+        pairs.only_right.len(),
+        pairs.only_left.len(),
+        changed,
+        pairs.matched.len() - changed
+    );
+}
+
+pub async fn diff(exports: Vec<PathBuf>, against: Option<PathBuf>) -> crate::Result<()> {
+    let (old, new) = match against {
+        Some(against) => {
+            let db = crate::util::open_database().await?;
+            (read_catalog(&against).await?, load_catalog(&db).await?)
+        }
+        None => (
+            read_catalog(&exports[0]).await?,
+            read_catalog(&exports[1]).await?,
+        ),
+    };
+    print_catalog_diff(&old, &new);
+    Ok(())
+}
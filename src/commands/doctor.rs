@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use tracing::debug;
+
+use crate::scanner::{find_files, ScanOptions};
+
+/// Recommended limits checked by `doctor`
+#[derive(clap::Args, Debug)]
+pub struct DoctorLimits {
+    /// Warn when the mod folder holds more package files than this
+    #[arg(long, default_value_t = 5000)]
+    pub max_packages: usize,
+
+    /// Warn when package files are nested deeper than this many folders
+    #[arg(long, default_value_t = 5)]
+    pub max_depth: usize,
+}
+
+/// Script mods are only loaded from the mod folder or one folder below it
+const MAX_SCRIPT_DEPTH: usize = 1;
+
+/// Relative paths of package and script files under the mod folder
+struct ModFiles {
+    packages: Vec<PathBuf>,
+    scripts: Vec<PathBuf>,
+}
+
+/// Finds the game's files as the game does, regardless of ignore patterns
+fn collect_mod_files(mod_dir: &Path) -> crate::Result<ModFiles> {
+    let (scripts, packages) = find_files(mod_dir, Path::new(""), &ScanOptions::game_files())?
+        .into_iter()
+        .map(|file| file.path)
+        .partition(|path| {
+            path.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("ts4script"))
+        });
+    Ok(ModFiles { packages, scripts })
+}
+
+/// Number of folders between the mod folder and a file
+fn folder_depth(path: &Path) -> usize {
+    path.components().count().saturating_sub(1)
+}
+
+/// The game fails to load some files whose paths contain non-ASCII characters
+fn has_unsupported_name(path: &Path) -> bool {
+    !path.to_string_lossy().is_ascii()
+}
+
+fn print_check(passed: bool, message: String) {
+    if passed {
+        println!("{} {}", "OK  ".green().bold(), message);
+    } else {
+        println!("{} {}", "WARN".yellow().bold(), message);
+    }
+}
+
+fn print_paths(paths: &[&PathBuf]) {
+    for path in paths.iter() {
+        println!("     - {}", path.display());
+    }
+}
+
+/// Checks the mod folder against recommended limits, returning whether all checks passed
+pub async fn doctor(limits: DoctorLimits) -> crate::Result<bool> {
+    let mod_dir = crate::util::get_sims_mod_dir()?;
+    debug!("Collecting mod files in {}", mod_dir.display());
+    let files = collect_mod_files(&mod_dir)?;
+
+    let package_count = files.packages.len();
+    let count_ok = package_count <= limits.max_packages;
+    print_check(
+        count_ok,
+        format!(
+            "{} package files ({} recommended at most)",
+            package_count, limits.max_packages
+        ),
+    );
+
+    let deep_packages: Vec<_> = files
+        .packages
+        .iter()
+        .filter(|p| folder_depth(p) > limits.max_depth)
+        .collect();
+    print_check(
+        deep_packages.is_empty(),
+        format!(
+            "{} package files nested more than {} folders deep",
+            deep_packages.len(),
+            limits.max_depth
+        ),
+    );
+    print_paths(&deep_packages);
+
+    let deep_scripts: Vec<_> = files
+        .scripts
+        .iter()
+        .filter(|p| folder_depth(p) > MAX_SCRIPT_DEPTH)
+        .collect();
+    print_check(
+        deep_scripts.is_empty(),
+        format!(
+            "{} script files nested more than {} folder deep",
+            deep_scripts.len(),
+            MAX_SCRIPT_DEPTH
+        ),
+    );
+    print_paths(&deep_scripts);
+
+    let bad_names: Vec<_> = files
+        .packages
+        .iter()
+        .chain(files.scripts.iter())
+        .filter(|p| has_unsupported_name(p))
+        .collect();
+    print_check(
+        bad_names.is_empty(),
+        format!(
+            "{} files with non-ASCII characters in their path",
+            bad_names.len()
+        ),
+    );
+    print_paths(&bad_names);
+
+    let interrupted = crate::transfer::interrupted()?;
+    print_check(
+        interrupted.is_empty(),
+        format!(
+            "{} interrupted file operations (moves and copies left half done)",
+            interrupted.len()
+        ),
+    );
+    for (journal, steps) in interrupted.iter() {
+        println!("     {}", journal.display());
+        for step in steps.iter() {
+            println!("     - {}", step);
+        }
+    }
+
+    Ok(count_ok
+        && deep_packages.is_empty()
+        && deep_scripts.is_empty()
+        && bad_names.is_empty()
+        && interrupted.is_empty())
+}
@@ -1,31 +1,64 @@
 use std::collections::HashSet;
 use std::fmt::Debug;
 
+use colored::Colorize;
 use inquire::error::InquireResult;
 use inquire::{Confirm, InquireError, MultiSelect, Select, Text};
+use itertools::Itertools;
 use sea_orm::{prelude::*, ActiveValue, Condition, IntoActiveModel, QuerySelect, TransactionTrait};
 use tracing::debug;
 use tracing_unwrap::OptionExt;
 
+use crate::commands::tags::TagDefaults;
+use crate::commands::{util::VerificationPassed, JournalAction, SourceType, UpdateChannel};
 use crate::entities::sims_mod::Model as SimsModModel;
 
 use crate::entities::{prelude::*, *};
 
+/// Cleanup views offered from the main menu
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ModListFilter {
+    FailingVerification,
+    Untagged,
+    NoSource,
+}
+
+impl std::fmt::Display for ModListFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ModListFilter::FailingVerification => write!(f, "Failing verification"),
+            ModListFilter::Untagged => write!(f, "Untagged"),
+            ModListFilter::NoSource => write!(f, "No source URL"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum EditMenuAction {
     MainMenu,
     TagList,
     TagModList(String, i32),
     AllModList,
+    FilteredModList(ModListFilter),
     EditMod(String, i32),
     EditModName(SimsModModel),
     EditModSource(SimsModModel),
+    EditModVersion(SimsModModel),
     EditModTags(SimsModModel),
+    ToggleProtected(SimsModModel),
+    ToggleFrozen(SimsModModel),
+    ToggleNsfw(SimsModModel),
+    EditModRating(SimsModModel),
+    EditModReinstall(SimsModModel),
+    EditModRecheck(SimsModModel),
+    EditModPacks(SimsModModel, Vec<String>),
     AddTag(SimsModModel),
     DeleteTag(SimsModModel, String, i32),
     BulkTag,
     ScanNoVerify,
     ScanVerify,
+    /// Mods found new or changed by the last scan
+    ScannedModList(Vec<i32>),
     Quit,
 }
 
@@ -47,6 +80,7 @@ impl std::fmt::Display for EditMenuAction {
             EditMenuAction::TagList => write!(f, "Mods by tag"),
             EditMenuAction::TagModList(tag_name, _) => write!(f, "{}", tag_name),
             EditMenuAction::AllModList => write!(f, "All mods"),
+            EditMenuAction::FilteredModList(filter) => write!(f, "{}", filter),
             EditMenuAction::EditMod(mod_name, mod_id) => {
                 write!(f, "{} ({})", mod_name, mod_id)
             }
@@ -54,12 +88,48 @@ impl std::fmt::Display for EditMenuAction {
             EditMenuAction::EditModSource(mod_model) => {
                 write!(f, "Source: {}", mod_model.source_url)
             }
+            EditMenuAction::EditModVersion(mod_model) => {
+                write!(f, "Version: {}", mod_model.version)
+            }
             EditMenuAction::EditModTags(mod_model) => write!(f, "Edit tags for {}", mod_model.name),
+            EditMenuAction::ToggleProtected(mod_model) => write!(
+                f,
+                "Protected: {}",
+                if mod_model.protected { "yes" } else { "no" }
+            ),
+            EditMenuAction::ToggleFrozen(mod_model) => {
+                write!(f, "Frozen: {}", if mod_model.frozen { "yes" } else { "no" })
+            }
+            EditMenuAction::ToggleNsfw(mod_model) => {
+                write!(f, "NSFW: {}", if mod_model.nsfw { "yes" } else { "no" })
+            }
+            EditMenuAction::EditModRating(mod_model) => write!(
+                f,
+                "Rating: {}",
+                super::util::format_rating(mod_model.rating)
+            ),
+            EditMenuAction::EditModReinstall(mod_model) => write!(
+                f,
+                "Would reinstall: {}",
+                super::util::format_would_reinstall(mod_model.would_reinstall)
+            ),
+            EditMenuAction::EditModRecheck(mod_model) => match mod_model.recheck_after {
+                Some(date) => write!(f, "Recheck after: {}", date),
+                None => write!(f, "Recheck after: none"),
+            },
+            EditMenuAction::EditModPacks(_, packs) => {
+                if packs.is_empty() {
+                    write!(f, "Required packs: none")
+                } else {
+                    write!(f, "Required packs: {}", packs.join(", "))
+                }
+            }
             EditMenuAction::AddTag(_) => write!(f, "Add tag"),
             EditMenuAction::DeleteTag(_, tag_name, _) => write!(f, "Delete tag {}", tag_name),
             EditMenuAction::BulkTag => write!(f, "Bulk tag mods"),
             EditMenuAction::ScanNoVerify => write!(f, "Scan for new/deleted mods"),
             EditMenuAction::ScanVerify => write!(f, "Scan for new/deleted/updated mods"),
+            EditMenuAction::ScannedModList(_) => write!(f, "Mods found by the last scan"),
             EditMenuAction::Quit => write!(f, "Quit"),
         }
     }
@@ -114,22 +184,355 @@ impl std::error::Error for DBOrInquireError {
     }
 }
 
+/// Mod metadata that can be set non-interactively
+#[derive(clap::Args, Debug)]
+pub struct EditFields {
+    /// Mod name to edit
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Source URL to set
+    #[arg(short, long)]
+    pub source_url: Option<String>,
+
+    /// Tags to set
+    #[arg(short, long, value_delimiter = ',')]
+    pub tags: Option<Vec<String>>,
+
+    /// Version to set
+    #[arg(short = 'v', long)]
+    pub mod_version: Option<String>,
+
+    /// Protect the mod from accidental removal (true/false)
+    #[arg(short, long)]
+    pub protected: Option<bool>,
+
+    /// Skip hashing the mod's files during scans without --thorough (true/false)
+    #[arg(long)]
+    pub frozen: Option<bool>,
+
+    /// Flag the mod as adult content, hidden in SFW mode (true/false)
+    #[arg(long)]
+    pub nsfw: Option<bool>,
+
+    /// How `fetch --all` may update the mod
+    #[arg(long, value_enum)]
+    pub update_channel: Option<UpdateChannel>,
+
+    /// Direct file URL used by `fetch`. Clears it if no URL is given.
+    #[arg(long, num_args = 0..=1)]
+    pub download_url: Option<Option<String>>,
+
+    /// Mod ID of the base mod this is an addon or translation of. Unlinks it if no ID is given.
+    #[arg(long, num_args = 0..=1)]
+    pub parent: Option<Option<i32>>,
+
+    /// Personal rating from 1 to 5. Clears it if no rating is given.
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..=5), num_args = 0..=1)]
+    pub rating: Option<Option<i32>>,
+
+    /// Whether you would reinstall the mod on a fresh Mods folder (true/false)
+    #[arg(long)]
+    pub would_reinstall: Option<bool>,
+
+    /// Date (YYYY-MM-DD) after which to check for a public release. Clears it if no date is given.
+    #[arg(short, long, value_parser = super::util::parse_date, num_args = 0..=1)]
+    pub recheck_after: Option<Option<chrono::NaiveDate>>,
+
+    /// Expansion, game, or stuff packs the mod requires
+    #[arg(long, value_delimiter = ',')]
+    pub required_packs: Option<Vec<String>>,
+}
+
+impl EditFields {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.source_url.is_none()
+            && self.tags.is_none()
+            && self.mod_version.is_none()
+            && self.protected.is_none()
+            && self.frozen.is_none()
+            && self.nsfw.is_none()
+            && self.update_channel.is_none()
+            && self.download_url.is_none()
+            && self.parent.is_none()
+            && self.rating.is_none()
+            && self.would_reinstall.is_none()
+            && self.recheck_after.is_none()
+            && self.required_packs.is_none()
+    }
+}
+
+/// Which mods a non-interactive edit applies to
+#[derive(clap::Args, Debug)]
+pub struct EditSelection {
+    /// Mod ID to edit
+    #[arg(short, long, conflicts_with_all = ["filter_source", "filter_tag"])]
+    pub mod_id: Option<i32>,
+
+    /// Edit all mods whose source URL starts with this, e.g. patreon.com/creator
+    #[arg(long)]
+    pub filter_source: Option<String>,
+
+    /// Edit all mods with any of the given tags
+    #[arg(long, value_delimiter = ',')]
+    pub filter_tag: Option<Vec<String>>,
+}
+
+impl EditSelection {
+    pub fn is_empty(&self) -> bool {
+        self.mod_id.is_none() && self.filter_source.is_none() && self.filter_tag.is_none()
+    }
+}
+
+/// Applies edited fields to a mod and journals the change
+async fn apply_fields<C>(db: &C, sims_mod: SimsModModel, fields: &EditFields) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let id = sims_mod.id;
+    // Defaults of newly added tags fill in blanks; fields set in the same edit win
+    let defaults = match &fields.tags {
+        Some(tags) => {
+            let current: Vec<String> = sims_mod
+                .find_related(Tag)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|t| t.tag)
+                .collect();
+            let added: Vec<String> = tags
+                .iter()
+                .filter(|t| !current.contains(t))
+                .cloned()
+                .collect();
+            let defaults = TagDefaults::for_tags(db, &added)
+                .await?
+                .missing_from(&sims_mod);
+            defaults.print_preview(&added);
+            defaults
+        }
+        None => TagDefaults::default(),
+    };
+    let mut active_model = sims_mod.into_active_model();
+    defaults.apply(&mut active_model);
+    if let Some(name) = &fields.name {
+        active_model.name = ActiveValue::set(name.clone());
+    }
+    if let Some(source_url) = &fields.source_url {
+        active_model.source_type =
+            ActiveValue::set(SourceType::name_for_url(source_url).to_string());
+        active_model.source_url = ActiveValue::set(source_url.clone());
+    }
+    if let Some(version) = &fields.mod_version {
+        active_model.version = ActiveValue::set(version.clone());
+    }
+    if let Some(protected) = fields.protected {
+        active_model.protected = ActiveValue::set(protected);
+    }
+    if let Some(frozen) = fields.frozen {
+        active_model.frozen = ActiveValue::set(frozen);
+    }
+    if let Some(nsfw) = fields.nsfw {
+        active_model.nsfw = ActiveValue::set(nsfw);
+    }
+    if let Some(update_channel) = fields.update_channel {
+        active_model.update_channel = ActiveValue::set(update_channel.to_string());
+    }
+    if let Some(download_url) = &fields.download_url {
+        active_model.download_url = ActiveValue::set(download_url.clone());
+    }
+    if let Some(parent) = fields.parent {
+        active_model.parent_mod_id = ActiveValue::set(parent);
+    }
+    if let Some(rating) = fields.rating {
+        active_model.rating = ActiveValue::set(rating);
+    }
+    if let Some(would_reinstall) = fields.would_reinstall {
+        active_model.would_reinstall = ActiveValue::set(Some(would_reinstall));
+    }
+    if let Some(recheck_after) = fields.recheck_after {
+        active_model.recheck_after = ActiveValue::set(recheck_after);
+    }
+    if let Some(tags) = &fields.tags {
+        ModTagRelation::delete_many()
+            .filter(Condition::any().add(mod_tag_relation::Column::ModId.eq(id)))
+            .exec(db)
+            .await?;
+        for tag in tags.iter() {
+            let new_relation = mod_tag_relation::ActiveModel {
+                mod_id: ActiveValue::Set(id),
+                tag_id: ActiveValue::Set(super::util::get_or_create_tag_id(db, tag).await?),
+            };
+            ModTagRelation::insert(new_relation).exec(db).await?;
+        }
+    }
+    if let Some(required_packs) = &fields.required_packs {
+        super::util::set_required_packs(db, id, required_packs).await?;
+    }
+    active_model.updated = ActiveValue::set(chrono::Utc::now());
+    let saved_model = active_model.update(db).await?;
+    super::log::record(db, JournalAction::Edited, &saved_model).await?;
+    Ok(())
+}
+
+/// Compares source URLs ignoring the scheme, a leading "www.", and case
+fn source_matches(source_url: &str, filter: &str) -> bool {
+    let normalize = |url: &str| {
+        let url = url.trim().to_lowercase();
+        let url = match url.split_once("://") {
+            Some((_, rest)) => rest.to_string(),
+            None => url,
+        };
+        match url.strip_prefix("www.") {
+            Some(rest) => rest.to_string(),
+            None => url,
+        }
+    };
+    normalize(source_url).starts_with(&normalize(filter))
+}
+
+/// Describes how a bulk edit changes one mod
+fn describe_changes(sims_mod: &SimsModModel, fields: &EditFields) -> Vec<String> {
+    let mut changes = Vec::new();
+    if let Some(version) = &fields.mod_version {
+        changes.push(format!("version {} -> {}", sims_mod.version, version));
+    }
+    if let Some(protected) = fields.protected {
+        changes.push(format!("protected {} -> {}", sims_mod.protected, protected));
+    }
+    if let Some(frozen) = fields.frozen {
+        changes.push(format!("frozen {} -> {}", sims_mod.frozen, frozen));
+    }
+    if let Some(nsfw) = fields.nsfw {
+        changes.push(format!("nsfw {} -> {}", sims_mod.nsfw, nsfw));
+    }
+    if let Some(update_channel) = fields.update_channel {
+        changes.push(format!(
+            "updates {} -> {}",
+            sims_mod.update_channel, update_channel
+        ));
+    }
+    if let Some(parent) = fields.parent {
+        let describe = |id: Option<i32>| {
+            id.map(|id| id.to_string())
+                .unwrap_or_else(|| "none".to_string())
+        };
+        changes.push(format!(
+            "parent {} -> {}",
+            describe(sims_mod.parent_mod_id),
+            describe(parent)
+        ));
+    }
+    if let Some(rating) = fields.rating {
+        changes.push(format!(
+            "rating {} -> {}",
+            super::util::format_rating(sims_mod.rating),
+            super::util::format_rating(rating)
+        ));
+    }
+    if let Some(would_reinstall) = fields.would_reinstall {
+        changes.push(format!(
+            "would reinstall {} -> {}",
+            super::util::format_would_reinstall(sims_mod.would_reinstall),
+            super::util::format_would_reinstall(Some(would_reinstall))
+        ));
+    }
+    if let Some(recheck_after) = fields.recheck_after {
+        let describe = |date: Option<chrono::NaiveDate>| {
+            date.map(|d| d.to_string())
+                .unwrap_or_else(|| "none".to_string())
+        };
+        changes.push(format!(
+            "recheck after {} -> {}",
+            describe(sims_mod.recheck_after),
+            describe(recheck_after)
+        ));
+    }
+    if let Some(required_packs) = &fields.required_packs {
+        changes.push(format!("required packs -> {}", required_packs.join(", ")));
+    }
+    changes
+}
+
+/// Applies the same edit to every mod matching a source or tag filter
+async fn bulk_edit(
+    db: &DatabaseConnection,
+    selection: EditSelection,
+    fields: EditFields,
+) -> crate::Result<()> {
+    if fields.name.is_some()
+        || fields.source_url.is_some()
+        || fields.download_url.is_some()
+        || fields.tags.is_some()
+    {
+        eprintln!(
+            "Names, source URLs, download URLs, and tags can only be edited one mod at a time"
+        );
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+    }
+
+    let mods = match selection.filter_tag {
+        Some(tags) => super::util::get_mods_for_tags(db, tags).await?,
+        None => super::util::active_mods().all(db).await?,
+    };
+    let mods: Vec<_> = mods
+        .into_iter()
+        .filter(|m| !super::util::is_hidden(m))
+        .filter(|m| {
+            selection
+                .filter_source
+                .as_ref()
+                .map(|filter| source_matches(&m.source_url, filter))
+                .unwrap_or(true)
+        })
+        .collect();
+    if mods.is_empty() {
+        eprintln!("No matching mods found!");
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    }
+
+    if let Some(Some(parent_id)) = fields.parent {
+        for sims_mod in mods.iter() {
+            super::util::validate_parent(db, sims_mod.id, parent_id).await?;
+        }
+    }
+    for sims_mod in mods.iter() {
+        println!(
+            "{}: {}",
+            sims_mod.name.bold(),
+            describe_changes(sims_mod, &fields).join(", ")
+        );
+    }
+    if !Confirm::new(format!("Apply changes to {} mods?", mods.len()).as_str())
+        .with_default(false)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let mod_count = mods.len();
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            for sims_mod in mods.into_iter() {
+                apply_fields(txn, sims_mod, &fields).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+    println!("Updated {} mods.", mod_count.to_string().green().bold());
+    Ok(())
+}
+
 pub async fn edit(
     interactive: bool,
-    id: Option<i32>,
-    name: Option<String>,
-    source_url: Option<String>,
-    tags: Option<Vec<String>>,
-    version: Option<String>,
+    selection: EditSelection,
+    mut fields: EditFields,
 ) -> crate::Result<()> {
     let db = crate::util::open_database().await?;
     if interactive {
-        if id.is_some()
-            || name.is_some()
-            || source_url.is_some()
-            || tags.is_some()
-            || version.is_some()
-        {
+        if !selection.is_empty() || !fields.is_empty() {
             eprintln!("Interactive mode is not compatible with other arguments");
             return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into());
         }
@@ -146,6 +549,9 @@ pub async fn edit(
                     let options: Vec<EditMenuAction> = vec![
                         EditMenuAction::TagList,
                         EditMenuAction::AllModList,
+                        EditMenuAction::FilteredModList(ModListFilter::FailingVerification),
+                        EditMenuAction::FilteredModList(ModListFilter::Untagged),
+                        EditMenuAction::FilteredModList(ModListFilter::NoSource),
                         EditMenuAction::BulkTag,
                         EditMenuAction::ScanNoVerify,
                         EditMenuAction::ScanVerify,
@@ -176,14 +582,10 @@ pub async fn edit(
                     }
                 }
                 EditMenuAction::TagModList(tag_name, tag_id) => {
-                    let (_, mut tag_mods) = Tag::find_by_id(tag_id)
-                        .find_with_related(SimsMod)
-                        .all(&db)
-                        .await?
-                        .pop()
-                        .expect_or_log(format!("Failed to get mods for tag {}", tag_name).as_str());
+                    let mut tag_mods = super::util::get_mods_for_tag_id(&db, tag_id).await?;
                     let menu_entries: Vec<_> = tag_mods
                         .drain(..)
+                        .filter(|m| !super::util::is_hidden(m))
                         .map(|m| EditMenuAction::EditMod(m.name, m.id))
                         .collect();
                     if menu_entries.is_empty() {
@@ -199,7 +601,7 @@ pub async fn edit(
                     }
                 }
                 EditMenuAction::AllModList => {
-                    let mod_list_options: Vec<EditMenuAction> = SimsMod::find()
+                    let mod_list_options: Vec<EditMenuAction> = super::util::visible_mods()
                         .select_only()
                         .column(sims_mod::Column::Name)
                         .column(sims_mod::Column::Id)
@@ -219,15 +621,70 @@ pub async fn edit(
                         previous_menu_state = EditMenuAction::AllModList;
                     }
                 }
+                EditMenuAction::FilteredModList(filter) => {
+                    let mods = super::util::visible_mods().all(&db).await?;
+                    let mut matching_mods = Vec::new();
+                    match filter {
+                        ModListFilter::FailingVerification => {
+                            for sims_mod in mods.into_iter() {
+                                if !super::util::verify_mod(&db, &sims_mod)
+                                    .await?
+                                    .verification_passed()
+                                {
+                                    matching_mods.push(sims_mod);
+                                }
+                            }
+                        }
+                        ModListFilter::Untagged => {
+                            let tagged_ids: HashSet<i32> = ModTagRelation::find()
+                                .all(&db)
+                                .await?
+                                .drain(..)
+                                .map(|r| r.mod_id)
+                                .collect();
+                            matching_mods
+                                .extend(mods.into_iter().filter(|m| !tagged_ids.contains(&m.id)));
+                        }
+                        ModListFilter::NoSource => {
+                            matching_mods.extend(
+                                mods.into_iter()
+                                    .filter(|m| url::Url::parse(&m.source_url).is_err()),
+                            );
+                        }
+                    }
+                    let menu_entries: Vec<_> = matching_mods
+                        .drain(..)
+                        .map(|m| EditMenuAction::EditMod(m.name, m.id))
+                        .collect();
+                    if menu_entries.is_empty() {
+                        println!("No mods found for {}.", filter);
+                        current_state = EditMenuAction::MainMenu;
+                    } else {
+                        current_state = Select::new(format!("{}:", filter).as_str(), menu_entries)
+                            .prompt()
+                            .with_interrupted_default(EditMenuAction::MainMenu)?;
+                        previous_menu_state = EditMenuAction::FilteredModList(filter);
+                    }
+                }
                 EditMenuAction::EditMod(mod_name, mod_id) => {
                     let mod_model = SimsMod::find_by_id(mod_id)
                         .one(&db)
                         .await?
                         .expect_or_log("Failed to load mod by ID!");
+                    let required_packs =
+                        super::util::get_required_packs_for_mod(&db, mod_model.id).await?;
                     let options: Vec<EditMenuAction> = vec![
                         EditMenuAction::EditModName(mod_model.clone()),
                         EditMenuAction::EditModSource(mod_model.clone()),
+                        EditMenuAction::EditModVersion(mod_model.clone()),
                         EditMenuAction::EditModTags(mod_model.clone()),
+                        EditMenuAction::ToggleProtected(mod_model.clone()),
+                        EditMenuAction::ToggleFrozen(mod_model.clone()),
+                        EditMenuAction::ToggleNsfw(mod_model.clone()),
+                        EditMenuAction::EditModRating(mod_model.clone()),
+                        EditMenuAction::EditModReinstall(mod_model.clone()),
+                        EditMenuAction::EditModRecheck(mod_model.clone()),
+                        EditMenuAction::EditModPacks(mod_model.clone(), required_packs),
                         previous_menu_state.clone(),
                     ];
                     current_state =
@@ -245,8 +702,9 @@ pub async fn edit(
                         let model_id = mod_model.id;
                         let mut active_model = mod_model.into_active_model();
                         active_model.name = ActiveValue::set(new_name.clone());
-                        active_model.updated = ActiveValue::set(chrono::offset::Local::now());
-                        active_model.save(&db).await?;
+                        active_model.updated = ActiveValue::set(chrono::Utc::now());
+                        let saved_model = active_model.update(&db).await?;
+                        super::log::record(&db, JournalAction::Edited, &saved_model).await?;
                         current_state = EditMenuAction::EditMod(new_name, model_id);
                     } else {
                         current_state = EditMenuAction::EditMod(mod_model.name, mod_model.id)
@@ -260,9 +718,12 @@ pub async fn edit(
                             let model_id = mod_model.id;
                             let model_name = mod_model.name.clone();
                             let mut active_model = mod_model.into_active_model();
+                            active_model.source_type =
+                                ActiveValue::set(SourceType::name_for_url(&new_source).to_string());
                             active_model.source_url = ActiveValue::set(new_source);
-                            active_model.updated = ActiveValue::set(chrono::offset::Local::now());
-                            active_model.save(&db).await?;
+                            active_model.updated = ActiveValue::set(chrono::Utc::now());
+                            let saved_model = active_model.update(&db).await?;
+                            super::log::record(&db, JournalAction::Edited, &saved_model).await?;
                             current_state = EditMenuAction::EditMod(model_name, model_id);
                         }
                         Err(inquire_err) => match inquire_err {
@@ -275,6 +736,23 @@ pub async fn edit(
                         },
                     }
                 }
+                EditMenuAction::EditModVersion(mod_model) => {
+                    let new_version_result = Text::new("Mod version:")
+                        .with_initial_value(&mod_model.version)
+                        .with_default(&mod_model.version)
+                        .with_validator(inquire::required!())
+                        .prompt_skippable()?;
+                    let model_id = mod_model.id;
+                    let model_name = mod_model.name.clone();
+                    if let Some(new_version) = new_version_result {
+                        let mut active_model = mod_model.into_active_model();
+                        active_model.version = ActiveValue::set(new_version);
+                        active_model.updated = ActiveValue::set(chrono::Utc::now());
+                        let saved_model = active_model.update(&db).await?;
+                        super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                    }
+                    current_state = EditMenuAction::EditMod(model_name, model_id);
+                }
                 EditMenuAction::EditModTags(mod_model) => {
                     let mut tag_options: Vec<_> = mod_model
                         .find_related(Tag)
@@ -293,6 +771,144 @@ pub async fn edit(
                     .prompt()
                     .with_interrupted_default(return_option)?;
                 }
+                EditMenuAction::ToggleProtected(mod_model) => {
+                    let model_id = mod_model.id;
+                    let model_name = mod_model.name.clone();
+                    let protected = !mod_model.protected;
+                    let mut active_model = mod_model.into_active_model();
+                    active_model.protected = ActiveValue::set(protected);
+                    active_model.updated = ActiveValue::set(chrono::Utc::now());
+                    let saved_model = active_model.update(&db).await?;
+                    super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                    current_state = EditMenuAction::EditMod(model_name, model_id);
+                }
+                EditMenuAction::ToggleFrozen(mod_model) => {
+                    let model_id = mod_model.id;
+                    let model_name = mod_model.name.clone();
+                    let frozen = !mod_model.frozen;
+                    let mut active_model = mod_model.into_active_model();
+                    active_model.frozen = ActiveValue::set(frozen);
+                    active_model.updated = ActiveValue::set(chrono::Utc::now());
+                    let saved_model = active_model.update(&db).await?;
+                    super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                    current_state = EditMenuAction::EditMod(model_name, model_id);
+                }
+                EditMenuAction::ToggleNsfw(mod_model) => {
+                    let model_id = mod_model.id;
+                    let model_name = mod_model.name.clone();
+                    let nsfw = !mod_model.nsfw;
+                    let mut active_model = mod_model.into_active_model();
+                    active_model.nsfw = ActiveValue::set(nsfw);
+                    active_model.updated = ActiveValue::set(chrono::Utc::now());
+                    let saved_model = active_model.update(&db).await?;
+                    super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                    current_state = EditMenuAction::EditMod(model_name, model_id);
+                }
+                EditMenuAction::EditModRating(mod_model) => {
+                    let options: Vec<Option<i32>> = std::iter::once(None)
+                        .chain((1..=5).rev().map(Some))
+                        .collect();
+                    let labels: Vec<String> = options
+                        .iter()
+                        .map(|rating| super::util::format_rating(*rating))
+                        .collect();
+                    let starting_cursor = options
+                        .iter()
+                        .position(|rating| *rating == mod_model.rating)
+                        .unwrap_or(0);
+                    let rating_result = Select::new("Rating:", labels.clone())
+                        .with_starting_cursor(starting_cursor)
+                        .prompt_skippable()?;
+                    let model_id = mod_model.id;
+                    let model_name = mod_model.name.clone();
+                    if let Some(selected) = rating_result {
+                        let mut active_model = mod_model.into_active_model();
+                        let index = labels.iter().position(|l| *l == selected).unwrap_or(0);
+                        active_model.rating = ActiveValue::set(options[index]);
+                        active_model.updated = ActiveValue::set(chrono::Utc::now());
+                        let saved_model = active_model.update(&db).await?;
+                        super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                    }
+                    current_state = EditMenuAction::EditMod(model_name, model_id);
+                }
+                EditMenuAction::EditModReinstall(mod_model) => {
+                    let options = [Some(true), Some(false), None];
+                    let labels: Vec<&str> = options
+                        .iter()
+                        .map(|w| super::util::format_would_reinstall(*w))
+                        .collect();
+                    let starting_cursor = options
+                        .iter()
+                        .position(|w| *w == mod_model.would_reinstall)
+                        .unwrap_or(0);
+                    let reinstall_result =
+                        Select::new("Would you reinstall this mod?", labels.clone())
+                            .with_starting_cursor(starting_cursor)
+                            .prompt_skippable()?;
+                    let model_id = mod_model.id;
+                    let model_name = mod_model.name.clone();
+                    if let Some(selected) = reinstall_result {
+                        let mut active_model = mod_model.into_active_model();
+                        let index = labels.iter().position(|l| *l == selected).unwrap_or(0);
+                        active_model.would_reinstall = ActiveValue::set(options[index]);
+                        active_model.updated = ActiveValue::set(chrono::Utc::now());
+                        let saved_model = active_model.update(&db).await?;
+                        super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                    }
+                    current_state = EditMenuAction::EditMod(model_name, model_id);
+                }
+                EditMenuAction::EditModRecheck(mod_model) => {
+                    let recheck_result = super::util::prompt_optional_date(
+                        "Recheck after:",
+                        mod_model.recheck_after,
+                    );
+                    match recheck_result {
+                        Ok(recheck_after) => {
+                            let model_id = mod_model.id;
+                            let model_name = mod_model.name.clone();
+                            let mut active_model = mod_model.into_active_model();
+                            active_model.recheck_after = ActiveValue::set(recheck_after);
+                            active_model.updated = ActiveValue::set(chrono::Utc::now());
+                            let saved_model = active_model.update(&db).await?;
+                            super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+                            current_state = EditMenuAction::EditMod(model_name, model_id);
+                        }
+                        Err(inquire_err) => match inquire_err {
+                            InquireError::OperationInterrupted
+                            | InquireError::OperationCanceled => {
+                                current_state =
+                                    EditMenuAction::EditMod(mod_model.name, mod_model.id);
+                            }
+                            _ => return Err(inquire_err.into()),
+                        },
+                    }
+                }
+                EditMenuAction::EditModPacks(mod_model, packs) => {
+                    let packs_result = Text::new("Required packs (comma separated):")
+                        .with_initial_value(&packs.join(", "))
+                        .prompt_skippable()?;
+                    if let Some(new_packs) = packs_result {
+                        let new_packs: Vec<String> =
+                            new_packs.split(',').map(|p| p.to_string()).collect();
+                        let model_id = mod_model.id;
+                        let model_name = mod_model.name.clone();
+                        db.transaction::<_, (), DbErr>(|txn| {
+                            Box::pin(async move {
+                                super::util::set_required_packs(txn, model_id, &new_packs).await?;
+                                let mut active_model = mod_model.into_active_model();
+                                active_model.updated = ActiveValue::set(chrono::Utc::now());
+                                let saved_model = active_model.update(txn).await?;
+                                super::log::record(txn, JournalAction::Edited, &saved_model)
+                                    .await?;
+                                Ok(())
+                            })
+                        })
+                        .await?;
+                        current_state = EditMenuAction::EditMod(model_name, model_id);
+                    } else {
+                        current_state = EditMenuAction::EditMod(mod_model.name, mod_model.id);
+                    }
+                }
                 EditMenuAction::DeleteTag(mod_model, tag_name, tag_id) => {
                     let mod_name = mod_model.name.clone();
                     let confirm_result = Confirm::new(
@@ -308,9 +924,10 @@ pub async fn edit(
                                     mod_tag_relation::Entity::delete_by_id((mod_model.id, tag_id))
                                         .exec(txn)
                                         .await?;
-                                    active_model.updated =
-                                        ActiveValue::set(chrono::offset::Local::now());
-                                    active_model.save(txn).await?;
+                                    active_model.updated = ActiveValue::set(chrono::Utc::now());
+                                    let saved_model = active_model.update(txn).await?;
+                                    super::log::record(txn, JournalAction::Edited, &saved_model)
+                                        .await?;
                                     super::util::cleanup_tags(txn).await?;
                                     Ok(())
                                 })
@@ -341,8 +958,20 @@ pub async fn edit(
                         )
                         .prompt_skippable()?;
                     if let Some(new_tag) = new_tag_result {
+                        let new_tag =
+                            super::tag_typos::check_tag(&db, new_tag.trim().to_string()).await?;
                         if !existing_tags.contains(&new_tag) {
+                            let defaults =
+                                TagDefaults::for_tags(&db, std::slice::from_ref(&new_tag))
+                                    .await?
+                                    .missing_from(&mod_model);
+                            defaults.print_preview(std::slice::from_ref(&new_tag));
+                            let fill = !defaults.is_empty()
+                                && Confirm::new("Fill these in?").with_default(true).prompt()?;
                             let mut active_model = mod_model.clone().into_active_model();
+                            if fill {
+                                defaults.apply(&mut active_model);
+                            }
                             db.transaction::<_, (), DbErr>(|txn| {
                                 Box::pin(async move {
                                     let tag_id =
@@ -352,9 +981,10 @@ pub async fn edit(
                                         tag_id: ActiveValue::set(tag_id),
                                     };
                                     ModTagRelation::insert(relation_model).exec(txn).await?;
-                                    active_model.updated =
-                                        ActiveValue::set(chrono::offset::Local::now());
-                                    active_model.save(txn).await?;
+                                    active_model.updated = ActiveValue::set(chrono::Utc::now());
+                                    let saved_model = active_model.update(txn).await?;
+                                    super::log::record(txn, JournalAction::Edited, &saved_model)
+                                        .await?;
                                     Ok(())
                                 })
                             })
@@ -366,7 +996,12 @@ pub async fn edit(
                     }
                 }
                 EditMenuAction::BulkTag => {
-                    if SimsMod::find().limit(1).one(&db).await?.is_none() {
+                    if super::util::visible_mods()
+                        .limit(1)
+                        .one(&db)
+                        .await?
+                        .is_none()
+                    {
                         eprintln!("No mods in database to tag!");
                         current_state = EditMenuAction::MainMenu;
                         continue;
@@ -376,25 +1011,21 @@ pub async fn edit(
                         .with_autocomplete(super::util::TagAutoComplete::create(&db).await?)
                         .prompt_skippable()?;
                     if let Some(bulk_tag) = tag_result {
+                        let bulk_tag =
+                            super::tag_typos::check_tag(&db, bulk_tag.trim().to_string()).await?;
                         db.transaction::<_, (), DBOrInquireError>(|txn| {
                             Box::pin(async move {
                                 let tag_id =
                                     super::util::get_or_create_tag_id(txn, &bulk_tag).await?;
-                                let tag_mods = Tag::find_by_id(tag_id)
-                                    .find_with_related(SimsMod)
-                                    .all(txn)
+                                // Hidden mods are not offered, so they keep their tag
+                                let tag_mods = super::util::get_mods_for_tag_id(txn, tag_id)
                                     .await?
-                                    .pop()
-                                    .map(|(_, mods)| mods)
-                                    .expect_or_log(
-                                        format!("Failed to get existing mods for tag {}", bulk_tag)
-                                            .as_str(),
-                                    )
                                     .drain(..)
+                                    .filter(|m| !super::util::is_hidden(m))
                                     .map(|m| m.id)
                                     .collect::<HashSet<_>>();
 
-                                let all_mods = SimsMod::find().all(txn).await?;
+                                let all_mods = super::util::visible_mods().all(txn).await?;
 
                                 let mod_options = all_mods
                                     .iter()
@@ -453,6 +1084,43 @@ pub async fn edit(
                                         };
                                         ModTagRelation::insert(new_model).exec(txn).await?;
                                     }
+                                    let defaults =
+                                        TagDefaults::for_tags(txn, std::slice::from_ref(&bulk_tag))
+                                            .await?;
+                                    let to_fill: Vec<_> = all_mods
+                                        .iter()
+                                        .filter(|m| {
+                                            selected_mod_ids.contains(&m.id)
+                                                && !tag_mods.contains(&m.id)
+                                        })
+                                        .map(|m| (m, defaults.missing_from(m)))
+                                        .filter(|(_, missing)| !missing.is_empty())
+                                        .collect();
+                                    if !to_fill.is_empty() {
+                                        defaults.print_preview(std::slice::from_ref(&bulk_tag));
+                                        if Confirm::new(&format!(
+                                            "Fill these in on {} newly tagged mods?",
+                                            to_fill.len()
+                                        ))
+                                        .with_default(true)
+                                        .prompt()?
+                                        {
+                                            for (sims_mod, missing) in to_fill.into_iter() {
+                                                let mut active_model =
+                                                    sims_mod.clone().into_active_model();
+                                                missing.apply(&mut active_model);
+                                                active_model.updated =
+                                                    ActiveValue::set(chrono::Utc::now());
+                                                let saved_model = active_model.update(txn).await?;
+                                                super::log::record(
+                                                    txn,
+                                                    JournalAction::Edited,
+                                                    &saved_model,
+                                                )
+                                                .await?;
+                                            }
+                                        }
+                                    }
                                     ModTagRelation::delete_many()
                                         .filter(
                                             Condition::all()
@@ -478,53 +1146,72 @@ pub async fn edit(
                         current_state = EditMenuAction::MainMenu;
                     }
                 }
-                EditMenuAction::ScanNoVerify => {
-                    super::scan(Some(db.clone()), false, true, false).await?;
+                EditMenuAction::ScanNoVerify | EditMenuAction::ScanVerify
+                    if crate::util::is_game_running() =>
+                {
+                    eprintln!("The Sims 4 is running. Close the game before scanning.");
                     current_state = EditMenuAction::MainMenu;
                 }
-                EditMenuAction::ScanVerify => {
-                    super::scan(Some(db.clone()), true, true, false).await?;
+                EditMenuAction::ScanNoVerify | EditMenuAction::ScanVerify => {
+                    let touched = super::scan(
+                        Some(db.clone()),
+                        super::ScanOptions {
+                            verify: current_state == EditMenuAction::ScanVerify,
+                            fix: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
                     current_state = EditMenuAction::MainMenu;
+                    if !touched.is_empty() {
+                        current_state = EditMenuAction::ScannedModList(touched);
+                    }
+                }
+                EditMenuAction::ScannedModList(mod_ids) => {
+                    let menu_entries: Vec<_> = super::util::visible_mods()
+                        .filter(sims_mod::Column::Id.is_in(mod_ids.clone()))
+                        .all(&db)
+                        .await?
+                        .drain(..)
+                        .sorted_by_key(|m| mod_ids.iter().position(|id| *id == m.id))
+                        .map(|m| EditMenuAction::EditMod(m.name, m.id))
+                        .collect();
+                    if menu_entries.is_empty() {
+                        current_state = EditMenuAction::MainMenu;
+                        continue;
+                    }
+                    let menu_entries: Vec<_> = menu_entries
+                        .into_iter()
+                        .chain(std::iter::once(EditMenuAction::MainMenu))
+                        .collect();
+                    current_state = Select::new("Edit a mod the scan touched:", menu_entries)
+                        .with_help_message("Press ESC to return to the main menu")
+                        .prompt()
+                        .with_interrupted_default(EditMenuAction::MainMenu)?;
+                    if current_state != EditMenuAction::MainMenu {
+                        previous_menu_state = EditMenuAction::ScannedModList(mod_ids);
+                    }
                 }
                 EditMenuAction::Quit => {
                     println!("Exiting...");
                 }
             }
         }
-    } else {
-        // id: Option<i32>, name: Option<String>, source_url: Option<String>, tags: Option<Vec<String>>, version: Option<String>
-        let id = id.expect_or_log("ID not present in non-interactive mode!");
-        let sims_mod = SimsMod::find_by_id(id).one(&db).await?;
+    } else if let Some(id) = selection.mod_id {
+        let sims_mod = super::util::active_mods()
+            .filter(sims_mod::Column::Id.eq(id))
+            .one(&db)
+            .await?;
         if let Some(sims_mod) = sims_mod {
+            if let Some(Some(parent_id)) = fields.parent {
+                super::util::validate_parent(&db, id, parent_id).await?;
+            }
+            if let Some(tags) = fields.tags.take() {
+                fields.tags = Some(super::tag_typos::check_tags(&db, tags).await?);
+            }
             db.transaction::<_, (), DbErr>(|txn| {
                 Box::pin(async move {
-                    let mut active_model = sims_mod.clone().into_active_model();
-                    if let Some(name) = name {
-                        active_model.name = ActiveValue::set(name);
-                    }
-                    if let Some(source_url) = source_url {
-                        active_model.source_url = ActiveValue::set(source_url);
-                    }
-                    if let Some(version) = version {
-                        active_model.version = ActiveValue::set(version);
-                    }
-                    if let Some(mut tags) = tags {
-                        ModTagRelation::delete_many()
-                            .filter(Condition::any().add(mod_tag_relation::Column::ModId.eq(id)))
-                            .exec(txn)
-                            .await?;
-                        for tag in tags.drain(..) {
-                            let new_relation = mod_tag_relation::ActiveModel {
-                                mod_id: ActiveValue::Set(id),
-                                tag_id: ActiveValue::Set(
-                                    super::util::get_or_create_tag_id(txn, &tag).await?,
-                                ),
-                            };
-                            ModTagRelation::insert(new_relation).exec(txn).await?;
-                        }
-                    }
-                    active_model.updated = ActiveValue::set(chrono::offset::Local::now());
-                    active_model.save(txn).await?;
+                    apply_fields(txn, sims_mod, &fields).await?;
                     super::util::cleanup_tags(txn).await?;
                     Ok(())
                 })
@@ -533,6 +1220,8 @@ pub async fn edit(
         } else {
             eprintln!("No mod with mod ID {} found!", id);
         }
+    } else {
+        bulk_edit(&db, selection, fields).await?;
     }
 
     Ok(())
@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use colored::Colorize;
+use inquire::{Confirm, Select};
+use sea_orm::prelude::*;
+
+use super::util::{ModRoots, DISABLED_SUFFIX};
+use crate::commands::JournalAction;
+use crate::entities::sims_mod;
+use crate::scanner::{find_files, is_mod_file, ScanOptions};
+use crate::style::{self, Status};
+
+/// Ways to clean up a registered mod whose folder has no mod files
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum EmptyModAction {
+    RemoveAll,
+    RemoveEntry,
+    Keep,
+}
+
+impl std::fmt::Display for EmptyModAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EmptyModAction::RemoveAll => {
+                write!(f, "Move the folder to the trash and remove the mod")
+            }
+            EmptyModAction::RemoveEntry => write!(f, "Remove the mod, but leave the folder"),
+            EmptyModAction::Keep => write!(f, "Keep both"),
+        }
+    }
+}
+
+/// Counts the other files of a mod folder without any mod files, enabled or disabled,
+/// at any depth. None if the folder has mod files.
+pub fn empty_folder_files(root: &Path, mod_path: &Path) -> crate::Result<Option<usize>> {
+    let files = find_files(root, mod_path, &ScanOptions::all_files())?;
+    let has_mod_files = files.iter().any(|file| {
+        let name = file.path.to_string_lossy().to_lowercase();
+        is_mod_file(Path::new(
+            name.strip_suffix(DISABLED_SUFFIX).unwrap_or(&name),
+        ))
+    });
+    Ok((!has_mod_files).then_some(files.len()))
+}
+
+fn describe(other_files: usize) -> String {
+    match other_files {
+        0 => "folder is empty".to_string(),
+        1 => "folder has no mod files, only 1 other file".to_string(),
+        n => format!("folder has no mod files, only {} other files", n),
+    }
+}
+
+/// Reports a registered mod whose folder has no mod files. When fixing, offers to
+/// remove the mod and its folder. Returns whether the mod was removed.
+pub async fn handle_empty_mod(
+    db: &DatabaseConnection,
+    roots: &ModRoots,
+    sims_mod: &sims_mod::Model,
+    other_files: usize,
+    fix: bool,
+) -> crate::Result<bool> {
+    println!(
+        "Empty mod:     {} ({})",
+        style::mark(Status::Warning, &sims_mod.name).bold(),
+        describe(other_files)
+    );
+    if !fix {
+        return Ok(false);
+    }
+    let action = Select::new(
+        &format!("What do you want to do with {}?", sims_mod.name.bold()),
+        vec![
+            EmptyModAction::RemoveAll,
+            EmptyModAction::RemoveEntry,
+            EmptyModAction::Keep,
+        ],
+    )
+    .prompt()?;
+    if action == EmptyModAction::Keep {
+        return Ok(false);
+    }
+    if sims_mod.protected && !super::util::confirm_protected_mod(&sims_mod.name, "remove")? {
+        println!("Keeping protected mod {}", sims_mod.name.bold());
+        return Ok(false);
+    }
+    if action == EmptyModAction::RemoveAll {
+        super::remove::remove_mod(db, sims_mod, &roots.mod_dir(sims_mod)?, false).await?;
+    } else {
+        super::util::delete_mod_entry(db, sims_mod, JournalAction::Deleted).await?;
+    }
+    println!(
+        "Removed mod:   {}",
+        style::mark(Status::Missing, &sims_mod.name).bold()
+    );
+    Ok(true)
+}
+
+/// Reports an unregistered folder without mod files. When fixing, offers to move it
+/// to the trash instead of adding it.
+pub async fn handle_empty_folder(
+    db: &DatabaseConnection,
+    roots: &ModRoots,
+    root_id: Option<i32>,
+    path: &Path,
+    other_files: usize,
+    fix: bool,
+) -> crate::Result<()> {
+    let display_path = roots.display_path(root_id, path);
+    println!(
+        "Empty folder:  {} ({})",
+        style::mark(Status::Warning, &display_path).bold(),
+        describe(other_files)
+    );
+    if !fix
+        || !Confirm::new(&format!(
+            "Move {} to the trash?",
+            display_path.bold().blue()
+        ))
+        .with_default(true)
+        .prompt()?
+    {
+        return Ok(());
+    }
+    super::trashed::remove_path(db, &roots.dir(root_id)?.join(path), None, false).await?;
+    Ok(())
+}
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use tracing::{debug, info, warn};
+
+use crate::commands::{
+    fields::{FieldSelection, ModField},
+    util::VerificationPassed,
+};
+use crate::entities::sims_mod;
+
+/// Output formats supported by `export`
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Html,
+    Json,
+}
+
+/// A mod and its related data, gathered once for any export format
+struct ExportedMod {
+    sims_mod: sims_mod::Model,
+    tags: Vec<String>,
+    required_packs: Vec<String>,
+    verified: Option<bool>,
+}
+
+const UNTAGGED: &str = "Untagged";
+
+const HTML_STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2em; background: #f4f5f7; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 2px solid #3b7dd8; padding-bottom: 0.2em; margin-top: 1.5em; }
+.summary { color: #666; }
+.gallery { display: flex; flex-wrap: wrap; gap: 1em; }
+.mod { background: #fff; border-radius: 8px; box-shadow: 0 1px 3px rgba(0,0,0,0.15); width: 240px; overflow: hidden; }
+.mod img { width: 100%; height: 160px; object-fit: cover; background: #ddd; display: block; }
+.mod .placeholder { width: 100%; height: 160px; background: #ddd; display: flex; align-items: center; justify-content: center; color: #888; }
+.mod .info { padding: 0.6em 0.8em; }
+.mod .name { font-weight: bold; }
+.mod .version { color: #666; font-size: 0.9em; }
+.mod .rating { color: #d8a21b; }
+.mod a { font-size: 0.9em; word-break: break-all; }
+.badge { display: inline-block; font-size: 0.75em; font-weight: bold; padding: 0.1em 0.5em; border-radius: 4px; color: #fff; }
+.badge.passed { background: #2e9d48; }
+.badge.failed { background: #c93636; }
+.badge.keep { background: #3b7dd8; }
+.badge.signed { background: #6b4fbb; }
+"#;
+
+fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether a source URL is safe to link to from the page. Anything else, such as a
+/// `javascript:` URL, is shown as text so opening the page can't run it.
+fn is_web_link(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn image_mime_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Returns an `img` source for a thumbnail, embedding local files as data URIs
+fn thumbnail_source(thumbnail: &str) -> Option<String> {
+    if url::Url::parse(thumbnail)
+        .map(|u| u.scheme() == "http" || u.scheme() == "https")
+        .unwrap_or(false)
+    {
+        return Some(thumbnail.to_string());
+    }
+    let path = Path::new(thumbnail);
+    match crate::fileio::read(path) {
+        Ok(data) => Some(format!(
+            "data:{};base64,{}",
+            image_mime_type(path),
+            base64::engine::general_purpose::STANDARD.encode(data)
+        )),
+        Err(e) => {
+            warn!("Failed to read thumbnail {}: {}", thumbnail, e);
+            None
+        }
+    }
+}
+
+/// Renders the catalog as a page. `signed_by` is the fingerprint of the key the
+/// page will be signed with, shown as a badge under the title.
+fn render_html(mods: &[ExportedMod], signed_by: Option<&str>) -> String {
+    let mut by_tag: BTreeMap<&str, Vec<&ExportedMod>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for exported in mods.iter() {
+        if exported.tags.is_empty() {
+            untagged.push(exported);
+        }
+        for tag in exported.tags.iter() {
+            by_tag.entry(tag.as_str()).or_default().push(exported);
+        }
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str("<title>Sims 4 Mods</title>\n<style>");
+    html.push_str(HTML_STYLE);
+    html.push_str("</style>\n</head>\n<body>\n<h1>Sims 4 Mods</h1>\n");
+    if let Some(fingerprint) = signed_by {
+        html.push_str(&format!(
+            "<p><span class=\"badge signed\" title=\"Check with sync import or the .sig file next to this page\">Signed {}</span></p>\n",
+            escape_html(fingerprint)
+        ));
+    }
+    html.push_str(&format!(
+        "<p class=\"summary\">{} mods, exported {}</p>\n",
+        mods.len(),
+        crate::datetime::format_datetime(&chrono::Utc::now())
+    ));
+
+    let sections = by_tag
+        .iter()
+        .map(|(tag, mods)| (*tag, mods))
+        .chain(std::iter::once((UNTAGGED, &untagged)).filter(|(_, m)| !m.is_empty()));
+    for (tag, tag_mods) in sections {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<div class=\"gallery\">\n",
+            escape_html(tag)
+        ));
+        for exported in tag_mods.iter() {
+            let sims_mod = &exported.sims_mod;
+            html.push_str("<div class=\"mod\">\n");
+            match sims_mod.thumbnail.as_deref().and_then(thumbnail_source) {
+                Some(src) => html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\">\n",
+                    escape_html(&src),
+                    escape_html(&sims_mod.name)
+                )),
+                None => html.push_str("<div class=\"placeholder\">No image</div>\n"),
+            }
+            html.push_str("<div class=\"info\">\n");
+            html.push_str(&format!(
+                "<div class=\"name\">{}</div>\n",
+                escape_html(&sims_mod.name)
+            ));
+            html.push_str(&format!(
+                "<div class=\"version\">Version {}</div>\n",
+                escape_html(&sims_mod.version)
+            ));
+            if let Some(rating) = sims_mod.rating {
+                html.push_str(&format!(
+                    "<div class=\"rating\">{}</div>\n",
+                    super::util::format_rating(Some(rating))
+                ));
+            }
+            if let Some(verified) = exported.verified {
+                html.push_str(if verified {
+                    "<span class=\"badge passed\">Verified</span>\n"
+                } else {
+                    "<span class=\"badge failed\">Changed</span>\n"
+                });
+            }
+            if sims_mod.broken_at.is_some() {
+                html.push_str("<span class=\"badge failed\">Broken</span>\n");
+            }
+            if sims_mod.would_reinstall == Some(true) {
+                html.push_str("<span class=\"badge keep\">Would reinstall</span>\n");
+            }
+            let source_url = escape_html(&sims_mod.source_url);
+            if is_web_link(&sims_mod.source_url) {
+                html.push_str(&format!("<div><a href=\"{0}\">{0}</a></div>\n", source_url));
+            } else {
+                html.push_str(&format!("<div>{}</div>\n", source_url));
+            }
+            html.push_str("</div>\n</div>\n");
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_json(mods: &[ExportedMod], fields: &FieldSelection) -> crate::Result<String> {
+    let values: Vec<_> = mods
+        .iter()
+        .map(|exported| {
+            fields.to_json(
+                &exported.sims_mod,
+                Some(&exported.tags),
+                Some(&exported.required_packs),
+                exported.verified,
+            )
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&values)? + "\n")
+}
+
+pub async fn export(
+    format: ExportFormat,
+    output: Option<PathBuf>,
+    verify: bool,
+    fields: Option<Vec<ModField>>,
+    filter: super::TagFilter,
+    sign: bool,
+) -> crate::Result<()> {
+    let fields = FieldSelection::new(fields);
+    let db = crate::util::open_database().await?;
+
+    let mods = if filter.is_empty() {
+        super::util::visible_mods().all(&db).await?
+    } else {
+        super::util::get_mods_for_filter(&db, filter)
+            .await?
+            .into_iter()
+            .filter(|sims_mod| !super::util::is_hidden(sims_mod))
+            .collect()
+    };
+    let mut exported = Vec::new();
+    for sims_mod in mods.into_iter() {
+        debug!("Collecting export data for {}", sims_mod.name);
+        let tags = super::util::get_tags_for_mod(&db, sims_mod.id).await?;
+        let required_packs = super::util::get_required_packs_for_mod(&db, sims_mod.id).await?;
+        let verified = if verify {
+            Some(
+                super::util::verify_mod(&db, &sims_mod)
+                    .await?
+                    .verification_passed(),
+            )
+        } else {
+            None
+        };
+        exported.push(ExportedMod {
+            sims_mod,
+            tags,
+            required_packs,
+            verified,
+        });
+    }
+
+    let signed_by = if sign {
+        Some(crate::signing::fingerprint(
+            &crate::signing::own_public_key()?,
+        ))
+    } else {
+        None
+    };
+    let rendered = match format {
+        ExportFormat::Html => render_html(&exported, signed_by.as_deref()),
+        ExportFormat::Json => render_json(&exported, &fields)?,
+    };
+
+    if let Some(output) = output {
+        info!("Writing export to {}", output.display());
+        crate::fileio::write(&output, rendered)?;
+        if sign {
+            let fingerprint = crate::signing::sign_file(&output)?;
+            println!("Signed {} with key {}", output.display(), fingerprint);
+        }
+    } else {
+        print!("{}", rendered);
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::{prelude::*, QueryOrder};
+use tracing::debug;
+
+use crate::entities::*;
+use crate::scanner::{self, find_files, ScanOptions};
+
+/// Files in a mod's folder that scans don't track, with their sizes.
+/// Script mods' `mod_data` folders, ignored paths, and the `.s4ignore` itself are skipped.
+fn untracked_files(
+    root: &Path,
+    mod_path: &Path,
+    extra_extensions: &[String],
+) -> crate::Result<Vec<(PathBuf, u64)>> {
+    Ok(find_files(root, mod_path, &ScanOptions::all_files())?
+        .into_iter()
+        // Game files directly in the mod's folder are the ones scans track
+        .filter(|file| {
+            file.path.components().count() > 1
+                || !scanner::is_tracked_file(&file.path, extra_extensions)
+        })
+        .map(|file| (file.path, file.size))
+        .collect())
+}
+
+/// Groups a file for the statistics by extension. Mod files in subfolders are
+/// listed separately, since scans don't track them.
+fn extension_key(file: &Path) -> String {
+    let extension = file
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "(none)".to_string());
+    if scanner::MOD_EXTENSIONS.contains(&extension.as_str()) {
+        format!("{} (in subfolder)", extension)
+    } else {
+        extension
+    }
+}
+
+/// Lists untracked files in the given mods' folders, followed by statistics per file type
+pub async fn report_extras(db: &DatabaseConnection, mod_ids: &HashSet<i32>) -> crate::Result<()> {
+    let roots = super::util::ModRoots::load(db).await?;
+    let mods = super::util::active_mods()
+        .filter(sims_mod::Column::Id.is_in(mod_ids.iter().copied()))
+        .order_by_asc(sims_mod::Column::Name)
+        .all(db)
+        .await?;
+
+    let mut stats: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    let mut mods_with_extras = 0;
+    for sims_mod in mods.iter() {
+        let root = roots.dir(sims_mod.root_id)?;
+        let extras = untracked_files(
+            root,
+            Path::new(&sims_mod.directory),
+            &super::util::tracked_extensions(db, sims_mod.id).await?,
+        )?;
+        debug!("{} untracked files in {}", extras.len(), sims_mod.name);
+        if extras.is_empty() {
+            continue;
+        }
+        mods_with_extras += 1;
+        let total: u64 = extras.iter().map(|(_, size)| size).sum();
+        println!(
+            "{} ({} untracked files, {})",
+            sims_mod.name.bold(),
+            extras.len(),
+            super::util::format_size(total)
+        );
+        for (file, size) in extras.iter() {
+            println!(
+                "  {} {}",
+                file.display(),
+                format!("({})", super::util::format_size(*size)).dimmed()
+            );
+            let entry = stats.entry(extension_key(file)).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        }
+    }
+
+    if stats.is_empty() {
+        println!("No untracked files found.");
+        return Ok(());
+    }
+    println!("\n{}", "Untracked file types:".bold());
+    let mut rows: Vec<(&String, &(usize, u64))> = stats.iter().collect();
+    rows.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(b.0)));
+    let width = rows
+        .iter()
+        .map(|(extension, _)| extension.chars().count())
+        .max()
+        .unwrap_or_default();
+    for (extension, (count, size)) in rows.iter() {
+        println!(
+            "  {} {:>5} {:>10}",
+            format!("{:<width$}", extension).bold(),
+            count,
+            super::util::format_size(*size)
+        );
+    }
+    println!(
+        "{} of {} mods contain untracked files.",
+        mods_with_extras,
+        mods.len()
+    );
+    Ok(())
+}
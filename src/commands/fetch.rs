@@ -0,0 +1,336 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::prelude::*;
+use tracing::{debug, info};
+
+use crate::commands::util::VerificationPassed;
+use crate::commands::UpdateChannel;
+use crate::entities::*;
+use crate::style::{self, Status};
+
+/// Rewrites share links of known hosts into links to the file itself
+fn direct_download_url(download_url: &str) -> crate::Result<url::Url> {
+    let mut url = url::Url::parse(download_url)?;
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    if host == "dropbox.com" || host.ends_with(".dropbox.com") {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != "dl")
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("dl", "1");
+    }
+    Ok(url)
+}
+
+/// Picks the downloaded file's name from the Content-Disposition header or the URL
+fn file_name(response: &reqwest::Response) -> Option<String> {
+    let from_header = response
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .split(';')
+                .filter_map(|part| part.trim().strip_prefix("filename="))
+                .next()
+                .map(|name| name.trim_matches('"').to_string())
+        });
+    from_header
+        .or_else(|| {
+            response
+                .url()
+                .path_segments()
+                .and_then(|mut s| s.next_back())
+                .map(|s| s.to_string())
+        })
+        // Only keep the last component, so a header cannot point outside the staging folder
+        .and_then(|name| {
+            Path::new(&name)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+        })
+        .filter(|name| !name.is_empty())
+}
+
+/// Folder holding downloads waiting to be installed
+fn get_staging_dir(mod_id: i32) -> crate::Result<PathBuf> {
+    let staging_dir = crate::util::get_data_dir()?
+        .join("staging")
+        .join(mod_id.to_string());
+    if !staging_dir.is_dir() {
+        info!("Creating staging directory");
+        crate::fileio::create_dir_all(&staging_dir)?;
+    }
+    Ok(staging_dir)
+}
+
+/// Moves a staged file into the mod's folder, trashing the file it replaces
+async fn install(
+    db: &DatabaseConnection,
+    sims_mod: &sims_mod::Model,
+    staged_path: &Path,
+    name: &str,
+) -> crate::Result<()> {
+    let roots = super::util::ModRoots::load(db).await?;
+    let mod_dir = roots.mod_dir(sims_mod)?;
+    if !mod_dir.is_dir() {
+        eprintln!("The folder of {} is missing!", sims_mod.name);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    }
+    let target = mod_dir.join(name);
+    if target.exists() {
+        info!("Trashing replaced file {}", target.display());
+        super::trashed::remove_path(db, &target, Some(sims_mod), false).await?;
+    }
+    crate::transfer::run("install", |transfer| {
+        transfer.copy_file(staged_path, &target)
+    })?;
+    crate::fileio::remove_file(staged_path)?;
+    println!(
+        "Installed {}. Run {} to record the update.",
+        name.bold(),
+        "scan --verify --fix".bold()
+    );
+    Ok(())
+}
+
+/// Downloads a file, rejecting web pages. Returns the file's name and contents.
+async fn download(download_url: &str) -> crate::Result<(String, Vec<u8>)> {
+    let Ok(download_url) = direct_download_url(download_url) else {
+        eprintln!("{} is not a valid URL!", download_url);
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+    };
+
+    info!("Fetching {}", download_url);
+    let response = reqwest::get(download_url.clone())
+        .await?
+        .error_for_status()?;
+    let is_page = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("text/html"))
+        .unwrap_or(false);
+    if is_page {
+        eprintln!(
+            "{} is a web page, not a file. Set a direct file link with {}.",
+            download_url,
+            "edit --download-url".bold()
+        );
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    }
+    let Some(name) = file_name(&response) else {
+        eprintln!("Could not tell the name of the file at {}!", download_url);
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    };
+    let data = response.bytes().await?.to_vec();
+    debug!("Downloaded {} bytes as {}", data.len(), name);
+    Ok((name, data))
+}
+
+/// Whether a downloaded mod file's hash is already stored for the mod
+async fn is_installed(
+    db: &DatabaseConnection,
+    sims_mod: &sims_mod::Model,
+    name: &str,
+    data: &[u8],
+) -> crate::Result<bool> {
+    if !crate::scanner::is_mod_file(Path::new(name)) {
+        return Ok(false);
+    }
+    let hash = crate::config::get().hash_algorithm().hash(data);
+    let installed = super::util::get_hashes_for_mod(db, sims_mod.id).await?;
+    Ok(installed.values().any(|h| *h == hash))
+}
+
+/// Hash of a downloaded archive, or nothing for a mod file, which is compared with
+/// the installed files instead
+fn archive_hash(name: &str, data: &[u8]) -> Option<String> {
+    (!crate::scanner::is_mod_file(Path::new(name)))
+        .then(|| crate::config::get().hash_algorithm().hash(data))
+}
+
+/// Downloads a mod's file from its download or source URL and stages it for install
+/// when it differs from the installed files. Without a mod ID, every mod is updated
+/// as its update channel allows.
+pub async fn fetch(
+    mod_id: Option<i32>,
+    url: Option<String>,
+    install_file: bool,
+) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let Some(mod_id) = mod_id else {
+        return fetch_all(&db, install_file).await;
+    };
+
+    let Some(sims_mod) = super::util::active_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(&db)
+        .await?
+    else {
+        eprintln!("No mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+    if UpdateChannel::from_stored(&sims_mod.update_channel) == UpdateChannel::Manual
+        && !Confirm::new(
+            format!(
+                "{} is only updated by hand. Fetch it anyway?",
+                sims_mod.name.bold()
+            )
+            .as_str(),
+        )
+        .with_default(false)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let download_url = url
+        .or_else(|| sims_mod.download_url.clone())
+        .unwrap_or_else(|| sims_mod.source_url.clone());
+    let (name, data) = download(&download_url).await?;
+
+    let archive_hash = archive_hash(&name, &data);
+    if let Some(archive_hash) = &archive_hash {
+        if let Some(installed) = super::archives::find_installed_archive(&db, archive_hash).await? {
+            super::archives::report_installed_archive(&name, &installed);
+            return Ok(());
+        }
+    }
+    if is_installed(&db, &sims_mod, &name, &data).await? {
+        let verified = super::util::verify_mod(&db, &sims_mod)
+            .await?
+            .verification_passed();
+        println!(
+            "{} is already installed for {}{}.",
+            name.bold(),
+            sims_mod.name.bold(),
+            if verified {
+                ""
+            } else {
+                ", but the installed files changed since they were recorded"
+            }
+        );
+        return Ok(());
+    }
+
+    let staged_path = get_staging_dir(sims_mod.id)?.join(&name);
+    crate::fileio::write(&staged_path, &data)?;
+    println!(
+        "Staged {} for {} at {}",
+        name.bold(),
+        sims_mod.name.bold(),
+        staged_path.display().to_string().blue()
+    );
+
+    if let Some(archive_hash) = &archive_hash {
+        super::archives::record_installed_archive(&db, &sims_mod, archive_hash).await?;
+        println!(
+            "Unpack it into the mod's folder, then run {}.",
+            "scan --verify --fix".bold()
+        );
+        return Ok(());
+    }
+    if install_file
+        && Confirm::new(format!("Install {} into {}?", name, sims_mod.name).as_str())
+            .with_default(true)
+            .prompt()?
+    {
+        install(&db, &sims_mod, &staged_path, &name).await?;
+    } else if !install_file {
+        println!(
+            "Run with {} to install it, or copy it into the mod's folder yourself.",
+            "--install".bold()
+        );
+    }
+    Ok(())
+}
+
+/// Checks every mod with a download URL for a new file, following its update channel:
+/// manual mods are skipped, new files of notify mods are reported, and new files of
+/// auto-fetch mods are staged, or installed without asking when `install_file` is set
+async fn fetch_all(db: &DatabaseConnection, install_file: bool) -> crate::Result<()> {
+    let mods: Vec<_> = super::util::active_mods()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|sims_mod| sims_mod.download_url.is_some())
+        .collect();
+    let mut skipped = 0;
+    let mut available = 0;
+    for sims_mod in mods.iter() {
+        let channel = UpdateChannel::from_stored(&sims_mod.update_channel);
+        if channel == UpdateChannel::Manual {
+            debug!("Skipping {}, which is updated by hand", sims_mod.name);
+            skipped += 1;
+            continue;
+        }
+        let Some(download_url) = &sims_mod.download_url else {
+            continue;
+        };
+        let (name, data) = match download(download_url).await {
+            Ok(downloaded) => downloaded,
+            Err(e) => {
+                println!(
+                    "Could not fetch {}: {}",
+                    style::mark(Status::Failed, &sims_mod.name).bold(),
+                    e
+                );
+                continue;
+            }
+        };
+        let archive_hash = archive_hash(&name, &data);
+        let installed_archive = match &archive_hash {
+            Some(archive_hash) => super::archives::find_installed_archive(db, archive_hash).await?,
+            None => None,
+        };
+        if installed_archive.is_some() || is_installed(db, sims_mod, &name, &data).await? {
+            println!(
+                "Up to date:    {}",
+                style::mark(Status::Passed, &sims_mod.name).bold()
+            );
+            continue;
+        }
+        available += 1;
+        if channel == UpdateChannel::Notify {
+            println!(
+                "New download:  {} ({})",
+                style::mark(Status::Changed, &sims_mod.name).bold(),
+                name
+            );
+            continue;
+        }
+        let staged_path = get_staging_dir(sims_mod.id)?.join(&name);
+        crate::fileio::write(&staged_path, &data)?;
+        if let Some(archive_hash) = &archive_hash {
+            super::archives::record_installed_archive(db, sims_mod, archive_hash).await?;
+        }
+        if install_file && crate::scanner::is_mod_file(Path::new(&name)) {
+            install(db, sims_mod, &staged_path, &name).await?;
+        } else {
+            println!(
+                "Staged:        {} ({} at {})",
+                style::mark(Status::Changed, &sims_mod.name).bold(),
+                name,
+                staged_path.display().to_string().blue()
+            );
+        }
+    }
+    println!(
+        "{} of {} mods with a download URL have a new file.{}",
+        available.to_string().bold(),
+        mods.len(),
+        if skipped > 0 {
+            format!(" Skipped {} mods that are updated by hand.", skipped)
+        } else {
+            String::new()
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
+
+use crate::entities::sims_mod;
+
+/// Mod fields that can be selected for display or export.
+/// The mod name is always included.
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModField {
+    Version,
+    Id,
+    Updated,
+    Added,
+    Source,
+    #[value(alias = "download_url")]
+    DownloadUrl,
+    #[value(alias = "source_type")]
+    SourceType,
+    #[value(alias = "update_channel")]
+    UpdateChannel,
+    Composition,
+    Parent,
+    Thumbnail,
+    Protected,
+    Frozen,
+    Nsfw,
+    Rating,
+    #[value(alias = "would_reinstall")]
+    WouldReinstall,
+    #[value(alias = "recheck_after")]
+    RecheckAfter,
+    Broken,
+    Directory,
+    #[value(alias = "required_packs")]
+    RequiredPacks,
+    Tags,
+    Verification,
+}
+
+impl ModField {
+    pub fn all() -> &'static [ModField] {
+        &[
+            ModField::Version,
+            ModField::Id,
+            ModField::Updated,
+            ModField::Added,
+            ModField::Source,
+            ModField::DownloadUrl,
+            ModField::SourceType,
+            ModField::UpdateChannel,
+            ModField::Composition,
+            ModField::Parent,
+            ModField::Thumbnail,
+            ModField::Protected,
+            ModField::Frozen,
+            ModField::Nsfw,
+            ModField::Rating,
+            ModField::WouldReinstall,
+            ModField::RecheckAfter,
+            ModField::Broken,
+            ModField::Directory,
+            ModField::RequiredPacks,
+            ModField::Tags,
+            ModField::Verification,
+        ]
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ModField::Version => "version",
+            ModField::Id => "id",
+            ModField::Updated => "updated",
+            ModField::Added => "added_at",
+            ModField::Source => "source",
+            ModField::DownloadUrl => "download_url",
+            ModField::SourceType => "source_type",
+            ModField::UpdateChannel => "update_channel",
+            ModField::Composition => "composition",
+            ModField::Parent => "parent_mod_id",
+            ModField::Thumbnail => "thumbnail",
+            ModField::Protected => "protected",
+            ModField::Frozen => "frozen",
+            ModField::Nsfw => "nsfw",
+            ModField::Rating => "rating",
+            ModField::WouldReinstall => "would_reinstall",
+            ModField::RecheckAfter => "recheck_after",
+            ModField::Broken => "broken",
+            ModField::Directory => "directory",
+            ModField::RequiredPacks => "required_packs",
+            ModField::Tags => "tags",
+            ModField::Verification => "verification",
+        }
+    }
+
+    /// JSON Schema of the value `FieldSelection::to_json` writes for the field
+    pub fn json_schema(&self) -> Value {
+        use super::composition::Composition;
+        use serde_json::json;
+
+        let nullable = |schema_type: &str| json!({ "type": [schema_type, "null"] });
+        let string_list = json!({ "type": "array", "items": { "type": "string" } });
+        match self {
+            ModField::Version | ModField::Source | ModField::SourceType | ModField::Directory => {
+                json!({ "type": "string" })
+            }
+            ModField::UpdateChannel => json!({
+                "type": "string",
+                "description": "manual, notify, or auto-fetch",
+            }),
+            ModField::Id => json!({ "type": "integer" }),
+            ModField::Updated => json!({ "type": "string", "format": "date-time" }),
+            ModField::Added => json!({ "type": ["string", "null"], "format": "date-time" }),
+            ModField::RecheckAfter => json!({ "type": ["string", "null"], "format": "date" }),
+            ModField::DownloadUrl | ModField::Thumbnail => nullable("string"),
+            ModField::Parent => nullable("integer"),
+            ModField::Rating => json!({ "type": ["integer", "null"], "minimum": 1, "maximum": 5 }),
+            ModField::WouldReinstall => nullable("boolean"),
+            ModField::Protected | ModField::Frozen | ModField::Nsfw | ModField::Verification => {
+                json!({ "type": "boolean" })
+            }
+            ModField::Composition => json!({
+                "type": "object",
+                "properties": {
+                    "kind": {
+                        "enum": [
+                            Composition::Script.as_str(),
+                            Composition::Mixed.as_str(),
+                            Composition::CcOnly.as_str(),
+                            Composition::Empty.as_str(),
+                        ],
+                    },
+                    "packages": { "type": "integer" },
+                    "scripts": { "type": "integer" },
+                },
+                "required": ["kind", "packages", "scripts"],
+            }),
+            ModField::Broken => json!({
+                "type": ["object", "null"],
+                "properties": {
+                    "since": { "type": "string", "format": "date-time" },
+                    "reason": { "type": ["string", "null"] },
+                },
+                "required": ["since", "reason"],
+            }),
+            ModField::RequiredPacks | ModField::Tags => string_list,
+        }
+    }
+}
+
+/// The set of fields selected with `--fields`, defaulting to all of them
+#[derive(Clone, Debug)]
+pub struct FieldSelection {
+    fields: HashSet<ModField>,
+}
+
+impl FieldSelection {
+    pub fn new(fields: Option<Vec<ModField>>) -> Self {
+        FieldSelection {
+            fields: match fields {
+                Some(fields) => fields.into_iter().collect(),
+                None => ModField::all().iter().copied().collect(),
+            },
+        }
+    }
+
+    pub fn contains(&self, field: ModField) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// Selected fields in their canonical order
+    pub fn iter(&self) -> impl Iterator<Item = ModField> + '_ {
+        ModField::all()
+            .iter()
+            .copied()
+            .filter(|f| self.fields.contains(f))
+    }
+
+    /// Builds a JSON object for a mod containing only the selected fields
+    pub fn to_json(
+        &self,
+        sims_mod: &sims_mod::Model,
+        tags: Option<&[String]>,
+        required_packs: Option<&[String]>,
+        verified: Option<bool>,
+    ) -> Value {
+        let mut object = Map::new();
+        object.insert("name".to_string(), Value::from(sims_mod.name.clone()));
+        for field in self.iter() {
+            let value = match field {
+                ModField::Version => Value::from(sims_mod.version.clone()),
+                ModField::Id => Value::from(sims_mod.id),
+                ModField::Updated => Value::from(sims_mod.updated.to_rfc3339()),
+                ModField::Added => Value::from(sims_mod.added_at.map(|d| d.to_rfc3339())),
+                ModField::Source => Value::from(sims_mod.source_url.clone()),
+                ModField::DownloadUrl => Value::from(sims_mod.download_url.clone()),
+                ModField::SourceType => Value::from(sims_mod.source_type.clone()),
+                ModField::UpdateChannel => Value::from(sims_mod.update_channel.clone()),
+                ModField::Composition => serde_json::json!({
+                    "kind": super::composition::Composition::of(sims_mod).as_str(),
+                    "packages": sims_mod.package_count,
+                    "scripts": sims_mod.script_count,
+                }),
+                ModField::Parent => Value::from(sims_mod.parent_mod_id),
+                ModField::Thumbnail => Value::from(sims_mod.thumbnail.clone()),
+                ModField::Protected => Value::from(sims_mod.protected),
+                ModField::Frozen => Value::from(sims_mod.frozen),
+                ModField::Nsfw => Value::from(sims_mod.nsfw),
+                ModField::Rating => Value::from(sims_mod.rating),
+                ModField::WouldReinstall => Value::from(sims_mod.would_reinstall),
+                ModField::RecheckAfter => {
+                    Value::from(sims_mod.recheck_after.map(|d| d.to_string()))
+                }
+                ModField::Broken => match sims_mod.broken_at {
+                    Some(broken_at) => serde_json::json!({
+                        "since": broken_at.to_rfc3339(),
+                        "reason": sims_mod.broken_reason,
+                    }),
+                    None => Value::Null,
+                },
+                ModField::Directory => Value::from(sims_mod.directory.clone()),
+                ModField::RequiredPacks => match required_packs {
+                    Some(required_packs) => Value::from(required_packs.to_vec()),
+                    None => continue,
+                },
+                ModField::Tags => match tags {
+                    Some(tags) => Value::from(tags.to_vec()),
+                    None => continue,
+                },
+                ModField::Verification => match verified {
+                    Some(verified) => Value::from(verified),
+                    None => continue,
+                },
+            };
+            object.insert(field.key().to_string(), value);
+        }
+        Value::Object(object)
+    }
+}
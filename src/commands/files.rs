@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::{prelude::*, QueryOrder};
+
+use crate::entities::{prelude::*, *};
+use crate::scanner::{find_files, ScanOptions};
+
+/// Where a matching file was found
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FileState {
+    /// Tracked, and on disk if the disk was searched
+    Tracked,
+    /// On disk but not tracked by scans
+    Untracked,
+    /// Tracked but no longer on disk
+    Missing,
+}
+
+/// Matches a file against the pattern: its name, or for patterns with a `/`, its
+/// path in the mod's folder. Case is ignored, as on Windows.
+fn matches(pattern: &glob::Pattern, file: &Path) -> bool {
+    let options = glob::MatchOptions {
+        case_sensitive: false,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    if pattern.as_str().contains('/') {
+        return pattern.matches_path_with(file, options);
+    }
+    file.file_name()
+        .is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options))
+}
+
+/// Lists the mods owning files that match a glob pattern, from the stored hashes
+/// and optionally from the mod folders on disk
+pub async fn find_mod_files(pattern: String, disk: bool) -> crate::Result<()> {
+    let glob = match glob::Pattern::new(&pattern) {
+        Ok(glob) => glob,
+        Err(e) => {
+            eprintln!("Invalid pattern {}: {}", pattern.bold(), e);
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+        }
+    };
+    let db = crate::util::open_database().await?;
+    let mods = super::util::active_mods()
+        .order_by_asc(sims_mod::Column::Name)
+        .all(&db)
+        .await?;
+    let mut tracked: HashMap<i32, Vec<PathBuf>> = HashMap::new();
+    for hash in ModHash::find().all(&db).await? {
+        tracked
+            .entry(hash.mod_id)
+            .or_default()
+            .push(PathBuf::from(hash.file));
+    }
+
+    let roots = super::util::ModRoots::load(&db).await?;
+    let on_disk_options = ScanOptions {
+        respect_ignores: false,
+        ..ScanOptions::all_files()
+    };
+    let (mut file_count, mut mod_count) = (0, 0);
+    for sims_mod in mods.iter() {
+        let mut found: BTreeMap<PathBuf, FileState> = tracked
+            .remove(&sims_mod.id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|file| matches(&glob, file))
+            .map(|file| (file, FileState::Tracked))
+            .collect();
+        if disk {
+            let root = roots.dir(sims_mod.root_id)?;
+            let mod_path = Path::new(&sims_mod.directory);
+            let on_disk: Vec<PathBuf> = if root.join(mod_path).is_dir() {
+                find_files(root, mod_path, &on_disk_options)?
+                    .into_iter()
+                    .map(|file| file.path)
+                    .filter(|file| matches(&glob, file))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            for state in found.values_mut() {
+                *state = FileState::Missing;
+            }
+            for file in on_disk.into_iter() {
+                let state = found.entry(file).or_insert(FileState::Untracked);
+                if *state == FileState::Missing {
+                    *state = FileState::Tracked;
+                }
+            }
+        }
+        if found.is_empty() {
+            continue;
+        }
+
+        mod_count += 1;
+        file_count += found.len();
+        println!(
+            "{} ({}) {}",
+            sims_mod.name.bold(),
+            sims_mod.id,
+            roots
+                .display_path(sims_mod.root_id, Path::new(&sims_mod.directory))
+                .dimmed()
+        );
+        for (file, state) in found.iter() {
+            let note = match state {
+                FileState::Tracked => "".normal(),
+                FileState::Untracked => "(not tracked)".yellow(),
+                FileState::Missing => "(missing)".red(),
+            };
+            println!("  {} {}", file.display(), note);
+        }
+    }
+
+    if mod_count == 0 {
+        println!("No files matching {} found.", pattern.bold());
+    } else {
+        println!(
+            "{} files matching {} in {} mods.",
+            file_count.to_string().bold(),
+            pattern.bold(),
+            mod_count.to_string().bold()
+        );
+    }
+    Ok(())
+}
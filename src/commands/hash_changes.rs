@@ -0,0 +1,343 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use sea_orm::{prelude::*, ActiveValue};
+use serde_json::{json, Value};
+use tracing::debug;
+use tracing_unwrap::OptionExt;
+
+use crate::entities::{mod_hash, prelude::*, sims_mod};
+use crate::style::{self, Status};
+
+/// What replacing a mod's stored file hashes changes, sorted by file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashChanges {
+    /// Files that start being tracked, with their new hashes
+    pub added: BTreeMap<PathBuf, String>,
+    /// Files that stop being tracked, with their stored hashes
+    pub removed: BTreeMap<PathBuf, String>,
+    /// Files whose hash changes, with their stored and new hashes
+    pub changed: BTreeMap<PathBuf, (String, String)>,
+}
+
+impl HashChanges {
+    /// Compares a mod's stored hashes with the ones about to replace them
+    pub fn between(
+        stored: &HashMap<PathBuf, String>,
+        current: &HashMap<PathBuf, String>,
+    ) -> HashChanges {
+        let mut changes = HashChanges::default();
+        for (file, hash) in current.iter() {
+            match stored.get(file) {
+                None => {
+                    changes.added.insert(file.clone(), hash.clone());
+                }
+                Some(old_hash) if old_hash != hash => {
+                    changes
+                        .changed
+                        .insert(file.clone(), (old_hash.clone(), hash.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        for (file, hash) in stored.iter() {
+            if !current.contains_key(file) {
+                changes.removed.insert(file.clone(), hash.clone());
+            }
+        }
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of files affected
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "added": self.added.iter().map(|(file, hash)| json!({
+                "file": file.to_string_lossy(),
+                "hash": hash,
+            })).collect::<Vec<_>>(),
+            "removed": self.removed.iter().map(|(file, hash)| json!({
+                "file": file.to_string_lossy(),
+                "hash": hash,
+            })).collect::<Vec<_>>(),
+            "changed": self.changed.iter().map(|(file, (old_hash, new_hash))| json!({
+                "file": file.to_string_lossy(),
+                "old_hash": old_hash,
+                "new_hash": new_hash,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Prints one indented line per affected file
+    pub fn print(&self) {
+        for file in self.added.keys() {
+            println!(
+                "    {} {}",
+                style::mark(Status::New, "added  "),
+                file.display()
+            );
+        }
+        for file in self.removed.keys() {
+            println!(
+                "    {} {}",
+                style::mark(Status::Missing, "removed"),
+                file.display()
+            );
+        }
+        for file in self.changed.keys() {
+            println!(
+                "    {} {}",
+                style::mark(Status::Changed, "changed"),
+                file.display()
+            );
+        }
+    }
+}
+
+/// Hash rows inserted per statement. Each row binds three values, which keeps a
+/// statement well within SQLite's limit on bound values.
+const INSERT_BATCH_SIZE: usize = 500;
+
+/// Inserts hash rows for a mod's files, batching many rows into each statement
+pub async fn insert_hashes<C>(
+    db: &C,
+    mod_id: i32,
+    hashes: impl IntoIterator<Item = (PathBuf, String)>,
+) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let new_hashes: Vec<_> = hashes
+        .into_iter()
+        .map(|(file, hash)| {
+            debug!("Saving hash for {} ({})", file.display(), hash);
+            mod_hash::ActiveModel {
+                mod_id: ActiveValue::Set(mod_id),
+                file: ActiveValue::Set(
+                    file.to_str()
+                        .expect_or_log("Failed to convert path to UTF-8")
+                        .to_string(),
+                ),
+                hash: ActiveValue::Set(hash),
+                ..Default::default()
+            }
+        })
+        .collect();
+    for batch in new_hashes.chunks(INSERT_BATCH_SIZE) {
+        ModHash::insert_many(batch.to_vec()).exec(db).await?;
+    }
+    Ok(())
+}
+
+/// Brings a mod's stored hashes in line with `hashes`: new files are inserted, changed
+/// ones updated in place, and files no longer present deleted. Unchanged rows keep their
+/// IDs. Returns what changed.
+pub async fn store_hashes<C>(
+    db: &C,
+    mod_id: i32,
+    hashes: &HashMap<PathBuf, String>,
+) -> Result<HashChanges, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let mut stored = HashMap::new();
+    let mut row_ids = HashMap::new();
+    for row in ModHash::find()
+        .filter(mod_hash::Column::ModId.eq(mod_id))
+        .all(db)
+        .await?
+    {
+        let file = PathBuf::from(row.file);
+        row_ids.insert(file.clone(), row.id);
+        stored.insert(file, row.hash);
+    }
+    let changes = HashChanges::between(&stored, hashes);
+
+    insert_hashes(
+        db,
+        mod_id,
+        changes
+            .added
+            .iter()
+            .map(|(file, hash)| (file.clone(), hash.clone())),
+    )
+    .await?;
+    for (file, (_, new_hash)) in changes.changed.iter() {
+        debug!("Updating hash for {} ({})", file.display(), new_hash);
+        let changed_hash = mod_hash::ActiveModel {
+            id: ActiveValue::Unchanged(row_ids[file]),
+            hash: ActiveValue::Set(new_hash.clone()),
+            ..Default::default()
+        };
+        ModHash::update(changed_hash).exec(db).await?;
+    }
+    if !changes.removed.is_empty() {
+        debug!("Deleting {} hashes", changes.removed.len());
+        ModHash::delete_many()
+            .filter(mod_hash::Column::Id.is_in(changes.removed.keys().map(|file| row_ids[file])))
+            .exec(db)
+            .await?;
+    }
+    Ok(changes)
+}
+
+/// Counts of a journal entry's hash changes, e.g. `1 added, 0 removed, 2 changed`.
+/// None if the details are not a change set.
+pub fn summarize(details: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(details).ok()?;
+    let count = |key: &str| value.get(key).and_then(Value::as_array).map(Vec::len);
+    Some(format!(
+        "{} added, {} removed, {} changed",
+        count("added")?,
+        count("removed")?,
+        count("changed")?
+    ))
+}
+
+/// Renders the hash changes found for several mods as a JSON report
+pub fn render_report(changes: &[(sims_mod::Model, HashChanges)]) -> crate::Result<String> {
+    let mods: Vec<Value> = changes
+        .iter()
+        .map(|(sims_mod, changes)| {
+            let mut value = changes.to_json();
+            value["id"] = json!(sims_mod.id);
+            value["name"] = json!(sims_mod.name);
+            value["directory"] = json!(sims_mod.directory);
+            value
+        })
+        .collect();
+    let value = json!({
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "mods": mods,
+    });
+    Ok(serde_json::to_string_pretty(&value)? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{Database, DatabaseConnection};
+    use sea_orm_migration::MigratorTrait;
+
+    use super::*;
+
+    fn hashes(entries: &[(&str, &str)]) -> HashMap<PathBuf, String> {
+        entries
+            .iter()
+            .map(|(file, hash)| (PathBuf::from(file), hash.to_string()))
+            .collect()
+    }
+
+    /// A fresh in-memory database holding one mod
+    async fn database_with_mod() -> (DatabaseConnection, sims_mod::Model) {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        crate::migrator::Migrator::up(&db, None).await.unwrap();
+        let sims_mod = SimsMod::insert(sims_mod::ActiveModel {
+            name: ActiveValue::Set("Cute Hair".to_string()),
+            directory: ActiveValue::Set("CuteHair".to_string()),
+            source_url: ActiveValue::Set("https://example.com/cute-hair".to_string()),
+            version: ActiveValue::Set("1.0".to_string()),
+            updated: ActiveValue::Set(chrono::Utc::now()),
+            ..Default::default()
+        })
+        .exec_with_returning(&db)
+        .await
+        .unwrap();
+        (db, sims_mod)
+    }
+
+    #[test]
+    fn sorts_files_into_added_removed_and_changed() {
+        let stored = hashes(&[("kept", "1"), ("changed", "2"), ("removed", "3")]);
+        let current = hashes(&[("kept", "1"), ("changed", "4"), ("added", "5")]);
+        let changes = HashChanges::between(&stored, &current);
+        assert_eq!(
+            changes.added,
+            BTreeMap::from([(PathBuf::from("added"), "5".to_string())])
+        );
+        assert_eq!(
+            changes.removed,
+            BTreeMap::from([(PathBuf::from("removed"), "3".to_string())])
+        );
+        assert_eq!(
+            changes.changed,
+            BTreeMap::from([(PathBuf::from("changed"), ("2".to_string(), "4".to_string()))])
+        );
+        assert_eq!(changes.len(), 3);
+    }
+
+    #[test]
+    fn finds_no_changes_between_equal_hashes() {
+        let stored = hashes(&[("a", "1"), ("b", "2")]);
+        assert!(HashChanges::between(&stored, &stored.clone()).is_empty());
+    }
+
+    #[test]
+    fn summarizes_the_json_it_writes() {
+        let changes = HashChanges::between(&hashes(&[("a", "1")]), &hashes(&[("b", "2")]));
+        assert_eq!(
+            summarize(&changes.to_json().to_string()).as_deref(),
+            Some("1 added, 1 removed, 0 changed")
+        );
+        assert_eq!(summarize("not a change set"), None);
+    }
+
+    #[tokio::test]
+    async fn reports_changes_per_mod() {
+        let (_, sims_mod) = database_with_mod().await;
+        let changes = HashChanges::between(
+            &hashes(&[("hair.package", "old"), ("gone.package", "3")]),
+            &hashes(&[("hair.package", "new"), ("new.package", "5")]),
+        );
+        let report: Value =
+            serde_json::from_str(&render_report(&[(sims_mod.clone(), changes)]).unwrap()).unwrap();
+        assert!(report["generated_at"].is_string());
+        let reported = &report["mods"][0];
+        assert_eq!(reported["id"], json!(sims_mod.id));
+        assert_eq!(reported["name"], json!("Cute Hair"));
+        assert_eq!(reported["directory"], json!("CuteHair"));
+        assert_eq!(
+            reported["added"],
+            json!([{ "file": "new.package", "hash": "5" }])
+        );
+        assert_eq!(
+            reported["removed"],
+            json!([{ "file": "gone.package", "hash": "3" }])
+        );
+        assert_eq!(
+            reported["changed"],
+            json!([{ "file": "hair.package", "old_hash": "old", "new_hash": "new" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn stores_only_what_changed() {
+        let (db, sims_mod) = database_with_mod().await;
+        let first = hashes(&[("kept", "1"), ("changed", "2"), ("removed", "3")]);
+        store_hashes(&db, sims_mod.id, &first).await.unwrap();
+        let row_id = |rows: &[mod_hash::Model], file: &str| {
+            rows.iter().find(|row| row.file == file).map(|row| row.id)
+        };
+        let before = ModHash::find().all(&db).await.unwrap();
+
+        let second = hashes(&[("kept", "1"), ("changed", "4"), ("added", "5")]);
+        let changes = store_hashes(&db, sims_mod.id, &second).await.unwrap();
+        assert_eq!(changes, HashChanges::between(&first, &second));
+
+        let after = ModHash::find().all(&db).await.unwrap();
+        let stored: HashMap<PathBuf, String> = after
+            .iter()
+            .map(|row| (PathBuf::from(&row.file), row.hash.clone()))
+            .collect();
+        assert_eq!(stored, second);
+        // Rows of unchanged and changed files are kept rather than reinserted
+        assert_eq!(row_id(&after, "kept"), row_id(&before, "kept"));
+        assert_eq!(row_id(&after, "changed"), row_id(&before, "changed"));
+    }
+}
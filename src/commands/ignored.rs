@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder};
+use tracing::debug;
+use tracing_unwrap::OptionExt;
+
+use crate::entities::{prelude::*, *};
+
+/// Actions for folders that scans never report as new mods
+#[derive(clap::Subcommand, Debug)]
+pub enum IgnoredAction {
+    /// Lists ignored folders
+    List,
+    /// Lets scans report a folder again
+    Remove {
+        /// ID of the ignored folder, as shown by `ignored list`
+        id: i32,
+    },
+}
+
+/// Remembers a folder that is not a mod, so scans stop reporting it
+pub async fn ignore_path<C>(db: &C, root_id: Option<i32>, path: &Path) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    debug!("Ignoring folder {}", path.display());
+    let entry = ignored_path::ActiveModel {
+        root_id: ActiveValue::Set(root_id),
+        path: ActiveValue::Set(
+            path.to_str()
+                .expect_or_log("Failed to convert path to UTF-8")
+                .to_string(),
+        ),
+        ignored_at: ActiveValue::Set(chrono::Utc::now()),
+        ..Default::default()
+    };
+    IgnoredPath::insert(entry).exec(db).await?;
+    Ok(())
+}
+
+/// Ignored folders with their roots
+pub async fn ignored_paths<C>(db: &C) -> Result<Vec<(Option<i32>, PathBuf)>, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    Ok(IgnoredPath::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|entry| (entry.root_id, PathBuf::from(entry.path)))
+        .collect())
+}
+
+/// Whether a folder in a root is ignored, or inside an ignored folder
+pub fn is_ignored(ignored: &[(Option<i32>, PathBuf)], root_id: Option<i32>, path: &Path) -> bool {
+    ignored.iter().any(|(ignored_root, ignored_path)| {
+        *ignored_root == root_id
+            && path
+                .ancestors()
+                .any(|ancestor| super::util::same_directory(ancestor, ignored_path))
+    })
+}
+
+pub async fn ignored(action: IgnoredAction) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    match action {
+        IgnoredAction::List => {
+            let roots = super::util::ModRoots::load(&db).await?;
+            let entries = IgnoredPath::find()
+                .order_by_asc(ignored_path::Column::Path)
+                .all(&db)
+                .await?;
+            if entries.is_empty() {
+                println!("No folders are ignored.");
+            }
+            for entry in entries.iter() {
+                println!(
+                    "{} {} {}",
+                    entry.id.to_string().bold(),
+                    roots.display_path(entry.root_id, Path::new(&entry.path)),
+                    format!(
+                        "(since {})",
+                        crate::datetime::format_date(&entry.ignored_at)
+                    )
+                    .dimmed()
+                );
+            }
+        }
+        IgnoredAction::Remove { id } => {
+            let Some(entry) = IgnoredPath::find_by_id(id).one(&db).await? else {
+                eprintln!("No ignored folder with ID {} found!", id);
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            };
+            IgnoredPath::delete_by_id(id).exec(&db).await?;
+            println!("Scans will report {} again.", entry.path.bold());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::{prelude::*, QueryOrder};
+use serde_json::{json, Value};
+
+use crate::commands::{fields::FieldSelection, util::VerificationPassed};
+use crate::entities::{prelude::*, *};
+
+/// Verification state of one mod file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FileStatus {
+    Matching,
+    Changed,
+    Missing,
+    New,
+}
+
+impl FileStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FileStatus::Matching => "matching",
+            FileStatus::Changed => "changed",
+            FileStatus::Missing => "missing",
+            FileStatus::New => "new",
+        }
+    }
+
+    fn colored(&self) -> colored::ColoredString {
+        let label = format!("{:<8}", self.as_str());
+        match self {
+            FileStatus::Matching => label.green(),
+            FileStatus::Changed => label.yellow(),
+            FileStatus::Missing => label.red(),
+            FileStatus::New => label.blue(),
+        }
+    }
+}
+
+struct FileInfo {
+    path: PathBuf,
+    status: FileStatus,
+    stored_hash: Option<String>,
+    current_hash: Option<String>,
+    size: Option<u64>,
+}
+
+pub async fn info(mod_id: i32, json: bool) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let Some(sims_mod) = SimsMod::find_by_id(mod_id).one(&db).await? else {
+        eprintln!("No mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+
+    let tags = super::util::get_tags_for_mod(&db, mod_id).await?;
+    let required_packs = super::util::get_required_packs_for_mod(&db, mod_id).await?;
+    let owned_packs = super::util::get_owned_packs(&db).await?;
+    let missing_packs = super::util::get_missing_packs(&required_packs, &owned_packs);
+    let history = Journal::find()
+        .filter(journal::Column::ModId.eq(mod_id))
+        .order_by_asc(journal::Column::Timestamp)
+        .all(&db)
+        .await?;
+
+    let parent = match sims_mod.parent_mod_id {
+        Some(parent_id) => SimsMod::find_by_id(parent_id).one(&db).await?,
+        None => None,
+    };
+    let addons = super::util::get_addons(&db, mod_id).await?;
+
+    let results = super::util::verify_mod(&db, &sims_mod).await?;
+    let roots = super::util::ModRoots::load(&db).await?;
+    let mod_path = roots.mod_dir(&sims_mod)?;
+    let file_size = |file: &Path| std::fs::metadata(mod_path.join(file)).ok().map(|m| m.len());
+    let mut files: Vec<FileInfo> = Vec::new();
+    for file in results.matching_files.iter() {
+        files.push(FileInfo {
+            path: file.clone(),
+            status: FileStatus::Matching,
+            stored_hash: results.stored_hashes.get(file).cloned(),
+            current_hash: results.stored_hashes.get(file).cloned(),
+            size: file_size(file),
+        });
+    }
+    for (file, hash) in results.changed_files.iter() {
+        files.push(FileInfo {
+            path: file.clone(),
+            status: FileStatus::Changed,
+            stored_hash: results.stored_hashes.get(file).cloned(),
+            current_hash: Some(hash.clone()),
+            size: file_size(file),
+        });
+    }
+    for file in results.missing_files.iter() {
+        files.push(FileInfo {
+            path: file.clone(),
+            status: FileStatus::Missing,
+            stored_hash: results.stored_hashes.get(file).cloned(),
+            current_hash: None,
+            size: None,
+        });
+    }
+    for (file, hash) in results.new_files.iter() {
+        files.push(FileInfo {
+            path: file.clone(),
+            status: FileStatus::New,
+            stored_hash: None,
+            current_hash: Some(hash.clone()),
+            size: file_size(file),
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let verified = results.verification_passed();
+
+    if json {
+        let mut value = FieldSelection::new(None).to_json(
+            &sims_mod,
+            Some(&tags),
+            Some(&required_packs),
+            Some(verified),
+        );
+        if let Value::Object(object) = &mut value {
+            object.insert(
+                "deleted_at".to_string(),
+                Value::from(sims_mod.deleted_at.map(|d| d.to_rfc3339())),
+            );
+            object.insert("missing_packs".to_string(), Value::from(missing_packs));
+            object.insert(
+                "addon_ids".to_string(),
+                addons.iter().map(|addon| addon.id).collect(),
+            );
+            object.insert(
+                "directory_missing".to_string(),
+                Value::from(results.directory_missing),
+            );
+            object.insert(
+                "files".to_string(),
+                files
+                    .iter()
+                    .map(|f| {
+                        json!({
+                            "path": f.path.to_string_lossy(),
+                            "status": f.status.as_str(),
+                            "stored_hash": f.stored_hash,
+                            "current_hash": f.current_hash,
+                            "size": f.size,
+                        })
+                    })
+                    .collect(),
+            );
+            object.insert(
+                "history".to_string(),
+                history
+                    .iter()
+                    .map(|entry| {
+                        json!({
+                            "timestamp": entry.timestamp.to_rfc3339(),
+                            "action": entry.action,
+                            "name": entry.mod_name,
+                            "version": entry.version,
+                            "source_url": entry.source_url,
+                        })
+                    })
+                    .collect(),
+            );
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} ({})",
+        if verified {
+            sims_mod.name.green()
+        } else {
+            sims_mod.name.red()
+        }
+        .bold(),
+        sims_mod.id
+    );
+    let mut details = vec![
+        ("Version", sims_mod.version.clone()),
+        (
+            "Directory",
+            roots.display_path(sims_mod.root_id, Path::new(&sims_mod.directory)),
+        ),
+        ("Source", sims_mod.source_url.clone()),
+        ("Source type", sims_mod.source_type.clone()),
+        ("Updates", sims_mod.update_channel.clone()),
+        ("Composition", super::composition::format_counts(&sims_mod)),
+        (
+            "Updated",
+            crate::datetime::format_datetime(&sims_mod.updated),
+        ),
+        (
+            "Downloaded",
+            sims_mod
+                .added_at
+                .map(|d| crate::datetime::format_datetime(&d))
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        (
+            "Protected",
+            if sims_mod.protected { "yes" } else { "no" }.to_string(),
+        ),
+        (
+            "Frozen",
+            if sims_mod.frozen { "yes" } else { "no" }.to_string(),
+        ),
+        ("NSFW", if sims_mod.nsfw { "yes" } else { "no" }.to_string()),
+        ("Rating", super::util::format_rating(sims_mod.rating)),
+        (
+            "Would reinstall",
+            super::util::format_would_reinstall(sims_mod.would_reinstall).to_string(),
+        ),
+    ];
+    if let Some(deleted_at) = sims_mod.deleted_at {
+        details.push(("Deleted", crate::datetime::format_datetime(&deleted_at)));
+    }
+    if let Some(broken_at) = sims_mod.broken_at {
+        details.push((
+            "Broken",
+            match &sims_mod.broken_reason {
+                Some(reason) => format!(
+                    "since {} ({})",
+                    crate::datetime::format_datetime(&broken_at),
+                    reason
+                ),
+                None => format!("since {}", crate::datetime::format_datetime(&broken_at)),
+            },
+        ));
+    }
+    if let Some(recheck_after) = sims_mod.recheck_after {
+        details.push(("Recheck after", crate::datetime::format_day(recheck_after)));
+    }
+    if let Some(thumbnail) = &sims_mod.thumbnail {
+        details.push(("Thumbnail", thumbnail.clone()));
+    }
+    if let Some(download_url) = &sims_mod.download_url {
+        details.push(("Download URL", download_url.clone()));
+    }
+    if let (Some(archive_hash), Some(installed_at)) =
+        (&sims_mod.archive_hash, sims_mod.archive_installed_at)
+    {
+        details.push((
+            "Archive",
+            format!(
+                "{} (installed {})",
+                archive_hash,
+                crate::datetime::format_date(&installed_at)
+            ),
+        ));
+    }
+    if let Some(parent) = &parent {
+        details.push(("Addon of", format!("{} ({})", parent.name, parent.id)));
+    }
+    if !addons.is_empty() {
+        details.push((
+            "Addons",
+            addons
+                .iter()
+                .map(|addon| format!("{} ({})", addon.name, addon.id))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+    details.push((
+        "Tags",
+        if tags.is_empty() {
+            "none".to_string()
+        } else {
+            tags.join(", ")
+        },
+    ));
+    details.push((
+        "Required packs",
+        if required_packs.is_empty() {
+            "none".to_string()
+        } else {
+            required_packs.join(", ")
+        },
+    ));
+    if !missing_packs.is_empty() {
+        details.push(("Missing packs", missing_packs.join(", ").red().to_string()));
+    }
+    for (label, value) in details.iter() {
+        println!("  {} {}", format!("{}:", label).bold(), value);
+    }
+
+    println!(
+        "\n{} {}",
+        "Files:".bold(),
+        if verified {
+            "PASSED".green()
+        } else {
+            "FAILED".red()
+        }
+        .bold()
+    );
+    if results.directory_missing {
+        println!("  Directory {} is missing", sims_mod.directory.bold());
+    }
+    for file in files.iter() {
+        let hash = match (&file.stored_hash, &file.current_hash) {
+            (Some(stored), Some(current)) if stored != current => {
+                format!("{} -> {}", stored.trim(), current.trim())
+            }
+            (Some(hash), _) | (None, Some(hash)) => hash.clone(),
+            (None, None) => String::new(),
+        };
+        println!(
+            "  {} {} ({}{})",
+            file.status.colored(),
+            file.path.display(),
+            file.size
+                .map(|s| super::util::format_size(s) + ", ")
+                .unwrap_or_default(),
+            hash.trim()
+        );
+    }
+
+    println!("\n{}", "History:".bold());
+    if history.is_empty() {
+        println!("  No journal entries");
+    }
+    for entry in history.iter() {
+        println!(
+            "  {} {} {} {}",
+            crate::datetime::format_datetime(&entry.timestamp),
+            entry.action.bold(),
+            entry.mod_name,
+            entry.version
+        );
+    }
+
+    println!("\n{}", "Actions:".bold());
+    let mut actions = Vec::new();
+    if sims_mod.deleted_at.is_some() {
+        actions.push(format!("restore-entry --mod-id {}", mod_id));
+    } else {
+        actions.push(format!("edit --mod-id {} --mod-version <VERSION>", mod_id));
+        actions.push(format!("verify --mod-id {}", mod_id));
+        actions.push(format!("thumbnail --mod-id {} <IMAGE>", mod_id));
+        if sims_mod.broken_at.is_some() {
+            actions.push(format!("mark-fixed --mod-id {}", mod_id));
+        } else {
+            actions.push(format!("mark-broken --mod-id {} --reason <REASON>", mod_id));
+        }
+    }
+    actions.push(format!("log --mod-id {}", mod_id));
+    for action in actions.iter() {
+        println!("  {}", action);
+    }
+    Ok(())
+}
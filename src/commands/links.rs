@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use colored::Colorize;
+use futures::StreamExt;
+use inquire::Confirm;
+use reqwest::{redirect::Policy, Client, Method, StatusCode};
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
+use tracing::{debug, info};
+
+use crate::commands::{JournalAction, SourceType};
+use crate::entities::*;
+use crate::style::{self, Status};
+
+/// Limits keeping `check-links` polite towards mod hosts
+#[derive(clap::Args, Debug)]
+pub struct LinkCheckLimits {
+    /// Number of hosts checked at the same time
+    #[arg(short, long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..=32))]
+    pub concurrency: u16,
+
+    /// Milliseconds to wait between requests to the same host
+    #[arg(short, long, default_value_t = 1000)]
+    pub delay: u64,
+
+    /// Seconds to wait for a host to answer
+    #[arg(short, long, default_value_t = 15)]
+    pub timeout: u64,
+}
+
+/// What checking a source URL found
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum LinkStatus {
+    Alive,
+    Dead(StatusCode),
+    Moved(String),
+    Failed(StatusCode),
+    Unreachable(String),
+}
+
+/// Sends a HEAD request, falling back to GET for hosts that don't support HEAD
+async fn check_link(client: &Client, source_url: &str) -> LinkStatus {
+    let mut response = client.request(Method::HEAD, source_url).send().await;
+    if let Ok(head) = &response {
+        if matches!(
+            head.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        ) {
+            debug!("{} does not support HEAD", source_url);
+            response = client.get(source_url).send().await;
+        }
+    }
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => return LinkStatus::Unreachable(e.without_url().to_string()),
+    };
+    let status = response.status();
+    match status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => LinkStatus::Dead(status),
+        StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT => {
+            let target = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| response.url().join(location).ok());
+            match target {
+                Some(target) => LinkStatus::Moved(target.to_string()),
+                None => LinkStatus::Failed(status),
+            }
+        }
+        // Temporary redirects usually lead to login or tracking pages, which aren't worth saving
+        _ if status.is_success() || status.is_redirection() => LinkStatus::Alive,
+        _ => LinkStatus::Failed(status),
+    }
+}
+
+/// Asks to replace a moved source URL with its target
+async fn update_source(
+    db: &DatabaseConnection,
+    sims_mod: sims_mod::Model,
+    target: &str,
+) -> crate::Result<bool> {
+    if !Confirm::new(
+        format!(
+            "Update the source of {} to {}?",
+            sims_mod.name.bold(),
+            target.blue()
+        )
+        .as_str(),
+    )
+    .with_default(true)
+    .prompt()?
+    {
+        return Ok(false);
+    }
+    let mut active_model = sims_mod.into_active_model();
+    active_model.source_type = ActiveValue::set(SourceType::name_for_url(target).to_string());
+    active_model.source_url = ActiveValue::set(target.to_string());
+    active_model.updated = ActiveValue::set(chrono::Utc::now());
+    let saved_model = active_model.update(db).await?;
+    super::log::record(db, JournalAction::Edited, &saved_model).await?;
+    Ok(true)
+}
+
+pub async fn check_links(limits: LinkCheckLimits, fix: bool) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let client = Client::builder()
+        .redirect(Policy::none())
+        .timeout(Duration::from_secs(limits.timeout))
+        .user_agent(concat!("sims4modorganizer/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    // Requests to one host are sent one after another, so only hosts run concurrently
+    let mut by_host: BTreeMap<String, Vec<sims_mod::Model>> = BTreeMap::new();
+    let mut skipped = 0;
+    for sims_mod in super::util::active_mods().all(&db).await?.into_iter() {
+        let host = url::Url::parse(&sims_mod.source_url)
+            .ok()
+            .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()));
+        match host {
+            Some(host) => by_host.entry(host).or_default().push(sims_mod),
+            None => skipped += 1,
+        }
+    }
+    let total: usize = by_host.values().map(|mods| mods.len()).sum();
+    println!("Checking {} links on {} hosts...", total, by_host.len());
+
+    let delay = Duration::from_millis(limits.delay);
+    let client = &client;
+    let mut results: Vec<(sims_mod::Model, LinkStatus)> = futures::stream::iter(by_host)
+        .map(|(host, mods)| async move {
+            let mut results = Vec::new();
+            for (i, sims_mod) in mods.into_iter().enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(delay).await;
+                }
+                info!("Checking {} on {}", sims_mod.source_url, host);
+                let status = check_link(client, &sims_mod.source_url).await;
+                results.push((sims_mod, status));
+            }
+            results
+        })
+        .buffer_unordered(limits.concurrency as usize)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    results.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+
+    let (mut dead, mut moved, mut failed, mut unreachable, mut updated) = (0, 0, 0, 0, 0);
+    for (sims_mod, status) in results.into_iter() {
+        match status {
+            LinkStatus::Alive => continue,
+            LinkStatus::Dead(code) => {
+                dead += 1;
+                println!(
+                    "{} {}: {}",
+                    style::mark(Status::Failed, &sims_mod.name).bold(),
+                    style::paint(Status::Failed, code.as_str()),
+                    sims_mod.source_url
+                );
+            }
+            LinkStatus::Failed(code) => {
+                failed += 1;
+                println!(
+                    "{} {}: {}",
+                    style::mark(Status::Warning, &sims_mod.name).bold(),
+                    style::paint(Status::Warning, code.as_str()),
+                    sims_mod.source_url
+                );
+            }
+            LinkStatus::Unreachable(error) => {
+                unreachable += 1;
+                println!(
+                    "{} unreachable: {} ({})",
+                    style::mark(Status::Missing, &sims_mod.name).bold(),
+                    sims_mod.source_url,
+                    error
+                );
+            }
+            LinkStatus::Moved(target) => {
+                moved += 1;
+                println!(
+                    "{} moved: {} -> {}",
+                    style::mark(Status::Changed, &sims_mod.name).bold(),
+                    sims_mod.source_url,
+                    target.blue()
+                );
+                if fix && update_source(&db, sims_mod, &target).await? {
+                    updated += 1;
+                }
+            }
+        }
+    }
+
+    println!(
+        "{} dead, {} moved, {} failing, and {} unreachable links.",
+        dead.to_string().bold(),
+        moved.to_string().bold(),
+        failed.to_string().bold(),
+        unreachable.to_string().bold()
+    );
+    if updated > 0 {
+        println!(
+            "Updated {} source URLs.",
+            updated.to_string().green().bold()
+        );
+    } else if moved > 0 && !fix {
+        println!("Run with {} to update moved links.", "--fix".bold());
+    }
+    if skipped > 0 {
+        println!("Skipped {} mods without a web source URL.", skipped);
+    }
+    Ok(())
+}
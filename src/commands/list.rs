@@ -1,390 +1,567 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use colored::Colorize;
-use sea_orm::prelude::*;
+use sea_orm::EntityTrait;
 
-use crate::{commands::util::VerificationPassed, entities::prelude::*};
+use crate::commands::{
+    composition::{self, Composition},
+    fields::{FieldSelection, ModField},
+    SourceType,
+};
+use crate::entities::{
+    prelude::{KeptDuplicate, ModTagRelation, SimsMod, Tag},
+    sims_mod,
+};
+use crate::render::{Node, Tree};
+use crate::style::{self, Status};
 
 use super::util;
-use tracing_unwrap::OptionExt;
 
-pub async fn list(tags: Option<Vec<String>>, verify: bool, details: bool) -> crate::Result<()> {
+const UNTAGGED: &str = "Untagged";
+const UNKNOWN_CREATOR: &str = "Unknown Creator";
+const NO_SOURCE: &str = "No source";
+
+/// What `list --group-by` sorts mods into sections by
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ListGroup {
+    /// One section per tag. Mods with several tags are listed under each.
+    Tag,
+    /// One section per creator, guessed from the source URL
+    #[value(alias = "creator")]
+    Author,
+    /// One section per source domain
+    Source,
+}
+
+/// Filters and display options for `list`
+#[derive(clap::Args, Debug)]
+pub struct ListOptions {
+    #[command(flatten)]
+    pub filter: super::TagFilter,
+
+    /// Show detailed information
+    #[arg(short, long)]
+    pub details: bool,
+
+    /// Show deleted mods instead of installed ones
+    #[arg(long)]
+    pub deleted: bool,
+
+    /// Only show the given detail fields. Implies --details.
+    #[arg(short, long, value_enum, value_delimiter = ',')]
+    pub fields: Option<Vec<ModField>>,
+
+    /// Only show mods from the given kind of source
+    #[arg(short, long, value_enum)]
+    pub source: Option<SourceType>,
+
+    /// Only show mods requiring packs that are not owned
+    #[arg(short, long)]
+    pub missing_packs: bool,
+
+    /// Only show mods marked as broken
+    #[arg(short, long)]
+    pub broken: bool,
+
+    /// Only show mods rated at least this (1-5)
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..=5))]
+    pub min_rating: Option<i32>,
+
+    /// Only show mods you would (true) or would not (false) reinstall
+    #[arg(long)]
+    pub would_reinstall: Option<bool>,
+
+    /// Only show mods added since the game was last played
+    #[arg(long)]
+    pub since_last_play: bool,
+
+    /// Only show mods downloaded before this date (YYYY-MM-DD)
+    #[arg(long, value_parser = util::parse_date)]
+    pub downloaded_before: Option<chrono::NaiveDate>,
+
+    /// Only show mods downloaded on or after this date (YYYY-MM-DD)
+    #[arg(long, value_parser = util::parse_date)]
+    pub downloaded_since: Option<chrono::NaiveDate>,
+
+    /// Show addons and translations indented below their base mod
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Only show mods with script files, which break most often after a patch
+    #[arg(long)]
+    pub script_mods: bool,
+
+    /// Show mods in sections by tag, creator, or source domain, each with its mod count
+    #[arg(short, long, value_enum, value_name = "GROUP")]
+    pub group_by: Option<ListGroup>,
+}
+
+/// Orders mods so addons follow their base mod, along with their depth in the tree.
+/// Addons whose base mod is not listed are shown at the top level.
+fn tree_order(mods: Vec<sims_mod::Model>) -> Vec<(usize, sims_mod::Model)> {
+    let ids: HashSet<i32> = mods.iter().map(|m| m.id).collect();
+    let mut addons: HashMap<i32, Vec<sims_mod::Model>> = HashMap::new();
+    let mut stack = Vec::new();
+    for sims_mod in mods.into_iter().rev() {
+        match sims_mod.parent_mod_id.filter(|id| ids.contains(id)) {
+            Some(parent_id) => addons.entry(parent_id).or_default().push(sims_mod),
+            None => stack.push((0, sims_mod)),
+        }
+    }
+    let mut ordered = Vec::new();
+    while let Some((depth, sims_mod)) = stack.pop() {
+        if let Some(children) = addons.remove(&sims_mod.id) {
+            stack.extend(children.into_iter().map(|child| (depth + 1, child)));
+        }
+        ordered.push((depth, sims_mod));
+    }
+    // Only reachable if links form a loop, which editing prevents
+    ordered.extend(addons.into_values().flatten().map(|sims_mod| (0, sims_mod)));
+    ordered
+}
+
+/// Domain of a mod's source URL, without `www.`
+fn source_domain(sims_mod: &sims_mod::Model) -> Option<String> {
+    let url = url::Url::parse(&sims_mod.source_url).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+/// Sorts mods into named sections, in alphabetical order with the section for mods
+/// that fit nowhere last
+async fn group_mods(
+    db: &sea_orm::DatabaseConnection,
+    mods: Vec<sims_mod::Model>,
+    group_by: ListGroup,
+) -> crate::Result<Vec<(String, Vec<sims_mod::Model>)>> {
+    let mut groups: HashMap<String, Vec<sims_mod::Model>> = HashMap::new();
+    let fallback = match group_by {
+        ListGroup::Tag => UNTAGGED,
+        ListGroup::Author => UNKNOWN_CREATOR,
+        ListGroup::Source => NO_SOURCE,
+    };
+    if group_by == ListGroup::Tag {
+        let tag_names: HashMap<i32, String> = Tag::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|t| (t.id, t.tag))
+            .collect();
+        let mut mod_tags: HashMap<i32, Vec<&str>> = HashMap::new();
+        for relation in ModTagRelation::find().all(db).await? {
+            if let Some(tag) = tag_names.get(&relation.tag_id) {
+                mod_tags.entry(relation.mod_id).or_default().push(tag);
+            }
+        }
+        for sims_mod in mods.into_iter() {
+            match mod_tags.get(&sims_mod.id) {
+                Some(tags) => {
+                    for tag in tags.iter() {
+                        groups
+                            .entry(tag.to_string())
+                            .or_default()
+                            .push(sims_mod.clone());
+                    }
+                }
+                None => groups
+                    .entry(fallback.to_string())
+                    .or_default()
+                    .push(sims_mod),
+            }
+        }
+    } else {
+        for sims_mod in mods.into_iter() {
+            let name = match group_by {
+                ListGroup::Author => SourceType::creator_from_url(&sims_mod.source_url),
+                _ => source_domain(&sims_mod),
+            };
+            groups
+                .entry(name.unwrap_or_else(|| fallback.to_string()))
+                .or_default()
+                .push(sims_mod);
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<sims_mod::Model>)> = groups.into_iter().collect();
+    groups.sort_by_cached_key(|(name, _)| (name == fallback, name.to_lowercase()));
+    Ok(groups)
+}
+
+pub async fn list(options: ListOptions) -> crate::Result<()> {
+    let ListOptions {
+        filter,
+        details,
+        deleted,
+        fields,
+        source,
+        missing_packs,
+        broken,
+        min_rating,
+        would_reinstall,
+        since_last_play,
+        downloaded_before,
+        downloaded_since,
+        tree,
+        script_mods,
+        group_by,
+    } = options;
     let db = crate::util::open_database().await?;
+    let details = details || fields.is_some();
+    let fields = FieldSelection::new(fields);
 
-    let mods = if let Some(tags) = tags {
-        util::get_mods_for_tags(&db, tags).await?
+    let mods = if deleted {
+        util::deleted_mods().all(&db).await?
+    } else if !filter.is_empty() {
+        util::get_mods_for_filter(&db, filter).await?
     } else {
-        SimsMod::find().all(&db).await?
-    };
+        util::active_mods().all(&db).await?
+    }
+    .into_iter()
+    .filter(|m| !util::is_hidden(m))
+    .filter(|m| source.map(|s| m.source_type == s.as_str()).unwrap_or(true))
+    .filter(|m| !broken || m.broken_at.is_some())
+    .filter(|m| !script_mods || composition::is_script_mod(m))
+    .filter(|m| min_rating.map(|r| m.rating >= Some(r)).unwrap_or(true))
+    .filter(|m| {
+        would_reinstall
+            .map(|w| m.would_reinstall == Some(w))
+            .unwrap_or(true)
+    })
+    .filter(|m| {
+        let timezone = crate::config::get().timezone();
+        let downloaded = m.added_at.map(|d| timezone.convert(&d).date_naive());
+        downloaded_before
+            .map(|b| downloaded.is_some_and(|d| d < b))
+            .unwrap_or(true)
+            && downloaded_since
+                .map(|s| downloaded.is_some_and(|d| d >= s))
+                .unwrap_or(true)
+    })
+    .collect::<Vec<_>>();
 
-    for sims_mod in mods.iter() {
-        let mod_path: PathBuf = sims_mod.directory.clone().into();
-        let tags = if details {
-            Some(util::get_tags_for_mod(&db, sims_mod.id).await?)
-        } else {
-            None
-        };
-        let hashes = if details || verify {
-            Some(util::get_hashes_for_mod(&db, sims_mod.id).await?)
-        } else {
-            None
+    let mods = if since_last_play {
+        let Some(last_played) = super::played::last_play_session(&db).await? else {
+            eprintln!("No play session found! Launch the game once so its files can be found.");
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
         };
+        println!(
+            "Mods added since the game was last played ({}):",
+            crate::datetime::format_datetime(&last_played)
+        );
+        let added: std::collections::HashSet<i32> =
+            super::played::mods_added_since(&db, last_played)
+                .await?
+                .into_iter()
+                .map(|m| m.id)
+                .collect();
+        mods.into_iter().filter(|m| added.contains(&m.id)).collect()
+    } else {
+        mods
+    };
 
-        let verification_result = if verify {
-            if let Some(hashes) = &hashes {
-                Some(util::verify_files(&mod_path, hashes)?)
-            } else {
-                None
+    let owned_packs = if missing_packs {
+        Some(util::get_owned_packs(&db).await?)
+    } else {
+        None
+    };
+    // Filtered here rather than while printing, so group counts only include shown mods
+    let mods = if let Some(owned) = &owned_packs {
+        let mut with_missing = Vec::new();
+        for sims_mod in mods.into_iter() {
+            let required_packs = util::get_required_packs_for_mod(&db, sims_mod.id).await?;
+            if !util::get_missing_packs(&required_packs, owned).is_empty() {
+                with_missing.push(sims_mod);
             }
-        } else {
-            None
-        };
+        }
+        with_missing
+    } else {
+        mods
+    };
 
-        let title_corner = boxy::Char::upper_left(boxy::Weight::Thick);
-        let title_side_h = boxy::Char::horizontal(boxy::Weight::Thick).to_string();
-        let title_side_v = boxy::Char::vertical(boxy::Weight::Thick);
-        let title_branch = boxy::Char::right_tee(boxy::Weight::Thick).down(boxy::Weight::Normal);
-        let left_branch_more = boxy::Char::right_tee(boxy::Weight::Normal);
-        let left_branch_more_str = boxy::Char::right_tee(boxy::Weight::Normal).to_string();
-        let left_branch_done = boxy::Char::lower_left(boxy::Weight::Normal);
-        let left_branch_done_str = boxy::Char::lower_left(boxy::Weight::Normal).to_string();
-        let left_node = boxy::Char::left_half(boxy::Weight::Normal);
-        let down_branch = boxy::Char::down_tee(boxy::Weight::Normal);
-        let down_branch_str = boxy::Char::down_tee(boxy::Weight::Normal).to_string();
-        let branch_v = boxy::Char::vertical(boxy::Weight::Normal).to_string();
-
-        if details {
+    let groups: Vec<(Option<String>, Vec<sims_mod::Model>)> = match group_by {
+        Some(group_by) => group_mods(&db, mods, group_by)
+            .await?
+            .into_iter()
+            .map(|(name, mods)| (Some(name), mods))
+            .collect(),
+        None => vec![(None, mods)],
+    };
+    let mod_names: HashMap<i32, String> = if details && fields.contains(ModField::Parent) {
+        SimsMod::find()
+            .all(&db)
+            .await?
+            .into_iter()
+            .filter(|m| !util::is_hidden(m))
+            .map(|m| (m.id, m.name))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let roots = util::ModRoots::load(&db).await?;
+    // Duplicates kept during a scan are on disk without being counted
+    let mut kept_duplicates: HashMap<i32, usize> = HashMap::new();
+    for kept in KeptDuplicate::find().all(&db).await? {
+        *kept_duplicates.entry(kept.mod_id).or_default() += 1;
+    }
+    let mut count_mismatches = 0;
+    for (index, (group, mods)) in groups.into_iter().enumerate() {
+        if let Some(group) = group {
+            if index > 0 {
+                println!();
+            }
             println!(
-                "{}{}{}",
-                title_corner,
-                title_side_h.repeat(sims_mod.name.len() + 2),
-                title_corner.rotate_cw(1)
+                "{} {}",
+                group.bold().underline(),
+                format!("({})", mods.len()).dimmed()
             );
-            println!(
-                "{} {} {}",
-                title_side_v,
-                if let Some(result) = &verification_result {
-                    if result.verification_passed() {
-                        sims_mod.name.green()
-                    } else {
-                        sims_mod.name.red()
-                    }
-                    .bold()
+        }
+        let mods = if tree {
+            tree_order(mods)
+        } else {
+            mods.into_iter().map(|sims_mod| (0, sims_mod)).collect()
+        };
+        for (depth, sims_mod) in mods.iter() {
+            let indent = "  ".repeat(*depth);
+            // Script mods are the ones to check after a patch, so they stand out
+            let composition = Composition::of(sims_mod);
+            let badge = match composition {
+                Composition::Script | Composition::Mixed => format!("[{}]", composition).yellow(),
+                Composition::CcOnly | Composition::Empty => format!("[{}]", composition).dimmed(),
+            };
+            let required_packs =
+                if missing_packs || (details && fields.contains(ModField::RequiredPacks)) {
+                    util::get_required_packs_for_mod(&db, sims_mod.id).await?
                 } else {
-                    sims_mod.name.bold()
-                },
-                title_side_v
-            );
-            println!(
-                "{}{}{}",
-                title_branch,
-                title_side_h.repeat(sims_mod.name.len() + 2),
-                title_corner.rotate_cw(2)
-            );
-            println!(
-                "{}{}{} {}",
-                left_branch_more,
-                left_node,
-                "Version:".bold(),
-                sims_mod.version
-            );
-            println!(
-                "{}{}{} {}",
-                left_branch_more,
-                left_node,
-                "Mod ID:".bold(),
-                sims_mod.id
-            );
-            println!(
-                "{}{}{} {}",
-                left_branch_more,
-                left_node,
-                "Updated:".bold(),
-                sims_mod.updated
-            );
-            println!(
-                "{}{}{} {}",
-                left_branch_more,
-                left_node,
-                "Source:".bold(),
-                sims_mod.source_url
-            );
-            println!(
-                "{}{}{} {}",
-                if tags.as_ref().map(|t| !t.is_empty()).unwrap_or(false)
-                    || verification_result.is_some()
+                    Vec::new()
+                };
+            let missing = owned_packs
+                .as_ref()
+                .map(|owned| util::get_missing_packs(&required_packs, owned))
+                .unwrap_or_default();
+            let tags = if details && fields.contains(ModField::Tags) {
+                Some(util::get_tags_for_mod(&db, sims_mod.id).await?)
+            } else {
+                None
+            };
+            // Counting files needs no hashing, so it is checked on every list
+            let root = roots.dir(sims_mod.root_id)?;
+            let expected_files = sims_mod.file_count as usize
+                + kept_duplicates
+                    .get(&sims_mod.id)
+                    .copied()
+                    .unwrap_or_default();
+            let files_on_disk = if deleted || !root.is_dir() {
+                None
+            } else {
+                Some(util::count_mod_files(
+                    root,
+                    Path::new(&sims_mod.directory),
+                    &util::tracked_extensions(&db, sims_mod.id).await?,
+                )?)
+            }
+            .filter(|count| *count != expected_files);
+            let count_warning = match files_on_disk {
+                Some(count) => {
+                    count_mismatches += 1;
+                    format!(
+                        " {}",
+                        style::mark(
+                            Status::Warning,
+                            &format!("{} of {} files", count, expected_files)
+                        )
+                    )
+                }
+                None => String::new(),
+            };
+
+            if details {
+                let mut tree = Tree::new(&sims_mod.name);
+                if fields.contains(ModField::Version) {
+                    tree.push(Node::field("Version:").value(sims_mod.version.clone()));
+                }
+                if fields.contains(ModField::Id) {
+                    tree.push(Node::field("Mod ID:").value(sims_mod.id.to_string()));
+                }
+                if fields.contains(ModField::Updated) {
+                    tree.push(
+                        Node::field("Updated:")
+                            .value(crate::datetime::format_datetime(&sims_mod.updated)),
+                    );
+                }
+                if let Some(added_at) = sims_mod
+                    .added_at
+                    .filter(|_| fields.contains(ModField::Added))
                 {
-                    left_branch_more
-                } else {
-                    left_branch_done
-                },
-                left_node,
-                "Subdirectory:".bold(),
-                sims_mod.directory
-            );
-            if let Some(tags) = tags {
-                if !tags.is_empty() {
-                    println!(
-                        "{}{}{}{}",
-                        if verification_result.is_some() {
-                            left_branch_more
-                        } else {
-                            left_branch_done
-                        },
-                        down_branch,
-                        left_node,
-                        "Tags:".bold()
+                    tree.push(
+                        Node::field("Downloaded:")
+                            .value(crate::datetime::format_datetime(&added_at)),
                     );
-                    let mut tags = tags.iter().peekable();
-                    let mut first = true;
-                    while let Some(tag) = tags.next() {
-                        println!(
-                            "{}{}{}{}{}",
-                            if verification_result.is_some() {
-                                &branch_v
-                            } else {
-                                " "
-                            },
-                            if first { &left_branch_done_str } else { " " },
-                            if first {
-                                if tags.peek().is_some() {
-                                    &down_branch_str
-                                } else {
-                                    ""
-                                }
-                            } else {
-                                if tags.peek().is_some() {
-                                    &left_branch_more_str
-                                } else {
-                                    &left_branch_done_str
-                                }
-                            },
-                            left_node,
-                            tag
-                        );
-                        if first {
-                            first = false;
-                        }
-                    }
                 }
-            }
-            if let Some(results) = verification_result {
-                let hashes = hashes.unwrap();
-                let no_children = results.matching_files.is_empty()
-                    && results.missing_files.is_empty()
-                    && results.new_files.is_empty()
-                    && results.changed_files.is_empty();
-                println!(
-                    "{}{}{}{} {}",
-                    left_branch_done,
-                    if no_children {
-                        String::new()
-                    } else {
-                        down_branch.to_string()
-                    },
-                    left_node,
-                    "Verification:".bold(),
-                    if results.verification_passed() {
-                        "PASSED".green()
-                    } else {
-                        "FAILED".red()
-                    }
-                    .bold()
-                );
-
-                if !results.matching_files.is_empty() {
-                    let no_children = results.missing_files.is_empty()
-                        && results.new_files.is_empty()
-                        && results.changed_files.is_empty();
-                    println!(
-                        " {}{}{}{}:",
-                        if no_children {
-                            left_branch_done
-                        } else {
-                            left_branch_more
-                        },
-                        down_branch,
-                        left_node,
-                        "Matching".green()
+                if let Some(deleted_at) = sims_mod.deleted_at {
+                    tree.push(
+                        Node::field("Deleted:")
+                            .value(crate::datetime::format_datetime(&deleted_at)),
                     );
-                    let mut matching = results.matching_files.iter().peekable();
-                    let mut first = true;
-                    while let Some(matched) = matching.next() {
-                        println!(
-                            " {}{}{}{}{} ({})",
-                            if no_children { " " } else { &branch_v },
-                            if first { &left_branch_done_str } else { " " },
-                            if first {
-                                if matching.peek().is_some() {
-                                    &down_branch_str
-                                } else {
-                                    ""
-                                }
-                            } else {
-                                if matching.peek().is_some() {
-                                    &left_branch_more_str
-                                } else {
-                                    &left_branch_done_str
-                                }
-                            },
-                            left_node,
-                            matched.display(),
-                            hashes
-                                .get(matched)
-                                .expect_or_log("Failed to find match in hashes")
-                        );
-                        if first {
-                            first = false;
-                        }
-                    }
                 }
-
-                if !results.missing_files.is_empty() {
-                    let no_children =
-                        results.new_files.is_empty() && results.changed_files.is_empty();
-                    println!(
-                        " {}{}{}{}:",
-                        if no_children {
-                            left_branch_done
-                        } else {
-                            left_branch_more
-                        },
-                        down_branch,
-                        left_node,
-                        "Missing".red()
+                if fields.contains(ModField::Source) {
+                    tree.push(Node::field("Source:").value(sims_mod.source_url.clone()));
+                }
+                if let Some(download_url) = sims_mod
+                    .download_url
+                    .as_ref()
+                    .filter(|_| fields.contains(ModField::DownloadUrl))
+                {
+                    tree.push(Node::field("Download URL:").value(download_url.clone()));
+                }
+                if fields.contains(ModField::SourceType) {
+                    tree.push(Node::field("Source type:").value(sims_mod.source_type.clone()));
+                }
+                if fields.contains(ModField::UpdateChannel) {
+                    tree.push(Node::field("Updates:").value(sims_mod.update_channel.clone()));
+                }
+                if fields.contains(ModField::Composition) {
+                    tree.push(
+                        Node::field("Composition:").value(composition::format_counts(sims_mod)),
                     );
-                    let mut missing_iter = results.missing_files.iter().peekable();
-                    let mut first = true;
-                    while let Some(missing) = missing_iter.next() {
-                        println!(
-                            " {}{}{}{}{} ({})",
-                            if no_children { " " } else { &branch_v },
-                            if first { &left_branch_done_str } else { " " },
-                            if first {
-                                if missing_iter.peek().is_some() {
-                                    &down_branch_str
-                                } else {
-                                    ""
-                                }
-                            } else {
-                                if missing_iter.peek().is_some() {
-                                    &left_branch_more_str
-                                } else {
-                                    &left_branch_done_str
-                                }
-                            },
-                            left_node,
-                            missing.display(),
-                            hashes
-                                .get(missing)
-                                .expect_or_log("Failed to find match in hashes")
-                        );
-                        if first {
-                            first = false;
-                        }
-                    }
                 }
-
-                if !results.new_files.is_empty() {
-                    let no_children = results.changed_files.is_empty();
-                    println!(
-                        " {}{}{}{}:",
-                        if no_children {
-                            left_branch_done
-                        } else {
-                            left_branch_more
-                        },
-                        down_branch,
-                        left_node,
-                        "New".blue()
+                if let Some(count) = files_on_disk {
+                    tree.push(
+                        Node::field("Files:")
+                            .value(format!("{} on disk, {} expected", count, expected_files))
+                            .status(Status::Warning),
                     );
-                    let mut new_iter = results.new_files.iter().peekable();
-                    let mut first = true;
-                    while let Some((new_path, hash)) = new_iter.next() {
-                        println!(
-                            " {}{}{}{}{} ({})",
-                            if no_children { " " } else { &branch_v },
-                            if first { &left_branch_done_str } else { " " },
-                            if first {
-                                if new_iter.peek().is_some() {
-                                    &down_branch_str
-                                } else {
-                                    ""
-                                }
-                            } else {
-                                if new_iter.peek().is_some() {
-                                    &left_branch_more_str
-                                } else {
-                                    &left_branch_done_str
-                                }
-                            },
-                            left_node,
-                            new_path.display(),
-                            hash
-                        );
-                        if first {
-                            first = false;
-                        }
-                    }
                 }
-
-                if !results.changed_files.is_empty() {
-                    println!(
-                        " {}{}{}{}:",
-                        left_branch_done,
-                        down_branch,
-                        left_node,
-                        "Changed".yellow()
+                if let Some(parent_id) = sims_mod
+                    .parent_mod_id
+                    .filter(|_| fields.contains(ModField::Parent))
+                {
+                    let parent = match mod_names.get(&parent_id) {
+                        Some(name) => format!("{} ({})", name, parent_id),
+                        None => parent_id.to_string(),
+                    };
+                    tree.push(Node::field("Addon of:").value(parent));
+                }
+                if let Some(thumbnail) = sims_mod
+                    .thumbnail
+                    .as_ref()
+                    .filter(|_| fields.contains(ModField::Thumbnail))
+                {
+                    tree.push(Node::field("Thumbnail:").value(thumbnail.clone()));
+                }
+                if fields.contains(ModField::Protected) {
+                    tree.push(
+                        Node::field("Protected:")
+                            .value(if sims_mod.protected { "yes" } else { "no" }.to_string()),
                     );
-                    let mut changed_files = results.changed_files.iter().peekable();
-                    let mut first = true;
-                    while let Some((changed, new_hash)) = changed_files.next() {
-                        println!(
-                            "  {}{}{}{} ({} -> {})",
-                            if first { &left_branch_done_str } else { " " },
-                            if first {
-                                if changed_files.peek().is_some() {
-                                    &down_branch_str
-                                } else {
-                                    ""
-                                }
-                            } else {
-                                if changed_files.peek().is_some() {
-                                    &left_branch_more_str
-                                } else {
-                                    &left_branch_done_str
-                                }
-                            },
-                            left_node,
-                            changed.display(),
-                            hashes
-                                .get(changed)
-                                .expect_or_log("Failed to find match in hashes"),
-                            new_hash
-                        );
-                        if first {
-                            first = false;
-                        }
-                    }
                 }
+                if fields.contains(ModField::Frozen) {
+                    tree.push(
+                        Node::field("Frozen:")
+                            .value(if sims_mod.frozen { "yes" } else { "no" }.to_string()),
+                    );
+                }
+                if fields.contains(ModField::Nsfw) {
+                    tree.push(
+                        Node::field("NSFW:")
+                            .value(if sims_mod.nsfw { "yes" } else { "no" }.to_string()),
+                    );
+                }
+                if fields.contains(ModField::Rating) {
+                    tree.push(Node::field("Rating:").value(util::format_rating(sims_mod.rating)));
+                }
+                if fields.contains(ModField::WouldReinstall) {
+                    tree.push(
+                        Node::field("Would reinstall:").value(
+                            util::format_would_reinstall(sims_mod.would_reinstall).to_string(),
+                        ),
+                    );
+                }
+                if let Some(recheck_after) = sims_mod
+                    .recheck_after
+                    .filter(|_| fields.contains(ModField::RecheckAfter))
+                {
+                    tree.push(
+                        Node::field("Recheck after:")
+                            .value(crate::datetime::format_day(recheck_after)),
+                    );
+                }
+                if let Some(broken_at) = sims_mod
+                    .broken_at
+                    .filter(|_| fields.contains(ModField::Broken))
+                {
+                    let broken = match &sims_mod.broken_reason {
+                        Some(reason) => format!(
+                            "since {} ({})",
+                            crate::datetime::format_datetime(&broken_at),
+                            reason
+                        ),
+                        None => format!("since {}", crate::datetime::format_datetime(&broken_at)),
+                    };
+                    tree.push(Node::field("Broken:").value(broken).status(Status::Failed));
+                }
+                if fields.contains(ModField::Directory) {
+                    tree.push(Node::field("Subdirectory:").value(sims_mod.directory.clone()));
+                }
+                if !required_packs.is_empty() && fields.contains(ModField::RequiredPacks) {
+                    tree.push(Node::field("Required packs:").value(required_packs.join(", ")));
+                }
+                if !missing.is_empty() {
+                    tree.push(
+                        Node::field("Missing packs:")
+                            .value(missing.join(", "))
+                            .status(Status::Missing),
+                    );
+                }
+                if let Some(tags) = tags {
+                    tree.section("Tags:", tags);
+                }
+                tree.print();
+            } else if sims_mod.broken_at.is_some() {
+                println!(
+                    "{}- {} ({}) {} {}{}{}",
+                    indent,
+                    sims_mod.name.bold(),
+                    sims_mod.version,
+                    badge,
+                    style::mark(Status::Failed, "broken").bold(),
+                    sims_mod
+                        .broken_reason
+                        .as_ref()
+                        .map(|r| format!(": {}", r))
+                        .unwrap_or_default(),
+                    count_warning
+                );
+            } else if !missing.is_empty() {
+                println!(
+                    "{}- {} ({}) {} missing {}{}",
+                    indent,
+                    sims_mod.name.bold(),
+                    sims_mod.version,
+                    badge,
+                    style::paint(Status::Missing, &missing.join(", ")),
+                    count_warning
+                );
+            } else {
+                println!(
+                    "{}- {} ({}) {}{}",
+                    indent,
+                    sims_mod.name.bold(),
+                    sims_mod.version,
+                    badge,
+                    count_warning
+                );
             }
-        } else {
-            println!(
-                "- {} ({})",
-                if let Some(result) = &verification_result {
-                    if result.verification_passed() {
-                        sims_mod.name.green()
-                    } else {
-                        sims_mod.name.red()
-                    }
-                    .bold()
-                } else {
-                    sims_mod.name.bold()
-                },
-                sims_mod.version
-            );
         }
     }
 
+    if count_mismatches > 0 {
+        println!(
+            "{} mods have a different number of files than expected. Run {} to see what changed.",
+            style::paint(Status::Warning, &count_mismatches.to_string()),
+            "scan --verify".bold()
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,137 @@
+use chrono::NaiveDate;
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, Condition, QueryOrder};
+use tracing::debug;
+
+use crate::entities::{prelude::*, *};
+
+/// Kinds of changes recorded in the operation journal
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JournalAction {
+    Added,
+    Updated,
+    Edited,
+    Deleted,
+    Restored,
+    Broken,
+    Fixed,
+    Archived,
+}
+
+impl JournalAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            JournalAction::Added => "added",
+            JournalAction::Updated => "updated",
+            JournalAction::Edited => "edited",
+            JournalAction::Deleted => "deleted",
+            JournalAction::Restored => "restored",
+            JournalAction::Broken => "broken",
+            JournalAction::Fixed => "fixed",
+            JournalAction::Archived => "archived",
+        }
+    }
+}
+
+impl std::fmt::Display for JournalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Appends a snapshot of a mod to the operation journal
+pub async fn record<C>(
+    db: &C,
+    action: JournalAction,
+    sims_mod: &sims_mod::Model,
+) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    record_with_details(db, action, sims_mod, None).await
+}
+
+/// Appends a snapshot of a mod to the operation journal, with JSON details of the change
+pub async fn record_with_details<C>(
+    db: &C,
+    action: JournalAction,
+    sims_mod: &sims_mod::Model,
+    details: Option<String>,
+) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    debug!("Journaling {} for {}", action, sims_mod.name);
+    let entry = journal::ActiveModel {
+        timestamp: ActiveValue::Set(chrono::Utc::now()),
+        action: ActiveValue::Set(action.to_string()),
+        mod_id: ActiveValue::Set(sims_mod.id),
+        mod_name: ActiveValue::Set(sims_mod.name.clone()),
+        directory: ActiveValue::Set(sims_mod.directory.clone()),
+        source_url: ActiveValue::Set(sims_mod.source_url.clone()),
+        version: ActiveValue::Set(sims_mod.version.clone()),
+        details: ActiveValue::Set(details),
+        ..Default::default()
+    };
+    Journal::insert(entry).exec(db).await?;
+    Ok(())
+}
+
+pub async fn log(
+    mod_id: Option<i32>,
+    since: Option<NaiveDate>,
+    action: Option<JournalAction>,
+) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let mut condition = Condition::all();
+    if let Some(mod_id) = mod_id {
+        condition = condition.add(journal::Column::ModId.eq(mod_id));
+    }
+    if let Some(since) = since {
+        condition =
+            condition.add(journal::Column::Timestamp.gte(crate::datetime::start_of_day(since)));
+    }
+    if let Some(action) = action {
+        condition = condition.add(journal::Column::Action.eq(action.to_string()));
+    }
+
+    let entries = Journal::find()
+        .filter(condition)
+        .order_by_asc(journal::Column::Timestamp)
+        .all(&db)
+        .await?;
+
+    if entries.is_empty() {
+        println!("No journal entries found.");
+        return Ok(());
+    }
+
+    for entry in entries.iter() {
+        let action = match entry.action.as_str() {
+            "added" | "restored" | "fixed" => entry.action.blue(),
+            "deleted" | "broken" => entry.action.red(),
+            "archived" => entry.action.dimmed(),
+            "updated" => entry.action.yellow(),
+            _ => entry.action.normal(),
+        }
+        .bold();
+        println!(
+            "{} {} {} ({}) {} [{}]",
+            crate::datetime::format_datetime(&entry.timestamp),
+            action,
+            entry.mod_name.bold(),
+            entry.mod_id,
+            entry.version,
+            entry.source_url
+        );
+        if let Some(summary) = entry
+            .details
+            .as_deref()
+            .and_then(super::hash_changes::summarize)
+        {
+            println!("    {}", summary.dimmed());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel, QueryOrder, TransactionTrait};
+use tracing::{debug, info};
+
+use crate::entities::{prelude::*, *};
+
+/// The machine and user the tool is running as
+struct Machine {
+    hostname: String,
+    username: String,
+}
+
+impl Machine {
+    fn current() -> Machine {
+        Machine {
+            hostname: sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string()),
+            username: std::env::var("USERNAME")
+                .or_else(|_| std::env::var("USER"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+        }
+    }
+}
+
+/// Splits two paths at the point where their trailing components stop matching,
+/// returning the differing leading parts
+fn differing_prefixes(old: &Path, new: &Path) -> Option<(PathBuf, PathBuf)> {
+    let old_components: Vec<_> = old.components().collect();
+    let new_components: Vec<_> = new.components().collect();
+    let common = old_components
+        .iter()
+        .rev()
+        .zip(new_components.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_prefix: PathBuf = old_components[..old_components.len() - common]
+        .iter()
+        .collect();
+    let new_prefix: PathBuf = new_components[..new_components.len() - common]
+        .iter()
+        .collect();
+    if old_prefix.as_os_str().is_empty() || old_prefix == new_prefix {
+        return None;
+    }
+    Some((old_prefix, new_prefix))
+}
+
+fn rebase(path: &str, old_prefix: &Path, new_prefix: &Path) -> Option<String> {
+    let rest = Path::new(path).strip_prefix(old_prefix).ok()?;
+    Some(new_prefix.join(rest).to_string_lossy().to_string())
+}
+
+/// Moves absolute paths stored for extra mod roots and local thumbnails onto a new prefix
+async fn rebase_paths(
+    db: &DatabaseConnection,
+    old_prefix: &Path,
+    new_prefix: &Path,
+) -> crate::Result<usize> {
+    let roots = ModRoot::find().all(db).await?;
+    let mods = SimsMod::find()
+        .filter(sims_mod::Column::Thumbnail.is_not_null())
+        .all(db)
+        .await?;
+    let mut root_updates = Vec::new();
+    for root in roots.into_iter() {
+        if let Some(path) = rebase(&root.path, old_prefix, new_prefix) {
+            debug!("Rebasing root {} to {}", root.name, path);
+            let mut active_model = root.into_active_model();
+            active_model.path = ActiveValue::Set(path);
+            root_updates.push(active_model);
+        }
+    }
+    let mut mod_updates = Vec::new();
+    for sims_mod in mods.into_iter() {
+        let rebased = sims_mod
+            .thumbnail
+            .as_deref()
+            .and_then(|thumbnail| rebase(thumbnail, old_prefix, new_prefix));
+        if let Some(thumbnail) = rebased {
+            debug!("Rebasing thumbnail of {} to {}", sims_mod.name, thumbnail);
+            let mut active_model = sims_mod.into_active_model();
+            active_model.thumbnail = ActiveValue::Set(Some(thumbnail));
+            mod_updates.push(active_model);
+        }
+    }
+    let count = root_updates.len() + mod_updates.len();
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            for active_model in root_updates.into_iter() {
+                active_model.update(txn).await?;
+            }
+            for active_model in mod_updates.into_iter() {
+                active_model.update(txn).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(count)
+}
+
+/// Warns when the database was last scanned from another machine or user,
+/// offering to rebase stored absolute paths when `fix` is set
+pub async fn check_machine(
+    db: &DatabaseConnection,
+    mods_dir: &Path,
+    fix: bool,
+) -> crate::Result<()> {
+    let Some(last_scan) = ScanRecord::find()
+        .order_by_desc(scan_record::Column::ScannedAt)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+    let machine = Machine::current();
+    if last_scan.hostname == machine.hostname && last_scan.username == machine.username {
+        return Ok(());
+    }
+    println!(
+        "{} This database was last scanned by {} on {} ({}), not by {} on {}.",
+        "Warning:".yellow().bold(),
+        last_scan.username.bold(),
+        last_scan.hostname.bold(),
+        crate::datetime::format_date(&last_scan.scanned_at),
+        machine.username.bold(),
+        machine.hostname.bold()
+    );
+    let Some((old_prefix, new_prefix)) =
+        differing_prefixes(Path::new(&last_scan.mods_dir), mods_dir)
+    else {
+        return Ok(());
+    };
+    println!(
+        "The Mods folder moved from {} to {}. Paths stored for extra roots and thumbnails may be out of date.",
+        last_scan.mods_dir.bold(),
+        mods_dir.display().to_string().bold()
+    );
+    if !fix {
+        println!(
+            "Run {} to rebase them onto the new location.",
+            "scan --fix".bold()
+        );
+        return Ok(());
+    }
+    if Confirm::new(
+        format!(
+            "Rebase stored paths under {} onto {}?",
+            old_prefix.display(),
+            new_prefix.display()
+        )
+        .as_str(),
+    )
+    .with_default(true)
+    .prompt()?
+    {
+        let count = rebase_paths(db, &old_prefix, &new_prefix).await?;
+        println!("Rebased {} stored paths.", count.to_string().bold());
+    }
+    Ok(())
+}
+
+/// Remembers which machine and user scanned the database, and from which Mods folder
+pub async fn record_machine(db: &DatabaseConnection, mods_dir: &Path) -> crate::Result<()> {
+    // Bookkeeping only, so read-only scans still complete
+    if crate::fileio::is_read_only() {
+        return Ok(());
+    }
+    let machine = Machine::current();
+    info!(
+        "Recording scan by {} on {}",
+        machine.username, machine.hostname
+    );
+    let record = scan_record::ActiveModel {
+        hostname: ActiveValue::Set(machine.hostname),
+        username: ActiveValue::Set(machine.username),
+        mods_dir: ActiveValue::Set(mods_dir.to_string_lossy().to_string()),
+        scanned_at: ActiveValue::Set(chrono::Utc::now()),
+        ..Default::default()
+    };
+    ScanRecord::insert(record).exec(db).await?;
+    Ok(())
+}
@@ -1,10 +1,103 @@
+mod add;
+mod adopt;
+mod archives;
+mod backup;
+mod broken;
+mod cache;
+mod catalog;
+mod collision;
+mod composition;
+mod config;
+mod copies;
+mod dedupe;
+mod diff;
+mod doctor;
 mod edit;
+mod empty;
+mod export;
+mod extras;
+mod fetch;
+mod fields;
+mod files;
+mod hash_changes;
+mod ignored;
+mod info;
+mod links;
 mod list;
+mod log;
+mod machine;
+mod organize;
+mod packs;
+mod played;
+mod postpatch;
+mod probe;
+mod purge;
+mod query;
+mod reminders;
+mod remove;
+mod repair;
+mod report;
+mod restore;
+mod root_files;
+mod roots;
 mod scan;
+mod scan_engine;
+mod schema;
+mod snapshot;
+mod source_type;
+mod stale;
+mod status;
+mod sync;
+mod tag_typos;
 mod tags;
+mod thumbnail;
+mod trashed;
+mod update_channel;
 mod util;
+mod verify;
+mod why_failed;
 
-pub use edit::edit;
-pub use list::list;
-pub use scan::scan;
-pub use tags::tags;
+pub use add::{add, AddOptions};
+pub use adopt::adopt;
+pub use backup::{backup, BackupAction};
+pub use broken::{mark_broken, mark_fixed};
+pub use cache::clear_cache;
+pub use config::{config, ConfigAction};
+pub use dedupe::dedupe;
+pub use diff::diff;
+pub use doctor::{doctor, DoctorLimits};
+pub use edit::{edit, EditFields, EditSelection};
+pub use export::{export, ExportFormat};
+pub use fetch::fetch;
+pub use fields::ModField;
+pub use files::find_mod_files;
+pub use ignored::{ignored, IgnoredAction};
+pub use info::info;
+pub use links::{check_links, LinkCheckLimits};
+pub use list::{list, ListOptions};
+pub use log::{log, JournalAction};
+pub use organize::{organize, OrganizeScheme};
+pub use packs::packs;
+pub use postpatch::postpatch;
+pub use probe::{probe, ProbeAction};
+pub use purge::purge_creator;
+pub use query::{query, QueryAction, TagFilter};
+pub use reminders::reminders;
+pub use remove::remove;
+pub use report::report;
+pub use restore::restore_entry;
+pub use roots::{roots, RootsAction};
+pub use scan::{scan, ScanOptions};
+pub use schema::{schema, SchemaName};
+pub use snapshot::{snapshot, SnapshotAction};
+pub use source_type::SourceType;
+pub use stale::stale;
+pub use status::{status, StatusFormat};
+pub use sync::{sync, SyncAction};
+pub use tags::{tags, TagsOptions};
+pub use thumbnail::thumbnail;
+pub use trashed::{record_trashed, trash, TrashAction};
+pub use update_channel::UpdateChannel;
+pub use util::{parse_age, parse_date};
+pub use verify::verify;
+pub use why_failed::why_failed;
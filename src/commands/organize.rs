@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel, TransactionTrait};
+use tracing::{debug, info, warn};
+
+use crate::commands::{JournalAction, SourceType};
+use crate::entities::sims_mod;
+
+/// Built-in folder layouts for `organize`
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OrganizeScheme {
+    /// Group mods into folders named after their creator
+    Creator,
+    /// Group mods into folders named after their alphabetically first tag
+    Tag,
+    /// Move mods back to the top level of the mod folder
+    Flat,
+}
+
+impl OrganizeScheme {
+    fn template(&self) -> &'static str {
+        match self {
+            OrganizeScheme::Creator => "{creator}/{folder}",
+            OrganizeScheme::Tag => "{tag}/{folder}",
+            OrganizeScheme::Flat => "{folder}",
+        }
+    }
+}
+
+const UNKNOWN_CREATOR: &str = "Unknown Creator";
+const UNTAGGED: &str = "Untagged";
+
+/// Makes a value safe to use as a single folder name
+fn sanitize_component(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    sanitized.trim().trim_end_matches('.').to_string()
+}
+
+/// Fills in a template for one mod, returning its new directory
+fn render_template(template: &str, sims_mod: &sims_mod::Model, tags: &[String]) -> PathBuf {
+    let folder = Path::new(&sims_mod.directory)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| sims_mod.directory.clone());
+    let creator = SourceType::creator_from_url(&sims_mod.source_url)
+        .unwrap_or_else(|| UNKNOWN_CREATOR.to_string());
+    let tag = tags
+        .iter()
+        .min()
+        .cloned()
+        .unwrap_or_else(|| UNTAGGED.to_string());
+    let values = [
+        ("{folder}", folder.as_str()),
+        ("{name}", sims_mod.name.as_str()),
+        ("{creator}", creator.as_str()),
+        ("{tag}", tag.as_str()),
+        ("{source_type}", sims_mod.source_type.as_str()),
+    ];
+    template
+        .split('/')
+        .map(|part| {
+            values.iter().fold(part.to_string(), |part, (key, value)| {
+                part.replace(key, &sanitize_component(value))
+            })
+        })
+        .collect()
+}
+
+/// Checks that a template produces relative paths inside the mod folder
+fn validate_template(template: &str) -> Result<(), String> {
+    if !template.contains("{folder}") && !template.contains("{name}") {
+        return Err("Templates must include {folder} or {name}".to_string());
+    }
+    for part in template.split('/') {
+        if part.trim().is_empty() {
+            return Err("Templates cannot contain empty folder names".to_string());
+        }
+        if !Path::new(part)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(format!("Invalid folder name in template: {}", part));
+        }
+    }
+    Ok(())
+}
+
+fn has_scripts(hashes: &HashMap<PathBuf, String>) -> bool {
+    hashes
+        .keys()
+        .any(|file| file.extension().map(|e| e == "ts4script").unwrap_or(false))
+}
+
+/// Removes empty group folders left behind after moving a mod
+fn remove_empty_parents(mod_dir: &Path, old_path: &Path) {
+    for parent in old_path.ancestors().skip(1) {
+        if parent.as_os_str().is_empty() {
+            break;
+        }
+        if crate::fileio::remove_dir(mod_dir.join(parent)).is_err() {
+            break;
+        }
+        debug!("Removed empty folder {}", parent.display());
+    }
+}
+
+struct PlannedMove {
+    sims_mod: sims_mod::Model,
+    new_directory: PathBuf,
+}
+
+pub async fn organize(
+    scheme: OrganizeScheme,
+    template: Option<String>,
+    dry_run: bool,
+) -> crate::Result<()> {
+    let template = template.as_deref().unwrap_or(scheme.template());
+    if let Err(e) = validate_template(template) {
+        eprintln!("{}", e);
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+    }
+
+    let db = crate::util::open_database().await?;
+    let mod_dir = crate::util::get_sims_mod_dir()?;
+    // Only the main Mods folder is reorganized; other roots keep their own layout
+    let mods = super::util::active_mods()
+        .filter(sims_mod::Column::RootId.is_null())
+        .all(&db)
+        .await?;
+
+    let mut planned: Vec<PlannedMove> = Vec::new();
+    let mut targets: HashMap<PathBuf, String> = HashMap::new();
+    for sims_mod in mods.into_iter() {
+        if super::util::is_root_entry(&sims_mod) {
+            continue;
+        }
+        let tags = super::util::get_tags_for_mod(&db, sims_mod.id).await?;
+        let new_directory = render_template(template, &sims_mod, &tags);
+        let old_directory = PathBuf::from(&sims_mod.directory);
+        if new_directory == old_directory {
+            continue;
+        }
+        if !mod_dir.join(&old_directory).is_dir() {
+            warn!("Skipping {}: directory is missing", sims_mod.name);
+            println!(
+                "Skipping {}: directory {} is missing",
+                sims_mod.name.bold(),
+                old_directory.display()
+            );
+            continue;
+        }
+        if new_directory.components().count() > 1
+            && has_scripts(&super::util::get_hashes_for_mod(&db, sims_mod.id).await?)
+        {
+            println!(
+                "Skipping {}: script mods only load one folder deep",
+                sims_mod.name.bold()
+            );
+            continue;
+        }
+        if mod_dir.join(&new_directory).exists() {
+            println!(
+                "Skipping {}: {} already exists",
+                sims_mod.name.bold(),
+                new_directory.display()
+            );
+            continue;
+        }
+        if let Some(other) = targets.get(&new_directory) {
+            println!(
+                "Skipping {}: {} would also be moved to {}",
+                sims_mod.name.bold(),
+                other.bold(),
+                new_directory.display()
+            );
+            continue;
+        }
+        targets.insert(new_directory.clone(), sims_mod.name.clone());
+        planned.push(PlannedMove {
+            sims_mod,
+            new_directory,
+        });
+    }
+
+    if planned.is_empty() {
+        println!("Mods are already organized.");
+        return Ok(());
+    }
+
+    for planned_move in planned.iter() {
+        println!(
+            "{}: {} -> {}",
+            planned_move.sims_mod.name.bold(),
+            planned_move.sims_mod.directory,
+            planned_move.new_directory.display().to_string().blue()
+        );
+    }
+    if dry_run {
+        return Ok(());
+    }
+    if !Confirm::new(format!("Move {} mods?", planned.len()).as_str())
+        .with_default(false)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let txn = db.begin().await?;
+    let mut transfer = crate::transfer::Transfer::begin("organize")?;
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut result: crate::Result<()> = Ok(());
+    for PlannedMove {
+        sims_mod,
+        new_directory,
+    } in planned.into_iter()
+    {
+        let old_directory = PathBuf::from(&sims_mod.directory);
+        info!(
+            "Moving {} to {}",
+            old_directory.display(),
+            new_directory.display()
+        );
+        if let Err(e) =
+            transfer.move_path(mod_dir.join(&old_directory), mod_dir.join(&new_directory))
+        {
+            eprintln!("Failed to move {}: {}", sims_mod.name, e);
+            result = Err(e);
+            break;
+        }
+        moved.push((old_directory, new_directory.clone()));
+
+        let mut active_model = sims_mod.into_active_model();
+        active_model.directory = ActiveValue::set(new_directory.to_string_lossy().to_string());
+        active_model.updated = ActiveValue::set(chrono::Utc::now());
+        let saved_model = match active_model.update(&txn).await {
+            Ok(saved_model) => saved_model,
+            Err(e) => {
+                result = Err(e.into());
+                break;
+            }
+        };
+        if let Err(e) = super::log::record(&txn, JournalAction::Edited, &saved_model).await {
+            result = Err(e.into());
+            break;
+        }
+    }
+
+    // Moved folders are checked before the database changes are kept
+    let result = match result {
+        Ok(()) => transfer.finish(),
+        Err(e) => {
+            transfer.rollback();
+            Err(e)
+        }
+    };
+    if let Err(e) = result {
+        warn!("Reverted {} moved mods", moved.len());
+        for (_, new_directory) in moved.iter().rev() {
+            remove_empty_parents(&mod_dir, new_directory);
+        }
+        txn.rollback().await?;
+        return Err(e);
+    }
+    txn.commit().await?;
+
+    for (old_directory, _) in moved.iter() {
+        remove_empty_parents(&mod_dir, old_directory);
+    }
+    println!("Moved {} mods.", moved.len().to_string().green().bold());
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use colored::Colorize;
+use sea_orm::{prelude::*, sea_query::Func, ActiveValue, QueryOrder, TransactionTrait};
+use tracing::info;
+
+use crate::entities::{prelude::*, *};
+
+pub async fn packs(add: Option<Vec<String>>, remove: Option<Vec<String>>) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            let mut owned = super::util::get_owned_packs(txn).await?;
+            for pack in add.unwrap_or_default().iter().map(|p| p.trim()) {
+                // Also skips packs given twice, which the first insert now covers
+                if pack.is_empty() || !owned.insert(pack.to_lowercase()) {
+                    continue;
+                }
+                info!("Adding owned pack: {}", pack);
+                let new_pack = owned_pack::ActiveModel {
+                    pack: ActiveValue::Set(pack.to_string()),
+                    ..Default::default()
+                };
+                OwnedPack::insert(new_pack).exec(txn).await?;
+            }
+            for pack in remove.unwrap_or_default().iter().map(|p| p.trim()) {
+                info!("Removing owned pack: {}", pack);
+                OwnedPack::delete_many()
+                    .filter(
+                        Expr::expr(Func::lower(Expr::col(owned_pack::Column::Pack)))
+                            .eq(pack.to_lowercase()),
+                    )
+                    .exec(txn)
+                    .await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+
+    let owned = OwnedPack::find()
+        .order_by_asc(owned_pack::Column::Pack)
+        .all(&db)
+        .await?;
+    if owned.is_empty() {
+        println!("No owned packs configured.");
+    } else {
+        println!("{}", "Owned packs:".bold());
+        for pack in owned.iter() {
+            println!("- {}", pack.pack);
+        }
+    }
+    Ok(())
+}
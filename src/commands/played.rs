@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder};
+use tracing::{debug, info};
+
+use crate::commands::JournalAction;
+use crate::entities::{prelude::*, *};
+
+/// Files in the game's user folder that the game rewrites while it runs
+const PLAY_MARKERS: &[&str] = &[
+    "localthumbcache.package",
+    "Config.log",
+    "Options.ini",
+    "GameVersion.txt",
+];
+
+/// The most recently modified play marker next to the Mods folder
+fn latest_marker(game_dir: &Path) -> Option<(DateTime<Utc>, &'static str)> {
+    PLAY_MARKERS
+        .iter()
+        .filter_map(|marker| {
+            let modified = game_dir.join(marker).metadata().ok()?.modified().ok()?;
+            Some((DateTime::<Utc>::from(modified), *marker))
+        })
+        .max_by_key(|(modified, _)| *modified)
+}
+
+/// Records a new play session if the game touched its files since the last one,
+/// and returns when the game was last played
+pub async fn last_play_session(db: &DatabaseConnection) -> crate::Result<Option<DateTime<Utc>>> {
+    let last_session = PlaySession::find()
+        .order_by_desc(play_session::Column::PlayedAt)
+        .one(db)
+        .await?
+        .map(|session| session.played_at);
+
+    let game_dir = crate::util::get_game_dir();
+    let Some((played_at, marker)) = game_dir.as_deref().and_then(latest_marker) else {
+        debug!("No play markers found");
+        return Ok(last_session);
+    };
+    if last_session.is_some_and(|last| played_at <= last) {
+        return Ok(last_session);
+    }
+    if crate::fileio::is_read_only() {
+        return Ok(Some(played_at));
+    }
+    info!("Recording play session at {} from {}", played_at, marker);
+    let session = play_session::ActiveModel {
+        played_at: ActiveValue::Set(played_at),
+        marker: ActiveValue::Set(marker.to_string()),
+        ..Default::default()
+    };
+    PlaySession::insert(session).exec(db).await?;
+    Ok(Some(played_at))
+}
+
+/// Installed mods added after the given time, which the game has not loaded yet
+pub async fn mods_added_since(
+    db: &DatabaseConnection,
+    since: DateTime<Utc>,
+) -> Result<Vec<sims_mod::Model>, DbErr> {
+    let mod_ids: Vec<i32> = Journal::find()
+        .filter(journal::Column::Action.eq(JournalAction::Added.to_string()))
+        .filter(journal::Column::Timestamp.gt(since))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|entry| entry.mod_id)
+        .collect();
+    super::util::active_mods()
+        .filter(sims_mod::Column::Id.is_in(mod_ids))
+        .order_by_asc(sims_mod::Column::Name)
+        .all(db)
+        .await
+}
+
+/// Lists mods added since the last play session, the prime suspects when a save misbehaves
+pub async fn report_added_since_play(db: &DatabaseConnection) -> crate::Result<()> {
+    let Some(last_played) = last_play_session(db).await? else {
+        return Ok(());
+    };
+    let added = mods_added_since(db, last_played).await?;
+    if added.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "{} mods were added since the game was last played ({}):",
+        added.len().to_string().yellow().bold(),
+        crate::datetime::format_datetime(&last_played)
+    );
+    for sims_mod in added.iter() {
+        println!("  {} ({})", sims_mod.name.bold(), sims_mod.id);
+    }
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use colored::Colorize;
+use inquire::Confirm;
+use regex::Regex;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder};
+use tracing::{info, warn};
+use tracing_unwrap::ResultExt;
+
+use crate::commands::{util::VerificationPassed, SourceType};
+use crate::entities::{prelude::*, *};
+use crate::style::{self, Status};
+
+/// Reads the installed game version from `GameVersion.txt`, which holds a few
+/// binary bytes followed by a version such as `1.105.332.1020`
+pub fn read_game_version(game_dir: &Path) -> Option<String> {
+    let contents = crate::fileio::read(game_dir.join("GameVersion.txt")).ok()?;
+    let contents = String::from_utf8_lossy(&contents);
+    let pattern = Regex::new(r"\d+(?:\.\d+){2,}").expect_or_log("Invalid game version pattern");
+    pattern.find(&contents).map(|m| m.as_str().to_string())
+}
+
+/// Compares the installed game version with the last one seen, recording it if it changed
+async fn detect_game_version(db: &DatabaseConnection) -> crate::Result<()> {
+    let Some(version) = crate::util::get_game_dir().and_then(|dir| read_game_version(&dir)) else {
+        println!(
+            "{}",
+            style::mark(
+                Status::Warning,
+                "Could not read the game version from GameVersion.txt."
+            )
+        );
+        return Ok(());
+    };
+    let last_seen = GameVersion::find()
+        .order_by_desc(game_version::Column::DetectedAt)
+        .one(db)
+        .await?;
+    match &last_seen {
+        Some(last_seen) if last_seen.version == version => {
+            println!(
+                "Game version {} is unchanged since {}.",
+                version.bold(),
+                crate::datetime::format_date(&last_seen.detected_at)
+            );
+            return Ok(());
+        }
+        Some(last_seen) => println!(
+            "{}",
+            style::mark(
+                Status::Changed,
+                &format!("Game updated from {} to {}.", last_seen.version, version)
+            )
+            .bold()
+        ),
+        None => println!("Game version {} recorded.", version.bold()),
+    }
+    info!("Recording game version {}", version);
+    let record = game_version::ActiveModel {
+        version: ActiveValue::Set(version),
+        detected_at: ActiveValue::Set(chrono::Utc::now()),
+        ..Default::default()
+    };
+    GameVersion::insert(record).exec(db).await?;
+    Ok(())
+}
+
+/// Installed mods with script files, which break most often after a patch
+async fn script_mods(db: &DatabaseConnection) -> Result<Vec<sims_mod::Model>, DbErr> {
+    let mod_ids: HashSet<i32> = ModHash::find()
+        .filter(mod_hash::Column::File.like("%.ts4script"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|hash| hash.mod_id)
+        .collect();
+    super::util::active_mods()
+        .filter(sims_mod::Column::Id.is_in(mod_ids))
+        .order_by_asc(sims_mod::Column::Name)
+        .all(db)
+        .await
+}
+
+/// Files disabled by `postpatch --disable`
+const SCRIPT_EXTENSIONS: &[&str] = &["ts4script"];
+
+/// Re-enables every script file disabled by an earlier `postpatch --disable`, except
+/// those of mods marked broken
+async fn enable_scripts(db: &DatabaseConnection) -> crate::Result<()> {
+    let roots = super::util::ModRoots::load(db).await?;
+    let mut enabled = 0;
+    for sims_mod in script_mods(db).await?.iter() {
+        if sims_mod.broken_at.is_some() {
+            println!(
+                "Skipped {} (broken, not enabled)",
+                style::paint(Status::Failed, &sims_mod.name).bold()
+            );
+            continue;
+        }
+        let mod_dir = roots.mod_dir(sims_mod)?;
+        if !mod_dir.is_dir() {
+            continue;
+        }
+        let count = super::util::set_files_enabled(&mod_dir, SCRIPT_EXTENSIONS, true)?;
+        if count > 0 {
+            println!("Enabled {}", sims_mod.name.bold());
+            enabled += count;
+        }
+    }
+    if enabled == 0 {
+        println!("No disabled script files found.");
+    } else {
+        println!(
+            "Enabled {} script files.",
+            enabled.to_string().green().bold()
+        );
+        super::cache::offer_clear_cache()?;
+    }
+    Ok(())
+}
+
+/// Chains the checks worth running after a game update: detects the new game version,
+/// lists script mods, optionally disables them, verifies them, and prints a checklist
+/// of creator pages to check for updates
+pub async fn postpatch(disable: bool, enable: bool) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    if enable {
+        return enable_scripts(&db).await;
+    }
+
+    println!("{}", "Game version".bold());
+    detect_game_version(&db).await?;
+
+    println!("\n{}", "Script mods".bold());
+    let mods = script_mods(&db).await?;
+    if mods.is_empty() {
+        println!("No script mods found.");
+        return Ok(());
+    }
+    for sims_mod in mods.iter() {
+        println!(
+            "  {} ({}) version {}",
+            sims_mod.name.bold(),
+            sims_mod.id,
+            sims_mod.version
+        );
+    }
+
+    if disable {
+        println!("\n{}", "Disable script mods".bold());
+        if Confirm::new(
+            format!("Disable {} script mods until they are updated?", mods.len()).as_str(),
+        )
+        .with_default(false)
+        .prompt()?
+        {
+            let roots = super::util::ModRoots::load(&db).await?;
+            for sims_mod in mods.iter() {
+                let mod_dir = roots.mod_dir(sims_mod)?;
+                if !mod_dir.is_dir() {
+                    warn!("Skipping missing folder of {}", sims_mod.name);
+                    continue;
+                }
+                super::util::set_files_enabled(&mod_dir, SCRIPT_EXTENSIONS, false)?;
+                let addons = super::util::get_addons(&db, sims_mod.id).await?;
+                if !addons.is_empty() {
+                    println!(
+                        "{}",
+                        style::mark(
+                            Status::Warning,
+                            &format!(
+                                "Addons of {} may not work while it is disabled: {}",
+                                sims_mod.name,
+                                addons
+                                    .iter()
+                                    .map(|addon| addon.name.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )
+                        )
+                    );
+                }
+            }
+            println!(
+                "Disabled. Run {} to turn them back on.",
+                "postpatch --enable".bold()
+            );
+            super::cache::offer_clear_cache()?;
+        }
+    }
+
+    println!("\n{}", "Verification".bold());
+    let mut failing = Vec::new();
+    for sims_mod in mods.iter() {
+        if !super::util::verify_mod(&db, sims_mod)
+            .await?
+            .verification_passed()
+        {
+            failing.push(sims_mod.name.as_str());
+        }
+    }
+    if failing.is_empty() {
+        println!(
+            "{}",
+            style::mark(Status::Passed, "All script mods match their stored files.")
+        );
+    } else {
+        println!(
+            "{}",
+            style::mark(
+                Status::Failed,
+                &format!(
+                    "{} script mods changed since they were recorded: {}",
+                    failing.len(),
+                    failing.join(", ")
+                )
+            )
+        );
+        println!("Run {} to record them.", "scan --verify --fix".bold());
+    }
+
+    println!("\n{}", "Check for updates".bold());
+    for sims_mod in mods.iter() {
+        let page = SourceType::creator_page(&sims_mod.source_url)
+            .unwrap_or_else(|| "no source URL".to_string());
+        let broken = if sims_mod.broken_at.is_some() {
+            format!(" {}", style::paint(Status::Failed, "(broken)"))
+        } else {
+            String::new()
+        };
+        println!("  [ ] {}{}: {}", sims_mod.name.bold(), broken, page);
+    }
+    println!(
+        "\nMark mods reported as broken with {}.",
+        "mark-broken --mod-id <ID>".bold()
+    );
+    Ok(())
+}
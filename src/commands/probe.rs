@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Confirm;
+use regex::Regex;
+use sea_orm::{prelude::*, sea_query::OnConflict, ActiveValue, IntoActiveModel};
+use tracing::{debug, info};
+
+use crate::commands::JournalAction;
+use crate::entities::{prelude::*, *};
+use crate::style::{self, Status};
+
+/// Matches the first dotted version number, such as `2024.3.0`
+const DEFAULT_PATTERN: &str = r"(\d+(?:\.\d+)+)";
+
+/// Actions for managing version probes, which read a mod's installed version from one of its files
+#[derive(clap::Subcommand, Debug)]
+pub enum ProbeAction {
+    /// Sets the file and pattern used to read a mod's installed version
+    Set {
+        /// Mod ID to probe
+        #[arg(short, long)]
+        mod_id: i32,
+
+        /// File to read, relative to the mod's folder
+        file: PathBuf,
+
+        /// Regular expression finding the version. The first capture group is used if there is one.
+        #[arg(short, long, default_value = DEFAULT_PATTERN)]
+        pattern: String,
+    },
+    /// Removes a mod's version probe
+    Clear {
+        /// Mod ID
+        #[arg(short, long)]
+        mod_id: i32,
+    },
+    /// Lists version probes with the versions they currently read
+    List,
+}
+
+/// Strips whitespace and a leading "v" so "v1.2" and "1.2" compare equal
+fn normalize_version(version: &str) -> &str {
+    let version = version.trim();
+    version
+        .strip_prefix(['v', 'V'])
+        .unwrap_or(version)
+        .trim_start()
+}
+
+/// Reads the installed version of a mod using its probe
+fn read_version(mod_dir: &Path, probe: &version_probe::Model) -> Result<String, String> {
+    let pattern = Regex::new(&probe.pattern).map_err(|e| e.to_string())?;
+    let path = mod_dir.join(&probe.file);
+    let data = crate::fileio::read(&path).map_err(|e| e.to_string())?;
+    let contents = String::from_utf8_lossy(&data);
+    let captures = pattern
+        .captures(&contents)
+        .ok_or_else(|| format!("No version found in {}", probe.file))?;
+    let version = captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str().trim().to_string())
+        .unwrap_or_default();
+    if version.is_empty() {
+        return Err(format!("No version found in {}", probe.file));
+    }
+    Ok(version)
+}
+
+/// Runs the version probes of the given mods, reporting versions that differ from the database.
+/// With `fix`, offers to record the installed version.
+pub async fn check_versions(
+    db: &DatabaseConnection,
+    mod_ids: &HashSet<i32>,
+    fix: bool,
+) -> crate::Result<()> {
+    let probes: Vec<_> = VersionProbe::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|probe| mod_ids.contains(&probe.mod_id))
+        .collect();
+    if probes.is_empty() {
+        return Ok(());
+    }
+    let roots = super::util::ModRoots::load(db).await?;
+    let mut mismatched = 0;
+    for probe in probes.iter() {
+        let Some(sims_mod) = super::util::active_mods()
+            .filter(sims_mod::Column::Id.eq(probe.mod_id))
+            .one(db)
+            .await?
+        else {
+            continue;
+        };
+        let installed = match read_version(&roots.mod_dir(&sims_mod)?, probe) {
+            Ok(installed) => installed,
+            Err(e) => {
+                println!(
+                    "Version probe {} for {}: {}",
+                    style::mark(Status::Failed, "failed"),
+                    sims_mod.name.bold(),
+                    e
+                );
+                continue;
+            }
+        };
+        debug!("{} reports version {}", sims_mod.name, installed);
+        if normalize_version(&installed) == normalize_version(&sims_mod.version) {
+            continue;
+        }
+        mismatched += 1;
+        println!(
+            "Version mismatch: {} (recorded {}, installed {})",
+            style::mark(Status::Changed, &sims_mod.name).bold(),
+            sims_mod.version,
+            installed.bold()
+        );
+        if fix
+            && Confirm::new(
+                format!(
+                    "Record version {} for {}?",
+                    installed.bold(),
+                    sims_mod.name.bold()
+                )
+                .as_str(),
+            )
+            .with_default(true)
+            .prompt()?
+        {
+            let mut active_model = sims_mod.into_active_model();
+            active_model.version = ActiveValue::Set(installed);
+            active_model.updated = ActiveValue::Set(chrono::Utc::now());
+            let saved_model = active_model.update(db).await?;
+            super::log::record(db, JournalAction::Edited, &saved_model).await?;
+        }
+    }
+    if mismatched > 0 && !fix {
+        println!(
+            "{} mods report a different installed version. Run {} to record them.",
+            style::paint(Status::Warning, &mismatched.to_string()).bold(),
+            "scan --fix".bold()
+        );
+    }
+    Ok(())
+}
+
+pub async fn probe(action: ProbeAction) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    match action {
+        ProbeAction::Set {
+            mod_id,
+            file,
+            pattern,
+        } => {
+            let Some(sims_mod) = super::util::active_mods()
+                .filter(sims_mod::Column::Id.eq(mod_id))
+                .one(&db)
+                .await?
+            else {
+                eprintln!("No mod with mod ID {} found!", mod_id);
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            };
+            if let Err(e) = Regex::new(&pattern) {
+                eprintln!("Invalid pattern: {}", e);
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            if file.is_absolute() {
+                eprintln!("The probe file must be relative to the mod's folder");
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            info!("Setting version probe for {}", sims_mod.name);
+            let new_probe = version_probe::ActiveModel {
+                mod_id: ActiveValue::Set(mod_id),
+                file: ActiveValue::Set(file.to_string_lossy().to_string()),
+                pattern: ActiveValue::Set(pattern),
+            };
+            let saved_probe = VersionProbe::insert(new_probe)
+                .on_conflict(
+                    OnConflict::column(version_probe::Column::ModId)
+                        .update_columns([
+                            version_probe::Column::File,
+                            version_probe::Column::Pattern,
+                        ])
+                        .to_owned(),
+                )
+                .exec_with_returning(&db)
+                .await?;
+            let roots = super::util::ModRoots::load(&db).await?;
+            match read_version(&roots.mod_dir(&sims_mod)?, &saved_probe) {
+                Ok(installed) => println!(
+                    "Set version probe for {}, which reads {}",
+                    sims_mod.name.bold(),
+                    installed.bold()
+                ),
+                Err(e) => println!(
+                    "Set version probe for {}, but it {}: {}",
+                    sims_mod.name.bold(),
+                    style::mark(Status::Failed, "failed"),
+                    e
+                ),
+            }
+        }
+        ProbeAction::Clear { mod_id } => {
+            let result = VersionProbe::delete_by_id(mod_id).exec(&db).await?;
+            if result.rows_affected == 0 {
+                eprintln!("No version probe for mod ID {} found!", mod_id);
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            }
+            println!("Cleared version probe for mod ID {}", mod_id);
+        }
+        ProbeAction::List => {
+            let roots = super::util::ModRoots::load(&db).await?;
+            let probes = VersionProbe::find()
+                .find_also_related(SimsMod)
+                .all(&db)
+                .await?;
+            if probes.is_empty() {
+                println!("No version probes.");
+            }
+            for (probe, sims_mod) in probes.iter() {
+                let Some(sims_mod) = sims_mod.as_ref().filter(|m| m.deleted_at.is_none()) else {
+                    continue;
+                };
+                let installed = match read_version(&roots.mod_dir(sims_mod)?, probe) {
+                    Ok(installed)
+                        if normalize_version(&installed)
+                            == normalize_version(&sims_mod.version) =>
+                    {
+                        style::mark(Status::Passed, &installed)
+                    }
+                    Ok(installed) => style::mark(Status::Changed, &installed),
+                    Err(e) => style::mark(Status::Failed, &e),
+                };
+                println!(
+                    "- {} ({}): {} /{}/ recorded {}, installed {}",
+                    sims_mod.name.bold(),
+                    sims_mod.id,
+                    probe.file,
+                    probe.pattern,
+                    sims_mod.version,
+                    installed
+                );
+            }
+        }
+    }
+    Ok(())
+}
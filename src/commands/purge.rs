@@ -0,0 +1,154 @@
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::QueryOrder;
+use tracing::{info, warn};
+
+use crate::commands::SourceType;
+use crate::entities::sims_mod;
+
+/// Mod files renamed when a creator's mods are disabled
+const GAME_EXTENSIONS: &[&str] = &["package", "ts4script"];
+
+/// Whether a mod is by a creator, given as the creator's name in the source URL
+/// or the source URL's domain
+fn is_by_creator(sims_mod: &sims_mod::Model, creator: &str) -> bool {
+    if SourceType::creator_from_url(&sims_mod.source_url)
+        .is_some_and(|name| name.to_lowercase() == creator)
+    {
+        return true;
+    }
+    url::Url::parse(&sims_mod.source_url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        .map(|host| {
+            let host = host.trim_start_matches("www.");
+            host == creator || host.ends_with(&format!(".{}", creator))
+        })
+        .unwrap_or(false)
+}
+
+/// Lists every mod by a creator with their sizes, then disables or removes them all
+/// after confirmation. With `enable`, files disabled earlier are turned back on,
+/// except for mods marked broken.
+pub async fn purge_creator(
+    creator: String,
+    remove: bool,
+    permanent: bool,
+    enable: bool,
+) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let creator = creator.trim().to_lowercase();
+    let creator = creator.trim_start_matches("www.");
+    let mods: Vec<sims_mod::Model> = super::util::active_mods()
+        .order_by_asc(sims_mod::Column::Name)
+        .all(&db)
+        .await?
+        .into_iter()
+        .filter(|sims_mod| is_by_creator(sims_mod, creator))
+        .collect();
+    if mods.is_empty() {
+        println!("No mods by {} found.", creator.bold());
+        return Ok(());
+    }
+
+    let roots = super::util::ModRoots::load(&db).await?;
+    let linked = super::dedupe::linked_paths(&db).await?;
+    let mut total = 0;
+    for sims_mod in mods.iter() {
+        let mod_dir = roots.mod_dir(sims_mod)?;
+        let size = if mod_dir.is_dir() {
+            let (size, _) = super::stale::folder_stats(&mod_dir, &linked);
+            total += size;
+            super::util::format_size(size).normal()
+        } else {
+            "missing".red()
+        };
+        println!(
+            "{} ({}) {} [{}]",
+            sims_mod.name.bold(),
+            sims_mod.id,
+            sims_mod.source_url.dimmed(),
+            size
+        );
+    }
+    println!(
+        "{} mods by {}, using {}.",
+        mods.len().to_string().yellow().bold(),
+        creator.bold(),
+        super::util::format_size(total).bold()
+    );
+
+    let action = if enable {
+        "Enable the disabled files of".normal()
+    } else if remove && permanent {
+        "Permanently delete".red().bold()
+    } else if remove {
+        "Move to the trash and remove".normal()
+    } else {
+        "Disable".normal()
+    };
+    if !Confirm::new(&format!("{} all {} mods?", action, mods.len()))
+        .with_default(false)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let mut changed = 0;
+    let mut skipped_broken = 0;
+    for sims_mod in mods.iter() {
+        let mod_dir = roots.mod_dir(sims_mod)?;
+        if remove {
+            if sims_mod.protected && !super::util::confirm_protected_mod(&sims_mod.name, "remove")?
+            {
+                println!("Keeping protected mod {}", sims_mod.name.bold());
+                continue;
+            }
+            super::remove::remove_mod(&db, sims_mod, &mod_dir, permanent).await?;
+            println!("Removed mod: {}", sims_mod.name.bold().red());
+            changed += 1;
+            continue;
+        }
+        if enable && sims_mod.broken_at.is_some() {
+            info!("Leaving broken mod {} disabled", sims_mod.name);
+            skipped_broken += 1;
+            continue;
+        }
+        if !mod_dir.is_dir() {
+            warn!("Skipping missing folder of {}", sims_mod.name);
+            continue;
+        }
+        let count = super::util::set_files_enabled(&mod_dir, GAME_EXTENSIONS, enable)?;
+        if count > 0 {
+            info!(
+                "{} {} files of {}",
+                if enable { "Enabled" } else { "Disabled" },
+                count,
+                sims_mod.name
+            );
+            changed += 1;
+        }
+    }
+    if remove {
+        println!("Removed {} mods.", changed.to_string().red().bold());
+    } else if enable {
+        println!("Enabled {} mods.", changed.to_string().green().bold());
+        if skipped_broken > 0 {
+            println!(
+                "Left {} broken mods disabled. Run {} once they are updated.",
+                skipped_broken.to_string().red().bold(),
+                "mark-fixed".bold()
+            );
+        }
+    } else {
+        println!(
+            "Disabled {} mods. Run {} to turn them back on.",
+            changed.to_string().yellow().bold(),
+            format!("purge-creator {} --enable", creator).bold()
+        );
+    }
+    if changed > 0 {
+        super::cache::offer_clear_cache()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use clap::Parser;
+use colored::Colorize;
+use sea_orm::{prelude::*, sea_query::OnConflict, ActiveValue, QueryOrder};
+use tracing::{debug, info};
+
+use crate::entities::{prelude::*, *};
+
+/// Flags a mod can be selected by
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModFlag {
+    Nsfw,
+    Protected,
+    Frozen,
+    Broken,
+}
+
+impl ModFlag {
+    fn is_set(&self, sims_mod: &sims_mod::Model) -> bool {
+        match self {
+            ModFlag::Nsfw => sims_mod.nsfw,
+            ModFlag::Protected => sims_mod.protected,
+            ModFlag::Frozen => sims_mod.frozen,
+            ModFlag::Broken => sims_mod.broken_at.is_some(),
+        }
+    }
+}
+
+/// Filters accepted anywhere mods are selected by tag, flag, or source
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct TagFilter {
+    /// Only include mods with any of the given tags
+    #[arg(short, long, value_delimiter = ',')]
+    pub tags: Option<Vec<String>>,
+
+    /// Only include mods with all of the given tags
+    #[arg(long, value_delimiter = ',')]
+    pub all_tags: Option<Vec<String>>,
+
+    /// Leave out mods with any of the given tags
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_tags: Option<Vec<String>>,
+
+    /// Only include mods with all of the given flags
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub flags: Option<Vec<ModFlag>>,
+
+    /// Leave out mods with any of the given flags
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub exclude_flags: Option<Vec<ModFlag>>,
+
+    /// Leave out mods from any of the given kinds of source
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub exclude_sources: Option<Vec<super::SourceType>>,
+
+    /// Apply a query saved with `query save`
+    #[arg(short, long)]
+    pub query: Option<String>,
+}
+
+/// Parser for the filter arguments stored in a saved query
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+struct SavedFilter {
+    #[command(flatten)]
+    filter: TagFilter,
+}
+
+impl TagFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_none()
+            && self.all_tags.is_none()
+            && self.exclude_tags.is_none()
+            && self.flags.is_none()
+            && self.exclude_flags.is_none()
+            && self.exclude_sources.is_none()
+            && self.query.is_none()
+    }
+
+    /// Whether a mod passes the flag and source filters. Tags are checked separately.
+    pub fn matches_flags(&self, sims_mod: &sims_mod::Model) -> bool {
+        self.flags
+            .iter()
+            .flatten()
+            .all(|flag| flag.is_set(sims_mod))
+            && !self
+                .exclude_flags
+                .iter()
+                .flatten()
+                .any(|flag| flag.is_set(sims_mod))
+            && !self
+                .exclude_sources
+                .iter()
+                .flatten()
+                .any(|source| sims_mod.source_type == source.as_str())
+    }
+
+    /// Parses filter arguments as written on the command line
+    fn parse(filter: &str) -> Result<TagFilter, String> {
+        let args = shlex::split(filter).ok_or("Unbalanced quotes in query")?;
+        let filter = SavedFilter::try_parse_from(args)
+            .map_err(|e| {
+                e.render()
+                    .to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            })?
+            .filter;
+        if filter.query.is_some() {
+            return Err("Saved queries cannot refer to other queries".to_string());
+        }
+        if filter.is_empty() {
+            return Err("Saved queries need at least one filter".to_string());
+        }
+        Ok(filter)
+    }
+
+    /// Replaces a saved query reference with the filters it stores
+    pub async fn resolve<C>(mut self, db: &C) -> crate::Result<TagFilter>
+    where
+        C: sea_orm::ConnectionTrait,
+    {
+        let Some(name) = self.query.take() else {
+            return Ok(self);
+        };
+        let Some(saved_query) = SavedQuery::find()
+            .filter(saved_query::Column::Name.eq(&name))
+            .one(db)
+            .await?
+        else {
+            eprintln!("No saved query named {} found!", name);
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        };
+        debug!("Applying saved query {}: {}", name, saved_query.filter);
+        let saved = TagFilter::parse(&saved_query.filter)?;
+        for (own, saved) in [
+            (&mut self.tags, saved.tags),
+            (&mut self.all_tags, saved.all_tags),
+            (&mut self.exclude_tags, saved.exclude_tags),
+        ] {
+            merge(own, saved);
+        }
+        merge(&mut self.flags, saved.flags);
+        merge(&mut self.exclude_flags, saved.exclude_flags);
+        merge(&mut self.exclude_sources, saved.exclude_sources);
+        Ok(self)
+    }
+}
+
+/// Adds a saved query's values for one filter to those given on the command line
+fn merge<T>(own: &mut Option<Vec<T>>, saved: Option<Vec<T>>) {
+    if let Some(saved) = saved {
+        own.get_or_insert_with(Vec::new).extend(saved);
+    }
+}
+
+/// Actions for managing saved queries
+#[derive(clap::Subcommand, Debug)]
+pub enum QueryAction {
+    /// Saves tag filters under a name, replacing any query with the same name
+    Save {
+        /// Name of the query
+        name: String,
+
+        /// Filter arguments, e.g. "--all-tags CAS,Maxis-Match --exclude-flags nsfw"
+        #[arg(allow_hyphen_values = true)]
+        filter: String,
+    },
+    /// Lists saved queries
+    List,
+    /// Deletes a saved query
+    Delete {
+        /// Name of the query
+        name: String,
+    },
+}
+
+pub async fn query(action: QueryAction) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    match action {
+        QueryAction::Save { name, filter } => {
+            if let Err(e) = TagFilter::parse(&filter) {
+                eprintln!("{}", e);
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            info!("Saving query {}: {}", name, filter);
+            let new_query = saved_query::ActiveModel {
+                name: ActiveValue::Set(name.clone()),
+                filter: ActiveValue::Set(filter),
+                ..Default::default()
+            };
+            SavedQuery::insert(new_query)
+                .on_conflict(
+                    OnConflict::column(saved_query::Column::Name)
+                        .update_column(saved_query::Column::Filter)
+                        .to_owned(),
+                )
+                .exec(&db)
+                .await?;
+            println!("Saved query {}", name.bold());
+        }
+        QueryAction::List => {
+            let saved_queries = SavedQuery::find()
+                .order_by_asc(saved_query::Column::Name)
+                .all(&db)
+                .await?;
+            if saved_queries.is_empty() {
+                println!("No saved queries.");
+            }
+            for saved_query in saved_queries.iter() {
+                println!("- {}: {}", saved_query.name.bold(), saved_query.filter);
+            }
+        }
+        QueryAction::Delete { name } => {
+            let result = SavedQuery::delete_many()
+                .filter(saved_query::Column::Name.eq(&name))
+                .exec(&db)
+                .await?;
+            if result.rows_affected == 0 {
+                eprintln!("No saved query named {} found!", name);
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            }
+            println!("Deleted query {}", name.bold());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use colored::Colorize;
+use sea_orm::{prelude::*, QueryOrder};
+
+use crate::entities::*;
+
+pub async fn reminders(all: bool) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let mods = if all {
+        super::util::active_mods()
+            .filter(sims_mod::Column::RecheckAfter.is_not_null())
+            .order_by_asc(sims_mod::Column::RecheckAfter)
+            .all(&db)
+            .await?
+    } else {
+        super::util::mods_due_for_recheck().all(&db).await?
+    };
+
+    if mods.is_empty() {
+        println!("No mods are due for a recheck.");
+        return Ok(());
+    }
+
+    let today = crate::datetime::today();
+    for sims_mod in mods.iter() {
+        let Some(recheck_after) = sims_mod.recheck_after else {
+            continue;
+        };
+        let date = crate::datetime::format_day(recheck_after);
+        let downloaded = sims_mod
+            .added_at
+            .map(|added_at| {
+                format!(
+                    " {}",
+                    format!(
+                        "(downloaded {} days ago)",
+                        (chrono::Utc::now() - added_at).num_days()
+                    )
+                    .dimmed()
+                )
+            })
+            .unwrap_or_default();
+        println!(
+            "- {} ({}){} recheck after {}: {}",
+            sims_mod.name.bold(),
+            sims_mod.version,
+            downloaded,
+            if recheck_after <= today {
+                date.yellow().bold()
+            } else {
+                date.normal()
+            },
+            sims_mod.source_url
+        );
+    }
+    Ok(())
+}
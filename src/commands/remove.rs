@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::prelude::*;
+use tracing::info;
+
+use crate::commands::JournalAction;
+use crate::entities::{prelude::*, *};
+
+/// Moves a mod's folder to the trash, or deletes it when `permanent` is set,
+/// and deletes its database entry. Stored hashes are only purged along with a
+/// permanently deleted folder, so a trashed mod can still be restored or relinked.
+pub async fn remove_mod(
+    db: &DatabaseConnection,
+    sims_mod: &sims_mod::Model,
+    mod_dir: &Path,
+    permanent: bool,
+) -> crate::Result<()> {
+    if super::util::is_root_entry(sims_mod) {
+        eprintln!(
+            "{} is the Mods folder itself and can't be removed!",
+            sims_mod.name.bold()
+        );
+        return Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied).into());
+    }
+    if mod_dir.is_dir() {
+        super::trashed::remove_path(db, mod_dir, Some(sims_mod), permanent).await?;
+    }
+    info!("Deleting {}...", sims_mod.name);
+    super::util::delete_mod_entry(db, sims_mod, JournalAction::Deleted).await?;
+    if permanent {
+        info!("Purging the stored hashes of {}", sims_mod.name);
+        ModHash::delete_many()
+            .filter(mod_hash::Column::ModId.eq(sims_mod.id))
+            .exec(db)
+            .await?;
+    }
+    super::util::cleanup_tags(db).await?;
+    Ok(())
+}
+
+/// Removes a mod's folder and deletes its database entry
+pub async fn remove(mod_id: i32, permanent: bool) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let Some(sims_mod) = super::util::active_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(&db)
+        .await?
+    else {
+        eprintln!("No mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+
+    let roots = super::util::ModRoots::load(&db).await?;
+    let mod_dir = roots.mod_dir(&sims_mod)?;
+    let destination = if permanent {
+        "permanently delete".red().bold()
+    } else {
+        "move to the trash".normal()
+    };
+    if !Confirm::new(&format!(
+        "Remove {} and {} {}?",
+        sims_mod.name.bold(),
+        destination,
+        mod_dir.display()
+    ))
+    .with_default(false)
+    .prompt()?
+    {
+        return Ok(());
+    }
+    if sims_mod.protected && !super::util::confirm_protected_mod(&sims_mod.name, "remove")? {
+        println!("Keeping protected mod {}", sims_mod.name.bold());
+        return Ok(());
+    }
+    if !super::util::confirm_parent_mod(&db, &sims_mod, "remove").await? {
+        return Ok(());
+    }
+
+    remove_mod(&db, &sims_mod, &mod_dir, permanent).await?;
+
+    if permanent {
+        println!("Removed mod: {}", sims_mod.name.bold().red());
+    } else {
+        println!(
+            "Removed mod: {} (files are in the trash)",
+            sims_mod.name.bold().red()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::path::{Component, Path};
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::{prelude::*, Condition, JoinType, QuerySelect, RelationTrait, TransactionTrait};
+use tracing::info;
+
+use crate::commands::JournalAction;
+use crate::entities::{prelude::*, *};
+
+/// Whether a stored mod directory can be joined onto a mod root
+fn is_valid_directory(directory: &str) -> bool {
+    let path = Path::new(directory);
+    !directory.trim().is_empty()
+        && !path.is_absolute()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Reports and removes rows left behind by interrupted saves or deletions outside the tool:
+/// hashes of missing mods, and tag relations pointing at missing mods or tags.
+/// Mods with an unusable directory or root are reported, and with `fix` their entries can be deleted.
+pub async fn repair_database(db: &DatabaseConnection, fix: bool) -> crate::Result<()> {
+    let orphaned_hashes: Vec<i32> = ModHash::find()
+        .join(JoinType::LeftJoin, mod_hash::Relation::SimsMod.def())
+        .filter(sims_mod::Column::Id.is_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|hash| hash.id)
+        .collect();
+    let dangling_relations = ModTagRelation::find()
+        .join(
+            JoinType::LeftJoin,
+            mod_tag_relation::Relation::SimsMod.def(),
+        )
+        .join(JoinType::LeftJoin, mod_tag_relation::Relation::Tag.def())
+        .filter(
+            Condition::any()
+                .add(sims_mod::Column::Id.is_null())
+                .add(tag::Column::Id.is_null()),
+        )
+        .all(db)
+        .await?;
+
+    let hash_count = orphaned_hashes.len();
+    let relation_count = dangling_relations.len();
+    if hash_count > 0 || relation_count > 0 {
+        db.transaction::<_, (), DbErr>(|txn| {
+            Box::pin(async move {
+                info!("Removing {} orphaned hashes", orphaned_hashes.len());
+                for ids in orphaned_hashes.chunks(500) {
+                    ModHash::delete_many()
+                        .filter(mod_hash::Column::Id.is_in(ids.to_vec()))
+                        .exec(txn)
+                        .await?;
+                }
+                info!(
+                    "Removing {} dangling tag relations",
+                    dangling_relations.len()
+                );
+                for relation in dangling_relations.iter() {
+                    ModTagRelation::delete_many()
+                        .filter(mod_tag_relation::Column::ModId.eq(relation.mod_id))
+                        .filter(mod_tag_relation::Column::TagId.eq(relation.tag_id))
+                        .exec(txn)
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .await?;
+    }
+    if hash_count > 0 {
+        println!(
+            "Removed {} hashes of missing mods.",
+            hash_count.to_string().yellow().bold()
+        );
+    }
+    if relation_count > 0 {
+        println!(
+            "Removed {} tag relations pointing at missing mods or tags.",
+            relation_count.to_string().yellow().bold()
+        );
+    }
+
+    let root_ids: HashSet<i32> = ModRoot::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|root| root.id)
+        .collect();
+    let mut invalid_mods = Vec::new();
+    for sims_mod in super::util::active_mods().all(db).await?.into_iter() {
+        if !is_valid_directory(&sims_mod.directory) {
+            let problem = format!("has an invalid directory: {:?}", sims_mod.directory);
+            invalid_mods.push((sims_mod, problem));
+        } else if let Some(root_id) = sims_mod.root_id.filter(|id| !root_ids.contains(id)) {
+            let problem = format!("belongs to mod root {}, which no longer exists", root_id);
+            invalid_mods.push((sims_mod, problem));
+        }
+    }
+    for (sims_mod, problem) in invalid_mods.iter() {
+        println!(
+            "Mod {} ({}) {}",
+            sims_mod.name.bold().red(),
+            sims_mod.id,
+            problem
+        );
+        if !fix {
+            continue;
+        }
+        if !Confirm::new(
+            format!(
+                "Do you want to remove {} from the database?",
+                sims_mod.name.bold().red()
+            )
+            .as_str(),
+        )
+        .with_default(false)
+        .prompt()?
+        {
+            continue;
+        }
+        if sims_mod.protected && !super::util::confirm_protected_mod(&sims_mod.name, "remove")? {
+            println!("Keeping protected mod {}", sims_mod.name.bold());
+            continue;
+        }
+        super::util::delete_mod_entry(db, sims_mod, JournalAction::Deleted).await?;
+    }
+    if !invalid_mods.is_empty() && !fix {
+        println!(
+            "Run {} to remove their entries.",
+            "scan --repair --fix".bold()
+        );
+    }
+
+    if hash_count == 0 && relation_count == 0 && invalid_mods.is_empty() {
+        println!("Database is consistent.");
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use sea_orm::{prelude::*, QueryOrder};
+use tracing::{debug, info};
+
+use crate::commands::util::VerificationPassed;
+use crate::entities::{prelude::*, *};
+
+/// Templates available without a file in the templates folder
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "bugreport",
+        "\
+**Bug report: {name} {version}**
+Game version: {game_version}
+Mod version: {version}
+Downloaded from: {source_url}
+Files verified: {verified}
+
+Files:
+{files}
+
+What happened:
+
+Steps to reproduce:
+",
+    ),
+    (
+        "short",
+        "{name} {version} ({source_url}) on game version {game_version}, files verified: {verified}",
+    ),
+];
+
+/// Folder holding user templates, named `<template>.txt`
+fn get_templates_dir() -> crate::Result<PathBuf> {
+    let templates_dir = crate::util::get_data_dir()?.join("templates");
+    if !templates_dir.is_dir() {
+        info!("Creating templates directory");
+        crate::fileio::create_dir_all(&templates_dir)?;
+    }
+    Ok(templates_dir)
+}
+
+/// Reads a template from the templates folder, falling back to the built-in ones
+fn load_template(name: &str) -> crate::Result<String> {
+    let path = get_templates_dir()?.join(format!("{}.txt", name));
+    if path.is_file() {
+        debug!("Using template {}", path.display());
+        return Ok(crate::fileio::read_to_string(&path)?);
+    }
+    if let Some((_, template)) = BUILTIN_TEMPLATES.iter().find(|(n, _)| *n == name) {
+        return Ok(template.to_string());
+    }
+    eprintln!(
+        "No template named {} found! Built-in templates: {}. Add your own as {}.",
+        name,
+        BUILTIN_TEMPLATES
+            .iter()
+            .map(|(n, _)| *n)
+            .collect::<Vec<_>>()
+            .join(", "),
+        path.display()
+    );
+    Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+}
+
+/// The installed game version, or the last one `postpatch` recorded
+async fn current_game_version(db: &DatabaseConnection) -> crate::Result<Option<String>> {
+    if let Some(version) =
+        crate::util::get_game_dir().and_then(|dir| super::postpatch::read_game_version(&dir))
+    {
+        return Ok(Some(version));
+    }
+    Ok(GameVersion::find()
+        .order_by_desc(game_version::Column::DetectedAt)
+        .one(db)
+        .await?
+        .map(|game_version| game_version.version))
+}
+
+/// Fills in a template with a mod's details, for pasting into bug reports
+pub async fn report(mod_id: i32, template: String) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let template = load_template(&template)?;
+
+    let Some(sims_mod) = super::util::active_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(&db)
+        .await?
+    else {
+        eprintln!("No mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+
+    let mut hashes: Vec<(PathBuf, String)> = super::util::get_hashes_for_mod(&db, mod_id)
+        .await?
+        .into_iter()
+        .collect();
+    hashes.sort();
+    let files = hashes
+        .iter()
+        .map(|(file, hash)| format!("- {} ({})", file.display(), hash.trim()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let verified = super::util::verify_mod(&db, &sims_mod)
+        .await?
+        .verification_passed();
+    let tags = super::util::get_tags_for_mod(&db, mod_id).await?;
+    let game_version = current_game_version(&db)
+        .await?
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let values = [
+        ("{id}", sims_mod.id.to_string()),
+        ("{name}", sims_mod.name.clone()),
+        ("{version}", sims_mod.version.clone()),
+        ("{source_url}", sims_mod.source_url.clone()),
+        (
+            "{download_url}",
+            sims_mod.download_url.clone().unwrap_or_default(),
+        ),
+        ("{directory}", sims_mod.directory.clone()),
+        ("{tags}", tags.join(", ")),
+        ("{files}", files),
+        ("{game_version}", game_version),
+        (
+            "{verified}",
+            if verified { "yes" } else { "no" }.to_string(),
+        ),
+    ];
+    let report = values
+        .iter()
+        .fold(template, |report, (key, value)| report.replace(key, value));
+    println!("{}", report.trim_end());
+    Ok(())
+}
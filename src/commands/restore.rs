@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel, TransactionTrait};
+use tracing::{debug, info};
+
+use crate::commands::JournalAction;
+use crate::entities::*;
+
+pub async fn restore_entry(mod_id: i32) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let Some(deleted_mod) = super::util::deleted_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(&db)
+        .await?
+    else {
+        eprintln!("No deleted mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+
+    let mod_path = PathBuf::from(&deleted_mod.directory);
+    let roots = super::util::ModRoots::load(&db).await?;
+    let root = roots.dir(deleted_mod.root_id)?;
+    let hashes = if root.join(&mod_path).is_dir() {
+        debug!("Rehashing files for {}", deleted_mod.name);
+        Some(
+            super::util::get_mod_file_hashes(&db, root, &deleted_mod)
+                .await?
+                .1,
+        )
+    } else {
+        None
+    };
+
+    info!("Restoring {}", deleted_mod.name);
+    let name = deleted_mod.name.clone();
+    let rehashed = hashes.is_some();
+    let mut active_model = deleted_mod.into_active_model();
+    active_model.deleted_at = ActiveValue::Set(None);
+    if let Some(hashes) = &hashes {
+        super::composition::set_file_counts(&mut active_model, hashes.keys());
+    }
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            let restored_mod = active_model.update(txn).await?;
+            super::log::record(txn, JournalAction::Restored, &restored_mod).await?;
+            if let Some(hashes) = hashes {
+                super::hash_changes::store_hashes(txn, mod_id, &hashes).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+
+    println!("Restored mod: {}", name.bold().green());
+    if !rehashed {
+        println!(
+            "Mod directory {} is not present; run a verified scan once it is reinstalled.",
+            mod_path.display().to_string().bold()
+        );
+    }
+    Ok(())
+}
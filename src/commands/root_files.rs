@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use colored::Colorize;
+use inquire::Confirm;
+use sea_orm::{prelude::*, ActiveValue, TransactionTrait};
+use tracing::{debug, info};
+
+use super::hash_changes::insert_hashes;
+use super::util::{ROOT_ENTRY_DIRECTORY, ROOT_ENTRY_NAME};
+use crate::commands::{JournalAction, SourceType, UpdateChannel};
+use crate::entities::{prelude::*, *};
+use crate::style::{self, Status};
+
+/// The built-in entry for the main Mods folder's own files, if it was created
+pub async fn find_root_entry<C>(db: &C) -> Result<Option<sims_mod::Model>, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    super::util::active_mods()
+        .filter(sims_mod::Column::RootId.is_null())
+        .filter(sims_mod::Column::Directory.eq(ROOT_ENTRY_DIRECTORY))
+        .one(db)
+        .await
+}
+
+/// Makes sure files directly in the main Mods folder, such as Resource.cfg and loose
+/// packages, are tracked by the built-in root entry, so scans verify them like a mod.
+/// The entry is created, protected, the first time such files are found while fixing.
+/// Runs before the scan, so the new entry is verified by the same scan.
+pub async fn check_root_files(db: &DatabaseConnection, fix: bool) -> crate::Result<()> {
+    if find_root_entry(db).await?.is_some() {
+        return Ok(());
+    }
+    let mods_dir = crate::util::get_sims_mod_dir()?;
+    if !mods_dir.is_dir() {
+        return Ok(());
+    }
+    let (files, hashes) = super::util::get_file_hashes(&mods_dir, Path::new(ROOT_ENTRY_DIRECTORY))?;
+    if files.is_empty() {
+        debug!("No loose files in the Mods folder");
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        style::mark(
+            Status::Warning,
+            &format!(
+                "Found {} untracked files directly in the Mods folder:",
+                files.len()
+            )
+        )
+    );
+    let mut sorted: Vec<_> = files.iter().collect();
+    sorted.sort();
+    for file in sorted.into_iter() {
+        println!("  {}", file.display());
+    }
+    if !fix {
+        println!("Run {} to track them.", "scan --fix".bold());
+        return Ok(());
+    }
+    if SimsMod::find()
+        .filter(sims_mod::Column::Name.eq(ROOT_ENTRY_NAME))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        eprintln!(
+            "A mod named {} already exists; rename it to track the Mods folder's files.",
+            ROOT_ENTRY_NAME.bold()
+        );
+        return Ok(());
+    }
+    if !Confirm::new("Track these files, so changes to them are reported like a mod's?")
+        .with_default(true)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now();
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            info!("Creating the entry for the Mods folder");
+            let mut root_entry = sims_mod::ActiveModel {
+                name: ActiveValue::Set(ROOT_ENTRY_NAME.to_string()),
+                directory: ActiveValue::Set(ROOT_ENTRY_DIRECTORY.to_string()),
+                root_id: ActiveValue::Set(None),
+                source_type: ActiveValue::Set(SourceType::UNKNOWN.to_string()),
+                source_url: ActiveValue::Set(String::new()),
+                version: ActiveValue::Set(crate::datetime::today().format("%d%m%y").to_string()),
+                updated: ActiveValue::Set(now),
+                added_at: ActiveValue::Set(Some(now)),
+                protected: ActiveValue::Set(true),
+                update_channel: ActiveValue::Set(UpdateChannel::Manual.as_str().to_string()),
+                ..Default::default()
+            };
+            super::composition::set_file_counts(&mut root_entry, hashes.keys());
+            let root_entry = SimsMod::insert(root_entry).exec_with_returning(txn).await?;
+            super::log::record(txn, JournalAction::Added, &root_entry).await?;
+            insert_hashes(txn, root_entry.id, hashes).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    println!(
+        "Tracking {} files as {}.",
+        files.len().to_string().green().bold(),
+        ROOT_ENTRY_NAME.bold()
+    );
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder, TransactionTrait};
+use tracing::info;
+
+use crate::entities::{prelude::*, *};
+
+use super::util::{ModRoots, MAIN_ROOT_NAME};
+
+/// Actions for managing additional folders holding mods
+#[derive(clap::Subcommand, Debug)]
+pub enum RootsAction {
+    /// Registers a folder whose subfolders are scanned as mods, like the main Mods folder
+    Add {
+        /// Folder to add
+        path: PathBuf,
+
+        /// Name for the root. Defaults to the folder's name.
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Lists mod roots
+    List,
+    /// Stops tracking a root. Fails while active mods belong to it.
+    Remove {
+        /// Name of the root
+        name: String,
+    },
+}
+
+pub async fn roots(action: RootsAction) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    match action {
+        RootsAction::Add { path, name } => {
+            let Ok(path) = path.canonicalize() else {
+                eprintln!("{} does not exist", path.display());
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            };
+            if !path.is_dir() {
+                eprintln!("{} is not a directory", path.display());
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            let name = name
+                .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            let name = name.trim().to_string();
+            if name.is_empty() || name.eq_ignore_ascii_case(MAIN_ROOT_NAME) {
+                eprintln!("Pick a different name with --name");
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            let existing_roots = ModRoots::load(&db).await?;
+            for (_, other_name, other_path) in existing_roots.iter() {
+                let other_path = other_path
+                    .canonicalize()
+                    .unwrap_or_else(|_| other_path.to_path_buf());
+                if path.starts_with(&other_path) || other_path.starts_with(&path) {
+                    eprintln!(
+                        "{} overlaps with root {} ({})",
+                        path.display(),
+                        other_name,
+                        other_path.display()
+                    );
+                    return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+                }
+                if other_name.eq_ignore_ascii_case(&name) {
+                    eprintln!("A root named {} already exists", other_name);
+                    return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+                }
+            }
+            info!("Adding mod root {}: {}", name, path.display());
+            let new_root = mod_root::ActiveModel {
+                name: ActiveValue::Set(name.clone()),
+                path: ActiveValue::Set(path.to_string_lossy().to_string()),
+                ..Default::default()
+            };
+            ModRoot::insert(new_root).exec(&db).await?;
+            println!(
+                "Added root {}. Run {} to find its mods.",
+                name.bold(),
+                "scan".bold()
+            );
+        }
+        RootsAction::List => {
+            let roots = ModRoots::load(&db).await?;
+            for (root_id, name, path) in roots.iter() {
+                let root_condition = match root_id {
+                    Some(id) => sims_mod::Column::RootId.eq(id),
+                    None => sims_mod::Column::RootId.is_null(),
+                };
+                let mod_count = super::util::active_mods()
+                    .filter(root_condition)
+                    .count(&db)
+                    .await?;
+                let availability = if path.is_dir() {
+                    String::new()
+                } else {
+                    format!(" {}", "(not available)".red())
+                };
+                println!(
+                    "{} {} ({} mods){}",
+                    name.bold(),
+                    path.display().to_string().dimmed(),
+                    mod_count,
+                    availability
+                );
+            }
+        }
+        RootsAction::Remove { name } => {
+            let Some(root) = ModRoot::find()
+                .filter(mod_root::Column::Name.eq(&name))
+                .order_by_asc(mod_root::Column::Id)
+                .one(&db)
+                .await?
+            else {
+                eprintln!("No root named {} found!", name);
+                return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+            };
+            let mod_count = super::util::active_mods()
+                .filter(sims_mod::Column::RootId.eq(root.id))
+                .count(&db)
+                .await?;
+            if mod_count > 0 {
+                eprintln!(
+                    "{} mods belong to root {}. Delete or move them first.",
+                    mod_count, root.name
+                );
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            let txn = db.begin().await?;
+            // Deleted mods from this root fall back to the main Mods folder
+            SimsMod::update_many()
+                .col_expr(sims_mod::Column::RootId, Expr::value(Option::<i32>::None))
+                .filter(sims_mod::Column::RootId.eq(root.id))
+                .exec(&txn)
+                .await?;
+            ModRoot::delete_by_id(root.id).exec(&txn).await?;
+            txn.commit().await?;
+            println!("Removed root {}", root.name.bold());
+        }
+    }
+    Ok(())
+}
@@ -1,92 +1,298 @@
 use crate::entities::{prelude::*, *};
-use crate::{commands::util::*, Result as CrateResult};
+use crate::{
+    commands::{
+        copies::{merge_copy, CopyAction},
+        hash_changes::{insert_hashes, store_hashes, HashChanges},
+        scan_engine::{
+            NewMod, NewModAction, ScanEngine, ScanEngineOptions, ScanHooks, ScanProgress,
+            VerificationResult,
+        },
+        util::*,
+        JournalAction, SourceType,
+    },
+    style::{self, Status},
+    Result as CrateResult,
+};
 use colored::*;
-use inquire::{Confirm, Text};
+use inquire::{Confirm, MultiSelect, Text};
 use sea_orm::{prelude::*, *};
 use std::{
     collections::{HashMap, HashSet},
-    ffi::OsString,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tracing::{debug, info};
 use tracing_unwrap::OptionExt;
 
-async fn ask_delete_mod(db: &DatabaseConnection, missing_mod: &sims_mod::Model) -> CrateResult<()> {
-    if Confirm::new(
-        format!(
-            "Do you want to remove {} from the database?",
-            missing_mod.name.bold().red()
-        )
-        .as_str(),
-    )
-    .with_default(false)
-    .prompt()?
+/// What a scan checks and fixes
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ScanOptions {
+    /// Verify file data for existing mods
+    #[arg(short, long)]
+    pub verify: bool,
+
+    /// Interactively update the database for detected changes
+    #[arg(short, long)]
+    pub fix: bool,
+
+    /// Update file hash data without changing mod metadata, after confirmation and
+    /// a database backup
+    #[arg(short, long)]
+    pub sync_hashes: bool,
+
+    /// Fix or sync hashes even while the game is running
+    #[arg(long)]
+    pub force: bool,
+
+    /// Also hash frozen mods
+    #[arg(short, long)]
+    pub thorough: bool,
+
+    /// Remove leftover hash and tag rows and report mods with unusable directories
+    #[arg(short, long)]
+    pub repair: bool,
+
+    /// List files in mod folders that scans don't track, such as images and stray archives
+    #[arg(long)]
+    pub report_extras: bool,
+
+    /// Write the file changes that verification finds to a JSON file, with old and
+    /// new hashes. Implies --verify.
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub subset: ScanSubset,
+}
+
+/// Limits the existing-mod checks of a scan to some mods
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ScanSubset {
+    /// Only check existing mods with any of the given tags
+    #[arg(long, value_delimiter = ',')]
+    pub tags: Option<Vec<String>>,
+
+    /// Only check the existing mods with the given IDs
+    #[arg(long = "mod-id", value_delimiter = ',')]
+    pub mod_ids: Option<Vec<i32>>,
+}
+
+impl ScanSubset {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_none() && self.mod_ids.is_none()
+    }
+
+    pub async fn contains<C>(&self, db: &C, sims_mod: &sims_mod::Model) -> CrateResult<bool>
+    where
+        C: sea_orm::ConnectionTrait,
     {
-        info!("Deleting {}...", missing_mod.name);
-        sims_mod::ActiveModel {
-            id: ActiveValue::Set(missing_mod.id),
-            ..Default::default()
+        if let Some(mod_ids) = &self.mod_ids {
+            if mod_ids.contains(&sims_mod.id) {
+                return Ok(true);
+            }
         }
-        .delete(db)
-        .await?;
+        let Some(tags) = &self.tags else {
+            return Ok(false);
+        };
+        Ok(get_tags_for_mod(db, sims_mod.id)
+            .await?
+            .iter()
+            .any(|tag| tags.iter().any(|t| t.eq_ignore_ascii_case(tag))))
     }
-    Ok(())
 }
 
-async fn detect_collision(
-    txn: &sea_orm::DatabaseTransaction,
-    name: &str,
-    file: &PathBuf,
-    hash: &str,
-) -> Result<(), sea_orm::DbErr> {
-    let collision = ModHash::find()
-        .filter(Condition::any().add(mod_hash::Column::Hash.eq(hash)))
-        .one(txn)
-        .await?;
-    if let Some(collision) = collision {
-        eprintln!("{}", "!!! HASH COLLISION DETECTED !!!".bold().red().blink());
-        eprintln!("Hash:           {}", collision.hash.bold().yellow());
-        eprintln!("Colliding mod:  {}", name.red().bold());
-        eprintln!("Colliding file: {}", file.display().to_string().red());
-        let existing_mod = collision
-            .find_related(SimsMod)
-            .one(txn)
-            .await?
-            .expect_or_log("Failed to find existing mod for collision");
-        eprintln!("Existing mod:   {}", existing_mod.name.blue().bold());
-        eprintln!("Existing file:  {}", collision.file.blue());
+/// A missing mod offered for removal
+struct MissingModSelection<'a> {
+    sims_mod: &'a sims_mod::Model,
+}
+
+impl std::fmt::Display for MissingModSelection<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({})", self.sims_mod.name, self.sims_mod.directory)?;
+        if self.sims_mod.protected {
+            write!(f, " [protected]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lets the user pick which missing mods to remove, with all but protected ones
+/// selected, and removes the picked ones together
+async fn ask_delete_mods(
+    db: &DatabaseConnection,
+    missing_mods: Vec<sims_mod::Model>,
+) -> CrateResult<()> {
+    let options: Vec<_> = missing_mods
+        .iter()
+        .map(|sims_mod| MissingModSelection { sims_mod })
+        .collect();
+    let preselected: Vec<usize> = missing_mods
+        .iter()
+        .enumerate()
+        .filter(|(_, sims_mod)| !sims_mod.protected)
+        .map(|(i, _)| i)
+        .collect();
+    let Some(selection) =
+        MultiSelect::new("Select missing mods to remove from the database:", options)
+            .with_default(&preselected)
+            .with_help_message("Deselect mods to keep, press ESC to keep all")
+            .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    let mut to_delete = Vec::new();
+    for selected in selection {
+        let sims_mod = selected.sims_mod;
+        if sims_mod.protected && !confirm_protected_mod(&sims_mod.name, "remove")? {
+            println!("Keeping protected mod {}", sims_mod.name.bold());
+            continue;
+        }
+        to_delete.push(sims_mod.clone());
     }
+    if to_delete.is_empty() {
+        println!("Kept all {} missing mods.", missing_mods.len());
+        return Ok(());
+    }
+    info!("Deleting {} missing mods...", to_delete.len());
+    delete_mod_entries(db, &to_delete, JournalAction::Deleted).await?;
+    for sims_mod in to_delete.iter() {
+        println!("  {}", style::mark(Status::Missing, &sims_mod.name));
+    }
+    println!(
+        "Removed {} missing mods, kept {}.",
+        to_delete.len().to_string().bold(),
+        (missing_mods.len() - to_delete.len()).to_string().bold()
+    );
     Ok(())
 }
 
-async fn add_mod(db: &DatabaseConnection, path: &PathBuf) -> CrateResult<()> {
+/// Ways to resolve a new mod whose name is already taken
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum NameConflictAction {
+    Rename,
+    Merge,
+    Skip,
+}
+
+impl std::fmt::Display for NameConflictAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NameConflictAction::Rename => write!(f, "Pick a different name"),
+            NameConflictAction::Merge => write!(f, "Merge into the existing mod as an update"),
+            NameConflictAction::Skip => write!(f, "Skip this mod"),
+        }
+    }
+}
+
+/// Points an existing mod at a new directory and updates it from that directory's files
+async fn merge_into_existing(
+    db: &DatabaseConnection,
+    existing: sims_mod::Model,
+    roots: &ModRoots,
+    root_id: Option<i32>,
+    path: &Path,
+) -> CrateResult<()> {
+    let old_path = roots.mod_dir(&existing)?;
+    if existing.deleted_at.is_none() && old_path.is_dir() {
+        println!(
+            "{} will be treated as a new mod on the next scan.",
+            existing.directory.bold()
+        );
+    }
+    let (_, hashes) = get_file_hashes_with(
+        roots.dir(root_id)?,
+        path,
+        &tracked_extensions(db, existing.id).await?,
+    )?;
+    let mut merged_model = existing.clone().into_active_model();
+    merged_model.directory = ActiveValue::Set(
+        path.to_str()
+            .expect_or_log("Failed to convert path to UTF-8")
+            .to_string(),
+    );
+    merged_model.root_id = ActiveValue::Set(root_id);
+    merged_model.deleted_at = ActiveValue::Set(None);
+    save_scanned_mod(db, existing, merged_model, hashes, false).await
+}
+
+async fn add_mod(
+    db: &DatabaseConnection,
+    roots: &ModRoots,
+    root_id: Option<i32>,
+    path: &Path,
+) -> CrateResult<()> {
     if !Confirm::new(
         format!(
             "Do you want to add {} to the database?",
-            path.display().to_string().bold().blue()
+            roots.display_path(root_id, path).bold().blue()
         )
         .as_str(),
     )
     .with_default(true)
     .prompt()?
     {
-        println!("Ignoring mod...");
+        if Confirm::new("Is this folder never a mod? It won't be reported again.")
+            .with_default(false)
+            .prompt()?
+        {
+            super::ignored::ignore_path(db, root_id, path).await?;
+            println!(
+                "Ignoring {} in future scans. Undo with {}.",
+                roots.display_path(root_id, path).bold(),
+                "ignored remove".bold()
+            );
+        } else {
+            println!("Ignoring mod...");
+        }
         return Ok(());
     }
 
     info!("Adding {}", path.display());
-    let name = Text::new("Name:")
-        .with_initial_value(&path.display().to_string())
-        .with_validator(inquire::required!())
-        .prompt()?;
-
-    let source_url = crate::commands::util::get_source_url(None)?;
-
-    let now = chrono::offset::Local::now();
-
-    let version = Text::new("Version:")
-        .with_default(&now.format("%d%m%y").to_string())
+    let mut initial_name = path.display().to_string();
+    let name = loop {
+        let name = Text::new("Name:")
+            .with_initial_value(&initial_name)
+            .with_validator(inquire::required!())
+            .prompt()?;
+        let Some(existing) = SimsMod::find()
+            .filter(sims_mod::Column::Name.eq(&name))
+            .one(db)
+            .await?
+        else {
+            break name;
+        };
+        println!(
+            "A mod named {} already exists{}:",
+            existing.name.bold().yellow(),
+            if existing.deleted_at.is_some() {
+                " (deleted)"
+            } else {
+                ""
+            }
+        );
+        println!("  Mod ID:    {}", existing.id);
+        println!("  Directory: {}", existing.directory);
+        println!("  Version:   {}", existing.version);
+        println!("  Source:    {}", existing.source_url);
+        let action = inquire::Select::new(
+            "What do you want to do?",
+            vec![
+                NameConflictAction::Rename,
+                NameConflictAction::Merge,
+                NameConflictAction::Skip,
+            ],
+        )
         .prompt()?;
+        match action {
+            NameConflictAction::Rename => initial_name = name,
+            NameConflictAction::Merge => {
+                return merge_into_existing(db, existing, roots, root_id, path).await
+            }
+            NameConflictAction::Skip => {
+                println!("Ignoring mod...");
+                return Ok(());
+            }
+        }
+    };
 
     let mut tags = {
         let mut tags = Vec::new();
@@ -100,14 +306,58 @@ async fn add_mod(db: &DatabaseConnection, path: &PathBuf) -> CrateResult<()> {
             if tag.is_empty() {
                 break;
             }
+            let tag = super::tag_typos::check_tag(db, tag).await?;
             autocomplete.remove_tag(&tag);
-            tags.push(tag);
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
         }
         tags
     };
 
+    // Tags are asked first, so their defaults can fill in the rest
+    let defaults = super::tags::TagDefaults::for_tags(db, &tags).await?;
+    defaults.print_preview(&tags);
+    let source_url = crate::commands::util::get_source_url(defaults.source_url.as_deref())?;
+
+    let now = chrono::Utc::now();
+    let today = crate::datetime::today();
+
+    let version = Text::new("Version:")
+        .with_default(&today.format("%d%m%y").to_string())
+        .prompt()?;
+
+    let source_type = SourceType::from_url(&source_url);
+    let recheck_after = if source_type == Some(SourceType::Patreon) {
+        prompt_optional_date(
+            "Check for a public release after:",
+            Some(today + chrono::Days::new(30)),
+        )?
+    } else {
+        None
+    };
+
     debug!("Fetching file hashes");
-    let (_, mut hashes) = crate::commands::util::get_file_hashes(&path)?;
+    let (files, mut hashes) = get_file_hashes_with(
+        roots.dir(root_id)?,
+        path,
+        &crate::config::get().tag_extensions(&tags),
+    )?;
+
+    let protected = Confirm::new("Protect this mod from accidental removal?")
+        .with_default(is_framework_mod(path, &files))
+        .prompt()?;
+
+    let kept = super::collision::resolve_collisions(
+        db,
+        roots,
+        &name,
+        None,
+        &roots.dir(root_id)?.join(path),
+        &mut hashes,
+        true,
+    )
+    .await?;
 
     let path = path
         .to_str()
@@ -116,17 +366,29 @@ async fn add_mod(db: &DatabaseConnection, path: &PathBuf) -> CrateResult<()> {
     db.transaction::<_, (), DbErr>(|txn| {
         Box::pin(async move {
             info!("Saving {}", name);
-            let new_mod = sims_mod::ActiveModel {
+            let mut new_mod = sims_mod::ActiveModel {
                 name: ActiveValue::Set(name.clone()),
                 directory: ActiveValue::Set(path),
+                root_id: ActiveValue::Set(root_id),
+                source_type: ActiveValue::Set(SourceType::name_for_url(&source_url).to_string()),
                 source_url: ActiveValue::Set(source_url),
+                recheck_after: ActiveValue::Set(recheck_after),
                 version: ActiveValue::Set(version),
                 updated: ActiveValue::Set(now),
+                added_at: ActiveValue::Set(Some(now)),
+                protected: ActiveValue::Set(protected),
+                nsfw: ActiveValue::Set(defaults.nsfw),
+                update_channel: match defaults.update_channel {
+                    Some(update_channel) => ActiveValue::Set(update_channel.as_str().to_string()),
+                    None => ActiveValue::NotSet,
+                },
                 ..Default::default()
             };
-            let res = SimsMod::insert(new_mod).exec(txn).await?;
-            let last_mod_id = res.last_insert_id;
+            super::composition::set_file_counts(&mut new_mod, hashes.keys());
+            let new_mod = SimsMod::insert(new_mod).exec_with_returning(txn).await?;
+            let last_mod_id = new_mod.id;
             debug!("Mod ID: {}", last_mod_id);
+            super::log::record(txn, JournalAction::Added, &new_mod).await?;
             debug!("Adding tags...");
             for mod_tag in tags.drain(..) {
                 let tag_id = super::util::get_or_create_tag_id(txn, &mod_tag).await?;
@@ -139,21 +401,8 @@ async fn add_mod(db: &DatabaseConnection, path: &PathBuf) -> CrateResult<()> {
             }
 
             debug!("Adding file hash data");
-            for (path, hash) in hashes.drain() {
-                debug!("Saving hash for {} ({})", path.display(), hash);
-                detect_collision(txn, &name, &path, &hash).await?;
-                let new_hash = mod_hash::ActiveModel {
-                    mod_id: ActiveValue::Set(last_mod_id),
-                    file: ActiveValue::Set(
-                        path.to_str()
-                            .expect_or_log("Failed to convert path to UTF-8")
-                            .to_string(),
-                    ),
-                    hash: ActiveValue::Set(hash),
-                    ..Default::default()
-                };
-                ModHash::insert(new_hash).exec(txn).await?;
-            }
+            insert_hashes(txn, last_mod_id, hashes.drain()).await?;
+            super::collision::record_kept_duplicates(txn, last_mod_id, kept).await?;
 
             Ok(())
         })
@@ -166,7 +415,7 @@ async fn add_mod(db: &DatabaseConnection, path: &PathBuf) -> CrateResult<()> {
 async fn update_mod_from_scan(
     db: &DatabaseConnection,
     to_save: sims_mod::Model,
-    mut verification: HashMap<PathBuf, String>,
+    verification: HashMap<PathBuf, String>,
     hash_update: bool,
 ) -> CrateResult<()> {
     if !hash_update
@@ -180,47 +429,113 @@ async fn update_mod_from_scan(
         return Ok(());
     }
 
+    let updated_model = to_save.clone().into_active_model();
+    save_scanned_mod(db, to_save, updated_model, verification, hash_update).await
+}
+
+/// Overwrites the stored hashes of changed mods with their current ones after
+/// confirmation, backing up the database first
+async fn sync_hashes(
+    db: &DatabaseConnection,
+    changed: Vec<(sims_mod::Model, HashMap<PathBuf, String>, usize)>,
+) -> CrateResult<()> {
+    let file_count: usize = changed.iter().map(|(_, _, count)| count).sum();
+    if !Confirm::new(
+        format!(
+            "Overwrite the stored hashes of {} files in {} mods?",
+            file_count,
+            changed.len()
+        )
+        .as_str(),
+    )
+    .with_help_message("Changed files are accepted as they are, without asking for a new version")
+    .with_default(false)
+    .prompt()?
+    {
+        println!("Leaving stored hashes unchanged");
+        return Ok(());
+    }
+    match crate::util::create_backup(db).await {
+        Ok(backup_path) => println!(
+            "Backed up the database to {}",
+            backup_path.display().to_string().blue()
+        ),
+        Err(e) => {
+            eprintln!("Could not back up the database: {}", e);
+            if !Confirm::new("Sync hashes without a backup?")
+                .with_default(false)
+                .prompt()?
+            {
+                return Ok(());
+            }
+        }
+    }
+    for (sims_mod, hashes, _) in changed.into_iter() {
+        println!(
+            "Updating mod:  {}",
+            style::mark(Status::Changed, &sims_mod.name).bold()
+        );
+        update_mod_from_scan(db, sims_mod, hashes, true).await?;
+    }
+    Ok(())
+}
+
+/// Prompts for updated metadata unless only syncing hashes, then saves the mod and its new hashes
+async fn save_scanned_mod(
+    db: &DatabaseConnection,
+    to_save: sims_mod::Model,
+    mut updated_model: sims_mod::ActiveModel,
+    mut verification: HashMap<PathBuf, String>,
+    hash_update: bool,
+) -> CrateResult<()> {
     let source_url = to_save.source_url.clone();
     let version = to_save.version.clone();
+    let recheck_after = to_save.recheck_after;
     let mod_id = to_save.id;
     let name = to_save.name.clone();
-    let mut updated_model = to_save.into_active_model();
 
-    updated_model.updated = ActiveValue::Set(chrono::offset::Local::now());
+    updated_model.updated = ActiveValue::Set(chrono::Utc::now());
     if !hash_update {
-        updated_model.source_url =
-            ActiveValue::Set(crate::commands::util::get_source_url(Some(&source_url))?);
+        let source_url = crate::commands::util::get_source_url(Some(&source_url))?;
+        let source_type = SourceType::from_url(&source_url);
+        updated_model.source_type =
+            ActiveValue::Set(SourceType::name_for_url(&source_url).to_string());
+        updated_model.source_url = ActiveValue::Set(source_url);
 
         updated_model.version =
             ActiveValue::Set(Text::new("Version:").with_default(&version).prompt()?);
+
+        if source_type == Some(SourceType::Patreon) {
+            updated_model.recheck_after = ActiveValue::Set(prompt_optional_date(
+                "Check for a public release after:",
+                recheck_after,
+            )?);
+        }
     }
 
+    let roots = ModRoots::load(db).await?;
+    let kept = super::collision::resolve_collisions(
+        db,
+        &roots,
+        &name,
+        Some(mod_id),
+        &roots.mod_dir(&to_save)?,
+        &mut verification,
+        !hash_update,
+    )
+    .await?;
+    super::composition::set_file_counts(&mut updated_model, verification.keys());
+
     db.transaction::<_, (), DbErr>(|txn| {
         Box::pin(async move {
             debug!("Updating {}", name);
-            SimsMod::update(updated_model).exec(txn).await?;
-            debug!("Clearing existing hash data");
-            mod_hash::Entity::delete_many()
-                .filter(mod_hash::Column::ModId.eq(mod_id))
-                .exec(txn)
+            let updated_mod = SimsMod::update(updated_model).exec(txn).await?;
+            debug!("Updating hash data");
+            let changes = store_hashes(txn, mod_id, &verification).await?;
+            let details = (!changes.is_empty()).then(|| changes.to_json().to_string());
+            super::log::record_with_details(txn, JournalAction::Updated, &updated_mod, details)
                 .await?;
-
-            debug!("Saving new hash data");
-            for (path, hash) in verification.drain() {
-                debug!("Saving hash for {} ({})", path.display(), hash);
-                detect_collision(txn, &name, &path, &hash).await?;
-                let new_hash = mod_hash::ActiveModel {
-                    mod_id: ActiveValue::Set(mod_id),
-                    file: ActiveValue::Set(
-                        path.to_str()
-                            .expect_or_log("Failed to convert path to UTF-8")
-                            .to_string(),
-                    ),
-                    hash: ActiveValue::Set(hash),
-                    ..Default::default()
-                };
-                ModHash::insert(new_hash).exec(txn).await?;
-            }
+            super::collision::record_kept_duplicates(txn, mod_id, kept).await?;
             Ok(())
         })
     })
@@ -229,125 +544,466 @@ async fn update_mod_from_scan(
     Ok(())
 }
 
-pub async fn scan(
-    db: Option<DatabaseConnection>,
-    verify: bool,
-    fix: bool,
-    hash_update: bool,
+/// Stores a mod's folder name after it was renamed only in case
+async fn update_directory_case(
+    db: &DatabaseConnection,
+    sims_mod: &sims_mod::Model,
+    path: &Path,
 ) -> CrateResult<()> {
-    debug!("Scanning mods");
-    let db = db.unwrap_or(crate::util::open_database().await?);
-
-    let mods = SimsMod::find().all(&db).await?;
+    debug!(
+        "Updating directory of {} to {}",
+        sims_mod.name,
+        path.display()
+    );
+    let mut renamed_model = sims_mod.clone().into_active_model();
+    renamed_model.directory = ActiveValue::Set(
+        path.to_str()
+            .expect_or_log("Failed to convert path to UTF-8")
+            .to_string(),
+    );
+    let renamed_mod = renamed_model.update(db).await?;
+    super::log::record(db, JournalAction::Edited, &renamed_mod).await?;
+    Ok(())
+}
 
-    debug!("Reading current mod directory list");
-    let mod_dir = crate::util::get_sims_mod_dir()?;
-    let mut mod_dir_entries: Vec<_> = mod_dir
-        .read_dir()?
-        .map(|entry| -> CrateResult<PathBuf> {
-            let entry = entry?;
-            Ok(entry.path())
-        })
-        .collect::<std::result::Result<_, _>>()?;
-
-    debug!("Checking for directory changes");
-    let mod_dir_subdirs: HashSet<_> = mod_dir_entries
-        .drain(..)
-        .filter_map(|entry| {
-            if entry.is_dir() && entry.file_name() != Some(&OsString::from("mod_data")) {
-                Some(PathBuf::from(entry.file_name().unwrap()))
-            } else {
-                None
-            }
+/// Points a deleted or missing mod at a reappearing directory and rehashes it
+async fn relink_mod(
+    db: &DatabaseConnection,
+    existing: &sims_mod::Model,
+    roots: &ModRoots,
+    root_id: Option<i32>,
+    path: &Path,
+) -> CrateResult<()> {
+    let (_, hashes) = get_file_hashes_with(
+        roots.dir(root_id)?,
+        path,
+        &tracked_extensions(db, existing.id).await?,
+    )?;
+    let action = if existing.deleted_at.is_some() {
+        JournalAction::Restored
+    } else {
+        JournalAction::Updated
+    };
+    let mod_id = existing.id;
+    let mut relinked_model = existing.clone().into_active_model();
+    relinked_model.directory = ActiveValue::Set(
+        path.to_str()
+            .expect_or_log("Failed to convert path to UTF-8")
+            .to_string(),
+    );
+    relinked_model.root_id = ActiveValue::Set(root_id);
+    relinked_model.deleted_at = ActiveValue::Set(None);
+    relinked_model.updated = ActiveValue::Set(chrono::Utc::now());
+    super::composition::set_file_counts(&mut relinked_model, hashes.keys());
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            let relinked_mod = relinked_model.update(txn).await?;
+            let changes = store_hashes(txn, mod_id, &hashes).await?;
+            let details = (!changes.is_empty()).then(|| changes.to_json().to_string());
+            super::log::record_with_details(txn, action, &relinked_mod, details).await?;
+            Ok(())
         })
-        .collect();
-
-    let path_mod_map: std::collections::HashMap<PathBuf, _> = mods
-        .iter()
-        .map(|sims_mod| (sims_mod.directory.clone().into(), sims_mod))
-        .collect();
-    let existing_subdirs = HashSet::from_iter(path_mod_map.keys().map(|k| k.to_owned()));
+    })
+    .await?;
+    Ok(())
+}
 
-    let mut new_mods: Vec<_> = mod_dir_subdirs.difference(&existing_subdirs).collect();
-    let mut missing_mods: Vec<_> = existing_subdirs.difference(&mod_dir_subdirs).collect();
-    let mut mods_to_scan: Vec<_> = existing_subdirs.intersection(&mod_dir_subdirs).collect();
+/// Interaction strategy of the `scan` command: prints what the scan finds, and
+/// with `--fix` asks what to do about it
+struct CliScanHooks {
+    fix: bool,
+    hash_update: bool,
+    /// Changed mods whose hashes `--sync-hashes` overwrites once the scan is done
+    to_sync: Vec<(sims_mod::Model, HashMap<PathBuf, String>, usize)>,
+    /// Changes found by verification, for `--report`
+    found_changes: Vec<(sims_mod::Model, HashChanges)>,
+    /// IDs of mods found new, renamed, relinked, or changed, in the order found
+    touched: Vec<i32>,
+}
 
-    if !new_mods.is_empty() {
-        println!(
-            "Found {} new mods.",
-            new_mods.len().to_string().blue().bold()
-        );
-        for new_mod in new_mods.drain(..) {
-            if fix {
-                add_mod(&db, new_mod).await?;
-            } else {
-                println!("Found mod: {}", new_mod.display().to_string().bold().blue());
-            }
+impl CliScanHooks {
+    fn touch(&mut self, mod_id: i32) {
+        if !self.touched.contains(&mod_id) {
+            self.touched.push(mod_id);
         }
     }
-    if !missing_mods.is_empty() {
-        println!(
-            "Found {} missing mods.",
-            missing_mods.len().to_string().red().bold()
-        );
-        for missing_mod in missing_mods.drain(..) {
-            let missing_mod_entry = *path_mod_map
-                .get(missing_mod)
-                .expect_or_log("Failed to get mod from mod map");
-            if fix {
-                ask_delete_mod(&db, missing_mod_entry).await?;
-            } else {
-                let name = &path_mod_map
-                    .get(missing_mod)
-                    .expect_or_log("Failed to get name from mod map")
-                    .name;
-                println!("Missing mod: {}", name.bold().red());
+}
+
+#[async_trait::async_trait(?Send)]
+impl ScanHooks for CliScanHooks {
+    fn progress(&mut self, progress: ScanProgress<'_>) {
+        match progress {
+            ScanProgress::UnavailableRoot { name, path } => println!(
+                "Mod root {} ({}) is not available, skipping its mods.",
+                style::paint(Status::Warning, name).bold(),
+                path.display()
+            ),
+            ScanProgress::IgnoredFolders(count) => debug!("Skipping {} ignored folders", count),
+            ScanProgress::NewMods(count) => println!(
+                "Found {} new mods.",
+                style::paint(Status::New, &count.to_string()).bold()
+            ),
+            ScanProgress::MissingMods(count) => println!(
+                "Found {} missing mods.",
+                style::paint(Status::Missing, &count.to_string()).bold()
+            ),
+            ScanProgress::NoSubsetMatch => {
+                println!("No existing mods match the given tags or mod IDs.")
+            }
+            ScanProgress::SkippedFrozen(count) => println!(
+                "Skipping {} frozen mods. Pass {} to check them too.",
+                count.to_string().bold(),
+                "--thorough".bold()
+            ),
+            ScanProgress::Verifying(count) => {
+                println!("Checking {} existing mods.", count.to_string().bold())
             }
+            ScanProgress::VerifyingMod {
+                done,
+                total,
+                sims_mod,
+            } => debug!("Verifying {} ({}/{})", sims_mod.name, done + 1, total),
         }
+    }
 
-        info!("Cleaning up tags...");
-        super::util::cleanup_tags(&db).await?;
+    async fn on_renamed_mod(
+        &mut self,
+        db: &DatabaseConnection,
+        sims_mod: &sims_mod::Model,
+        path: &Path,
+    ) -> CrateResult<()> {
+        self.touch(sims_mod.id);
+        if self.fix {
+            update_directory_case(db, sims_mod, path).await?;
+            println!(
+                "Renamed mod:   {} ({} -> {})",
+                style::mark(Status::Changed, &sims_mod.name).bold(),
+                sims_mod.directory,
+                path.display()
+            );
+        } else {
+            println!(
+                "Renamed mod:   {} ({} -> {}, run {} to record it)",
+                style::mark(Status::Changed, &sims_mod.name).bold(),
+                sims_mod.directory,
+                path.display(),
+                "scan --fix".bold()
+            );
+        }
+        Ok(())
     }
 
-    if (verify || hash_update) && !mods_to_scan.is_empty() {
-        println!(
-            "Checking {} existing mods.",
-            mods_to_scan.len().to_string().bold()
-        );
-        for to_scan in mods_to_scan.drain(..) {
-            let mod_to_scan = *path_mod_map
-                .get(to_scan)
-                .expect_or_log("Failed to get mod from mod map");
-            let mut hashes = super::util::get_hashes_for_mod(&db, mod_to_scan.id).await?;
-            let verify_results = crate::commands::util::verify_files(to_scan, &hashes)?;
-            if verify_results.verification_passed() {
-                println!("Validated mod: {}", mod_to_scan.name.bold().green());
+    async fn on_new_mod(
+        &mut self,
+        db: &DatabaseConnection,
+        roots: &ModRoots,
+        new_mod: &NewMod<'_>,
+    ) -> CrateResult<NewModAction> {
+        let display_path = roots.display_path(new_mod.root_id, new_mod.path);
+        if let Some((existing, reason)) = &new_mod.relink_candidate {
+            let state = if existing.deleted_at.is_some() {
+                "deleted"
             } else {
-                if hash_update {
-                    println!("Updating mod:  {}", mod_to_scan.name.bold().yellow());
+                "missing"
+            };
+            if !self.fix {
+                println!(
+                    "Found mod: {} (looks like {} mod {}, {})",
+                    style::mark(Status::New, &display_path).bold(),
+                    state,
+                    existing.name.bold(),
+                    reason
+                );
+                return Ok(NewModAction::Skipped);
+            }
+            if Confirm::new(
+                format!(
+                    "{} looks like {} mod {} ({}). Relink it to the existing entry?",
+                    display_path.bold().blue(),
+                    state,
+                    existing.name.bold(),
+                    reason
+                )
+                .as_str(),
+            )
+            .with_default(true)
+            .prompt()?
+            {
+                relink_mod(db, existing, roots, new_mod.root_id, new_mod.path).await?;
+                self.touch(existing.id);
+                println!(
+                    "Relinked mod: {}",
+                    style::mark(Status::Passed, &existing.name).bold()
+                );
+                return Ok(NewModAction::Relinked);
+            }
+        }
+        if let Some(copy_of) = &new_mod.copy_of {
+            let original = copy_of.original;
+            if !self.fix {
+                println!(
+                    "Found mod: {} (looks like a copy of {}, {})",
+                    style::mark(Status::New, &display_path).bold(),
+                    original.name.bold(),
+                    copy_of.describe()
+                );
+                return Ok(NewModAction::Skipped);
+            }
+            println!(
+                "{} looks like a copy of {} in {} ({}{}).",
+                display_path.bold().blue(),
+                original.name.bold(),
+                original.directory,
+                copy_of.describe(),
+                if copy_of.is_identical() {
+                    ", nothing else changed"
                 } else {
+                    ""
+                }
+            );
+            // An identical copy has nothing worth keeping, a different one may be an update
+            let mut actions = if copy_of.is_identical() {
+                vec![CopyAction::Delete, CopyAction::Merge]
+            } else {
+                vec![CopyAction::Merge, CopyAction::Delete]
+            };
+            actions.extend([CopyAction::AddAsNew, CopyAction::Skip]);
+            let action = inquire::Select::new("What do you want to do?", actions).prompt()?;
+            let copy_dir = roots.dir(new_mod.root_id)?.join(new_mod.path);
+            match action {
+                CopyAction::Delete => {
+                    super::trashed::remove_path(db, &copy_dir, Some(original), false).await?;
                     println!(
-                        "Mod {} validation: {}",
-                        "failed".red(),
-                        mod_to_scan.name.bold().green()
+                        "Moved {} to the trash.",
+                        style::mark(Status::Missing, &display_path).bold()
                     );
+                    return Ok(NewModAction::Removed);
                 }
-                if fix || hash_update {
-                    for missing in verify_results.missing_files.iter() {
-                        hashes.remove(missing);
+                CopyAction::Merge => {
+                    merge_copy(db, roots, new_mod.root_id, new_mod.path, original).await?;
+                    self.touch(original.id);
+                    if !copy_of.is_identical() {
+                        println!(
+                            "Run {} to record the changes to {}.",
+                            "scan --verify --fix".bold(),
+                            original.name.bold()
+                        );
                     }
+                    return Ok(NewModAction::Removed);
+                }
+                CopyAction::Skip => {
+                    println!("Ignoring mod...");
+                    return Ok(NewModAction::Skipped);
+                }
+                CopyAction::AddAsNew => {}
+            }
+        }
+        if let Some(other) = new_mod.used_by {
+            println!(
+                "Found mod: {} (skipped, folder name is already used by {})",
+                style::mark(Status::New, &display_path).bold(),
+                other.name.bold()
+            );
+            return Ok(NewModAction::Skipped);
+        }
+        if self.fix {
+            add_mod(db, roots, new_mod.root_id, new_mod.path).await?;
+            // Merging into an existing mod also points it at the folder
+            if let Some(added) = active_mods()
+                .filter(sims_mod::Column::RootId.eq(new_mod.root_id))
+                .filter(sims_mod::Column::Directory.eq(new_mod.path.to_string_lossy().to_string()))
+                .one(db)
+                .await?
+            {
+                self.touch(added.id);
+            }
+            Ok(NewModAction::Added)
+        } else {
+            println!(
+                "Found mod: {}",
+                style::mark(Status::New, &display_path).bold()
+            );
+            Ok(NewModAction::Skipped)
+        }
+    }
 
-                    for (file, hash) in verify_results.changed_files.iter() {
-                        hashes.insert(file.clone(), hash.clone());
-                    }
+    async fn on_empty_folder(
+        &mut self,
+        db: &DatabaseConnection,
+        roots: &ModRoots,
+        root_id: Option<i32>,
+        path: &Path,
+        other_files: usize,
+    ) -> CrateResult<()> {
+        super::empty::handle_empty_folder(db, roots, root_id, path, other_files, self.fix).await
+    }
 
-                    for (file, hash) in verify_results.new_files.iter() {
-                        hashes.insert(file.clone(), hash.clone());
-                    }
-                    update_mod_from_scan(&db, mod_to_scan.clone(), hashes, hash_update).await?;
+    async fn on_missing_mods(
+        &mut self,
+        db: &DatabaseConnection,
+        sims_mods: &[&sims_mod::Model],
+    ) -> CrateResult<()> {
+        if !self.fix {
+            for sims_mod in sims_mods.iter() {
+                println!(
+                    "Missing mod: {}",
+                    style::mark(Status::Missing, &sims_mod.name).bold()
+                );
+            }
+            return Ok(());
+        }
+        let mut still_missing = Vec::new();
+        for sims_mod in sims_mods.iter() {
+            // Entries merged into a new directory are no longer missing
+            match SimsMod::find_by_id(sims_mod.id).one(db).await? {
+                Some(current)
+                    if current.deleted_at.is_none()
+                        && current.root_id == sims_mod.root_id
+                        && current.directory == sims_mod.directory =>
+                {
+                    still_missing.push(current)
                 }
+                _ => debug!("{} was moved, skipping", sims_mod.name),
             }
         }
+        if still_missing.is_empty() {
+            return Ok(());
+        }
+        ask_delete_mods(db, still_missing).await
     }
-    Ok(())
+
+    async fn on_empty_mod(
+        &mut self,
+        db: &DatabaseConnection,
+        roots: &ModRoots,
+        sims_mod: &sims_mod::Model,
+        other_files: usize,
+    ) -> CrateResult<bool> {
+        super::empty::handle_empty_mod(db, roots, sims_mod, other_files, self.fix).await
+    }
+
+    async fn on_verification_result(
+        &mut self,
+        db: &DatabaseConnection,
+        result: &VerificationResult<'_>,
+    ) -> CrateResult<()> {
+        let sims_mod = result.sims_mod;
+        if result.passed() {
+            println!(
+                "Validated mod: {}",
+                style::mark(Status::Passed, &sims_mod.name).bold()
+            );
+            return Ok(());
+        }
+        if self.hash_update {
+            println!(
+                "Changed mod:   {} ({} files)",
+                style::mark(Status::Changed, &sims_mod.name).bold(),
+                result.changes.len()
+            );
+        } else {
+            println!(
+                "Mod {} validation: {}",
+                style::mark(Status::Failed, "failed"),
+                sims_mod.name.bold()
+            );
+        }
+        result.changes.print();
+        self.touch(sims_mod.id);
+        self.found_changes
+            .push((sims_mod.clone(), result.changes.clone()));
+        if self.hash_update {
+            self.to_sync.push((
+                sims_mod.clone(),
+                result.hashes.clone(),
+                result.changes.len(),
+            ));
+        } else if self.fix {
+            update_mod_from_scan(db, sims_mod.clone(), result.hashes.clone(), false).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a scan and returns the IDs of the mods it found new, renamed, relinked, or
+/// changed, so the interactive editor can offer to edit them
+pub async fn scan(db: Option<DatabaseConnection>, options: ScanOptions) -> CrateResult<Vec<i32>> {
+    debug!("Scanning mods");
+    let db = db.unwrap_or(crate::util::open_database().await?);
+    let ScanOptions {
+        verify,
+        fix,
+        sync_hashes: hash_update,
+        thorough,
+        repair,
+        report_extras,
+        report,
+        subset,
+        ..
+    } = options;
+
+    let mods_dir = crate::util::get_sims_mod_dir()?;
+    super::machine::check_machine(&db, &mods_dir, fix).await?;
+    if repair {
+        super::repair::repair_database(&db, fix).await?;
+    }
+    super::archives::check_archives(&db, fix).await?;
+    super::root_files::check_root_files(&db, fix).await?;
+
+    let options = ScanEngineOptions {
+        verify: verify || hash_update,
+        thorough,
+        subset: subset.clone(),
+    };
+    let mut hooks = CliScanHooks {
+        fix,
+        hash_update,
+        to_sync: Vec::new(),
+        found_changes: Vec::new(),
+        touched: Vec::new(),
+    };
+    let summary = ScanEngine::new(&db, options).run(&mut hooks).await?;
+    debug!(
+        "Verified {} mods, {} failed",
+        summary.verified, summary.failed
+    );
+    if let Some(report) = report.filter(|_| summary.verified > 0) {
+        crate::fileio::write(
+            &report,
+            super::hash_changes::render_report(&hooks.found_changes)?,
+        )?;
+        println!(
+            "Wrote the changes of {} mods to {}",
+            hooks.found_changes.len(),
+            report.display().to_string().blue()
+        );
+    }
+    if !hooks.to_sync.is_empty() {
+        sync_hashes(&db, hooks.to_sync).await?;
+    }
+
+    let present_ids = summary.present_ids;
+    let mods = active_mods().all(&db).await?;
+    super::probe::check_versions(&db, &present_ids, fix).await?;
+    if report_extras {
+        let mut extras_ids = HashSet::new();
+        for sims_mod in mods.iter().filter(|m| present_ids.contains(&m.id)) {
+            if subset.is_empty() || subset.contains(&db, sims_mod).await? {
+                extras_ids.insert(sims_mod.id);
+            }
+        }
+        super::extras::report_extras(&db, &extras_ids).await?;
+    }
+    super::played::report_added_since_play(&db).await?;
+
+    let due = mods_due_for_recheck().count(&db).await?;
+    if due > 0 {
+        println!(
+            "{} mods are due for a recheck. Run {} to see them.",
+            style::paint(Status::Warning, &due.to_string()).bold(),
+            "reminders".bold()
+        );
+    }
+    super::machine::record_machine(&db, &mods_dir).await?;
+    Ok(hooks.touched)
 }
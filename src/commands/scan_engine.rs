@@ -0,0 +1,507 @@
+//! The part of `scan` that finds new, missing, renamed, and changed mods, without
+//! deciding what to do about them. Front-ends drive a [`ScanEngine`] and supply a
+//! [`ScanHooks`] implementation for progress output and for every decision, such
+//! as the interactive prompts of the `scan` command.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use sea_orm::prelude::*;
+use tracing::{debug, info};
+use tracing_unwrap::OptionExt;
+
+use super::copies::{find_copy_of, CopyOf};
+use super::hash_changes::HashChanges;
+use super::scan::ScanSubset;
+use super::util::{
+    active_mods, deleted_mods, get_file_hashes, get_hashes_for_mod, is_frozen, is_root_entry,
+    ModRoots, VerificationPassed, VerificationValues, ROOT_ENTRY_DIRECTORY,
+};
+use crate::entities::sims_mod;
+use crate::Result as CrateResult;
+
+/// What a scan looks at
+#[derive(Clone, Debug, Default)]
+pub struct ScanEngineOptions {
+    /// Hash the files of existing mods and compare them with the stored hashes
+    pub verify: bool,
+    /// Also verify frozen mods
+    pub thorough: bool,
+    /// Only verify some existing mods
+    pub subset: ScanSubset,
+}
+
+/// Where a scan is up to, for front-ends to report
+#[derive(Debug)]
+pub enum ScanProgress<'a> {
+    /// A mod root's folder is not available, so its mods are skipped
+    UnavailableRoot { name: &'a str, path: &'a Path },
+    /// Folders without a mod entry were left out because they are ignored
+    IgnoredFolders(usize),
+    /// Folders without a mod entry were found
+    NewMods(usize),
+    /// Mods whose folders are gone were found
+    MissingMods(usize),
+    /// No existing mods match the subset to verify
+    NoSubsetMatch,
+    /// Frozen mods were left out of verification
+    SkippedFrozen(usize),
+    /// Verification of existing mods is starting
+    Verifying(usize),
+    /// A mod is about to be verified
+    VerifyingMod {
+        done: usize,
+        total: usize,
+        sims_mod: &'a sims_mod::Model,
+    },
+}
+
+/// A folder in a mod root without a mod entry
+pub struct NewMod<'a> {
+    pub root_id: Option<i32>,
+    /// Folder relative to its root
+    pub path: &'a Path,
+    /// A missing or deleted mod the folder looks like a reinstall of, and why
+    pub relink_candidate: Option<(&'a sims_mod::Model, String)>,
+    /// Another mod whose entry already uses the folder's name
+    pub used_by: Option<&'a sims_mod::Model>,
+    /// A present mod the folder is named like a copy of, when it has no relink candidate
+    pub copy_of: Option<CopyOf<'a>>,
+}
+
+/// What a front-end did about a new folder
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NewModAction {
+    /// Left alone
+    Skipped,
+    /// Registered as a new mod
+    Added,
+    /// Linked to its relink candidate
+    Relinked,
+    /// Deleted or merged into the mod it is a copy of
+    Removed,
+}
+
+/// An existing mod's files compared with its stored hashes
+pub struct VerificationResult<'a> {
+    pub sims_mod: &'a sims_mod::Model,
+    pub values: &'a VerificationValues,
+    /// The stored hashes updated with the files on disk
+    pub hashes: &'a HashMap<PathBuf, String>,
+    /// Files added, removed, or changed since the hashes were stored
+    pub changes: &'a HashChanges,
+}
+
+impl VerificationResult<'_> {
+    pub fn passed(&self) -> bool {
+        self.values.verification_passed()
+    }
+}
+
+/// Callbacks through which a front-end reports progress and handles what a scan finds.
+/// Every hook does nothing by default, so a read-only front-end only implements the
+/// ones it shows.
+#[async_trait::async_trait(?Send)]
+pub trait ScanHooks {
+    fn progress(&mut self, _progress: ScanProgress<'_>) {}
+
+    /// A mod's folder was renamed only in case
+    async fn on_renamed_mod(
+        &mut self,
+        _db: &DatabaseConnection,
+        _sims_mod: &sims_mod::Model,
+        _path: &Path,
+    ) -> CrateResult<()> {
+        Ok(())
+    }
+
+    /// A folder without a mod entry that has mod files
+    async fn on_new_mod(
+        &mut self,
+        _db: &DatabaseConnection,
+        _roots: &ModRoots,
+        _new_mod: &NewMod<'_>,
+    ) -> CrateResult<NewModAction> {
+        Ok(NewModAction::Skipped)
+    }
+
+    /// A folder without a mod entry or any mod files
+    async fn on_empty_folder(
+        &mut self,
+        _db: &DatabaseConnection,
+        _roots: &ModRoots,
+        _root_id: Option<i32>,
+        _path: &Path,
+        _other_files: usize,
+    ) -> CrateResult<()> {
+        Ok(())
+    }
+
+    /// Mods whose folders are gone, all at once so they can be handled together
+    async fn on_missing_mods(
+        &mut self,
+        _db: &DatabaseConnection,
+        _sims_mods: &[&sims_mod::Model],
+    ) -> CrateResult<()> {
+        Ok(())
+    }
+
+    /// A mod whose folder has no mod files left. Returns whether the mod was removed,
+    /// which leaves it out of verification.
+    async fn on_empty_mod(
+        &mut self,
+        _db: &DatabaseConnection,
+        _roots: &ModRoots,
+        _sims_mod: &sims_mod::Model,
+        _other_files: usize,
+    ) -> CrateResult<bool> {
+        Ok(false)
+    }
+
+    /// An existing mod was verified, whether or not it passed
+    async fn on_verification_result(
+        &mut self,
+        _db: &DatabaseConnection,
+        _result: &VerificationResult<'_>,
+    ) -> CrateResult<()> {
+        Ok(())
+    }
+}
+
+/// What a finished scan saw
+#[derive(Clone, Debug, Default)]
+pub struct ScanSummary {
+    /// IDs of the mods whose folders are present
+    pub present_ids: HashSet<i32>,
+    /// Mods verified, and how many of them failed
+    pub verified: usize,
+    pub failed: usize,
+}
+
+/// Finds a deleted or missing mod that a new directory is likely a reinstall of,
+/// matching on the directory's folder name or on file hashes stored for missing mods
+fn find_relink_candidate<'a>(
+    root: &Path,
+    new_mod: &Path,
+    candidates: &[&'a sims_mod::Model],
+    candidate_hashes: &HashMap<i32, HashSet<String>>,
+) -> CrateResult<Option<(&'a sims_mod::Model, String)>> {
+    if let Some(sims_mod) = candidates
+        .iter()
+        .find(|m| Path::new(&m.directory) == new_mod)
+        .or_else(|| {
+            candidates
+                .iter()
+                .find(|m| Path::new(&m.directory).file_name() == new_mod.file_name())
+        })
+    {
+        return Ok(Some((sims_mod, format!("was in {}", sims_mod.directory))));
+    }
+
+    if candidate_hashes.values().all(|h| h.is_empty()) {
+        return Ok(None);
+    }
+    let (files, hashes) = get_file_hashes(root, new_mod)?;
+    let new_hashes: HashSet<&String> = hashes.values().collect();
+    Ok(candidates
+        .iter()
+        .filter_map(|m| {
+            let overlap = candidate_hashes
+                .get(&m.id)
+                .map(|h| h.iter().filter(|hash| new_hashes.contains(hash)).count())
+                .unwrap_or(0);
+            (overlap > 0).then_some((*m, overlap))
+        })
+        .max_by_key(|(_, overlap)| *overlap)
+        .map(|(m, overlap)| (m, format!("{} of {} files match", overlap, files.len()))))
+}
+
+fn mod_for<'m>(
+    path_mod_map: &HashMap<(Option<i32>, PathBuf), &'m sims_mod::Model>,
+    key: &(Option<i32>, PathBuf),
+) -> &'m sims_mod::Model {
+    path_mod_map
+        .get(key)
+        .copied()
+        .expect_or_log("Failed to get mod from mod map")
+}
+
+/// Compares the mod roots with the database and verifies existing mods
+pub struct ScanEngine<'a> {
+    db: &'a DatabaseConnection,
+    options: ScanEngineOptions,
+}
+
+impl<'a> ScanEngine<'a> {
+    pub fn new(db: &'a DatabaseConnection, options: ScanEngineOptions) -> Self {
+        ScanEngine { db, options }
+    }
+
+    /// Runs a scan, calling the hooks for everything it finds
+    pub async fn run<H: ScanHooks>(&self, hooks: &mut H) -> CrateResult<ScanSummary> {
+        let db = self.db;
+        let roots = ModRoots::load(db).await?;
+        let mods = active_mods().all(db).await?;
+        let deleted = deleted_mods().all(db).await?;
+
+        debug!("Reading current mod directory list");
+        let canonical_roots: Vec<(Option<i32>, PathBuf)> = roots
+            .iter()
+            .filter_map(|(id, _, path)| Some((id, path.canonicalize().ok()?)))
+            .collect();
+        let mut mod_dir_subdirs: HashSet<(Option<i32>, PathBuf)> = HashSet::new();
+        let mut unavailable_roots = HashSet::new();
+        for (root_id, root_name, root_dir) in roots.iter() {
+            if !root_dir.is_dir() {
+                hooks.progress(ScanProgress::UnavailableRoot {
+                    name: root_name,
+                    path: root_dir,
+                });
+                unavailable_roots.insert(root_id);
+                continue;
+            }
+            let known_dirs: Vec<PathBuf> = mods
+                .iter()
+                .chain(deleted.iter())
+                .filter(|sims_mod| sims_mod.root_id == root_id)
+                .map(|sims_mod| PathBuf::from(&sims_mod.directory))
+                .collect();
+            let other_roots: Vec<PathBuf> = canonical_roots
+                .iter()
+                .filter(|(id, _)| *id != root_id)
+                .map(|(_, path)| path.clone())
+                .collect();
+            for dir in crate::scanner::find_mod_directories(root_dir, &known_dirs, &other_roots)? {
+                mod_dir_subdirs.insert((root_id, dir));
+            }
+            if root_id.is_none() && mods.iter().any(is_root_entry) {
+                mod_dir_subdirs.insert((None, PathBuf::from(ROOT_ENTRY_DIRECTORY)));
+            }
+        }
+
+        debug!("Checking for directory changes");
+
+        let path_mod_map: HashMap<(Option<i32>, PathBuf), _> = mods
+            .iter()
+            .filter(|sims_mod| !unavailable_roots.contains(&sims_mod.root_id))
+            .map(|sims_mod| {
+                (
+                    (sims_mod.root_id, sims_mod.directory.clone().into()),
+                    sims_mod,
+                )
+            })
+            .collect();
+        let existing_subdirs = HashSet::from_iter(path_mod_map.keys().map(|k| k.to_owned()));
+        // Directory names are unique across roots
+        let used_directories: HashMap<&str, &sims_mod::Model> = mods
+            .iter()
+            .chain(deleted.iter())
+            .map(|sims_mod| (sims_mod.directory.as_str(), sims_mod))
+            .collect();
+
+        let mut new_mods: Vec<_> = mod_dir_subdirs.difference(&existing_subdirs).collect();
+        let ignored = super::ignored::ignored_paths(db).await?;
+        let before_ignoring = new_mods.len();
+        new_mods.retain(|(root_id, path)| !super::ignored::is_ignored(&ignored, *root_id, path));
+        if new_mods.len() < before_ignoring {
+            hooks.progress(ScanProgress::IgnoredFolders(
+                before_ignoring - new_mods.len(),
+            ));
+        }
+        let mut missing_mods: Vec<_> = existing_subdirs.difference(&mod_dir_subdirs).collect();
+        let mut mods_to_scan: Vec<_> = existing_subdirs.intersection(&mod_dir_subdirs).collect();
+
+        // A folder renamed only in case is the same folder on case-insensitive filesystems
+        let mut renamed = Vec::new();
+        new_mods.retain(|new_key| {
+            let Some(i) = missing_mods.iter().position(|(root_id, missing_mod)| {
+                *root_id == new_key.0 && super::util::same_directory(missing_mod, &new_key.1)
+            }) else {
+                return true;
+            };
+            renamed.push((missing_mods.remove(i), *new_key));
+            false
+        });
+        for (missing_key, (_, new_mod)) in renamed.into_iter() {
+            hooks
+                .on_renamed_mod(db, mod_for(&path_mod_map, missing_key), new_mod)
+                .await?;
+            mods_to_scan.push(missing_key);
+        }
+
+        if !new_mods.is_empty() {
+            hooks.progress(ScanProgress::NewMods(new_mods.len()));
+            let relink_candidates: Vec<&sims_mod::Model> = missing_mods
+                .iter()
+                .filter_map(|key| path_mod_map.get(*key).copied())
+                .chain(deleted.iter())
+                .collect();
+            let mut candidate_hashes: HashMap<i32, HashSet<String>> = HashMap::new();
+            for sims_mod in relink_candidates.iter() {
+                let hashes = get_hashes_for_mod(db, sims_mod.id).await?;
+                candidate_hashes.insert(sims_mod.id, hashes.into_values().collect());
+            }
+            let mut relinked_ids = HashSet::new();
+            for (root_id, path) in new_mods.drain(..) {
+                let root_id = *root_id;
+                if let Some(other_files) =
+                    super::empty::empty_folder_files(roots.dir(root_id)?, path)?
+                {
+                    hooks
+                        .on_empty_folder(db, &roots, root_id, path, other_files)
+                        .await?;
+                    continue;
+                }
+                let remaining_candidates: Vec<_> = relink_candidates
+                    .iter()
+                    .copied()
+                    .filter(|m| !relinked_ids.contains(&m.id))
+                    .collect();
+                let relink_candidate = find_relink_candidate(
+                    roots.dir(root_id)?,
+                    path,
+                    &remaining_candidates,
+                    &candidate_hashes,
+                )?;
+                let used_by = path
+                    .to_str()
+                    .and_then(|dir| used_directories.get(dir))
+                    .copied()
+                    .filter(|other| !relinked_ids.contains(&other.id));
+                let copy_of = if relink_candidate.is_none() {
+                    find_copy_of(db, roots.dir(root_id)?, path, |original_dir| {
+                        let key = (root_id, original_dir.to_path_buf());
+                        // Originals that are missing are relink candidates instead
+                        path_mod_map
+                            .get(&key)
+                            .copied()
+                            .filter(|_| mod_dir_subdirs.contains(&key))
+                    })
+                    .await?
+                } else {
+                    None
+                };
+                let new_mod = NewMod {
+                    root_id,
+                    path,
+                    relink_candidate,
+                    used_by,
+                    copy_of,
+                };
+                if hooks.on_new_mod(db, &roots, &new_mod).await? == NewModAction::Relinked {
+                    if let Some((existing, _)) = new_mod.relink_candidate {
+                        relinked_ids.insert(existing.id);
+                    }
+                }
+            }
+        }
+        if !missing_mods.is_empty() {
+            hooks.progress(ScanProgress::MissingMods(missing_mods.len()));
+            let missing: Vec<_> = missing_mods
+                .drain(..)
+                .map(|missing_mod| mod_for(&path_mod_map, missing_mod))
+                .collect();
+            hooks.on_missing_mods(db, &missing).await?;
+
+            info!("Cleaning up tags...");
+            super::util::cleanup_tags(db).await?;
+        }
+
+        let mut with_files = Vec::new();
+        for to_scan in mods_to_scan.drain(..) {
+            let (root_id, path) = to_scan;
+            // The Mods folder's own files are checked even when it has none left
+            if is_root_entry(mod_for(&path_mod_map, to_scan)) {
+                with_files.push(to_scan);
+                continue;
+            }
+            match super::empty::empty_folder_files(roots.dir(*root_id)?, path)? {
+                Some(other_files)
+                    if hooks
+                        .on_empty_mod(db, &roots, mod_for(&path_mod_map, to_scan), other_files)
+                        .await? => {}
+                _ => with_files.push(to_scan),
+            }
+        }
+        mods_to_scan = with_files;
+
+        let mut summary = ScanSummary {
+            present_ids: existing_subdirs
+                .intersection(&mod_dir_subdirs)
+                .map(|key| mod_for(&path_mod_map, key).id)
+                .collect(),
+            ..Default::default()
+        };
+        if !self.options.verify {
+            return Ok(summary);
+        }
+
+        let subset = &self.options.subset;
+        if !subset.is_empty() {
+            let mut selected = Vec::new();
+            for to_scan in mods_to_scan.drain(..) {
+                if subset.contains(db, mod_for(&path_mod_map, to_scan)).await? {
+                    selected.push(to_scan);
+                }
+            }
+            mods_to_scan = selected;
+            if mods_to_scan.is_empty() {
+                hooks.progress(ScanProgress::NoSubsetMatch);
+            }
+        }
+
+        if !self.options.thorough {
+            let frozen_tags = crate::config::get().frozen_tags();
+            let mut frozen_count = 0;
+            let mut unfrozen = Vec::new();
+            for to_scan in mods_to_scan.drain(..) {
+                let mod_to_scan = mod_for(&path_mod_map, to_scan);
+                if is_frozen(db, mod_to_scan, &frozen_tags).await? {
+                    debug!("Skipping frozen mod {}", mod_to_scan.name);
+                    frozen_count += 1;
+                } else {
+                    unfrozen.push(to_scan);
+                }
+            }
+            mods_to_scan = unfrozen;
+            if frozen_count > 0 {
+                hooks.progress(ScanProgress::SkippedFrozen(frozen_count));
+            }
+        }
+
+        if mods_to_scan.is_empty() {
+            return Ok(summary);
+        }
+        hooks.progress(ScanProgress::Verifying(mods_to_scan.len()));
+        let total = mods_to_scan.len();
+        for (done, to_scan) in mods_to_scan.into_iter().enumerate() {
+            let mod_to_scan = mod_for(&path_mod_map, to_scan);
+            hooks.progress(ScanProgress::VerifyingMod {
+                done,
+                total,
+                sims_mod: mod_to_scan,
+            });
+            let values = super::util::verify_mod(db, mod_to_scan).await?;
+            let mut hashes = values.stored_hashes.clone();
+            for missing in values.missing_files.iter() {
+                hashes.remove(missing);
+            }
+            for (file, hash) in values.changed_files.iter().chain(values.new_files.iter()) {
+                hashes.insert(file.clone(), hash.clone());
+            }
+            let changes = HashChanges::between(&values.stored_hashes, &hashes);
+            let result = VerificationResult {
+                sims_mod: mod_to_scan,
+                values: &values,
+                hashes: &hashes,
+                changes: &changes,
+            };
+            summary.verified += 1;
+            if !result.passed() {
+                summary.failed += 1;
+            }
+            hooks.on_verification_result(db, &result).await?;
+        }
+        Ok(summary)
+    }
+}
@@ -0,0 +1,315 @@
+//! JSON Schemas of everything the tool writes as JSON, so spreadsheets, web viewers and
+//! scripts can check what they read before relying on it
+
+use colored::Colorize;
+use serde_json::{json, Map, Value};
+
+use super::catalog::{BUNDLE_FORMAT, BUNDLE_VERSION};
+use super::fields::ModField;
+
+const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// JSON formats with a schema, by the name given to `schema`
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SchemaName {
+    Export,
+    Info,
+    Sync,
+    ScanReport,
+    AdoptReport,
+    Status,
+    Taxonomy,
+}
+
+impl SchemaName {
+    fn all() -> &'static [SchemaName] {
+        &[
+            SchemaName::Export,
+            SchemaName::Info,
+            SchemaName::Sync,
+            SchemaName::ScanReport,
+            SchemaName::AdoptReport,
+            SchemaName::Status,
+            SchemaName::Taxonomy,
+        ]
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            SchemaName::Export => "export",
+            SchemaName::Info => "info",
+            SchemaName::Sync => "sync",
+            SchemaName::ScanReport => "scan-report",
+            SchemaName::AdoptReport => "adopt-report",
+            SchemaName::Status => "status",
+            SchemaName::Taxonomy => "taxonomy",
+        }
+    }
+
+    /// What writes and reads the format
+    fn description(&self) -> &'static str {
+        match self {
+            SchemaName::Export => {
+                "Mod list written by export --format json, also read by diff and sync import"
+            }
+            SchemaName::Info => "A single mod with its files and history, from info --json",
+            SchemaName::Sync => "Bundle written by sync export, read by sync import and diff",
+            SchemaName::ScanReport => "Hash changes written by scan --report",
+            SchemaName::AdoptReport => "What adopted mods still need, written by adopt --report",
+            SchemaName::Status => "Counts written by status --format json",
+            SchemaName::Taxonomy => "Tags written by tags --export, read by tags --import",
+        }
+    }
+
+    fn schema(&self) -> Value {
+        match self {
+            SchemaName::Export => json!({
+                "type": "array",
+                "items": mod_schema(),
+            }),
+            SchemaName::Info => info_schema(),
+            SchemaName::Sync => sync_schema(),
+            SchemaName::ScanReport => scan_report_schema(),
+            SchemaName::AdoptReport => adopt_report_schema(),
+            SchemaName::Status => status_schema(),
+            SchemaName::Taxonomy => taxonomy_schema(),
+        }
+    }
+}
+
+fn nullable(schema_type: &str) -> Value {
+    json!({ "type": [schema_type, "null"] })
+}
+
+fn date_time() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+/// Schema of an object with the given properties, all of them required
+fn object(properties: Value) -> Value {
+    let required: Vec<String> = properties
+        .as_object()
+        .map(|p| p.keys().cloned().collect())
+        .unwrap_or_default();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// A mod as written by `export` and `info`. Only the name is always there, as the
+/// other fields can be picked with `--fields`.
+fn mod_properties() -> Map<String, Value> {
+    let mut properties = Map::new();
+    properties.insert("name".to_string(), json!({ "type": "string" }));
+    for field in ModField::all() {
+        properties.insert(field.key().to_string(), field.json_schema());
+    }
+    properties
+}
+
+fn mod_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": mod_properties(),
+        "required": ["name"],
+    })
+}
+
+fn info_schema() -> Value {
+    let mut properties = mod_properties();
+    properties.insert(
+        "deleted_at".to_string(),
+        json!({ "type": ["string", "null"], "format": "date-time" }),
+    );
+    properties.insert(
+        "missing_packs".to_string(),
+        json!({ "type": "array", "items": { "type": "string" } }),
+    );
+    properties.insert(
+        "addon_ids".to_string(),
+        json!({ "type": "array", "items": { "type": "integer" } }),
+    );
+    properties.insert(
+        "directory_missing".to_string(),
+        json!({ "type": "boolean" }),
+    );
+    properties.insert(
+        "files".to_string(),
+        json!({
+            "type": "array",
+            "items": object(json!({
+                "path": { "type": "string" },
+                "status": { "enum": ["matching", "changed", "missing", "new"] },
+                "stored_hash": nullable("string"),
+                "current_hash": nullable("string"),
+                "size": { "type": ["integer", "null"], "minimum": 0 },
+            })),
+        }),
+    );
+    properties.insert(
+        "history".to_string(),
+        json!({
+            "type": "array",
+            "items": object(json!({
+                "timestamp": date_time(),
+                "action": { "type": "string" },
+                "name": { "type": "string" },
+                "version": { "type": "string" },
+                "source_url": { "type": "string" },
+            })),
+        }),
+    );
+    let required: Vec<&str> = std::iter::once("name")
+        .chain(ModField::all().iter().map(|field| field.key()))
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn sync_schema() -> Value {
+    object(json!({
+        "format": { "const": BUNDLE_FORMAT },
+        "version": {
+            "type": "integer",
+            "maximum": BUNDLE_VERSION,
+            "description": "Bundles with a newer version are refused by sync import",
+        },
+        "exported": date_time(),
+        "mods": {
+            "type": "array",
+            "items": object(json!({
+                "name": { "type": "string" },
+                "version": nullable("string"),
+                "source": nullable("string"),
+                "directory": nullable("string"),
+                "files": {
+                    "type": ["object", "null"],
+                    "description": "File paths relative to the mod folder, mapped to their hashes",
+                    "additionalProperties": { "type": "string" },
+                },
+                "tags": {
+                    "type": ["array", "null"],
+                    "items": { "type": "string" },
+                },
+            })),
+        },
+    }))
+}
+
+fn scan_report_schema() -> Value {
+    let file_hashes = |properties: Value| json!({ "type": "array", "items": object(properties) });
+    object(json!({
+        "generated_at": date_time(),
+        "mods": {
+            "type": "array",
+            "items": object(json!({
+                "id": { "type": "integer" },
+                "name": { "type": "string" },
+                "directory": { "type": "string" },
+                "added": file_hashes(json!({
+                    "file": { "type": "string" },
+                    "hash": { "type": "string" },
+                })),
+                "removed": file_hashes(json!({
+                    "file": { "type": "string" },
+                    "hash": { "type": "string" },
+                })),
+                "changed": file_hashes(json!({
+                    "file": { "type": "string" },
+                    "old_hash": { "type": "string" },
+                    "new_hash": { "type": "string" },
+                })),
+            })),
+        },
+    }))
+}
+
+fn adopt_report_schema() -> Value {
+    object(json!({
+        "generated_at": date_time(),
+        "mods": {
+            "type": "array",
+            "items": object(json!({
+                "id": { "type": "integer" },
+                "name": { "type": "string" },
+                "directory": { "type": "string" },
+                "files": { "type": "integer", "minimum": 0 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "protected": { "type": "boolean" },
+                "missing": { "type": "array", "items": { "type": "string" } },
+                "issues": { "type": "array", "items": { "type": "string" } },
+            })),
+        },
+        "failed": {
+            "type": "array",
+            "items": object(json!({
+                "directory": { "type": "string" },
+                "error": { "type": "string" },
+            })),
+        },
+    }))
+}
+
+fn status_schema() -> Value {
+    let count = json!({ "type": "integer", "minimum": 0 });
+    object(json!({
+        "total_mods": count,
+        "broken_mods": count,
+        "protected_mods": count,
+        "frozen_mods": count,
+        "due_for_recheck": count,
+        "new_folders": count,
+        "missing_folders": count,
+        "failing_verification": {
+            "type": ["integer", "null"],
+            "minimum": 0,
+            "description": "Only counted with status --verify",
+        },
+        "hardlink_saved_bytes": count,
+        "last_scan": { "type": ["string", "null"], "format": "date-time" },
+        "generated_at": date_time(),
+    }))
+}
+
+fn taxonomy_schema() -> Value {
+    let mut tag = object(json!({
+        "tag": { "type": "string", "minLength": 1 },
+        "description": nullable("string"),
+        "color": nullable("string"),
+        "parent": nullable("string"),
+        "default_source_url": nullable("string"),
+        "default_update_channel": nullable("string"),
+        "default_nsfw": { "type": "boolean" },
+    }));
+    // Files from before tags had details only name the tag
+    tag["required"] = json!(["tag"]);
+    object(json!({
+        "tags": { "type": "array", "items": tag },
+    }))
+}
+
+/// Prints the JSON Schema of a format, or lists the formats with a schema
+pub fn schema(name: Option<SchemaName>) -> crate::Result<()> {
+    let Some(name) = name else {
+        for name in SchemaName::all() {
+            println!("{:<14} {}", name.as_str().bold(), name.description());
+        }
+        return Ok(());
+    };
+    let mut schema = json!({
+        "$schema": SCHEMA_DRAFT,
+        "title": format!("sims4modorganizer {}", name.as_str()),
+        "description": name.description(),
+        "$comment": format!("Written by sims4modorganizer {}", env!("CARGO_PKG_VERSION")),
+    });
+    if let (Value::Object(schema), Value::Object(definition)) = (&mut schema, name.schema()) {
+        schema.extend(definition);
+    }
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
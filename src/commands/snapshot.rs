@@ -0,0 +1,134 @@
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder};
+use tracing::info;
+
+use crate::config::HashAlgorithm;
+use crate::entities::{prelude::*, *};
+
+use super::catalog::{catalog_to_bundle, load_catalog, parse_catalog};
+use super::diff::print_catalog_diff;
+
+/// Actions for managing point-in-time snapshots of the catalog
+#[derive(clap::Subcommand, Debug)]
+pub enum SnapshotAction {
+    /// Stores the current mods, versions, tags, and file hashes under a name
+    Create {
+        /// Name of the snapshot, e.g. "before summer patch"
+        name: String,
+    },
+    /// Lists snapshots
+    List,
+    /// Shows what changed between a snapshot and the current state
+    Diff {
+        /// Snapshot ID
+        id: i32,
+    },
+    /// Deletes a snapshot
+    Delete {
+        /// Snapshot ID
+        id: i32,
+    },
+}
+
+async fn find_snapshot(db: &DatabaseConnection, id: i32) -> crate::Result<snapshot::Model> {
+    match Snapshot::find_by_id(id).one(db).await? {
+        Some(snapshot) => Ok(snapshot),
+        None => {
+            eprintln!("No snapshot with ID {} found!", id);
+            Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+        }
+    }
+}
+
+fn snapshot_checksum(data: &str) -> String {
+    HashAlgorithm::Xxh3.hash(data.as_bytes())
+}
+
+/// Whether a snapshot's data still matches the checksum stored with it.
+/// Snapshots taken before checksums were recorded are assumed intact.
+fn snapshot_intact(snapshot: &snapshot::Model) -> bool {
+    snapshot
+        .checksum
+        .as_ref()
+        .is_none_or(|checksum| *checksum == snapshot_checksum(&snapshot.data))
+}
+
+pub async fn snapshot(action: SnapshotAction) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    match action {
+        SnapshotAction::Create { name } => {
+            let catalog = load_catalog(&db).await?;
+            info!("Creating snapshot {} of {} mods", name, catalog.len());
+            let data = serde_json::to_string(&catalog_to_bundle(&catalog))?;
+            let new_snapshot = snapshot::ActiveModel {
+                name: ActiveValue::Set(name.clone()),
+                created: ActiveValue::Set(chrono::Utc::now()),
+                checksum: ActiveValue::Set(Some(snapshot_checksum(&data))),
+                data: ActiveValue::Set(data),
+                ..Default::default()
+            };
+            let result = Snapshot::insert(new_snapshot).exec(&db).await?;
+            println!(
+                "Created snapshot {} ({}) of {} mods",
+                result.last_insert_id,
+                name.bold(),
+                catalog.len()
+            );
+        }
+        SnapshotAction::List => {
+            let snapshots = Snapshot::find()
+                .order_by_asc(snapshot::Column::Created)
+                .all(&db)
+                .await?;
+            if snapshots.is_empty() {
+                println!("No snapshots.");
+            }
+            for snapshot in snapshots.iter() {
+                let mod_count = parse_catalog(&snapshot.data)
+                    .map(|c| c.len().to_string())
+                    .unwrap_or_else(|| "?".to_string());
+                let status = if snapshot_intact(snapshot) {
+                    String::new()
+                } else {
+                    format!(" {}", "(corrupted)".red())
+                };
+                println!(
+                    "{} {} {} ({} mods){}",
+                    format!("#{}", snapshot.id).dimmed(),
+                    crate::datetime::format_datetime(&snapshot.created),
+                    snapshot.name.bold(),
+                    mod_count,
+                    status
+                );
+            }
+        }
+        SnapshotAction::Diff { id } => {
+            let snapshot = find_snapshot(&db, id).await?;
+            if !snapshot_intact(&snapshot) {
+                eprintln!(
+                    "Snapshot {} does not match its checksum and may be corrupted",
+                    id
+                );
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+            }
+            let Some(old) = parse_catalog(&snapshot.data) else {
+                eprintln!("Snapshot {} could not be read", id);
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+            };
+            let new = load_catalog(&db).await?;
+            println!(
+                "Changes since {} ({}):",
+                snapshot.name.bold(),
+                crate::datetime::format_datetime(&snapshot.created)
+            );
+            print_catalog_diff(&old, &new);
+        }
+        SnapshotAction::Delete { id } => {
+            let snapshot = find_snapshot(&db, id).await?;
+            Snapshot::delete_by_id(snapshot.id).exec(&db).await?;
+            println!("Deleted snapshot {}", snapshot.name.bold());
+        }
+    }
+    Ok(())
+}
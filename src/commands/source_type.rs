@@ -0,0 +1,86 @@
+/// Where a mod is downloaded from, classified by its source URL's host
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SourceType {
+    Patreon,
+    Curseforge,
+    Tumblr,
+    Modthesims,
+    Personal,
+}
+
+/// Host suffixes for each recognized source. Any other host is a personal site.
+const SOURCE_HOSTS: &[(&str, SourceType)] = &[
+    ("patreon.com", SourceType::Patreon),
+    ("curseforge.com", SourceType::Curseforge),
+    ("tumblr.com", SourceType::Tumblr),
+    ("modthesims.info", SourceType::Modthesims),
+];
+
+impl SourceType {
+    /// Stored as the source type of mods whose source URL has no valid host
+    pub const UNKNOWN: &'static str = "unknown";
+
+    /// Classifies a source URL by its host, or `None` if it has no valid host
+    pub fn from_url(source_url: &str) -> Option<SourceType> {
+        let host = url::Url::parse(source_url).ok()?.host_str()?.to_lowercase();
+        let source_type = SOURCE_HOSTS
+            .iter()
+            .find(|(suffix, _)| host == *suffix || host.ends_with(&format!(".{}", suffix)))
+            .map(|(_, source_type)| *source_type)
+            .unwrap_or(SourceType::Personal);
+        Some(source_type)
+    }
+
+    /// The source type stored for a source URL, or [`SourceType::UNKNOWN`]
+    pub fn name_for_url(source_url: &str) -> &'static str {
+        SourceType::from_url(source_url).map_or(SourceType::UNKNOWN, |t| t.as_str())
+    }
+
+    /// Guesses the mod's creator from its source URL, where the URL names one
+    pub fn creator_from_url(source_url: &str) -> Option<String> {
+        let url = url::Url::parse(source_url).ok()?;
+        let host = url.host_str()?.to_lowercase();
+        let first_segment = url
+            .path_segments()
+            .and_then(|mut s| s.find(|s| !s.is_empty() && *s != "c"))
+            .map(|s| s.to_string());
+        match SourceType::from_url(source_url)? {
+            SourceType::Patreon => first_segment.filter(|s| s != "posts" && s != "user"),
+            SourceType::Tumblr => match host.strip_suffix(".tumblr.com") {
+                Some(blog) if blog != "www" => Some(blog.to_string()),
+                _ => first_segment,
+            },
+            SourceType::Curseforge | SourceType::Modthesims => None,
+            SourceType::Personal => Some(host.trim_start_matches("www.").to_string()),
+        }
+    }
+
+    /// Page listing the creator's mods, falling back to the mod's own page
+    pub fn creator_page(source_url: &str) -> Option<String> {
+        let source_type = SourceType::from_url(source_url)?;
+        let creator = SourceType::creator_from_url(source_url);
+        Some(match (source_type, creator) {
+            (SourceType::Patreon, Some(creator)) => {
+                format!("https://www.patreon.com/{}/posts", creator)
+            }
+            (SourceType::Tumblr, Some(blog)) => format!("https://{}.tumblr.com", blog),
+            _ => source_url.to_string(),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceType::Patreon => "patreon",
+            SourceType::Curseforge => "curseforge",
+            SourceType::Tumblr => "tumblr",
+            SourceType::Modthesims => "modthesims",
+            SourceType::Personal => "personal",
+        }
+    }
+}
+
+impl std::fmt::Display for SourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
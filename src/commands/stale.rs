@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use sea_orm::{prelude::*, QueryOrder};
+use tracing::{debug, info};
+
+use crate::commands::JournalAction;
+use crate::entities::{prelude::*, *};
+
+struct StaleMod {
+    sims_mod: sims_mod::Model,
+    mod_dir: PathBuf,
+    last_activity: DateTime<Utc>,
+    size: Option<u64>,
+}
+
+/// Choices offered for each mod while reviewing stale mods
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ReviewAction {
+    Keep,
+    Archive,
+    Remove,
+    Stop,
+}
+
+impl std::fmt::Display for ReviewAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReviewAction::Keep => write!(f, "Keep"),
+            ReviewAction::Archive => write!(f, "Archive (move to the data directory)"),
+            ReviewAction::Remove => write!(f, "Remove"),
+            ReviewAction::Stop => write!(f, "Stop reviewing"),
+        }
+    }
+}
+
+/// Total size and newest modification time of the files under a folder.
+/// Hardlinked files in `linked` are left out of the size, as removing them frees nothing.
+pub fn folder_stats(path: &Path, linked: &HashSet<PathBuf>) -> (u64, Option<SystemTime>) {
+    let mut size = 0;
+    let mut newest: Option<SystemTime> = None;
+    let mut to_read = vec![path.to_path_buf()];
+    while let Some(dir) = to_read.pop() {
+        let Ok(entries) = crate::fileio::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                to_read.push(entry.path());
+                continue;
+            }
+            if !linked.contains(&entry.path()) {
+                size += metadata.len();
+            }
+            if let Ok(modified) = metadata.modified() {
+                newest = Some(newest.map_or(modified, |n| n.max(modified)));
+            }
+        }
+    }
+    (size, newest)
+}
+
+/// Moves a mod's folder into the archive folder and deletes its database entry
+async fn archive_mod(db: &DatabaseConnection, stale_mod: &StaleMod) -> crate::Result<()> {
+    let archive_path = crate::util::get_archive_dir()?.join(&stale_mod.sims_mod.directory);
+    if archive_path.exists() {
+        eprintln!(
+            "{} already exists in the archive",
+            stale_mod.sims_mod.directory
+        );
+        return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+    }
+    if stale_mod.mod_dir.is_dir() {
+        info!(
+            "Archiving {} to {}",
+            stale_mod.mod_dir.display(),
+            archive_path.display()
+        );
+        crate::transfer::run("archive", |transfer| {
+            transfer.move_path(&stale_mod.mod_dir, &archive_path)
+        })?;
+    }
+    super::util::delete_mod_entry(db, &stale_mod.sims_mod, JournalAction::Archived).await?;
+    super::util::cleanup_tags(db).await?;
+    Ok(())
+}
+
+/// Offers to keep, archive, or remove each stale mod in turn
+async fn review(
+    db: &DatabaseConnection,
+    stale_mods: &[StaleMod],
+    permanent: bool,
+) -> crate::Result<()> {
+    let options = vec![
+        ReviewAction::Keep,
+        ReviewAction::Archive,
+        ReviewAction::Remove,
+        ReviewAction::Stop,
+    ];
+    let (mut archived, mut removed) = (0, 0);
+    for stale_mod in stale_mods.iter() {
+        let name = &stale_mod.sims_mod.name;
+        let Some(action) = inquire::Select::new(
+            &format!(
+                "{} (last touched {}):",
+                name.bold(),
+                crate::datetime::format_date(&stale_mod.last_activity)
+            ),
+            options.clone(),
+        )
+        .with_help_message("Press ESC to stop reviewing")
+        .prompt_skippable()?
+        else {
+            break;
+        };
+        match action {
+            ReviewAction::Keep => {}
+            ReviewAction::Stop => break,
+            ReviewAction::Archive | ReviewAction::Remove => {
+                let verb = if action == ReviewAction::Archive {
+                    "archive"
+                } else {
+                    "remove"
+                };
+                if stale_mod.sims_mod.protected && !super::util::confirm_protected_mod(name, verb)?
+                {
+                    println!("Keeping protected mod {}", name.bold());
+                    continue;
+                }
+                if !super::util::confirm_parent_mod(db, &stale_mod.sims_mod, verb).await? {
+                    continue;
+                }
+                if action == ReviewAction::Archive {
+                    archive_mod(db, stale_mod).await?;
+                    println!("Archived mod: {}", name.bold());
+                    archived += 1;
+                } else {
+                    super::remove::remove_mod(
+                        db,
+                        &stale_mod.sims_mod,
+                        &stale_mod.mod_dir,
+                        permanent,
+                    )
+                    .await?;
+                    println!("Removed mod: {}", name.bold().red());
+                    removed += 1;
+                }
+            }
+        }
+    }
+    println!("Archived {} and removed {} mods.", archived, removed);
+    Ok(())
+}
+
+pub async fn stale(
+    older_than: chrono::Duration,
+    mtimes: bool,
+    review_mods: bool,
+    permanent: bool,
+) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let roots = super::util::ModRoots::load(&db).await?;
+    let linked = super::dedupe::linked_paths(&db).await?;
+    let cutoff = Utc::now() - older_than;
+
+    let mut stale_mods = Vec::new();
+    for sims_mod in super::util::active_mods().all(&db).await?.into_iter() {
+        if super::util::is_root_entry(&sims_mod) {
+            continue;
+        }
+        let mut last_activity = sims_mod.updated;
+        // Updates found by verification and edits are journaled even when `updated` is not touched
+        if let Some(entry) = Journal::find()
+            .filter(journal::Column::ModId.eq(sims_mod.id))
+            .order_by_desc(journal::Column::Timestamp)
+            .one(&db)
+            .await?
+        {
+            last_activity = last_activity.max(entry.timestamp);
+        }
+        if last_activity > cutoff {
+            continue;
+        }
+        let mod_dir = roots.mod_dir(&sims_mod)?;
+        let (size, newest) = if mod_dir.is_dir() {
+            let (size, newest) = folder_stats(&mod_dir, &linked);
+            (Some(size), newest)
+        } else {
+            (None, None)
+        };
+        if let Some(newest) = newest.filter(|_| mtimes) {
+            last_activity = last_activity.max(DateTime::<Utc>::from(newest));
+        }
+        if last_activity > cutoff {
+            continue;
+        }
+        debug!("{} was last touched {}", sims_mod.name, last_activity);
+        stale_mods.push(StaleMod {
+            sims_mod,
+            mod_dir,
+            last_activity,
+            size,
+        });
+    }
+
+    if stale_mods.is_empty() {
+        println!(
+            "No mods untouched since {}.",
+            crate::datetime::format_date(&cutoff)
+        );
+        return Ok(());
+    }
+    stale_mods.sort_by_key(|stale_mod| stale_mod.last_activity);
+
+    for stale_mod in stale_mods.iter() {
+        let days = (Utc::now() - stale_mod.last_activity).num_days();
+        let size = match stale_mod.size {
+            Some(size) => super::util::format_size(size).normal(),
+            None => "missing".red(),
+        };
+        let downloaded = stale_mod
+            .sims_mod
+            .added_at
+            .map(|added_at| format!(" downloaded {}", crate::datetime::format_date(&added_at)))
+            .unwrap_or_default();
+        println!(
+            "{} ({}) {} {} [{}]{}",
+            stale_mod.sims_mod.name.bold(),
+            stale_mod.sims_mod.id,
+            crate::datetime::format_date(&stale_mod.last_activity),
+            format!("({} days ago)", days).dimmed(),
+            size,
+            downloaded
+        );
+    }
+    let total: u64 = stale_mods.iter().filter_map(|m| m.size).sum();
+    println!(
+        "{} mods untouched since {}, using {}.",
+        stale_mods.len().to_string().yellow().bold(),
+        crate::datetime::format_date(&cutoff),
+        super::util::format_size(total).bold()
+    );
+
+    if review_mods {
+        review(&db, &stale_mods, permanent).await?;
+    }
+    Ok(())
+}
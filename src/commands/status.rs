@@ -0,0 +1,217 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use sea_orm::{prelude::*, QueryOrder};
+use serde_json::json;
+use tracing::{debug, info};
+
+use crate::commands::util::VerificationPassed;
+use crate::entities::{prelude::*, *};
+
+/// Output formats supported by `status`
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatusFormat {
+    Json,
+    Prometheus,
+}
+
+/// Counts describing how far the Mods folder has drifted from the database
+struct Status {
+    total_mods: usize,
+    broken_mods: usize,
+    protected_mods: usize,
+    frozen_mods: usize,
+    due_for_recheck: usize,
+    new_folders: usize,
+    missing_folders: usize,
+    failing_verification: Option<usize>,
+    hardlink_savings: u64,
+    last_scan: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+async fn collect_status(db: &DatabaseConnection, verify: bool) -> crate::Result<Status> {
+    let roots = super::util::ModRoots::load(db).await?;
+    let mods = super::util::active_mods().all(db).await?;
+    let deleted = super::util::deleted_mods().all(db).await?;
+
+    let canonical_roots: Vec<(Option<i32>, PathBuf)> = roots
+        .iter()
+        .filter_map(|(id, _, path)| Some((id, path.canonicalize().ok()?)))
+        .collect();
+    let mut found: HashSet<(Option<i32>, PathBuf)> = HashSet::new();
+    let mut unavailable_roots = HashSet::new();
+    for (root_id, _, root_dir) in roots.iter() {
+        if !root_dir.is_dir() {
+            unavailable_roots.insert(root_id);
+            continue;
+        }
+        let known_dirs: Vec<PathBuf> = mods
+            .iter()
+            .chain(deleted.iter())
+            .filter(|sims_mod| sims_mod.root_id == root_id)
+            .map(|sims_mod| PathBuf::from(&sims_mod.directory))
+            .collect();
+        let other_roots: Vec<PathBuf> = canonical_roots
+            .iter()
+            .filter(|(id, _)| *id != root_id)
+            .map(|(_, path)| path.clone())
+            .collect();
+        for dir in crate::scanner::find_mod_directories(root_dir, &known_dirs, &other_roots)? {
+            found.insert((root_id, super::util::directory_key(&dir)));
+        }
+    }
+    let known: HashSet<(Option<i32>, PathBuf)> = mods
+        .iter()
+        .filter(|sims_mod| !unavailable_roots.contains(&sims_mod.root_id))
+        .map(|sims_mod| {
+            (
+                sims_mod.root_id,
+                super::util::directory_key(Path::new(&sims_mod.directory)),
+            )
+        })
+        .collect();
+
+    let failing_verification = if verify {
+        let mut failing = 0;
+        for sims_mod in mods.iter() {
+            debug!("Verifying {}", sims_mod.name);
+            if !super::util::verify_mod(db, sims_mod)
+                .await?
+                .verification_passed()
+            {
+                failing += 1;
+            }
+        }
+        Some(failing)
+    } else {
+        None
+    };
+
+    let last_scan = ScanRecord::find()
+        .order_by_desc(scan_record::Column::ScannedAt)
+        .one(db)
+        .await?
+        .map(|record| record.scanned_at);
+
+    Ok(Status {
+        total_mods: mods.len(),
+        broken_mods: mods.iter().filter(|m| m.broken_at.is_some()).count(),
+        protected_mods: mods.iter().filter(|m| m.protected).count(),
+        frozen_mods: mods.iter().filter(|m| m.frozen).count(),
+        due_for_recheck: super::util::mods_due_for_recheck().count(db).await? as usize,
+        new_folders: found.difference(&known).count(),
+        missing_folders: known.difference(&found).count(),
+        failing_verification,
+        hardlink_savings: super::dedupe::hardlink_savings(db).await?,
+        last_scan,
+    })
+}
+
+fn render_json(status: &Status) -> crate::Result<String> {
+    let value = json!({
+        "total_mods": status.total_mods,
+        "broken_mods": status.broken_mods,
+        "protected_mods": status.protected_mods,
+        "frozen_mods": status.frozen_mods,
+        "due_for_recheck": status.due_for_recheck,
+        "new_folders": status.new_folders,
+        "missing_folders": status.missing_folders,
+        "failing_verification": status.failing_verification,
+        "hardlink_saved_bytes": status.hardlink_savings,
+        "last_scan": status.last_scan.map(|t| t.to_rfc3339()),
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+    });
+    Ok(serde_json::to_string_pretty(&value)? + "\n")
+}
+
+fn render_prometheus(status: &Status) -> String {
+    let mut metrics = vec![
+        (
+            "mods_total",
+            "Active mods in the database",
+            status.total_mods as i64,
+        ),
+        (
+            "mods_broken",
+            "Mods marked as broken",
+            status.broken_mods as i64,
+        ),
+        (
+            "mods_protected",
+            "Protected mods",
+            status.protected_mods as i64,
+        ),
+        (
+            "mods_frozen",
+            "Mods marked as frozen",
+            status.frozen_mods as i64,
+        ),
+        (
+            "mods_due_for_recheck",
+            "Mods whose recheck date has passed",
+            status.due_for_recheck as i64,
+        ),
+        (
+            "mod_folders_new",
+            "Mod folders not in the database",
+            status.new_folders as i64,
+        ),
+        (
+            "mod_folders_missing",
+            "Mods whose folder is missing",
+            status.missing_folders as i64,
+        ),
+        (
+            "hardlink_saved_bytes",
+            "Disk space saved by hardlinked duplicates",
+            status.hardlink_savings as i64,
+        ),
+    ];
+    if let Some(failing) = status.failing_verification {
+        metrics.push((
+            "mods_failing_verification",
+            "Mods whose files do not match their stored hashes",
+            failing as i64,
+        ));
+    }
+    if let Some(last_scan) = status.last_scan {
+        metrics.push((
+            "last_scan_timestamp_seconds",
+            "Time of the last scan",
+            last_scan.timestamp(),
+        ));
+    }
+    let mut rendered = String::new();
+    for (name, help, value) in metrics.iter() {
+        rendered.push_str(&format!(
+            "# HELP sims4modorganizer_{0} {1}\n# TYPE sims4modorganizer_{0} gauge\nsims4modorganizer_{0} {2}\n",
+            name, help, value
+        ));
+    }
+    rendered
+}
+
+pub async fn status(
+    format: StatusFormat,
+    write: Option<PathBuf>,
+    verify: bool,
+) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+    let status = collect_status(&db, verify).await?;
+
+    let rendered = match format {
+        StatusFormat::Json => render_json(&status)?,
+        StatusFormat::Prometheus => render_prometheus(&status),
+    };
+
+    if let Some(write) = write {
+        info!("Writing status to {}", write.display());
+        // Write next to the target and rename, so readers never see a partial file
+        let temp_path = write.with_extension("tmp");
+        crate::fileio::write(&temp_path, rendered)?;
+        crate::fileio::rename(&temp_path, &write)?;
+    } else {
+        print!("{}", rendered);
+    }
+    Ok(())
+}
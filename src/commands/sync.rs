@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::prelude::*;
+
+use super::catalog::{
+    catalog_to_bundle, field_changed, load_catalog, pair_catalogs, parse_catalog, CatalogMod,
+};
+use crate::signing::SignatureStatus;
+
+/// Actions for comparing mod folders between installs
+#[derive(clap::Subcommand, Debug)]
+pub enum SyncAction {
+    /// Writes a bundle with every mod's metadata and file hashes, but no mod files
+    Export {
+        /// File to write the bundle to
+        output: PathBuf,
+
+        /// Sign the bundle with this install's key, writing the signature next to it
+        #[arg(long)]
+        sign: bool,
+
+        #[command(flatten)]
+        filter: super::TagFilter,
+    },
+    /// Compares a bundle from another install against this one. Nothing is changed.
+    Import {
+        /// Bundle written by `sync export`
+        bundle: PathBuf,
+    },
+    /// Shows this install's public key, for friends to trust its signed bundles
+    Key,
+    /// Trusts bundles signed with a friend's public key
+    Trust {
+        /// Name to show for bundles signed with the key
+        name: String,
+
+        /// Public key shown by `sync key` on the friend's install
+        public_key: String,
+    },
+}
+
+pub async fn sync(action: SyncAction) -> crate::Result<()> {
+    match action {
+        SyncAction::Export {
+            output,
+            sign,
+            filter,
+        } => {
+            let db = crate::util::open_database().await?;
+            export_bundle(&db, &output, filter).await?;
+            if sign {
+                let fingerprint = crate::signing::sign_file(&output)?;
+                println!("Signed the bundle with key {}", fingerprint);
+            }
+            Ok(())
+        }
+        SyncAction::Import { bundle } => {
+            let db = crate::util::open_database().await?;
+            compare_bundle(&db, &bundle).await
+        }
+        SyncAction::Key => {
+            let key = crate::signing::own_public_key()?;
+            println!(
+                "Public key:  {}",
+                crate::signing::encode_public_key(&key).bold()
+            );
+            println!("Fingerprint: {}", crate::signing::fingerprint(&key));
+            println!(
+                "Friends can trust your signed bundles with {}",
+                "sync trust <name> <public key>".bold()
+            );
+            Ok(())
+        }
+        SyncAction::Trust { name, public_key } => {
+            let key = match crate::signing::trust_key(&name, &public_key) {
+                Ok(key) => key,
+                Err(e) => {
+                    eprintln!("{} is not a valid public key: {}", public_key.bold(), e);
+                    return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+                }
+            };
+            println!(
+                "Trusting bundles signed by {} ({})",
+                name.bold(),
+                crate::signing::fingerprint(&key)
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Tells whether a bundle is signed, and refuses bundles that changed after signing
+fn check_signature(bundle: &Path) -> crate::Result<()> {
+    match crate::signing::check_file(bundle)? {
+        SignatureStatus::Unsigned => println!("{}", "The bundle is not signed.".dimmed()),
+        SignatureStatus::Trusted(name) => {
+            println!("{} {}", "Signed by".green(), name.green().bold())
+        }
+        SignatureStatus::Untrusted(fingerprint) => println!(
+            "{} {}. Trust it with {} if it is your friend's.",
+            "Signed by an unknown key".yellow(),
+            fingerprint.yellow().bold(),
+            "sync trust".bold()
+        ),
+        SignatureStatus::Invalid => {
+            eprintln!(
+                "{} {} changed after it was signed, or was damaged in transit.",
+                "Signature mismatch:".red().bold(),
+                bundle.display()
+            );
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+        }
+    }
+    Ok(())
+}
+
+async fn export_bundle(
+    db: &DatabaseConnection,
+    output: &Path,
+    filter: super::TagFilter,
+) -> crate::Result<()> {
+    let selected: HashSet<String> = if filter.is_empty() {
+        super::util::visible_mods().all(db).await?
+    } else {
+        super::util::get_mods_for_filter(db, filter).await?
+    }
+    .into_iter()
+    .filter(|sims_mod| !super::util::is_hidden(sims_mod))
+    .map(|sims_mod| sims_mod.name)
+    .collect();
+    let mut mods = load_catalog(db).await?;
+    mods.retain(|catalog_mod| selected.contains(&catalog_mod.name));
+    let bundle = catalog_to_bundle(&mods);
+    crate::fileio::write(output, serde_json::to_string(&bundle)? + "\n")?;
+    println!("Exported {} mods to {}", mods.len(), output.display());
+    Ok(())
+}
+
+async fn compare_bundle(db: &DatabaseConnection, bundle: &Path) -> crate::Result<()> {
+    check_signature(bundle)?;
+    let contents = crate::fileio::read_to_string(bundle)?;
+    let Some(theirs) = parse_catalog(&contents) else {
+        eprintln!("{} is not a sync bundle", bundle.display());
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    };
+    let ours = load_catalog(db).await?;
+
+    let pairs = pair_catalogs(&theirs, &ours);
+    let mut version_mismatches = Vec::new();
+    let mut file_mismatches = Vec::new();
+    for (their_mod, our_mod) in pairs.matched.iter() {
+        if field_changed(&our_mod.version, &their_mod.version) {
+            version_mismatches.push((our_mod, their_mod));
+        } else if field_changed(&our_mod.files, &their_mod.files) {
+            file_mismatches.push((our_mod, their_mod));
+        }
+    }
+
+    let describe = |m: &CatalogMod| {
+        let mut description = format!(
+            "- {} ({})",
+            m.name.bold(),
+            m.version.as_deref().unwrap_or("unknown version")
+        );
+        if let Some(source_url) = m.source_url.as_deref().filter(|s| !s.is_empty()) {
+            description.push_str(&format!(" {}", source_url.dimmed()));
+        }
+        description
+    };
+
+    if !pairs.only_left.is_empty() {
+        println!("{}", "Only in the bundle:".bold());
+        pairs
+            .only_left
+            .iter()
+            .for_each(|m| println!("{}", describe(m)));
+    }
+    if !pairs.only_right.is_empty() {
+        println!("{}", "Only here:".bold());
+        pairs
+            .only_right
+            .iter()
+            .for_each(|m| println!("{}", describe(m)));
+    }
+    if !version_mismatches.is_empty() {
+        println!("{}", "Different versions:".bold());
+        for (our_mod, their_mod) in version_mismatches.iter() {
+            let name = if our_mod.name == their_mod.name {
+                our_mod.name.clone()
+            } else {
+                format!("{} / {}", our_mod.name, their_mod.name)
+            };
+            println!(
+                "- {}: {} here, {} in the bundle",
+                name.bold(),
+                our_mod.version.as_deref().unwrap_or_default().yellow(),
+                their_mod.version.as_deref().unwrap_or_default().yellow()
+            );
+        }
+    }
+    if !file_mismatches.is_empty() {
+        println!("{}", "Same version, different files:".bold());
+        for (our_mod, _) in file_mismatches.iter() {
+            println!("{}", describe(our_mod));
+        }
+    }
+
+    println!(
+        "{} mods in common, {} only in the bundle, {} only here, {} with differences",
+        pairs.matched.len(),
+        pairs.only_left.len(),
+        pairs.only_right.len(),
+        version_mismatches.len() + file_mismatches.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Catching new tags that look like typos of existing ones, such as `Funriture` for
+//! `Furniture`, before they are created and spread the same tag over two names
+
+use std::io::IsTerminal;
+
+use colored::Colorize;
+use inquire::Select;
+use sea_orm::EntityTrait;
+
+use crate::entities::prelude::*;
+use crate::style::{self, Status};
+
+/// What to do with a new tag that is close to an existing one
+enum TypoAction {
+    UseExisting(String),
+    CreateAnyway(String),
+}
+
+impl std::fmt::Display for TypoAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TypoAction::UseExisting(tag) => write!(f, "Use {}", tag),
+            TypoAction::CreateAnyway(tag) => write!(f, "Create {} anyway", tag),
+        }
+    }
+}
+
+/// Edits needed to turn one string into another, counting two swapped neighbouring
+/// letters as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows: Vec<Vec<usize>> = vec![(0..=b.len()).collect()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            row[j] = substitution.min(rows[i - 1][j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// How many edits away from an existing tag a new one may be to count as a typo of it.
+/// Short tags only match in a different case, as one letter changes them too much.
+fn max_distance(tag: &str) -> usize {
+    match tag.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// The existing tag closest to a new one, ignoring case, if it is close enough to be
+/// what was meant
+fn closest_tag<'a>(tag: &str, existing: &'a [String]) -> Option<&'a String> {
+    let tag_lower = tag.to_lowercase();
+    existing
+        .iter()
+        .map(|e| (edit_distance(&tag_lower, &e.to_lowercase()), e))
+        .filter(|(distance, _)| *distance <= max_distance(tag))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, e)| e)
+}
+
+/// Asks whether the existing tag was meant. Without a terminal to ask on, the tag is
+/// kept as typed with a warning, so scripts are never left waiting. Cancelling the
+/// prompt cancels the whole change.
+fn confirm_tag(tag: String, suggestion: &str) -> crate::Result<String> {
+    if !std::io::stdin().is_terminal() {
+        eprintln!(
+            "{} {} looks like a typo of {}; creating it anyway",
+            style::mark(Status::Warning, "Tag"),
+            tag.bold(),
+            suggestion.bold()
+        );
+        return Ok(tag);
+    }
+    let action = Select::new(
+        &format!(
+            "{} isn't a tag yet. Did you mean {}?",
+            tag,
+            suggestion.bold()
+        ),
+        vec![
+            TypoAction::UseExisting(suggestion.to_string()),
+            TypoAction::CreateAnyway(tag.clone()),
+        ],
+    )
+    .prompt()?;
+    match action {
+        TypoAction::UseExisting(tag) | TypoAction::CreateAnyway(tag) => Ok(tag),
+    }
+}
+
+/// Checks tags about to be given to mods against the existing ones. Tags that don't
+/// exist yet but are close to one that does are offered to be replaced by it. Tags that
+/// end up the same are only kept once.
+pub async fn check_tags<C>(db: &C, tags: Vec<String>) -> crate::Result<Vec<String>>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let existing: Vec<String> = Tag::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| t.tag)
+        .collect();
+    let mut checked: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = match closest_tag(&tag, &existing).filter(|_| !existing.contains(&tag)) {
+            Some(suggestion) => confirm_tag(tag, suggestion)?,
+            None => tag,
+        };
+        if !checked.contains(&tag) {
+            checked.push(tag);
+        }
+    }
+    Ok(checked)
+}
+
+/// Checks a single tag about to be given to a mod, as [`check_tags`] does
+pub async fn check_tag<C>(db: &C, tag: String) -> crate::Result<String>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let mut checked = check_tags(db, vec![tag]).await?;
+    Ok(checked.remove(0))
+}
@@ -1,20 +1,345 @@
-use colored::Colorize;
-use sea_orm::{prelude::*, Condition, IntoActiveModel};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use colored::{Color, Colorize};
+use inquire::{Select, Text};
+use itertools::Itertools;
+use sea_orm::{prelude::*, ActiveValue, Condition, IntoActiveModel, QueryOrder, TransactionTrait};
+use serde_json::{json, Value};
 use tracing::info;
+use tracing_unwrap::OptionExt;
 
+use crate::commands::UpdateChannel;
 use crate::entities::{prelude::*, *};
+use crate::style::{self, Status};
+
+use super::util::get_or_create_tag_id;
+
+/// Metadata a tag fills in on mods it is added to, such as the page of the creator a
+/// tag stands for
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagDefaults {
+    pub source_url: Option<String>,
+    pub update_channel: Option<UpdateChannel>,
+    pub nsfw: bool,
+}
+
+impl TagDefaults {
+    fn from_tag(tag: &tag::Model) -> TagDefaults {
+        TagDefaults {
+            source_url: tag.default_source_url.clone(),
+            update_channel: tag
+                .default_update_channel
+                .as_deref()
+                .map(UpdateChannel::from_stored),
+            nsfw: tag.default_nsfw,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source_url.is_none() && self.update_channel.is_none() && !self.nsfw
+    }
+
+    /// The combined defaults of the given tags. The first tag setting a field wins.
+    pub async fn for_tags<C>(db: &C, tags: &[String]) -> Result<TagDefaults, DbErr>
+    where
+        C: sea_orm::ConnectionTrait,
+    {
+        let models = Tag::find()
+            .filter(tag::Column::Tag.is_in(tags.iter().map(|t| t.as_str())))
+            .all(db)
+            .await?;
+        let mut defaults = TagDefaults::default();
+        for name in tags.iter() {
+            let Some(tag) = models.iter().find(|t| t.tag == *name) else {
+                continue;
+            };
+            let tag_defaults = TagDefaults::from_tag(tag);
+            defaults.source_url = defaults.source_url.or(tag_defaults.source_url);
+            defaults.update_channel = defaults.update_channel.or(tag_defaults.update_channel);
+            defaults.nsfw |= tag_defaults.nsfw;
+        }
+        Ok(defaults)
+    }
+
+    /// The defaults a mod is still missing. Only blanks are filled in: an empty source
+    /// URL and the default update channel. Values set by hand are kept.
+    pub fn missing_from(&self, sims_mod: &sims_mod::Model) -> TagDefaults {
+        TagDefaults {
+            source_url: self
+                .source_url
+                .clone()
+                .filter(|_| sims_mod.source_url.trim().is_empty()),
+            update_channel: self.update_channel.filter(|_| {
+                UpdateChannel::from_stored(&sims_mod.update_channel) == UpdateChannel::default()
+            }),
+            nsfw: self.nsfw && !sims_mod.nsfw,
+        }
+    }
+
+    /// What the defaults fill in, for previews
+    pub fn describe(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        if let Some(source_url) = &self.source_url {
+            fields.push(format!("source {}", source_url));
+        }
+        if let Some(update_channel) = self.update_channel {
+            fields.push(format!("updates {}", update_channel));
+        }
+        if self.nsfw {
+            fields.push("NSFW".to_string());
+        }
+        fields
+    }
+
+    /// Sets the defaults on a mod
+    pub fn apply(&self, model: &mut sims_mod::ActiveModel) {
+        if let Some(source_url) = &self.source_url {
+            model.source_type =
+                ActiveValue::Set(super::SourceType::name_for_url(source_url).to_string());
+            model.source_url = ActiveValue::Set(source_url.clone());
+        }
+        if let Some(update_channel) = self.update_channel {
+            model.update_channel = ActiveValue::Set(update_channel.as_str().to_string());
+        }
+        if self.nsfw {
+            model.nsfw = ActiveValue::Set(true);
+        }
+    }
+
+    /// Prints what the defaults fill in
+    pub fn print_preview(&self, tags: &[String]) {
+        if self.is_empty() {
+            return;
+        }
+        println!(
+            "{} {}: {}",
+            if tags.len() == 1 { "Tag" } else { "Tags" },
+            tags.join(", ").bold(),
+            format!("fills in {}", self.describe().join(", ")).dimmed()
+        );
+    }
+}
+
+/// Output formats for the tag list
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TagsFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct TagsOptions {
+    /// Deletes a given tag. Does not delete any mods.
+    #[arg(short, long)]
+    pub delete: Option<String>,
+
+    /// Only show given tags
+    #[arg(short, long, value_delimiter = ',')]
+    pub tags: Option<Vec<String>>,
+
+    /// Creates the given tags without tagging any mods, so a taxonomy can be set up
+    /// before tagging. Created tags are kept even while no mod uses them.
+    #[arg(short, long, value_delimiter = ',', value_name = "TAGS")]
+    pub create: Option<Vec<String>>,
+
+    /// Sets the description, color or parent of a tag, creating it if needed
+    #[arg(short, long, value_name = "TAG")]
+    pub set: Option<String>,
+
+    /// Description for --set. An empty value clears it.
+    #[arg(long, requires = "set")]
+    pub description: Option<String>,
+
+    /// Color for --set, as a name like "bright blue" or #RRGGBB. An empty value clears it.
+    #[arg(long, requires = "set")]
+    pub color: Option<String>,
+
+    /// Parent tag for --set. An empty value clears it.
+    #[arg(long, requires = "set")]
+    pub parent: Option<String>,
+
+    /// Source URL filled in for mods given the tag with --set, such as a creator's page.
+    /// An empty value clears it.
+    #[arg(long, requires = "set", value_name = "URL")]
+    pub default_source_url: Option<String>,
+
+    /// Update channel set on mods given the tag with --set. Clears it if no channel is given.
+    #[arg(long, requires = "set", value_enum, num_args = 0..=1, value_name = "CHANNEL")]
+    pub default_update_channel: Option<Option<UpdateChannel>>,
+
+    /// Whether mods given the tag with --set are flagged as adult content (true/false)
+    #[arg(long, requires = "set")]
+    pub default_nsfw: Option<bool>,
+
+    /// Writes all tags with their descriptions, colors and parents to a JSON file
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+
+    /// Reads tags from a JSON file written by --export, merging them into existing tags
+    #[arg(long, value_name = "FILE")]
+    pub import: Option<PathBuf>,
+
+    /// Shows how many mods use each tag
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Lists the tags most often found on mods with the given tag
+    #[arg(short, long, value_name = "TAG")]
+    pub related: Option<String>,
+
+    /// Interactively compares two tags' mods and tags their intersection, union, or difference
+    #[arg(long)]
+    pub combine: bool,
 
-pub async fn tags(delete: Option<String>, tags: Option<Vec<String>>) -> crate::Result<()> {
+    /// Output format for the tag list
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = TagsFormat::Text,
+        conflicts_with_all = ["delete", "create", "set", "export", "import", "stats", "related", "combine"]
+    )]
+    pub format: TagsFormat,
+}
+
+impl TagsOptions {
+    /// Number of mutually exclusive actions selected
+    pub fn action_count(&self) -> usize {
+        [
+            self.delete.is_some(),
+            self.tags.is_some(),
+            self.create.is_some(),
+            self.set.is_some(),
+            self.export.is_some(),
+            self.import.is_some(),
+            self.stats,
+            self.related.is_some(),
+            self.combine,
+        ]
+        .iter()
+        .filter(|selected| **selected)
+        .count()
+    }
+}
+
+/// Parses a tag color, either a terminal color name or a #RRGGBB hex value
+pub fn parse_tag_color(color: &str) -> Option<Color> {
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::TrueColor {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    Color::from_str(color).ok()
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_tags_json(tags_and_mods: &[(tag::Model, Vec<sims_mod::Model>)]) -> crate::Result<String> {
+    let entries: Vec<Value> = tags_and_mods
+        .iter()
+        .map(|(tag, mods)| {
+            json!({
+                "tag": tag.tag,
+                "id": tag.id,
+                "mod_count": mods.len(),
+                "mod_ids": mods.iter().map(|m| m.id).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&entries)? + "\n")
+}
+
+/// Renders tags as CSV, with member mod IDs separated by semicolons
+fn render_tags_csv(tags_and_mods: &[(tag::Model, Vec<sims_mod::Model>)]) -> String {
+    let mut rendered = String::from("tag,id,mod_count,mod_ids\n");
+    for (tag, mods) in tags_and_mods.iter() {
+        rendered.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&tag.tag),
+            tag.id,
+            mods.len(),
+            mods.iter()
+                .map(|m| m.id.to_string())
+                .collect::<Vec<_>>()
+                .join(";")
+        ));
+    }
+    rendered
+}
+
+/// Turns an empty option value into a request to clear the field
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Whether making `parent_id` the parent of `tag_id` would create a cycle
+fn creates_cycle(parents: &HashMap<i32, Option<i32>>, tag_id: i32, parent_id: i32) -> bool {
+    let mut current = Some(parent_id);
+    let mut seen = HashSet::new();
+    while let Some(id) = current {
+        if id == tag_id || !seen.insert(id) {
+            return true;
+        }
+        current = parents.get(&id).copied().flatten();
+    }
+    false
+}
+
+/// Tag names from the root of the taxonomy down to the given tag
+fn tag_path(tags_by_id: &HashMap<i32, tag::Model>, tag: &tag::Model) -> Vec<String> {
+    let mut path = vec![tag.tag.clone()];
+    let mut seen = HashSet::from([tag.id]);
+    let mut current = tag.parent_id;
+    while let Some(parent) = current.and_then(|id| tags_by_id.get(&id)) {
+        if !seen.insert(parent.id) {
+            break;
+        }
+        path.push(parent.tag.clone());
+        current = parent.parent_id;
+    }
+    path.reverse();
+    path
+}
+
+pub async fn tags(options: TagsOptions) -> crate::Result<()> {
     let db = crate::util::open_database().await?;
 
-    if let Some(to_delete) = delete {
+    if let Some(to_delete) = options.delete {
         info!("Deleting tag: {}", to_delete);
         if let Some(tag_model) = Tag::find()
             .filter(Condition::any().add(tag::Column::Tag.eq(&to_delete)))
             .one(&db)
             .await?
         {
-            tag_model.into_active_model().delete(&db).await?;
+            let txn = db.begin().await?;
+            // Children move up to the deleted tag's parent
+            Tag::update_many()
+                .col_expr(tag::Column::ParentId, Expr::value(tag_model.parent_id))
+                .filter(tag::Column::ParentId.eq(tag_model.id))
+                .exec(&txn)
+                .await?;
+            tag_model.into_active_model().delete(&txn).await?;
+            txn.commit().await?;
             return Ok(());
         } else {
             eprintln!("Tag not found: {}", to_delete);
@@ -22,56 +347,622 @@ pub async fn tags(delete: Option<String>, tags: Option<Vec<String>>) -> crate::R
         }
     }
 
-    let tags_and_mods: Vec<(tag::Model, Vec<sims_mod::Model>)> = if let Some(mut tags) = tags {
-        Tag::find().filter(
-            tags.drain(..)
-                .fold(Condition::any(), |c, t| c.add(tag::Column::Tag.eq(t))),
-        )
-    } else {
-        Tag::find()
+    if let Some(to_create) = options.create {
+        return create_tags(&db, to_create).await;
+    }
+
+    if let Some(to_set) = &options.set {
+        return set_tag(&db, to_set, &options).await;
+    }
+
+    if let Some(path) = options.export {
+        return export_taxonomy(&db, &path).await;
+    }
+
+    if let Some(path) = options.import {
+        return import_taxonomy(&db, &path).await;
+    }
+
+    if options.stats {
+        return print_tag_stats(&db).await;
+    }
+
+    if let Some(tag_name) = options.related {
+        return print_related_tags(&db, &tag_name).await;
+    }
+
+    if options.combine {
+        return combine_tags(&db).await;
+    }
+
+    let tags_by_id: HashMap<i32, tag::Model> = Tag::find()
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t))
+        .collect();
+
+    let tags_and_mods: Vec<(tag::Model, Vec<sims_mod::Model>)> =
+        if let Some(mut tags) = options.tags {
+            Tag::find().filter(
+                tags.drain(..)
+                    .fold(Condition::any(), |c, t| c.add(tag::Column::Tag.eq(t))),
+            )
+        } else {
+            Tag::find()
+        }
+        .find_with_related(SimsMod)
+        .filter(sims_mod::Column::DeletedAt.is_null())
+        .all(&db)
+        .await?;
+
+    match options.format {
+        TagsFormat::Json => {
+            print!("{}", render_tags_json(&tags_and_mods)?);
+            return Ok(());
+        }
+        TagsFormat::Csv => {
+            print!("{}", render_tags_csv(&tags_and_mods));
+            return Ok(());
+        }
+        TagsFormat::Text => {}
     }
-    .find_with_related(SimsMod)
-    .all(&db)
-    .await?;
 
     for (tag, mods) in tags_and_mods.iter() {
-        let title_corner = boxy::Char::upper_left(boxy::Weight::Thick);
-        let title_side_h = boxy::Char::horizontal(boxy::Weight::Thick).to_string();
-        let title_side_v = boxy::Char::vertical(boxy::Weight::Thick);
-        let title_branch = boxy::Char::right_tee(boxy::Weight::Thick).down(boxy::Weight::Normal);
-        let left_branch_more_str = boxy::Char::right_tee(boxy::Weight::Normal).to_string();
-        let left_branch_done_str = boxy::Char::lower_left(boxy::Weight::Normal).to_string();
-        let left_node = boxy::Char::left_half(boxy::Weight::Normal);
+        let title = tag_path(&tags_by_id, tag).join(" > ");
+        let description = tag.description.clone().unwrap_or_default();
+        let color = tag.color.as_deref().and_then(parse_tag_color);
+        let mut tree = crate::render::Tree::new(title).title_style(|i, line| match (i, color) {
+            (0, Some(color)) => line.bold().color(color),
+            (0, None) => line.bold(),
+            _ => line.dimmed(),
+        });
+        if !description.is_empty() {
+            tree = tree.subtitle(description);
+        }
+        for current_mod in mods.iter() {
+            tree.push(crate::render::Node::item(current_mod.name.clone()));
+        }
+        tree.print();
+    }
+    Ok(())
+}
+
+/// Tag IDs of every active mod, including mods without tags
+async fn load_mod_tag_ids(db: &DatabaseConnection) -> crate::Result<HashMap<i32, HashSet<i32>>> {
+    let mut mod_tags: HashMap<i32, HashSet<i32>> = super::util::active_mods()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.id, HashSet::new()))
+        .collect();
+    for relation in ModTagRelation::find().all(db).await? {
+        if let Some(tag_ids) = mod_tags.get_mut(&relation.mod_id) {
+            tag_ids.insert(relation.tag_id);
+        }
+    }
+    Ok(mod_tags)
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 * 100.0 / total as f64
+    }
+}
 
-        let tag = tag.tag.clone();
+async fn print_tag_stats(db: &DatabaseConnection) -> crate::Result<()> {
+    let tags = Tag::find().all(db).await?;
+    let mod_tags = load_mod_tag_ids(db).await?;
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for tag_id in mod_tags.values().flatten() {
+        *counts.entry(*tag_id).or_default() += 1;
+    }
 
+    let mut rows: Vec<(&tag::Model, usize)> = tags
+        .iter()
+        .map(|t| (t, counts.get(&t.id).copied().unwrap_or_default()))
+        .collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.tag.cmp(&b.0.tag)));
+    let width = rows
+        .iter()
+        .map(|(t, _)| t.tag.chars().count())
+        .max()
+        .unwrap_or_default();
+    for (tag, count) in rows.iter() {
         println!(
-            "{}{}{}",
-            title_corner,
-            title_side_h.repeat(tag.len() + 2),
-            title_corner.rotate_cw(1)
+            "{} {:>5} {}",
+            format!("{:<width$}", tag.tag).bold(),
+            count,
+            format!("({:.1}%)", percent(*count, mod_tags.len())).dimmed()
         );
-        println!("{} {} {}", title_side_v, tag.bold(), title_side_v);
+    }
+
+    let untagged = mod_tags.values().filter(|t| t.is_empty()).count();
+    let single_use = rows.iter().filter(|(_, count)| *count == 1).count();
+    println!(
+        "{} tags on {} mods. {} mods have no tags, {} tags are used by a single mod.",
+        tags.len(),
+        mod_tags.len(),
+        style::paint(Status::Warning, &untagged.to_string()),
+        style::paint(Status::Warning, &single_use.to_string())
+    );
+    Ok(())
+}
+
+async fn print_related_tags(db: &DatabaseConnection, tag_name: &str) -> crate::Result<()> {
+    let Some(tag) = Tag::find()
+        .filter(tag::Column::Tag.eq(tag_name))
+        .one(db)
+        .await?
+    else {
+        eprintln!("Tag not found: {}", tag_name);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+    let names_by_id: HashMap<i32, String> = Tag::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t.tag))
+        .collect();
+    let mod_tags = load_mod_tag_ids(db).await?;
+    let tagged: Vec<&HashSet<i32>> = mod_tags
+        .values()
+        .filter(|tag_ids| tag_ids.contains(&tag.id))
+        .collect();
+    if tagged.is_empty() {
+        println!("No mods are tagged {}.", tag.tag.bold());
+        return Ok(());
+    }
+
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for tag_id in tagged.iter().copied().flatten().filter(|id| **id != tag.id) {
+        *counts.entry(*tag_id).or_default() += 1;
+    }
+    let mut rows: Vec<(&str, usize)> = counts
+        .iter()
+        .filter_map(|(id, count)| names_by_id.get(id).map(|name| (name.as_str(), *count)))
+        .collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    println!(
+        "{} mods are tagged {}. Tags on the same mods:",
+        tagged.len(),
+        tag.tag.bold()
+    );
+    if rows.is_empty() {
+        println!("  (none)");
+    }
+    let width = rows
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or_default();
+    for (name, count) in rows.iter() {
+        // A tag on every one of these mods may be redundant with this one
+        let always = if *count == tagged.len() {
+            format!(" {}", style::mark(Status::Warning, "always together"))
+        } else {
+            String::new()
+        };
         println!(
-            "{}{}{}",
-            title_branch,
-            title_side_h.repeat(tag.len() + 2),
-            title_corner.rotate_cw(2)
+            "  {} {:>5} {}{}",
+            format!("{:<width$}", name).bold(),
+            count,
+            format!("({:.1}%)", percent(*count, tagged.len())).dimmed(),
+            always
         );
+    }
+    Ok(())
+}
+
+/// Prints one part of the breakdown, listing a few of its mods
+fn print_mod_set(label: &str, mod_ids: &HashSet<i32>, names: &HashMap<i32, String>) {
+    const SHOWN: usize = 10;
+    println!(
+        "{} {}",
+        label.bold(),
+        format!("({})", mod_ids.len()).dimmed()
+    );
+    let mut mod_names: Vec<&str> = mod_ids
+        .iter()
+        .filter_map(|id| names.get(id).map(|n| n.as_str()))
+        .collect();
+    mod_names.sort_unstable();
+    for name in mod_names.iter().take(SHOWN) {
+        println!("  {}", name);
+    }
+    if mod_names.len() > SHOWN {
+        println!("  ... and {} more", mod_names.len() - SHOWN);
+    }
+}
+
+async fn combine_tags(db: &DatabaseConnection) -> crate::Result<()> {
+    let tags: Vec<String> = Tag::find()
+        .order_by_asc(tag::Column::Tag)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| t.tag)
+        .collect();
+    if tags.len() < 2 {
+        eprintln!("At least two tags are needed to combine!");
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    }
+    let Some(first) = Select::new("First tag:", tags.clone()).prompt_skippable()? else {
+        return Ok(());
+    };
+    let others: Vec<String> = tags.into_iter().filter(|t| *t != first).collect();
+    let Some(second) = Select::new("Second tag:", others).prompt_skippable()? else {
+        return Ok(());
+    };
+
+    let tag_ids: HashMap<String, i32> = Tag::find()
+        .filter(tag::Column::Tag.is_in([first.as_str(), second.as_str()]))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| (t.tag, t.id))
+        .collect();
+    let mod_tags = load_mod_tag_ids(db).await?;
+    let tagged = |tag: &str| -> HashSet<i32> {
+        mod_tags
+            .iter()
+            .filter(|(_, ids)| tag_ids.get(tag).map(|id| ids.contains(id)).unwrap_or(false))
+            .map(|(mod_id, _)| *mod_id)
+            .collect()
+    };
+    let (first_mods, second_mods) = (tagged(&first), tagged(&second));
+    let names: HashMap<i32, String> = super::util::active_mods()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.id, m.name))
+        .collect();
+
+    let both: HashSet<i32> = first_mods.intersection(&second_mods).copied().collect();
+    let only_first: HashSet<i32> = first_mods.difference(&second_mods).copied().collect();
+    let only_second: HashSet<i32> = second_mods.difference(&first_mods).copied().collect();
+    print_mod_set(&format!("Only {}", first), &only_first, &names);
+    print_mod_set(&format!("Both {} and {}", first, second), &both, &names);
+    print_mod_set(&format!("Only {}", second), &only_second, &names);
+
+    let union: HashSet<i32> = first_mods.union(&second_mods).copied().collect();
+    let operations = [
+        (format!("{} ∩ {}", first, second), both),
+        (format!("{} ∪ {}", first, second), union),
+        (format!("{} − {}", first, second), only_first),
+        (format!("{} − {}", second, first), only_second),
+    ];
+    let labels: Vec<String> = operations
+        .iter()
+        .map(|(label, mods)| format!("{} ({} mods)", label, mods.len()))
+        .collect();
+    let Some(selected) = Select::new("Tag which mods?", labels.clone()).prompt_skippable()? else {
+        return Ok(());
+    };
+    let index = labels.iter().position(|l| *l == selected).unwrap_or(0);
+    let selected_mods = operations[index].1.clone();
+    if selected_mods.is_empty() {
+        println!("No mods to tag.");
+        return Ok(());
+    }
+
+    let Some(new_tag) = Text::new("New tag:")
+        .with_validator(inquire::required!())
+        .with_autocomplete(super::util::TagAutoComplete::create(db).await?)
+        .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    let new_tag = super::tag_typos::check_tag(db, new_tag.trim().to_string()).await?;
+    let mod_count = selected_mods.len();
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            let tag_id = get_or_create_tag_id(txn, &new_tag).await?;
+            let already_tagged: HashSet<i32> = ModTagRelation::find()
+                .filter(mod_tag_relation::Column::TagId.eq(tag_id))
+                .all(txn)
+                .await?
+                .into_iter()
+                .map(|r| r.mod_id)
+                .collect();
+            for mod_id in selected_mods.difference(&already_tagged) {
+                let relation = mod_tag_relation::ActiveModel {
+                    mod_id: ActiveValue::Set(*mod_id),
+                    tag_id: ActiveValue::Set(tag_id),
+                };
+                ModTagRelation::insert(relation).exec(txn).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+    println!("Tagged {} mods.", mod_count.to_string().green().bold());
+    Ok(())
+}
+
+/// Creates tags ahead of tagging, offering an existing tag instead of a new one that
+/// looks like a typo of it. Existing tags are left as they are, but are kept from now
+/// on even while no mod uses them.
+async fn create_tags(db: &DatabaseConnection, tag_names: Vec<String>) -> crate::Result<()> {
+    let tag_names: Vec<String> = tag_names
+        .into_iter()
+        .filter_map(|t| non_empty(&t))
+        .unique()
+        .collect();
+    if tag_names.is_empty() {
+        eprintln!("Tag name cannot be empty");
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+    }
+    let tag_names = super::tag_typos::check_tags(db, tag_names).await?;
+
+    let txn = db.begin().await?;
+    let existing: HashSet<String> = Tag::find()
+        .filter(tag::Column::Tag.is_in(tag_names.iter().map(|t| t.as_str())))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|t| t.tag)
+        .collect();
+    for tag_name in tag_names.iter() {
+        let tag_id = get_or_create_tag_id(&txn, tag_name).await?;
+        Tag::update_many()
+            .col_expr(tag::Column::Explicit, Expr::value(true))
+            .filter(tag::Column::Id.eq(tag_id))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+
+    for tag_name in tag_names.iter() {
+        if existing.contains(tag_name) {
+            println!("{} already exists", tag_name.bold());
+        } else {
+            println!("Created tag {}", tag_name.bold().green());
+        }
+    }
+    Ok(())
+}
+
+async fn set_tag(
+    db: &DatabaseConnection,
+    tag_name: &str,
+    options: &TagsOptions,
+) -> crate::Result<()> {
+    let Some(tag_name) = non_empty(tag_name) else {
+        eprintln!("Tag name cannot be empty");
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+    };
+    if let Some(color) = options.color.as_deref().and_then(non_empty) {
+        if parse_tag_color(&color).is_none() {
+            eprintln!("Unknown color: {}", color);
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+        }
+    }
+    if let Some(source_url) = options.default_source_url.as_deref().and_then(non_empty) {
+        if url::Url::parse(&source_url).is_err() {
+            eprintln!("{} is not a valid URL!", source_url);
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+        }
+    }
 
-        let mut mods = mods.iter().peekable();
-        while let Some(current_mod) = mods.next() {
-            println!(
-                "{}{}{}",
-                if mods.peek().is_some() {
-                    &left_branch_more_str
-                } else {
-                    &left_branch_done_str
+    let txn = db.begin().await?;
+    let tag_id = get_or_create_tag_id(&txn, &tag_name).await?;
+    let mut tag_model = Tag::find_by_id(tag_id)
+        .one(&txn)
+        .await?
+        .expect_or_log("Tag was just found or created")
+        .into_active_model();
+    tag_model.explicit = ActiveValue::Set(true);
+    if let Some(description) = &options.description {
+        tag_model.description = ActiveValue::Set(non_empty(description));
+    }
+    if let Some(color) = &options.color {
+        tag_model.color = ActiveValue::Set(non_empty(color));
+    }
+    if let Some(source_url) = &options.default_source_url {
+        tag_model.default_source_url = ActiveValue::Set(non_empty(source_url));
+    }
+    if let Some(update_channel) = options.default_update_channel {
+        tag_model.default_update_channel =
+            ActiveValue::Set(update_channel.map(|c| c.as_str().to_string()));
+    }
+    if let Some(nsfw) = options.default_nsfw {
+        tag_model.default_nsfw = ActiveValue::Set(nsfw);
+    }
+    if let Some(parent) = &options.parent {
+        let parent_id = match non_empty(parent) {
+            Some(parent) => {
+                let parent_id = get_or_create_tag_id(&txn, &parent).await?;
+                let parents: HashMap<i32, Option<i32>> = Tag::find()
+                    .all(&txn)
+                    .await?
+                    .into_iter()
+                    .map(|t| (t.id, t.parent_id))
+                    .collect();
+                if creates_cycle(&parents, tag_id, parent_id) {
+                    eprintln!("{} cannot be placed under {}", tag_name, parent);
+                    return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+                }
+                Some(parent_id)
+            }
+            None => None,
+        };
+        tag_model.parent_id = ActiveValue::Set(parent_id);
+    }
+    let tag_model = tag_model.update(&txn).await?;
+    txn.commit().await?;
+    println!("Updated tag {}", tag_name);
+    TagDefaults::from_tag(&tag_model).print_preview(&[tag_name]);
+    Ok(())
+}
+
+async fn export_taxonomy(db: &DatabaseConnection, path: &Path) -> crate::Result<()> {
+    let tags = Tag::find().order_by_asc(tag::Column::Tag).all(db).await?;
+    let names_by_id: HashMap<i32, String> = tags.iter().map(|t| (t.id, t.tag.clone())).collect();
+
+    let entries: Vec<Value> = tags
+        .iter()
+        .map(|t| {
+            json!({
+                "tag": t.tag,
+                "description": t.description,
+                "color": t.color,
+                "parent": t.parent_id.and_then(|id| names_by_id.get(&id)),
+                "default_source_url": t.default_source_url,
+                "default_update_channel": t.default_update_channel,
+                "default_nsfw": t.default_nsfw,
+            })
+        })
+        .collect();
+    let rendered = serde_json::to_string_pretty(&json!({ "tags": entries }))? + "\n";
+    crate::fileio::write(path, rendered)?;
+    println!("Exported {} tags to {}", tags.len(), path.display());
+    Ok(())
+}
+
+/// A tag read from a taxonomy file
+struct ImportedTag {
+    tag: String,
+    description: Option<String>,
+    color: Option<String>,
+    parent: Option<String>,
+    defaults: TagDefaults,
+}
+
+fn parse_taxonomy(contents: &str) -> Option<Vec<ImportedTag>> {
+    let value: Value = serde_json::from_str(contents).ok()?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(object) => object.get("tags")?.as_array()?,
+        _ => return None,
+    };
+    let optional_string = |entry: &Value, key: &str| -> Option<Option<String>> {
+        match entry.get(key) {
+            None | Some(Value::Null) => Some(None),
+            Some(Value::String(s)) => Some(non_empty(s)),
+            Some(_) => None,
+        }
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            Some(ImportedTag {
+                tag: non_empty(entry.get("tag")?.as_str()?)?,
+                description: optional_string(entry, "description")?,
+                color: optional_string(entry, "color")?,
+                parent: optional_string(entry, "parent")?,
+                defaults: TagDefaults {
+                    source_url: optional_string(entry, "default_source_url")?,
+                    update_channel: optional_string(entry, "default_update_channel")?
+                        .as_deref()
+                        .map(UpdateChannel::from_stored),
+                    // Files exported before tags had defaults have no such key
+                    nsfw: match entry.get("default_nsfw") {
+                        None | Some(Value::Null) => false,
+                        Some(value) => value.as_bool()?,
+                    },
                 },
-                left_node,
-                current_mod.name
+            })
+        })
+        .collect()
+}
+
+async fn import_taxonomy(db: &DatabaseConnection, path: &Path) -> crate::Result<()> {
+    let contents = crate::fileio::read_to_string(path)?;
+    let Some(imported) = parse_taxonomy(&contents) else {
+        eprintln!("{} is not a tag taxonomy file", path.display());
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    };
+    if let Some(bad) = imported.iter().find(|t| {
+        t.color
+            .as_deref()
+            .is_some_and(|c| parse_tag_color(c).is_none())
+    }) {
+        eprintln!(
+            "Unknown color for tag {}: {}",
+            bad.tag,
+            bad.color.as_deref().unwrap_or_default()
+        );
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    }
+
+    let txn = db.begin().await?;
+    let existing: HashSet<String> = Tag::find()
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|t| t.tag)
+        .collect();
+
+    let mut ids = HashMap::new();
+    for entry in imported.iter() {
+        let tag_id = get_or_create_tag_id(&txn, &entry.tag).await?;
+        let mut tag_model = Tag::find_by_id(tag_id)
+            .one(&txn)
+            .await?
+            .expect_or_log("Tag was just found or created")
+            .into_active_model();
+        tag_model.explicit = ActiveValue::Set(true);
+        if entry.description.is_some() {
+            tag_model.description = ActiveValue::Set(entry.description.clone());
+        }
+        if entry.color.is_some() {
+            tag_model.color = ActiveValue::Set(entry.color.clone());
+        }
+        if entry.defaults.source_url.is_some() {
+            tag_model.default_source_url = ActiveValue::Set(entry.defaults.source_url.clone());
+        }
+        if let Some(update_channel) = entry.defaults.update_channel {
+            tag_model.default_update_channel =
+                ActiveValue::Set(Some(update_channel.as_str().to_string()));
+        }
+        if entry.defaults.nsfw {
+            tag_model.default_nsfw = ActiveValue::Set(true);
+        }
+        tag_model.update(&txn).await?;
+        ids.insert(entry.tag.clone(), tag_id);
+    }
+
+    let mut parents: HashMap<i32, Option<i32>> = Tag::find()
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t.parent_id))
+        .collect();
+    for entry in imported.iter() {
+        let Some(parent) = &entry.parent else {
+            continue;
+        };
+        let tag_id = ids[&entry.tag];
+        let parent_id = match ids.get(parent) {
+            Some(id) => *id,
+            None => get_or_create_tag_id(&txn, parent).await?,
+        };
+        if creates_cycle(&parents, tag_id, parent_id) {
+            eprintln!(
+                "Skipping parent {} for {}, it would make a cycle",
+                parent, entry.tag
             );
+            continue;
         }
+        Tag::update_many()
+            .col_expr(tag::Column::ParentId, Expr::value(parent_id))
+            .filter(tag::Column::Id.eq(tag_id))
+            .exec(&txn)
+            .await?;
+        parents.insert(tag_id, Some(parent_id));
     }
+    txn.commit().await?;
+
+    let new_count = ids.keys().filter(|t| !existing.contains(*t)).count();
+    println!(
+        "Imported {} tags from {} ({} new)",
+        ids.len(),
+        path.display(),
+        new_count
+    );
     Ok(())
 }
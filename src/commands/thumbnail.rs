@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, IntoActiveModel};
+use tracing::{debug, info};
+
+use crate::commands::JournalAction;
+use crate::entities::*;
+
+fn is_url(thumbnail: &str) -> bool {
+    url::Url::parse(thumbnail)
+        .map(|u| u.scheme() == "http" || u.scheme() == "https")
+        .unwrap_or(false)
+}
+
+/// Downloads a thumbnail into the data directory, returning the saved path
+async fn fetch_thumbnail(mod_id: i32, thumbnail_url: &str) -> crate::Result<PathBuf> {
+    let thumbnail_dir = crate::util::get_data_dir()?.join("thumbnails");
+    if !thumbnail_dir.is_dir() {
+        info!("Creating thumbnail directory");
+        crate::fileio::create_dir_all(&thumbnail_dir)?;
+    }
+    let extension = url::Url::parse(thumbnail_url)?
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .and_then(|f| Path::new(f).extension())
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_else(|| "img".to_string());
+
+    info!("Fetching thumbnail from {}", thumbnail_url);
+    let response = reqwest::get(thumbnail_url).await?.error_for_status()?;
+    let data = response.bytes().await?;
+    let thumbnail_path = thumbnail_dir.join(format!("{}.{}", mod_id, extension));
+    debug!(
+        "Saving {} bytes to {}",
+        data.len(),
+        thumbnail_path.display()
+    );
+    crate::fileio::write(&thumbnail_path, &data)?;
+    Ok(thumbnail_path)
+}
+
+pub async fn thumbnail(
+    mod_id: i32,
+    image: Option<String>,
+    fetch: bool,
+    clear: bool,
+) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let Some(sims_mod) = super::util::active_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(&db)
+        .await?
+    else {
+        eprintln!("No mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+
+    let thumbnail = if clear {
+        None
+    } else {
+        let Some(thumbnail) = image.or_else(|| sims_mod.thumbnail.clone()) else {
+            eprintln!("{} has no thumbnail set", sims_mod.name);
+            return Ok(());
+        };
+        if fetch {
+            if !is_url(&thumbnail) {
+                eprintln!("Only HTTP(S) thumbnails can be fetched");
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+            }
+            let saved = fetch_thumbnail(mod_id, &thumbnail).await?;
+            Some(saved.to_string_lossy().to_string())
+        } else if !is_url(&thumbnail) && !Path::new(&thumbnail).is_file() {
+            eprintln!("Thumbnail file {} does not exist", thumbnail);
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        } else if is_url(&thumbnail) {
+            Some(thumbnail)
+        } else {
+            Some(
+                std::fs::canonicalize(&thumbnail)?
+                    .to_string_lossy()
+                    .to_string(),
+            )
+        }
+    };
+
+    if thumbnail == sims_mod.thumbnail {
+        println!("Thumbnail for {} is unchanged", sims_mod.name.bold());
+        return Ok(());
+    }
+
+    let mut active_model = sims_mod.into_active_model();
+    active_model.thumbnail = ActiveValue::set(thumbnail);
+    active_model.updated = ActiveValue::set(chrono::Utc::now());
+    let saved_model = active_model.update(&db).await?;
+    super::log::record(&db, JournalAction::Edited, &saved_model).await?;
+
+    match &saved_model.thumbnail {
+        Some(thumbnail) => println!(
+            "Thumbnail for {}: {}",
+            saved_model.name.bold(),
+            thumbnail.blue()
+        ),
+        None => println!("Cleared thumbnail for {}", saved_model.name.bold()),
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use colored::Colorize;
+use sea_orm::{prelude::*, ActiveValue, QueryOrder};
+use tracing::debug;
+
+use crate::entities::{prelude::*, *};
+
+/// Actions for files the tool has moved to the system trash
+#[derive(clap::Subcommand, Debug)]
+pub enum TrashAction {
+    /// Lists files and folders moved to the trash, newest first
+    List,
+}
+
+/// Remembers a path moved to the system trash, so `trash list` can show it
+pub async fn record_trashed<C>(
+    db: &C,
+    path: &Path,
+    sims_mod: Option<&sims_mod::Model>,
+) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    debug!("Recording trashed path {}", path.display());
+    let entry = trashed_file::ActiveModel {
+        path: ActiveValue::Set(path.to_string_lossy().to_string()),
+        mod_id: ActiveValue::Set(sims_mod.map(|m| m.id)),
+        mod_name: ActiveValue::Set(sims_mod.map(|m| m.name.clone())),
+        trashed_at: ActiveValue::Set(chrono::Utc::now()),
+        ..Default::default()
+    };
+    TrashedFile::insert(entry).exec(db).await?;
+    Ok(())
+}
+
+/// Moves a path to the trash and records it, or deletes it outright when `permanent` is set
+pub async fn remove_path<C>(
+    db: &C,
+    path: &Path,
+    sims_mod: Option<&sims_mod::Model>,
+    permanent: bool,
+) -> crate::Result<()>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    crate::util::remove_path(path, permanent)?;
+    if !permanent {
+        record_trashed(db, path, sims_mod).await?;
+    }
+    Ok(())
+}
+
+pub async fn trash(action: TrashAction) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    match action {
+        TrashAction::List => {
+            let entries = TrashedFile::find()
+                .order_by_desc(trashed_file::Column::TrashedAt)
+                .all(&db)
+                .await?;
+            if entries.is_empty() {
+                println!("Nothing has been moved to the trash.");
+            }
+            for entry in entries.iter() {
+                let owner = match (&entry.mod_name, entry.mod_id) {
+                    (Some(name), Some(id)) => format!(" {} ({})", name.bold(), id),
+                    _ => String::new(),
+                };
+                // A path that exists again was most likely restored from the trash
+                let status = if Path::new(&entry.path).exists() {
+                    format!(" {}", "(restored)".green())
+                } else {
+                    String::new()
+                };
+                println!(
+                    "{}{} {}{}",
+                    crate::datetime::format_datetime(&entry.trashed_at),
+                    owner,
+                    entry.path.dimmed(),
+                    status
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+/// How `fetch --all` may update a mod
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UpdateChannel {
+    /// Never downloaded by `fetch --all`, and fetching it alone asks first
+    Manual,
+    /// New downloads are reported by `fetch --all`, but not kept
+    #[default]
+    Notify,
+    /// New downloads are staged by `fetch --all`, and installed with --install
+    AutoFetch,
+}
+
+impl UpdateChannel {
+    /// Reads the channel stored for a mod, treating unknown values as the default
+    pub fn from_stored(value: &str) -> UpdateChannel {
+        <UpdateChannel as clap::ValueEnum>::from_str(value, true).unwrap_or_default()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateChannel::Manual => "manual",
+            UpdateChannel::Notify => "notify",
+            UpdateChannel::AutoFetch => "auto-fetch",
+        }
+    }
+}
+
+impl std::fmt::Display for UpdateChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -1,5 +1,8 @@
 use crate::entities::{prelude::*, *};
+use crate::s4ignore::IgnoreFile;
+use crate::scanner::ScanOptions;
 use crate::Result as CrateResult;
+use colored::Colorize;
 use inquire::autocompletion::Replacement;
 use inquire::error::InquireResult;
 use inquire::CustomUserError;
@@ -8,18 +11,17 @@ use radix_trie::TrieCommon;
 use sea_orm::{prelude::*, *};
 use std::{
     collections::{HashMap, HashSet},
-    ffi::OsString,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
-use tracing::debug;
-use tracing_unwrap::OptionExt;
-use xxhash_rust::xxh3::xxh3_64;
+use tracing::{debug, warn};
 
 pub struct VerificationValues {
     pub new_files: HashMap<PathBuf, String>,
     pub missing_files: Vec<PathBuf>,
     pub matching_files: Vec<PathBuf>,
     pub changed_files: HashMap<PathBuf, String>,
+    pub stored_hashes: HashMap<PathBuf, String>,
+    pub directory_missing: bool,
 }
 
 pub trait VerificationPassed {
@@ -28,68 +30,259 @@ pub trait VerificationPassed {
 
 impl VerificationPassed for VerificationValues {
     fn verification_passed(&self) -> bool {
-        self.new_files.is_empty() && self.missing_files.is_empty() && self.changed_files.is_empty()
+        self.new_files.is_empty()
+            && self.missing_files.is_empty()
+            && self.changed_files.is_empty()
+            && !self.directory_missing
+    }
+}
+
+/// Name of the main Mods folder when listed alongside other roots
+pub const MAIN_ROOT_NAME: &str = "Mods";
+
+/// Directory of the built-in entry tracking the files directly in the main Mods folder,
+/// such as Resource.cfg and loose packages that no mod folder owns
+pub const ROOT_ENTRY_DIRECTORY: &str = ".";
+
+/// Name of the built-in entry for the main Mods folder's own files
+pub const ROOT_ENTRY_NAME: &str = "Mods folder (loose files)";
+
+/// Whether a mod entry is the built-in entry for the main Mods folder's own files.
+/// Its folder is the whole Mods folder, so it must never be moved or removed.
+pub fn is_root_entry(sims_mod: &sims_mod::Model) -> bool {
+    sims_mod.root_id.is_none() && sims_mod.directory == ROOT_ENTRY_DIRECTORY
+}
+
+/// Folders holding mods: the main Mods folder, which has no root ID,
+/// and any roots added with `roots add`
+pub struct ModRoots {
+    roots: Vec<(Option<i32>, String, PathBuf)>,
+}
+
+impl ModRoots {
+    pub async fn load<C>(db: &C) -> CrateResult<ModRoots>
+    where
+        C: sea_orm::ConnectionTrait,
+    {
+        let mut roots = vec![(
+            None,
+            MAIN_ROOT_NAME.to_string(),
+            crate::util::get_sims_mod_dir()?,
+        )];
+        roots.extend(
+            ModRoot::find()
+                .order_by_asc(mod_root::Column::Name)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|root| (Some(root.id), root.name, PathBuf::from(root.path))),
+        );
+        Ok(ModRoots { roots })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Option<i32>, &str, &Path)> {
+        self.roots
+            .iter()
+            .map(|(id, name, path)| (*id, name.as_str(), path.as_path()))
+    }
+
+    pub fn dir(&self, root_id: Option<i32>) -> CrateResult<&Path> {
+        match self.iter().find(|(id, _, _)| *id == root_id) {
+            Some((_, _, path)) => Ok(path),
+            None => {
+                eprintln!("Mod root {} no longer exists", root_id.unwrap_or_default());
+                Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+            }
+        }
+    }
+
+    /// A mod's folder on disk
+    pub fn mod_dir(&self, sims_mod: &sims_mod::Model) -> CrateResult<PathBuf> {
+        Ok(self.dir(sims_mod.root_id)?.join(&sims_mod.directory))
+    }
+
+    /// A directory for display, prefixed with its root's name unless it is in the main Mods folder
+    pub fn display_path(&self, root_id: Option<i32>, path: &Path) -> String {
+        match self
+            .iter()
+            .find(|(id, _, _)| id.is_some() && *id == root_id)
+        {
+            Some((_, name, _)) => format!("[{}] {}", name, path.display()),
+            None => path.display().to_string(),
+        }
+    }
+}
+
+/// Which files are tracked in a mod's folder
+fn tracked_options(mod_path: &Path, extra_extensions: &[String]) -> ScanOptions {
+    if mod_path == Path::new(ROOT_ENTRY_DIRECTORY) {
+        ScanOptions::root_files()
+    } else {
+        ScanOptions::tracked().with_extensions(extra_extensions)
     }
 }
 
 pub fn get_file_hashes(
-    mod_path: &PathBuf,
+    root: &Path,
+    mod_path: &Path,
+) -> CrateResult<(HashSet<PathBuf>, HashMap<PathBuf, String>)> {
+    get_file_hashes_with(root, mod_path, &[])
+}
+
+/// Hashes a mod's files, also tracking files with the given extensions, as the
+/// `tag_extensions` setting asks for the mod's tags
+pub fn get_file_hashes_with(
+    root: &Path,
+    mod_path: &Path,
+    extra_extensions: &[String],
 ) -> CrateResult<(HashSet<PathBuf>, HashMap<PathBuf, String>)> {
-    let final_mod_path = crate::util::get_sims_mod_dir()?.join(mod_path);
+    let final_mod_path = root.join(mod_path);
     debug!("Scanning files in {}", mod_path.display());
-    let mut files: Vec<PathBuf> = final_mod_path
-        .read_dir()?
-        .map(|entry| -> CrateResult<PathBuf> {
-            let entry = entry?;
-            Ok(entry.path())
-        })
-        .collect::<Result<_, _>>()?;
-    let current_packages: HashSet<_> = files
-        .drain(..)
-        .filter(|path| {
-            if path.is_file()
-                && (path.extension() == Some(&OsString::from("package"))
-                    || path.extension() == Some(&OsString::from("ts4script")))
-            {
-                true
+    let options = tracked_options(mod_path, extra_extensions);
+    let found_files: Vec<PathBuf> = crate::scanner::find_files(root, mod_path, &options)?
+        .into_iter()
+        .map(|file| file.path)
+        .collect();
+
+    debug!("Gathering checksums for {} files", found_files.len());
+
+    let results = crate::hasher::hash_files(&final_mod_path, &found_files);
+    let mut current_packages = HashSet::new();
+    let mut hashes = HashMap::new();
+    for (file, result) in found_files.into_iter().zip(results) {
+        // Files can vanish or be locked between listing and reading
+        match result {
+            Ok(hash) => {
+                hashes.insert(file.clone(), hash);
+                current_packages.insert(file);
+            }
+            Err(e) => warn!("Skipping {}: {}", file.display(), e),
+        }
+    }
+    Ok((current_packages, hashes))
+}
+
+/// A mod folder path for comparisons, lowercased when folder names ignore case
+pub fn directory_key(path: &Path) -> PathBuf {
+    if crate::config::get().ignore_directory_case() {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Whether two mod folder paths name the same folder
+pub fn same_directory(a: &Path, b: &Path) -> bool {
+    a == b || directory_key(a) == directory_key(b)
+}
+
+/// Suffix added to files disabled by `postpatch --disable` or `purge-creator`
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// Name of a mod file once it is disabled
+pub fn disabled_name(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_os_string();
+    name.push(DISABLED_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Files of a mod with one of the given extensions, optionally only disabled ones
+fn game_files(mod_dir: &Path, extensions: &[&str], disabled: bool) -> CrateResult<Vec<PathBuf>> {
+    let suffixes: Vec<String> = extensions
+        .iter()
+        .map(|extension| {
+            if disabled {
+                format!(".{}{}", extension, DISABLED_SUFFIX)
             } else {
-                false
+                format!(".{}", extension)
             }
         })
-        .map(|path| PathBuf::from(path.file_name().expect_or_log("Path is invalid!")))
         .collect();
+    let mut files = Vec::new();
+    for entry in crate::fileio::read_dir(mod_dir)? {
+        let path = entry?.path();
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase());
+        if path.is_file() && name.is_some_and(|n| suffixes.iter().any(|s| n.ends_with(s))) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
 
-    debug!("Gathering checksums for {} files", current_packages.len());
+/// How many tracked files a mod's folder holds, counting disabled ones, without hashing
+/// them. A missing folder holds none.
+pub fn count_mod_files(
+    root: &Path,
+    mod_path: &Path,
+    extra_extensions: &[String],
+) -> CrateResult<usize> {
+    let mod_dir = root.join(mod_path);
+    if !mod_dir.is_dir() {
+        return Ok(0);
+    }
+    let options = tracked_options(mod_path, extra_extensions);
+    let found = crate::scanner::find_files(root, mod_path, &options)?.len();
+    let disabled = game_files(&mod_dir, crate::scanner::MOD_EXTENSIONS, true)?.len();
+    Ok(found + disabled)
+}
 
-    let hashes = current_packages
-        .iter()
-        .map(|file_path| -> CrateResult<_> {
-            debug!("Generating checksum for {}", file_path.display());
-            let mod_file_path = final_mod_path.join(file_path);
-            let mod_file_data = std::fs::read(mod_file_path)?;
-            Ok((
-                file_path.clone(),
-                format!("{:10X}", xxh3_64(&mod_file_data)),
-            ))
-        })
-        .collect::<Result<_, _>>()?;
-    Ok((current_packages, hashes))
+/// Renames a mod's files with the given extensions so the game skips them, or back
+/// again, returning how many were renamed
+pub fn set_files_enabled(mod_dir: &Path, extensions: &[&str], enabled: bool) -> CrateResult<usize> {
+    let files = game_files(mod_dir, extensions, enabled)?;
+    crate::transfer::run(if enabled { "enable" } else { "disable" }, |transfer| {
+        for file in files.iter() {
+            let target = if enabled {
+                let name = file.to_string_lossy();
+                PathBuf::from(&name[..name.len() - DISABLED_SUFFIX.len()])
+            } else {
+                disabled_name(file)
+            };
+            debug!("Renaming {} to {}", file.display(), target.display());
+            transfer.move_path(file, &target)?;
+        }
+        Ok(())
+    })?;
+    Ok(files.len())
 }
 
 pub fn verify_files(
-    mod_path: &PathBuf,
+    root: &Path,
+    mod_path: &Path,
     hashes: &HashMap<PathBuf, String>,
+    extra_extensions: &[String],
 ) -> CrateResult<VerificationValues> {
     debug!("Verifying mod_path {}", mod_path.display());
 
-    let (current_packages, package_hashes) = get_file_hashes(mod_path)?;
+    if !root.join(mod_path).is_dir() {
+        debug!("Mod directory is missing");
+        return Ok(VerificationValues {
+            new_files: HashMap::new(),
+            missing_files: hashes.keys().cloned().collect(),
+            matching_files: Vec::new(),
+            changed_files: HashMap::new(),
+            stored_hashes: hashes.clone(),
+            directory_missing: true,
+        });
+    }
+
+    let (current_packages, package_hashes) =
+        get_file_hashes_with(root, mod_path, extra_extensions)?;
 
     debug!("Sorting verification statuses");
-    let db_file_list: HashSet<_> = hashes.keys().map(|k| k.clone()).collect();
+    // Files recorded before they were added to `.s4ignore` are no longer checked
+    let ignore_file = IgnoreFile::load(&root.join(mod_path))?;
+    let db_file_list: HashSet<_> = hashes
+        .keys()
+        .filter(|file| !ignore_file.is_ignored(file, false))
+        .cloned()
+        .collect();
 
+    // Disabled files are still installed
     let missing_files: Vec<PathBuf> = db_file_list
         .difference(&current_packages)
-        .map(|k| k.clone())
+        .filter(|file| !root.join(mod_path).join(disabled_name(file)).is_file())
+        .cloned()
         .collect();
     let new_files: HashSet<_> = current_packages.difference(&db_file_list).collect();
     let new_files: HashMap<PathBuf, String> = package_hashes
@@ -129,9 +322,310 @@ pub fn verify_files(
         missing_files,
         matching_files,
         changed_files,
+        stored_hashes: hashes.clone(),
+        directory_missing: false,
     })
 }
 
+/// Extensions the `tag_extensions` setting tracks for a mod's tags, besides the game's
+/// own files
+pub async fn tracked_extensions<C>(db: &C, mod_id: i32) -> CrateResult<Vec<String>>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let config = crate::config::get();
+    if !config.has_tag_extensions() {
+        return Ok(Vec::new());
+    }
+    Ok(config.tag_extensions(&get_tags_for_mod(db, mod_id).await?))
+}
+
+/// Hashes an existing mod's files, tracking the extensions its tags ask for
+pub async fn get_mod_file_hashes<C>(
+    db: &C,
+    root: &Path,
+    sims_mod: &sims_mod::Model,
+) -> CrateResult<(HashSet<PathBuf>, HashMap<PathBuf, String>)>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    get_file_hashes_with(
+        root,
+        Path::new(&sims_mod.directory),
+        &tracked_extensions(db, sims_mod.id).await?,
+    )
+}
+
+/// Verifies a mod's files against the hashes stored in the database
+pub async fn verify_mod<C>(db: &C, sims_mod: &sims_mod::Model) -> CrateResult<VerificationValues>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let hashes = get_hashes_for_mod(db, sims_mod.id).await?;
+    let roots = ModRoots::load(db).await?;
+    let mut results = verify_files(
+        roots.dir(sims_mod.root_id)?,
+        Path::new(&sims_mod.directory),
+        &hashes,
+        &tracked_extensions(db, sims_mod.id).await?,
+    )?;
+    // Duplicates of another mod's files that were kept during a scan are not tracked.
+    // Hardlinked ones share the original's contents, so they also match its current hash.
+    let kept = KeptDuplicate::find()
+        .filter(kept_duplicate::Column::ModId.eq(sims_mod.id))
+        .all(db)
+        .await?;
+    let mut accepted: Vec<(PathBuf, String)> = Vec::new();
+    for k in kept.into_iter() {
+        if let (Some(linked_mod_id), Some(linked_file)) = (k.linked_mod_id, &k.linked_file) {
+            if let Some(original) = ModHash::find()
+                .filter(mod_hash::Column::ModId.eq(linked_mod_id))
+                .filter(mod_hash::Column::File.eq(linked_file))
+                .one(db)
+                .await?
+            {
+                accepted.push((PathBuf::from(&k.file), original.hash));
+            }
+        }
+        accepted.push((PathBuf::from(k.file), k.hash));
+    }
+    results.new_files.retain(|file, hash| {
+        !accepted
+            .iter()
+            .any(|(kept_file, kept_hash)| kept_file == file && kept_hash == hash)
+    });
+    Ok(results)
+}
+
+/// Name fragments of large script frameworks that other mods depend on.
+/// Compared against directory and file names with punctuation and case removed.
+const FRAMEWORK_MOD_PATTERNS: &[&str] = &[
+    "mccc",
+    "mccmdcenter",
+    "mccommandcenter",
+    "xmlinjector",
+    "basementalcc",
+    "uicheats",
+];
+
+fn normalize_mod_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Guesses whether a mod is a script framework that should be protected by default.
+pub fn is_framework_mod<'a, I>(mod_path: &Path, files: I) -> bool
+where
+    I: IntoIterator<Item = &'a PathBuf>,
+{
+    std::iter::once(mod_path)
+        .chain(files.into_iter().map(|f| f.as_path()))
+        .map(|p| normalize_mod_name(&p.to_string_lossy()))
+        .any(|name| FRAMEWORK_MOD_PATTERNS.iter().any(|p| name.contains(p)))
+}
+
+/// Whether a scan should skip hashing a mod, because it is frozen itself
+/// or has one of the configured frozen tags
+pub async fn is_frozen<C>(
+    db: &C,
+    sims_mod: &sims_mod::Model,
+    frozen_tags: &[String],
+) -> CrateResult<bool>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    if sims_mod.frozen {
+        return Ok(true);
+    }
+    if frozen_tags.is_empty() {
+        return Ok(false);
+    }
+    Ok(get_tags_for_mod(db, sims_mod.id)
+        .await?
+        .iter()
+        .any(|tag| frozen_tags.iter().any(|f| f.eq_ignore_ascii_case(tag))))
+}
+
+/// Asks the user to type a protected mod's name before a destructive action.
+pub fn confirm_protected_mod(name: &str, action: &str) -> InquireResult<bool> {
+    let typed = inquire::Text::new(
+        format!("{} is protected. Type its name to {} it:", name, action).as_str(),
+    )
+    .with_help_message("Press ESC to cancel")
+    .prompt_skippable()?;
+    Ok(typed.map(|t| t.trim() == name).unwrap_or(false))
+}
+
+/// Installed addons and translations linked to a mod
+pub async fn get_addons<C>(db: &C, mod_id: i32) -> Result<Vec<sims_mod::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    active_mods()
+        .filter(sims_mod::Column::ParentModId.eq(mod_id))
+        .order_by_asc(sims_mod::Column::Name)
+        .all(db)
+        .await
+}
+
+/// Warns that a mod's addons stop working without it, and asks whether to go on.
+/// Returns true right away for mods without addons.
+pub async fn confirm_parent_mod(
+    db: &DatabaseConnection,
+    sims_mod: &sims_mod::Model,
+    action: &str,
+) -> CrateResult<bool> {
+    let addons = get_addons(db, sims_mod.id).await?;
+    if addons.is_empty() {
+        return Ok(true);
+    }
+    println!(
+        "{} has {} addons that need it:",
+        sims_mod.name.bold(),
+        addons.len()
+    );
+    for addon in addons.iter() {
+        println!("  {} ({})", addon.name, addon.id);
+    }
+    Ok(
+        inquire::Confirm::new(format!("Still {} {}?", action, sims_mod.name).as_str())
+            .with_default(false)
+            .prompt()?,
+    )
+}
+
+/// Checks that a mod can be linked to a parent: the parent must be installed,
+/// and the link must not make a mod its own ancestor
+pub async fn validate_parent<C>(db: &C, mod_id: i32, parent_id: i32) -> CrateResult<()>
+where
+    C: ConnectionTrait,
+{
+    let mut ancestor_id = Some(parent_id);
+    let mut seen = HashSet::new();
+    while let Some(id) = ancestor_id {
+        if id == mod_id {
+            eprintln!(
+                "Mod {} cannot be an addon of itself or of its own addons!",
+                mod_id
+            );
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput).into());
+        }
+        if !seen.insert(id) {
+            break;
+        }
+        let Some(ancestor) = active_mods()
+            .filter(sims_mod::Column::Id.eq(id))
+            .one(db)
+            .await?
+        else {
+            eprintln!("No mod with mod ID {} found!", id);
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        };
+        ancestor_id = ancestor.parent_mod_id;
+    }
+    Ok(())
+}
+
+/// Parses a `YYYY-MM-DD` date given on the command line
+pub fn parse_date(input: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map_err(|e| format!("{} (expected YYYY-MM-DD)", e))
+}
+
+/// Parses an age such as `30d`, `6w`, `3m`, or `1y` given on the command line.
+/// Months count as 30 days and years as 365.
+pub fn parse_age(input: &str) -> Result<chrono::Duration, String> {
+    let input = input.trim();
+    let split = input.len() - input.chars().last().map_or(0, |c| c.len_utf8());
+    let (count, unit) = input.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("Invalid age {} (expected e.g. 30d, 6w, 3m, or 1y)", input))?;
+    let days = match unit {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        "y" => 365,
+        _ => {
+            return Err(format!(
+                "Unknown unit in {} (expected d, w, m, or y)",
+                input
+            ))
+        }
+    };
+    Ok(chrono::Duration::days(count * days))
+}
+
+/// Formats a 1-5 rating as stars
+pub fn format_rating(rating: Option<i32>) -> String {
+    match rating {
+        Some(rating) => {
+            let rating = rating.clamp(0, 5) as usize;
+            format!("{}{}", "★".repeat(rating), "☆".repeat(5 - rating))
+        }
+        None => "unrated".to_string(),
+    }
+}
+
+/// Formats the "would reinstall" flag, which is unset until decided
+pub fn format_would_reinstall(would_reinstall: Option<bool>) -> &'static str {
+    match would_reinstall {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "undecided",
+    }
+}
+
+pub fn format_size(size: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Prompts for an optional date. An empty answer means no date.
+pub fn prompt_optional_date(
+    message: &str,
+    current: Option<chrono::NaiveDate>,
+) -> InquireResult<Option<chrono::NaiveDate>> {
+    let current = current.map(|d| d.format("%Y-%m-%d").to_string());
+    let prompt = inquire::Text::new(message)
+        .with_help_message("YYYY-MM-DD, or empty for none")
+        .with_validator(|input: &str| {
+            let input = input.trim();
+            if input.is_empty() || parse_date(input).is_ok() {
+                Ok(inquire::validator::Validation::Valid)
+            } else {
+                Ok(inquire::validator::Validation::Invalid(
+                    "Expected a YYYY-MM-DD date".into(),
+                ))
+            }
+        });
+    let answer = if let Some(current) = &current {
+        prompt.with_initial_value(current).prompt()?
+    } else {
+        prompt.prompt()?
+    };
+    Ok(parse_date(answer.trim()).ok())
+}
+
+/// Selects mods whose recheck date has passed
+pub fn mods_due_for_recheck() -> Select<SimsMod> {
+    active_mods()
+        .filter(sims_mod::Column::RecheckAfter.lte(crate::datetime::today()))
+        .order_by_asc(sims_mod::Column::RecheckAfter)
+}
+
 #[derive(Clone)]
 pub struct URLValidator {}
 
@@ -206,6 +700,86 @@ where
     }
 }
 
+/// Selects mods that have not been soft-deleted
+pub fn active_mods() -> Select<SimsMod> {
+    SimsMod::find().filter(sims_mod::Column::DeletedAt.is_null())
+}
+
+/// Selects active mods, leaving out those flagged as NSFW in SFW mode
+pub fn visible_mods() -> Select<SimsMod> {
+    if crate::config::get().sfw() {
+        active_mods().filter(sims_mod::Column::Nsfw.eq(false))
+    } else {
+        active_mods()
+    }
+}
+
+/// Whether a mod is hidden because it is flagged as NSFW and SFW mode is on
+pub fn is_hidden(sims_mod: &sims_mod::Model) -> bool {
+    sims_mod.nsfw && crate::config::get().sfw()
+}
+
+/// Soft-deletes a mod's database entry, journaling it under `action` and clearing its hashes
+pub async fn delete_mod_entry(
+    db: &DatabaseConnection,
+    sims_mod: &sims_mod::Model,
+    action: super::JournalAction,
+) -> CrateResult<()> {
+    delete_mod_entries(db, std::slice::from_ref(sims_mod), action).await
+}
+
+/// Soft-deletes several mods in one transaction, so either all or none are removed.
+/// Their stored hashes are kept, so a restored or relinked mod can be compared against them.
+pub async fn delete_mod_entries(
+    db: &DatabaseConnection,
+    sims_mods: &[sims_mod::Model],
+    action: super::JournalAction,
+) -> CrateResult<()> {
+    let now = chrono::Utc::now();
+    let deleted_models: Vec<_> = sims_mods
+        .iter()
+        .map(|sims_mod| {
+            let mut deleted_model = sims_mod.clone().into_active_model();
+            deleted_model.deleted_at = ActiveValue::Set(Some(now));
+            deleted_model
+        })
+        .collect();
+    db.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            for deleted_model in deleted_models {
+                let deleted_mod = deleted_model.update(txn).await?;
+                super::log::record(txn, action, &deleted_mod).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Selects mods that have been soft-deleted
+pub fn deleted_mods() -> Select<SimsMod> {
+    SimsMod::find().filter(sims_mod::Column::DeletedAt.is_not_null())
+}
+
+/// Selects the stored hashes of mods that have not been deleted
+pub fn active_mod_hashes() -> Select<ModHash> {
+    ModHash::find()
+        .join(JoinType::InnerJoin, mod_hash::Relation::SimsMod.def())
+        .filter(sims_mod::Column::DeletedAt.is_null())
+}
+
+pub async fn get_mods_for_tag_id<C>(db: &C, tag_id: i32) -> Result<Vec<sims_mod::Model>, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    active_mods()
+        .inner_join(Tag)
+        .filter(tag::Column::Id.eq(tag_id))
+        .all(db)
+        .await
+}
+
 pub async fn get_mods_for_tags<C>(
     db: &C,
     mut tags: Vec<String>,
@@ -234,7 +808,40 @@ where
         .fold(Condition::any(), |c, r| {
             c.add(sims_mod::Column::Id.eq(r.mod_id))
         });
-    Ok(SimsMod::find().filter(tag_relations_cond).all(db).await?)
+    Ok(active_mods().filter(tag_relations_cond).all(db).await?)
+}
+
+/// Fetches active mods matching a tag filter, applying any saved query it names
+pub async fn get_mods_for_filter<C>(
+    db: &C,
+    filter: super::TagFilter,
+) -> CrateResult<Vec<crate::entities::sims_mod::Model>>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    let filter = filter.resolve(db).await?;
+    let mut mods = if let Some(tags) = filter.tags.clone() {
+        get_mods_for_tags(db, tags).await?
+    } else {
+        active_mods().all(db).await?
+    };
+    mods.retain(|sims_mod| filter.matches_flags(sims_mod));
+    if filter.all_tags.is_none() && filter.exclude_tags.is_none() {
+        return Ok(mods);
+    }
+
+    let all_tags = filter.all_tags.unwrap_or_default();
+    let exclude_tags = filter.exclude_tags.unwrap_or_default();
+    let mut matching = Vec::new();
+    for sims_mod in mods.drain(..) {
+        let mod_tags = get_tags_for_mod(db, sims_mod.id).await?;
+        if all_tags.iter().all(|t| mod_tags.contains(t))
+            && !exclude_tags.iter().any(|t| mod_tags.contains(t))
+        {
+            matching.push(sims_mod);
+        }
+    }
+    Ok(matching)
 }
 
 pub async fn get_tags_for_mod<C>(db: &C, mod_id: i32) -> CrateResult<Vec<String>>
@@ -269,6 +876,66 @@ where
         .collect())
 }
 
+pub async fn get_required_packs_for_mod<C>(db: &C, mod_id: i32) -> Result<Vec<String>, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    Ok(ModRequiredPack::find()
+        .filter(mod_required_pack::Column::ModId.eq(mod_id))
+        .order_by_asc(mod_required_pack::Column::Pack)
+        .all(db)
+        .await?
+        .drain(..)
+        .map(|p| p.pack)
+        .collect())
+}
+
+/// Replaces the packs a mod requires
+pub async fn set_required_packs<C>(db: &C, mod_id: i32, packs: &[String]) -> Result<(), DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    ModRequiredPack::delete_many()
+        .filter(mod_required_pack::Column::ModId.eq(mod_id))
+        .exec(db)
+        .await?;
+    for pack in packs
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .unique_by(|p| p.to_lowercase())
+    {
+        debug!("Adding required pack {} for mod {}", pack, mod_id);
+        let new_pack = mod_required_pack::ActiveModel {
+            mod_id: ActiveValue::Set(mod_id),
+            pack: ActiveValue::Set(pack.to_string()),
+        };
+        ModRequiredPack::insert(new_pack).exec(db).await?;
+    }
+    Ok(())
+}
+
+/// Returns the user's owned packs, lowercased for comparison
+pub async fn get_owned_packs<C>(db: &C) -> Result<HashSet<String>, DbErr>
+where
+    C: sea_orm::ConnectionTrait,
+{
+    Ok(OwnedPack::find()
+        .all(db)
+        .await?
+        .drain(..)
+        .map(|p| p.pack.to_lowercase())
+        .collect())
+}
+
+pub fn get_missing_packs(required: &[String], owned: &HashSet<String>) -> Vec<String> {
+    required
+        .iter()
+        .filter(|p| !owned.contains(&p.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
 enum QueryAs {
     TagId,
@@ -287,7 +954,20 @@ where
         .await?
         .drain(..)
         .unique()
-        .fold::<Condition, _>(Condition::all(), |c, i| c.add(tag::Column::Id.ne(i)));
+        .fold::<Condition, _>(Condition::all(), |c, i| c.add(tag::Column::Id.ne(i)))
+        .add(tag::Column::Explicit.eq(false));
+    // Parents in the taxonomy are kept even if only their children are in use
+    let parent_ids = Tag::find()
+        .select_only()
+        .column(tag::Column::ParentId)
+        .filter(tag::Column::ParentId.is_not_null())
+        .into_tuple::<i32>()
+        .all(db)
+        .await?;
+    let unused_tags_cond = parent_ids
+        .into_iter()
+        .unique()
+        .fold(unused_tags_cond, |c, i| c.add(tag::Column::Id.ne(i)));
     let result = Tag::delete_many().filter(unused_tags_cond).exec(db).await?;
     debug!("Deleted {} tags", result.rows_affected);
     Ok(())
@@ -315,7 +995,7 @@ impl TagAutoComplete {
 
     pub async fn create_with_exclusions<C>(
         db: &C,
-        exclusions: &Vec<String>,
+        exclusions: &[String],
     ) -> Result<TagAutoComplete, DbErr>
     where
         C: sea_orm::ConnectionTrait,
@@ -349,7 +1029,7 @@ impl inquire::Autocomplete for TagAutoComplete {
     // Required methods
     fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
         if let Some(suggestions) = self.tag_trie.subtrie(input) {
-            return Ok(suggestions.keys().map(|k| k.clone()).collect());
+            return Ok(suggestions.keys().cloned().collect());
         }
         Ok(vec![])
     }
@@ -364,7 +1044,7 @@ impl inquire::Autocomplete for TagAutoComplete {
         } else {
             let suggestions = self.get_suggestions(input)?;
             if !suggestions.is_empty() {
-                Ok(suggestions.first().map(|s| s.clone()))
+                Ok(suggestions.first().cloned())
             } else {
                 Ok(None)
             }
@@ -0,0 +1,77 @@
+use colored::Colorize;
+use sea_orm::prelude::*;
+use tracing::debug;
+
+use crate::commands::util::VerificationPassed;
+use crate::entities::*;
+
+/// Verifies installed mods, returning whether every checked mod passed
+pub async fn verify(
+    mod_id: Option<i32>,
+    filter: super::TagFilter,
+    fail_fast: bool,
+) -> crate::Result<bool> {
+    let db = crate::util::open_database().await?;
+
+    let mods = if let Some(mod_id) = mod_id {
+        super::util::active_mods()
+            .filter(sims_mod::Column::Id.eq(mod_id))
+            .all(&db)
+            .await?
+    } else if !filter.is_empty() {
+        super::util::get_mods_for_filter(&db, filter).await?
+    } else {
+        super::util::active_mods().all(&db).await?
+    };
+
+    if mods.is_empty() {
+        eprintln!("No matching mods found!");
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    }
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for sims_mod in mods.iter() {
+        debug!("Verifying {}", sims_mod.name);
+        let results = super::util::verify_mod(&db, sims_mod).await?;
+        if results.verification_passed() {
+            passed += 1;
+            println!("Validated mod: {}", sims_mod.name.bold().green());
+            continue;
+        }
+
+        failed += 1;
+        if results.directory_missing {
+            println!(
+                "Failed mod:    {} (directory {} is missing)",
+                sims_mod.name.bold().red(),
+                sims_mod.directory
+            );
+        } else {
+            println!(
+                "Failed mod:    {} ({} matching, {} missing, {} new, {} changed)",
+                sims_mod.name.bold().red(),
+                results.matching_files.len(),
+                results.missing_files.len(),
+                results.new_files.len(),
+                results.changed_files.len()
+            );
+        }
+        if fail_fast {
+            break;
+        }
+    }
+
+    let skipped = mods.len() - passed - failed;
+    println!(
+        "{} passed, {} failed{}",
+        passed.to_string().green().bold(),
+        failed.to_string().red().bold(),
+        if skipped > 0 {
+            format!(", {} skipped", skipped)
+        } else {
+            String::new()
+        }
+    );
+    Ok(failed == 0)
+}
@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use itertools::Itertools;
+use sea_orm::prelude::*;
+use tracing::debug;
+
+use crate::commands::util::{VerificationPassed, VerificationValues};
+use crate::entities::{prelude::*, *};
+use crate::scanner::ScanOptions;
+
+/// Extensions of files mods write their settings and logs to
+const SETTINGS_EXTENSIONS: &[&str] = &["cfg", "ini", "json", "log", "txt"];
+
+/// A likely cause of a failed verification, with what to do about it
+struct Explanation {
+    cause: String,
+    remedy: String,
+    command: Option<String>,
+}
+
+/// Settings files in a mod's folder written after the mod was last updated. They are
+/// not verified, but show that the mod writes to its own folder.
+fn recent_settings_files(root: &Path, sims_mod: &sims_mod::Model) -> crate::Result<Vec<PathBuf>> {
+    let mod_path = Path::new(&sims_mod.directory);
+    let updated: std::time::SystemTime = sims_mod.updated.into();
+    Ok(
+        crate::scanner::find_files(root, mod_path, &ScanOptions::all_files())?
+            .into_iter()
+            .map(|file| file.path)
+            .filter(|file| {
+                file.extension().is_some_and(|e| {
+                    SETTINGS_EXTENSIONS
+                        .iter()
+                        .any(|x| e.eq_ignore_ascii_case(x))
+                })
+            })
+            .filter(|file| {
+                root.join(mod_path)
+                    .join(file)
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| modified > updated)
+            })
+            .collect(),
+    )
+}
+
+/// Picks the most likely cause from the shape of the differences
+fn explain(
+    sims_mod: &sims_mod::Model,
+    results: &VerificationValues,
+    trashed: &[trashed_file::Model],
+    settings_files: &[PathBuf],
+) -> Explanation {
+    let mod_id = sims_mod.id;
+    let stored = results.stored_hashes.len();
+    let missing = results.missing_files.len();
+    let new = results.new_files.len();
+    let changed = results.changed_files.len();
+    if results.directory_missing {
+        return Explanation {
+            cause: "The mod's folder is gone: it was moved, renamed, or deleted.".to_string(),
+            remedy: "A scan offers to relink a moved folder, or to remove the entry.".to_string(),
+            command: Some("scan --fix".to_string()),
+        };
+    }
+    if !trashed.is_empty() && trashed.len() == missing {
+        return Explanation {
+            cause: format!(
+                "The missing files were moved to the trash by sims4modorganizer on {}.",
+                crate::datetime::format_datetime(&trashed[0].trashed_at)
+            ),
+            remedy: "Restore them from the system trash, or accept their removal.".to_string(),
+            command: Some(format!("scan --sync-hashes --mod-id {}", mod_id)),
+        };
+    }
+    if stored > 0 && (changed == stored || (missing == stored && new >= stored)) {
+        // New files beyond those replacing the stored ones were added on top
+        let added = if changed == stored { new } else { new - stored };
+        let all_changed = if stored == 1 {
+            "The mod's only stored file changed".to_string()
+        } else {
+            format!("All {} stored files changed", stored)
+        };
+        let added = match added {
+            0 => String::new(),
+            1 => " and 1 file was added".to_string(),
+            added => format!(" and {} files were added", added),
+        };
+        return Explanation {
+            cause: format!(
+                "{}{}, as happens when the creator releases an update or a batch fix tool \
+                 rewrites the mod's packages.",
+                all_changed, added
+            ),
+            remedy: "Record the update with new metadata, such as the version.".to_string(),
+            command: Some(format!("scan --verify --fix --mod-id {}", mod_id)),
+        };
+    }
+    if missing == 1 && new == 0 && changed == 0 {
+        return Explanation {
+            cause: format!(
+                "Only {} is missing, most likely deleted by hand.",
+                results.missing_files[0].display()
+            ),
+            remedy: "Reinstall the file, or accept its removal if it was deleted on purpose."
+                .to_string(),
+            command: Some(format!("scan --sync-hashes --mod-id {}", mod_id)),
+        };
+    }
+    if new > 0 && missing == 0 && changed == 0 && !settings_files.is_empty() {
+        return Explanation {
+            cause: "New files appeared next to settings the mod wrote while the game ran."
+                .to_string(),
+            remedy: format!(
+                "If the mod generates these files, list them in the mod's {} so scans skip them.",
+                crate::s4ignore::IGNORE_FILE
+            ),
+            command: None,
+        };
+    }
+    if new > 0 && missing == 0 && changed == 0 {
+        return Explanation {
+            cause: "Files were added: an update with extra files, or another mod dropped into \
+                    this folder."
+                .to_string(),
+            remedy: "Record them if they belong to this mod, or move them to their own folder."
+                .to_string(),
+            command: Some(format!("scan --verify --fix --mod-id {}", mod_id)),
+        };
+    }
+    if missing > 0 && missing == new && changed == 0 {
+        return Explanation {
+            cause: "Files were renamed, as creators often do when they release an update."
+                .to_string(),
+            remedy: "Record the update with new metadata, such as the version.".to_string(),
+            command: Some(format!("scan --verify --fix --mod-id {}", mod_id)),
+        };
+    }
+    if changed > 0 && missing == 0 && new == 0 {
+        return Explanation {
+            cause: "Some files changed: a partial update, a file replaced by hand, or a \
+                    damaged download."
+                .to_string(),
+            remedy: "Record the update if it was intended, or fetch the mod again.".to_string(),
+            command: Some(format!("scan --verify --fix --mod-id {}", mod_id)),
+        };
+    }
+    Explanation {
+        cause: "Files were added, removed, and changed, most likely an update installed over \
+                the old version."
+            .to_string(),
+        remedy: "Check the files listed above, then record the update.".to_string(),
+        command: Some(format!("scan --verify --fix --mod-id {}", mod_id)),
+    }
+}
+
+/// Explains why a mod fails verification and suggests how to fix it
+pub async fn why_failed(mod_id: i32) -> crate::Result<()> {
+    let db = crate::util::open_database().await?;
+
+    let Some(sims_mod) = super::util::active_mods()
+        .filter(sims_mod::Column::Id.eq(mod_id))
+        .one(&db)
+        .await?
+    else {
+        eprintln!("No mod with mod ID {} found!", mod_id);
+        return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+    };
+
+    debug!("Verifying {}", sims_mod.name);
+    let results = super::util::verify_mod(&db, &sims_mod).await?;
+    if results.verification_passed() {
+        println!(
+            "{} passes verification; there is nothing to explain.",
+            sims_mod.name.bold().green()
+        );
+        return Ok(());
+    }
+
+    let roots = super::util::ModRoots::load(&db).await?;
+    let mod_dir = roots.mod_dir(&sims_mod)?;
+    let mut trashed = Vec::new();
+    for file in results.missing_files.iter() {
+        let path = mod_dir.join(file).to_string_lossy().to_string();
+        if let Some(entry) = TrashedFile::find()
+            .filter(trashed_file::Column::Path.eq(path))
+            .one(&db)
+            .await?
+        {
+            trashed.push(entry);
+        }
+    }
+    let settings_files = if results.directory_missing {
+        Vec::new()
+    } else {
+        recent_settings_files(roots.dir(sims_mod.root_id)?, &sims_mod)?
+    };
+
+    println!(
+        "{} fails verification: {} missing, {} new, {} changed.",
+        sims_mod.name.bold().red(),
+        results.missing_files.len(),
+        results.new_files.len(),
+        results.changed_files.len()
+    );
+    for file in results.missing_files.iter().sorted() {
+        println!("  {} {}", "missing".red(), file.display());
+    }
+    for file in results.new_files.keys().sorted() {
+        println!("  {} {}", "new".blue(), file.display());
+    }
+    for file in results.changed_files.keys().sorted() {
+        println!("  {} {}", "changed".yellow(), file.display());
+    }
+    if !settings_files.is_empty() {
+        println!("Settings files written since the mod was last updated:");
+        for file in settings_files.iter() {
+            println!("  {}", file.display().to_string().dimmed());
+        }
+    }
+
+    let explanation = explain(&sims_mod, &results, &trashed, &settings_files);
+    println!("{} {}", "Likely cause:".bold(), explanation.cause);
+    println!("{} {}", "Suggestion:".bold(), explanation.remedy);
+    if let Some(command) = explanation.command {
+        println!("  sims4modorganizer {}", command.bold());
+    }
+    Ok(())
+}
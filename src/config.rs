@@ -0,0 +1,817 @@
+use crate::Result;
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    io::{Error as IOError, ErrorKind as IOErrorKind},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use tracing::{debug, warn};
+use tracing_unwrap::OptionExt;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Prefix for environment variables overriding config keys, e.g. `S4MO_MODS_DIR`
+const ENV_PREFIX: &str = "S4MO_";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Settings that can be changed with `config set`
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConfigKey {
+    #[value(name = "mods_dir", alias = "mods-dir")]
+    ModsDir,
+    #[value(name = "hash_algorithm", alias = "hash-algorithm")]
+    HashAlgorithm,
+    #[value(name = "ignore_globs", alias = "ignore-globs")]
+    IgnoreGlobs,
+    #[value(name = "scan_fix", alias = "scan-fix")]
+    ScanFix,
+    #[value(name = "scan_verify", alias = "scan-verify")]
+    ScanVerify,
+    #[value(name = "color")]
+    Color,
+    #[value(name = "frozen_tags", alias = "frozen-tags")]
+    FrozenTags,
+    #[value(name = "tag_extensions", alias = "tag-extensions")]
+    TagExtensions,
+    #[value(name = "theme")]
+    Theme,
+    #[value(name = "directory_case", alias = "directory-case")]
+    DirectoryCase,
+    #[value(name = "sfw")]
+    Sfw,
+    #[value(name = "hash_workers", alias = "hash-workers")]
+    HashWorkers,
+    #[value(name = "read_chunk_size", alias = "read-chunk-size")]
+    ReadChunkSize,
+    #[value(name = "io_throttle", alias = "io-throttle")]
+    IoThrottle,
+    #[value(name = "timezone")]
+    Timezone,
+    #[value(name = "date_format", alias = "date-format")]
+    DateFormat,
+    #[value(name = "time_format", alias = "time-format")]
+    TimeFormat,
+    #[value(name = "offer_clear_cache", alias = "offer-clear-cache")]
+    OfferClearCache,
+    #[value(name = "sqlite_journal_mode", alias = "sqlite-journal-mode")]
+    SqliteJournalMode,
+    #[value(name = "sqlite_synchronous", alias = "sqlite-synchronous")]
+    SqliteSynchronous,
+    #[value(name = "sqlite_busy_timeout", alias = "sqlite-busy-timeout")]
+    SqliteBusyTimeout,
+}
+
+/// How file hashes are computed
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Xxh3,
+}
+
+impl HashAlgorithm {
+    pub fn hash(&self, data: &[u8]) -> String {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    /// Starts a hash of data given in chunks, for files too large to read at once
+    pub fn hasher(&self) -> Hasher {
+        match self {
+            HashAlgorithm::Xxh3 => Hasher::Xxh3(Box::new(Xxh3::new())),
+        }
+    }
+}
+
+/// A hash in progress. Gives the same result as hashing all data at once.
+pub enum Hasher {
+    Xxh3(Box<Xxh3>),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Xxh3(state) => state.update(data),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        match self {
+            Hasher::Xxh3(state) => format!("{:10X}", state.digest()),
+        }
+    }
+}
+
+/// When to color output
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether mod folder names differing only in case are the same folder
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DirectoryCase {
+    /// Ignore case on Windows and macOS, whose filesystems usually do
+    Auto,
+    Sensitive,
+    Insensitive,
+}
+
+/// How SQLite journals changes to the database
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JournalMode {
+    /// Write-ahead log, so reads don't wait for writes
+    Wal,
+    Delete,
+    Truncate,
+}
+
+/// How often SQLite waits for changes to reach the disk
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Synchronous {
+    Off,
+    /// Safe from corruption in WAL mode, but may lose the last changes on power loss
+    Normal,
+    Full,
+    Extra,
+}
+
+/// Where a config value came from, lowest precedence first
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigSource {
+    Default,
+    File,
+    Environment,
+    Flag,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ConfigSource::Default => "default",
+                ConfigSource::File => "config file",
+                ConfigSource::Environment => "environment",
+                ConfigSource::Flag => "command line",
+            }
+        )
+    }
+}
+
+fn parse_value_enum<T: clap::ValueEnum>(value: &str) -> std::result::Result<Value, String> {
+    let parsed = T::from_str(value, true)?;
+    Ok(Value::from(
+        parsed
+            .to_possible_value()
+            .expect_or_log("Config enums have no skipped values")
+            .get_name(),
+    ))
+}
+
+fn parse_count(value: &str, min: u64) -> std::result::Result<Value, String> {
+    match value.trim().parse::<u64>() {
+        Ok(count) if count >= min => Ok(Value::from(count)),
+        _ => Err(format!(
+            "{} is not a whole number of at least {}",
+            value, min
+        )),
+    }
+}
+
+/// Parses `Tag=ext,ext;Tag=ext` into an object of extension lists by tag. Extensions
+/// are stored lowercase, without a leading dot.
+fn parse_tag_extensions(value: &str) -> std::result::Result<Value, String> {
+    let mut tags = Map::new();
+    for entry in value.split(';').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+        let Some((tag, extensions)) = entry.split_once('=') else {
+            return Err(format!("{} is not Tag=extensions", entry));
+        };
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(format!("{} has no tag", entry));
+        }
+        let extensions = extensions
+            .split(',')
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                if e.contains(['/', '\\', '*']) {
+                    Err(format!("{} is not a file extension", e))
+                } else {
+                    Ok(Value::from(e))
+                }
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if extensions.is_empty() {
+            return Err(format!("{} lists no extensions", entry));
+        }
+        tags.insert(tag.to_string(), Value::Array(extensions));
+    }
+    Ok(Value::Object(tags))
+}
+
+fn parse_bool(value: &str) -> std::result::Result<Value, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(Value::from(true)),
+        "false" | "no" | "off" | "0" => Ok(Value::from(false)),
+        _ => Err(format!("{} is not true or false", value)),
+    }
+}
+
+impl ConfigKey {
+    pub fn all() -> &'static [ConfigKey] {
+        &[
+            ConfigKey::ModsDir,
+            ConfigKey::HashAlgorithm,
+            ConfigKey::IgnoreGlobs,
+            ConfigKey::ScanFix,
+            ConfigKey::ScanVerify,
+            ConfigKey::Color,
+            ConfigKey::FrozenTags,
+            ConfigKey::TagExtensions,
+            ConfigKey::Theme,
+            ConfigKey::DirectoryCase,
+            ConfigKey::Sfw,
+            ConfigKey::HashWorkers,
+            ConfigKey::ReadChunkSize,
+            ConfigKey::IoThrottle,
+            ConfigKey::Timezone,
+            ConfigKey::DateFormat,
+            ConfigKey::TimeFormat,
+            ConfigKey::OfferClearCache,
+            ConfigKey::SqliteJournalMode,
+            ConfigKey::SqliteSynchronous,
+            ConfigKey::SqliteBusyTimeout,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigKey::ModsDir => "mods_dir",
+            ConfigKey::HashAlgorithm => "hash_algorithm",
+            ConfigKey::IgnoreGlobs => "ignore_globs",
+            ConfigKey::ScanFix => "scan_fix",
+            ConfigKey::ScanVerify => "scan_verify",
+            ConfigKey::Color => "color",
+            ConfigKey::FrozenTags => "frozen_tags",
+            ConfigKey::TagExtensions => "tag_extensions",
+            ConfigKey::Theme => "theme",
+            ConfigKey::DirectoryCase => "directory_case",
+            ConfigKey::Sfw => "sfw",
+            ConfigKey::HashWorkers => "hash_workers",
+            ConfigKey::ReadChunkSize => "read_chunk_size",
+            ConfigKey::IoThrottle => "io_throttle",
+            ConfigKey::Timezone => "timezone",
+            ConfigKey::DateFormat => "date_format",
+            ConfigKey::TimeFormat => "time_format",
+            ConfigKey::OfferClearCache => "offer_clear_cache",
+            ConfigKey::SqliteJournalMode => "sqlite_journal_mode",
+            ConfigKey::SqliteSynchronous => "sqlite_synchronous",
+            ConfigKey::SqliteBusyTimeout => "sqlite_busy_timeout",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ConfigKey::ModsDir => {
+                "Mods folder, instead of the one in Documents. May be relative in portable mode."
+            }
+            ConfigKey::HashAlgorithm => "Algorithm used for file hashes (xxh3)",
+            ConfigKey::IgnoreGlobs => {
+                "Comma separated patterns of files and folders to skip, relative to the Mods folder"
+            }
+            ConfigKey::ScanFix => "Run `scan` as if --fix was given",
+            ConfigKey::ScanVerify => "Run `scan` as if --verify was given",
+            ConfigKey::Color => "When to color output (auto, always, never)",
+            ConfigKey::FrozenTags => {
+                "Comma separated tags whose mods `scan` skips hashing unless --thorough is given"
+            }
+            ConfigKey::TagExtensions => {
+                "Further file extensions tracked for mods with a tag, e.g. Poses=trayitem;ReShade=ini,fx"
+            }
+            ConfigKey::Theme => "How statuses are shown (default, colorblind, emoji)",
+            ConfigKey::DirectoryCase => {
+                "Whether mod folder names are matched ignoring case (auto, sensitive, insensitive)"
+            }
+            ConfigKey::Sfw => "Hide mods flagged as NSFW from lists, exports, and menus",
+            ConfigKey::HashWorkers => "Files hashed at the same time (auto, or a number)",
+            ConfigKey::ReadChunkSize => "KiB read from a file at a time while hashing",
+            ConfigKey::IoThrottle => "MB per second hashing may read, or 0 for no limit",
+            ConfigKey::Timezone => {
+                "Timezone times are shown in (local, utc, or an offset like +02:00)"
+            }
+            ConfigKey::DateFormat => "How dates are shown, in strftime format, e.g. %d.%m.%Y",
+            ConfigKey::TimeFormat => {
+                "How times of day are shown, in strftime format, e.g. %I:%M %p"
+            }
+            ConfigKey::OfferClearCache => {
+                "Offer to clear the game's cache after mod files are turned on or off"
+            }
+            ConfigKey::SqliteJournalMode => "How the database journals changes (wal, delete, truncate)",
+            ConfigKey::SqliteSynchronous => {
+                "How often the database waits for writes to reach the disk (off, normal, full, extra)"
+            }
+            ConfigKey::SqliteBusyTimeout => {
+                "Milliseconds to wait for another run to release the database before failing"
+            }
+        }
+    }
+
+    pub fn env_var(&self) -> String {
+        format!("{}{}", ENV_PREFIX, self.name().to_uppercase())
+    }
+
+    fn default_value(&self) -> Value {
+        match self {
+            ConfigKey::ModsDir => Value::Null,
+            ConfigKey::HashAlgorithm => Value::from("xxh3"),
+            ConfigKey::IgnoreGlobs | ConfigKey::FrozenTags => Value::Array(Vec::new()),
+            ConfigKey::TagExtensions => Value::Object(Map::new()),
+            ConfigKey::ScanFix | ConfigKey::ScanVerify | ConfigKey::Sfw => Value::from(false),
+            ConfigKey::OfferClearCache => Value::from(true),
+            ConfigKey::Color => Value::from("auto"),
+            ConfigKey::Theme => Value::from("default"),
+            ConfigKey::DirectoryCase => Value::from("auto"),
+            ConfigKey::HashWorkers => Value::from("auto"),
+            // Windows filter drivers such as antivirus scanners add a cost to every read
+            ConfigKey::ReadChunkSize => Value::from(if cfg!(windows) { 1024 } else { 256 }),
+            ConfigKey::IoThrottle => Value::from(0),
+            ConfigKey::Timezone => Value::from("local"),
+            ConfigKey::DateFormat => Value::from("%Y-%m-%d"),
+            ConfigKey::TimeFormat => Value::from("%H:%M"),
+            ConfigKey::SqliteJournalMode => Value::from("wal"),
+            ConfigKey::SqliteSynchronous => Value::from("normal"),
+            ConfigKey::SqliteBusyTimeout => Value::from(5000),
+        }
+    }
+
+    /// Parses and validates a value as written on the command line
+    pub fn parse(&self, value: &str) -> std::result::Result<Value, String> {
+        match self {
+            ConfigKey::ModsDir => {
+                let path = PathBuf::from(value.trim());
+                let resolved = match crate::util::get_portable_dir() {
+                    Some(portable_dir) => portable_dir.join(&path),
+                    None if path.is_absolute() => path.clone(),
+                    None => return Err(format!("{} is not an absolute path", value)),
+                };
+                if !resolved.is_dir() {
+                    return Err(format!("{} is not a directory", value));
+                }
+                Ok(Value::from(path.to_string_lossy().to_string()))
+            }
+            ConfigKey::HashAlgorithm => parse_value_enum::<HashAlgorithm>(value),
+            ConfigKey::IgnoreGlobs => value
+                .split(',')
+                .map(|glob| glob.trim())
+                .filter(|glob| !glob.is_empty())
+                .map(|glob| {
+                    glob::Pattern::new(glob)
+                        .map(|_| Value::from(glob))
+                        .map_err(|e| format!("Invalid pattern {}: {}", glob, e))
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map(Value::Array),
+            ConfigKey::ScanFix
+            | ConfigKey::ScanVerify
+            | ConfigKey::Sfw
+            | ConfigKey::OfferClearCache => parse_bool(value),
+            ConfigKey::Color => parse_value_enum::<ColorChoice>(value),
+            ConfigKey::Theme => parse_value_enum::<crate::style::Theme>(value),
+            ConfigKey::DirectoryCase => parse_value_enum::<DirectoryCase>(value),
+            ConfigKey::SqliteJournalMode => parse_value_enum::<JournalMode>(value),
+            ConfigKey::SqliteSynchronous => parse_value_enum::<Synchronous>(value),
+            ConfigKey::HashWorkers if value.trim().eq_ignore_ascii_case("auto") => {
+                Ok(Value::from("auto"))
+            }
+            ConfigKey::HashWorkers => parse_count(value, 1),
+            ConfigKey::ReadChunkSize => parse_count(value, 4),
+            ConfigKey::IoThrottle => parse_count(value, 0),
+            ConfigKey::SqliteBusyTimeout => parse_count(value, 0),
+            ConfigKey::Timezone => match crate::datetime::DisplayTimeZone::parse(value) {
+                Some(crate::datetime::DisplayTimeZone::Local) => Ok(Value::from("local")),
+                Some(_) if value.trim().eq_ignore_ascii_case("utc") => Ok(Value::from("utc")),
+                Some(_) => Ok(Value::from(value.trim())),
+                None => Err(format!(
+                    "{} is not local, utc, or an offset like +02:00",
+                    value
+                )),
+            },
+            ConfigKey::DateFormat | ConfigKey::TimeFormat
+                if value.is_empty() || !crate::datetime::is_valid_format(value) =>
+            {
+                Err(format!("{} is not a valid strftime format", value))
+            }
+            ConfigKey::DateFormat | ConfigKey::TimeFormat => Ok(Value::from(value)),
+            ConfigKey::FrozenTags => Ok(Value::Array(
+                value
+                    .split(',')
+                    .map(|tag| tag.trim())
+                    .filter(|tag| !tag.is_empty())
+                    .map(Value::from)
+                    .collect(),
+            )),
+            ConfigKey::TagExtensions => parse_tag_extensions(value),
+        }
+    }
+
+    /// Checks a value read from the config file
+    fn validate(&self, value: &Value) -> std::result::Result<Value, String> {
+        match (self, value) {
+            (ConfigKey::IgnoreGlobs, Value::Array(globs)) => {
+                let globs = globs
+                    .iter()
+                    .map(|g| g.as_str().ok_or("Patterns must be strings".to_string()))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                self.parse(&globs.join(","))
+            }
+            (ConfigKey::TagExtensions, Value::Object(tags)) => {
+                let entries = tags
+                    .iter()
+                    .map(|(tag, extensions)| {
+                        let extensions = extensions
+                            .as_array()
+                            .ok_or("Extensions must be lists".to_string())?
+                            .iter()
+                            .map(|e| e.as_str().ok_or("Extensions must be strings".to_string()))
+                            .collect::<std::result::Result<Vec<_>, _>>()?;
+                        Ok(format!("{}={}", tag, extensions.join(",")))
+                    })
+                    .collect::<std::result::Result<Vec<_>, String>>()?;
+                self.parse(&entries.join(";"))
+            }
+            (ConfigKey::FrozenTags, Value::Array(tags)) => {
+                let tags = tags
+                    .iter()
+                    .map(|t| t.as_str().ok_or("Tags must be strings".to_string()))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                self.parse(&tags.join(","))
+            }
+            (
+                ConfigKey::ScanFix
+                | ConfigKey::ScanVerify
+                | ConfigKey::Sfw
+                | ConfigKey::OfferClearCache,
+                Value::Bool(_),
+            ) => Ok(value.clone()),
+            (
+                ConfigKey::HashWorkers
+                | ConfigKey::ReadChunkSize
+                | ConfigKey::IoThrottle
+                | ConfigKey::SqliteBusyTimeout,
+                Value::Number(n),
+            ) => self.parse(&n.to_string()),
+            (_, Value::String(s)) => self.parse(s),
+            _ => Err(format!("Unexpected value {}", value)),
+        }
+    }
+}
+
+/// Formats a config value for display
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "(not set)".to_string(),
+        Value::Array(values) if values.is_empty() => "(none)".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(","),
+        Value::Object(values) if values.is_empty() => "(none)".to_string(),
+        Value::Object(values) => values
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, display_value(value)))
+            .collect::<Vec<_>>()
+            .join(";"),
+        other => other.to_string(),
+    }
+}
+
+/// The config file in the config folder
+pub const CONFIG_FILE: &str = "config.json";
+
+pub fn get_config_path() -> Result<PathBuf> {
+    let config_dir = if crate::util::get_portable_dir().is_some() {
+        crate::util::get_default_data_dir()
+    } else {
+        crate::legacy_dirs::resolve(
+            &dirs::config_dir().expect_or_log("Failed to get user config directory"),
+            CONFIG_FILE,
+        )
+    };
+    Ok(config_dir.join(CONFIG_FILE))
+}
+
+/// Reads the values stored in the config file, without validating them
+pub fn read_config_file() -> Result<Map<String, Value>> {
+    let path = get_config_path()?;
+    if !path.is_file() {
+        return Ok(Map::new());
+    }
+    debug!("Reading config file {}", path.display());
+    match serde_json::from_str(&crate::fileio::read_to_string(&path)?)? {
+        Value::Object(values) => Ok(values),
+        _ => Err(IOError::new(
+            IOErrorKind::InvalidData,
+            format!("{} does not contain a JSON object", path.display()),
+        )
+        .into()),
+    }
+}
+
+pub fn write_config_file(values: &Map<String, Value>) -> Result<()> {
+    let path = get_config_path()?;
+    if let Some(parent) = path.parent() {
+        crate::fileio::create_dir_all(parent)?;
+    }
+    debug!("Writing config file {}", path.display());
+    crate::fileio::write(
+        path,
+        serde_json::to_string_pretty(&Value::Object(values.clone()))? + "\n",
+    )?;
+    Ok(())
+}
+
+/// Effective settings after layering defaults, the config file, environment variables,
+/// and command line flags
+#[derive(Clone, Debug)]
+pub struct Config {
+    values: HashMap<ConfigKey, (Value, ConfigSource)>,
+}
+
+impl Config {
+    pub fn load(flags: &[(ConfigKey, String)]) -> Result<Config> {
+        let file = read_config_file()?;
+        let mut values = HashMap::new();
+        for key in ConfigKey::all() {
+            let mut value = (key.default_value(), ConfigSource::Default);
+            if let Some(file_value) = file.get(key.name()) {
+                let parsed = key.validate(file_value).map_err(|e| {
+                    IOError::new(
+                        IOErrorKind::InvalidData,
+                        format!("Invalid {} in config file: {}", key.name(), e),
+                    )
+                })?;
+                value = (parsed, ConfigSource::File);
+            }
+            if let Ok(env_value) = std::env::var(key.env_var()) {
+                let parsed = key.parse(&env_value).map_err(|e| {
+                    IOError::new(
+                        IOErrorKind::InvalidInput,
+                        format!("Invalid {}: {}", key.env_var(), e),
+                    )
+                })?;
+                value = (parsed, ConfigSource::Environment);
+            }
+            if let Some((_, flag_value)) = flags.iter().find(|(k, _)| k == key) {
+                let parsed = key.parse(flag_value).map_err(|e| {
+                    IOError::new(
+                        IOErrorKind::InvalidInput,
+                        format!("Invalid {}: {}", key.name(), e),
+                    )
+                })?;
+                value = (parsed, ConfigSource::Flag);
+            }
+            values.insert(*key, value);
+        }
+        Ok(Config { values })
+    }
+
+    pub fn value(&self, key: ConfigKey) -> &Value {
+        &self.values[&key].0
+    }
+
+    pub fn source(&self, key: ConfigKey) -> ConfigSource {
+        self.values[&key].1
+    }
+
+    fn string(&self, key: ConfigKey) -> Option<&str> {
+        self.value(key).as_str()
+    }
+
+    /// The configured mods folder. Relative paths are only allowed in portable mode,
+    /// where they are relative to the executable's folder.
+    pub fn mods_dir(&self) -> Option<PathBuf> {
+        let mods_dir = PathBuf::from(self.string(ConfigKey::ModsDir)?);
+        match crate::util::get_portable_dir() {
+            Some(portable_dir) => Some(portable_dir.join(mods_dir)),
+            None => Some(mods_dir),
+        }
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        self.string(ConfigKey::HashAlgorithm)
+            .and_then(|s| <HashAlgorithm as clap::ValueEnum>::from_str(s, true).ok())
+            .unwrap_or(HashAlgorithm::Xxh3)
+    }
+
+    /// How many files are hashed at the same time. Automatically, Windows uses at most
+    /// two, since its antivirus scanning makes parallel reads contend, and other
+    /// platforms use half the cores, up to four.
+    pub fn hash_workers(&self) -> usize {
+        if let Some(workers) = self.value(ConfigKey::HashWorkers).as_u64() {
+            return workers.max(1) as usize;
+        }
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        if cfg!(windows) {
+            cores.min(2)
+        } else {
+            (cores / 2).clamp(1, 4)
+        }
+    }
+
+    /// Bytes read from a file at a time while hashing
+    pub fn read_chunk_size(&self) -> usize {
+        self.value(ConfigKey::ReadChunkSize)
+            .as_u64()
+            .map_or(256, |kib| kib.max(4)) as usize
+            * 1024
+    }
+
+    /// Bytes per second hashing may read, if limited
+    pub fn io_throttle(&self) -> Option<u64> {
+        self.value(ConfigKey::IoThrottle)
+            .as_u64()
+            .filter(|mb| *mb > 0)
+            .map(|mb| mb * 1_000_000)
+    }
+
+    /// Timezone timestamps are shown in
+    pub fn timezone(&self) -> crate::datetime::DisplayTimeZone {
+        self.string(ConfigKey::Timezone)
+            .and_then(crate::datetime::DisplayTimeZone::parse)
+            .unwrap_or(crate::datetime::DisplayTimeZone::Local)
+    }
+
+    /// strftime format for dates
+    pub fn date_format(&self) -> &str {
+        self.string(ConfigKey::DateFormat)
+            .filter(|format| crate::datetime::is_valid_format(format))
+            .unwrap_or("%Y-%m-%d")
+    }
+
+    /// strftime format for times of day
+    pub fn time_format(&self) -> &str {
+        self.string(ConfigKey::TimeFormat)
+            .filter(|format| crate::datetime::is_valid_format(format))
+            .unwrap_or("%H:%M")
+    }
+
+    /// Whether a path relative to the mods folder, or a folder containing it,
+    /// matches an ignore pattern
+    pub fn is_ignored(&self, relative_path: &Path) -> bool {
+        let patterns: Vec<glob::Pattern> = self
+            .value(ConfigKey::IgnoreGlobs)
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|glob| glob.as_str())
+            .filter_map(|glob| glob::Pattern::new(glob).ok())
+            .collect();
+        relative_path
+            .ancestors()
+            .filter(|path| !path.as_os_str().is_empty())
+            .any(|path| patterns.iter().any(|pattern| pattern.matches_path(path)))
+    }
+
+    pub fn scan_fix(&self) -> bool {
+        self.value(ConfigKey::ScanFix).as_bool().unwrap_or(false)
+    }
+
+    pub fn scan_verify(&self) -> bool {
+        self.value(ConfigKey::ScanVerify).as_bool().unwrap_or(false)
+    }
+
+    /// Whether mods flagged as NSFW are hidden
+    pub fn sfw(&self) -> bool {
+        self.value(ConfigKey::Sfw).as_bool().unwrap_or(false)
+    }
+
+    /// Whether to offer clearing the game's cache after mod files are turned on or off
+    pub fn offer_clear_cache(&self) -> bool {
+        self.value(ConfigKey::OfferClearCache)
+            .as_bool()
+            .unwrap_or(true)
+    }
+
+    /// How SQLite journals changes to the database
+    pub fn sqlite_journal_mode(&self) -> JournalMode {
+        self.string(ConfigKey::SqliteJournalMode)
+            .and_then(|s| <JournalMode as clap::ValueEnum>::from_str(s, true).ok())
+            .unwrap_or(JournalMode::Wal)
+    }
+
+    /// How often SQLite waits for changes to reach the disk
+    pub fn sqlite_synchronous(&self) -> Synchronous {
+        self.string(ConfigKey::SqliteSynchronous)
+            .and_then(|s| <Synchronous as clap::ValueEnum>::from_str(s, true).ok())
+            .unwrap_or(Synchronous::Normal)
+    }
+
+    /// How long to wait for a lock held by another connection
+    pub fn sqlite_busy_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(
+            self.value(ConfigKey::SqliteBusyTimeout)
+                .as_u64()
+                .unwrap_or(5000),
+        )
+    }
+
+    /// Tags marking their mods as frozen
+    pub fn frozen_tags(&self) -> Vec<String> {
+        self.value(ConfigKey::FrozenTags)
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|tag| tag.as_str())
+            .map(|tag| tag.to_string())
+            .collect()
+    }
+
+    /// Whether any tag tracks further extensions
+    pub fn has_tag_extensions(&self) -> bool {
+        self.value(ConfigKey::TagExtensions)
+            .as_object()
+            .is_some_and(|tags| !tags.is_empty())
+    }
+
+    /// Extensions tracked, besides the game's own files, for mods with any of the given
+    /// tags, matching tags ignoring case
+    pub fn tag_extensions(&self, tags: &[String]) -> Vec<String> {
+        let mut extensions: Vec<String> = Vec::new();
+        for (tag, tag_extensions) in self
+            .value(ConfigKey::TagExtensions)
+            .as_object()
+            .into_iter()
+            .flatten()
+        {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                continue;
+            }
+            for extension in tag_extensions.as_array().into_iter().flatten() {
+                if let Some(extension) = extension.as_str() {
+                    if !extensions.iter().any(|e| e == extension) {
+                        extensions.push(extension.to_string());
+                    }
+                }
+            }
+        }
+        extensions
+    }
+
+    pub fn color(&self) -> ColorChoice {
+        self.string(ConfigKey::Color)
+            .and_then(|s| <ColorChoice as clap::ValueEnum>::from_str(s, true).ok())
+            .unwrap_or(ColorChoice::Auto)
+    }
+
+    pub fn theme(&self) -> crate::style::Theme {
+        self.string(ConfigKey::Theme)
+            .and_then(|s| <crate::style::Theme as clap::ValueEnum>::from_str(s, true).ok())
+            .unwrap_or(crate::style::Theme::Default)
+    }
+
+    /// Whether mod folder names are compared ignoring case
+    pub fn ignore_directory_case(&self) -> bool {
+        match self
+            .string(ConfigKey::DirectoryCase)
+            .and_then(|s| <DirectoryCase as clap::ValueEnum>::from_str(s, true).ok())
+            .unwrap_or(DirectoryCase::Auto)
+        {
+            DirectoryCase::Auto => cfg!(any(windows, target_os = "macos")),
+            DirectoryCase::Sensitive => false,
+            DirectoryCase::Insensitive => true,
+        }
+    }
+}
+
+/// Loads the config with the given command line overrides. Call once at startup.
+pub fn init(flags: &[(ConfigKey, String)]) -> Result<&'static Config> {
+    let config = Config::load(flags)?;
+    match config.color() {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => {}
+    }
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The effective config, loaded without command line overrides if `init` was not called
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        Config::load(&[]).unwrap_or_else(|e| {
+            warn!("Failed to load config, using defaults: {}", e);
+            Config {
+                values: ConfigKey::all()
+                    .iter()
+                    .map(|k| (*k, (k.default_value(), ConfigSource::Default)))
+                    .collect(),
+            }
+        })
+    })
+}
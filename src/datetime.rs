@@ -0,0 +1,109 @@
+//! Showing timestamps. They are stored in UTC so that databases and bundles from
+//! machines in other timezones order and compare correctly, and are shown in the
+//! configured timezone with the configured date and time formats.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone, Utc};
+
+/// Timezone timestamps are shown in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisplayTimeZone {
+    /// The machine's timezone
+    Local,
+    /// A fixed offset from UTC, such as UTC itself or +02:00
+    Fixed(FixedOffset),
+}
+
+impl DisplayTimeZone {
+    /// Parses `local`, `utc`, or an offset such as `+02:00` or `-0530`
+    pub fn parse(value: &str) -> Option<DisplayTimeZone> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("local") {
+            return Some(DisplayTimeZone::Local);
+        }
+        if value.eq_ignore_ascii_case("utc") || value == "Z" {
+            return FixedOffset::east_opt(0).map(DisplayTimeZone::Fixed);
+        }
+        let (sign, offset) = if let Some(offset) = value.strip_prefix('+') {
+            (1, offset)
+        } else {
+            (-1, value.strip_prefix('-')?)
+        };
+        let digits = offset.replacen(':', "", 1);
+        if !matches!(digits.len(), 2 | 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let hours: i32 = digits[..2].parse().ok()?;
+        let minutes: i32 = digits[2..].parse().unwrap_or(0);
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(DisplayTimeZone::Fixed)
+    }
+
+    /// A timestamp in this timezone
+    pub fn convert<Tz: TimeZone>(&self, timestamp: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        match self {
+            DisplayTimeZone::Local => timestamp.with_timezone(&Local).fixed_offset(),
+            DisplayTimeZone::Fixed(offset) => timestamp.with_timezone(offset),
+        }
+    }
+}
+
+/// Whether a strftime format only has valid specifiers
+pub fn is_valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// A timestamp's date, in the configured timezone and date format
+pub fn format_date<Tz: TimeZone>(timestamp: &DateTime<Tz>) -> String {
+    let config = crate::config::get();
+    config
+        .timezone()
+        .convert(timestamp)
+        .format(config.date_format())
+        .to_string()
+}
+
+/// A timestamp's date and time, in the configured timezone and formats
+pub fn format_datetime<Tz: TimeZone>(timestamp: &DateTime<Tz>) -> String {
+    let config = crate::config::get();
+    config
+        .timezone()
+        .convert(timestamp)
+        .format(&format!(
+            "{} {}",
+            config.date_format(),
+            config.time_format()
+        ))
+        .to_string()
+}
+
+/// A date in the configured date format
+pub fn format_day(date: NaiveDate) -> String {
+    date.format(crate::config::get().date_format()).to_string()
+}
+
+/// Today's date in the configured timezone
+pub fn today() -> NaiveDate {
+    crate::config::get()
+        .timezone()
+        .convert(&Utc::now())
+        .date_naive()
+}
+
+/// When a date starts in the configured timezone
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let start = match crate::config::get().timezone() {
+        DisplayTimeZone::Local => Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|start| start.with_timezone(&Utc)),
+        DisplayTimeZone::Fixed(offset) => offset
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|start| start.with_timezone(&Utc)),
+    };
+    start.unwrap_or_else(|| midnight.and_utc())
+}
@@ -2,7 +2,20 @@
 
 pub mod prelude;
 
+pub mod game_version;
+pub mod ignored_path;
+pub mod journal;
+pub mod kept_duplicate;
 pub mod mod_hash;
+pub mod mod_required_pack;
+pub mod mod_root;
 pub mod mod_tag_relation;
+pub mod owned_pack;
+pub mod play_session;
+pub mod saved_query;
+pub mod scan_record;
 pub mod sims_mod;
+pub mod snapshot;
 pub mod tag;
+pub mod trashed_file;
+pub mod version_probe;
@@ -1,6 +1,19 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.14
 
+pub use super::game_version::Entity as GameVersion;
+pub use super::ignored_path::Entity as IgnoredPath;
+pub use super::journal::Entity as Journal;
+pub use super::kept_duplicate::Entity as KeptDuplicate;
 pub use super::mod_hash::Entity as ModHash;
+pub use super::mod_required_pack::Entity as ModRequiredPack;
+pub use super::mod_root::Entity as ModRoot;
 pub use super::mod_tag_relation::Entity as ModTagRelation;
+pub use super::owned_pack::Entity as OwnedPack;
+pub use super::play_session::Entity as PlaySession;
+pub use super::saved_query::Entity as SavedQuery;
+pub use super::scan_record::Entity as ScanRecord;
 pub use super::sims_mod::Entity as SimsMod;
+pub use super::snapshot::Entity as Snapshot;
 pub use super::tag::Entity as Tag;
+pub use super::trashed_file::Entity as TrashedFile;
+pub use super::version_probe::Entity as VersionProbe;
@@ -13,13 +13,37 @@ pub struct Model {
     pub directory: String,
     pub source_url: String,
     pub version: String,
-    pub updated: DateTimeLocal,
+    pub updated: DateTimeUtc,
+    pub protected: bool,
+    pub deleted_at: Option<DateTimeUtc>,
+    pub thumbnail: Option<String>,
+    pub source_type: String,
+    pub recheck_after: Option<Date>,
+    pub broken_at: Option<DateTimeUtc>,
+    pub broken_reason: Option<String>,
+    pub root_id: Option<i32>,
+    pub frozen: bool,
+    pub rating: Option<i32>,
+    pub would_reinstall: Option<bool>,
+    pub download_url: Option<String>,
+    pub parent_mod_id: Option<i32>,
+    pub added_at: Option<DateTimeUtc>,
+    pub nsfw: bool,
+    pub update_channel: String,
+    pub package_count: i32,
+    pub script_count: i32,
+    pub archive_hash: Option<String>,
+    pub archive_installed_at: Option<DateTimeUtc>,
+    pub file_count: i32,
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::mod_hash::Entity")]
     ModHash,
+    #[sea_orm(has_many = "super::mod_required_pack::Entity")]
+    ModRequiredPack,
     #[sea_orm(has_many = "super::mod_tag_relation::Entity")]
     ModTagRelation,
 }
@@ -30,6 +54,12 @@ impl Related<super::mod_hash::Entity> for Entity {
     }
 }
 
+impl Related<super::mod_required_pack::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ModRequiredPack.def()
+    }
+}
+
 impl Related<super::mod_tag_relation::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ModTagRelation.def()
@@ -9,6 +9,13 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(unique)]
     pub tag: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub parent_id: Option<i32>,
+    pub explicit: bool,
+    pub default_source_url: Option<String>,
+    pub default_update_channel: Option<String>,
+    pub default_nsfw: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,215 @@
+//! File operations that retry transient failures, such as Windows sharing
+//! violations while OneDrive, antivirus software, or the game holds a file open.
+//! Failures that persist are reported with the offending path and a hint.
+//! In read-only mode every change is refused here, before it reaches the disk.
+
+use std::{
+    io::{Error, ErrorKind, Result, Write},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+/// Attempts made before giving up on a transient failure
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled after each attempt
+const INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+/// `ERROR_SHARING_VIOLATION` and `ERROR_LOCK_VIOLATION`
+#[cfg(windows)]
+const LOCK_ERROR_CODES: &[i32] = &[32, 33];
+/// `EBUSY` and `ETXTBSY`
+#[cfg(not(windows))]
+const LOCK_ERROR_CODES: &[i32] = &[16, 26];
+
+/// Set by `--read-only`
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Refuses every file and database change for the rest of the run
+pub fn enable_read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// A change refused in read-only mode, describing what would have been done
+#[derive(Debug)]
+pub struct ReadOnlyError(String);
+
+impl std::fmt::Display for ReadOnlyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "would {}", self.0)
+    }
+}
+
+impl std::error::Error for ReadOnlyError {}
+
+pub fn read_only_error(change: String) -> Error {
+    Error::new(ErrorKind::PermissionDenied, ReadOnlyError(change))
+}
+
+/// Fails with a [`ReadOnlyError`] in read-only mode
+pub fn ensure_writable(action: &str, path: &Path) -> Result<()> {
+    if !is_read_only() {
+        return Ok(());
+    }
+    debug!(
+        "Refusing to {} {} in read-only mode",
+        action,
+        path.display()
+    );
+    Err(read_only_error(format!("{} {}", action, path.display())))
+}
+
+/// Whether an error is likely to go away once another program lets go of the file
+fn is_transient(error: &Error) -> bool {
+    if error
+        .raw_os_error()
+        .is_some_and(|code| LOCK_ERROR_CODES.contains(&code))
+    {
+        return true;
+    }
+    // Windows reports files pending deletion or briefly opened by scanners as access denied
+    (cfg!(windows) && error.kind() == ErrorKind::PermissionDenied)
+        || error.kind() == ErrorKind::ResourceBusy
+}
+
+fn hint(error: &Error) -> Option<&'static str> {
+    if is_transient(error) {
+        Some("The file is in use by another program. Close The Sims 4, pause OneDrive syncing, or wait for antivirus scans to finish, then try again.")
+    } else if error.kind() == ErrorKind::PermissionDenied {
+        Some("Check that the file is not read-only and that you have permission to change it.")
+    } else {
+        None
+    }
+}
+
+/// Runs a file operation, retrying with backoff while it fails transiently
+fn retry<T, F>(action: &str, path: &Path, mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    let mut delay = INITIAL_DELAY;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                debug!(
+                    "Failed to {} {} ({}), retrying in {:?}",
+                    action,
+                    path.display(),
+                    e,
+                    delay
+                );
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => {
+                debug!("Failed to {} {}: {}", action, path.display(), e);
+                if let Some(hint) = hint(&e) {
+                    warn!("Giving up on {} after {} attempts", path.display(), attempt);
+                    eprintln!("Could not {} {}: {}", action, path.display(), e);
+                    eprintln!("{}", hint);
+                }
+                return Err(Error::new(
+                    e.kind(),
+                    format!("Could not {} {}: {}", action, path.display(), e),
+                ));
+            }
+        }
+    }
+}
+
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    retry("read", path, || std::fs::read(path))
+}
+
+pub fn open<P: AsRef<Path>>(path: P) -> Result<std::fs::File> {
+    let path = path.as_ref();
+    retry("read", path, || std::fs::File::open(path))
+}
+
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    retry("read", path, || std::fs::read_to_string(path))
+}
+
+pub fn read_dir<P: AsRef<Path>>(path: P) -> Result<std::fs::ReadDir> {
+    let path = path.as_ref();
+    retry("list", path, || std::fs::read_dir(path))
+}
+
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let path = path.as_ref();
+    ensure_writable("write", path)?;
+    retry("write", path, || std::fs::write(path, contents.as_ref()))
+}
+
+/// Writes a file and waits until its contents reached the disk
+pub fn write_synced<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let path = path.as_ref();
+    ensure_writable("write", path)?;
+    retry("write", path, || {
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()
+    })
+}
+
+/// Waits until a file, or on Unix a folder's entries, reached the disk
+pub fn sync<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    // Windows cannot open folders as files
+    if path.is_dir() && !cfg!(unix) {
+        return Ok(());
+    }
+    retry("sync", path, || std::fs::File::open(path)?.sync_all())
+}
+
+pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<u64> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    ensure_writable("write", to)?;
+    retry("copy", from, || std::fs::copy(from, to))
+}
+
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    ensure_writable("move", from)?;
+    retry("move", from, || std::fs::rename(from, to))
+}
+
+pub fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> Result<()> {
+    let (original, link) = (original.as_ref(), link.as_ref());
+    ensure_writable("link", link)?;
+    retry("link", link, || std::fs::hard_link(original, link))
+}
+
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    ensure_writable("create", path)?;
+    retry("create", path, || std::fs::create_dir_all(path))
+}
+
+pub fn remove_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    ensure_writable("delete", path)?;
+    retry("delete", path, || std::fs::remove_file(path))
+}
+
+pub fn remove_dir<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    ensure_writable("delete", path)?;
+    retry("delete", path, || std::fs::remove_dir(path))
+}
+
+pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    ensure_writable("delete", path)?;
+    retry("delete", path, || std::fs::remove_dir_all(path))
+}
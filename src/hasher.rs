@@ -0,0 +1,114 @@
+//! Hashing mod files. Files are read in chunks by a pool of worker threads, with
+//! the pool size, chunk size, and an optional read rate limit taken from the config,
+//! so that scanning a large Mods folder can stay in the background on slow disks.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+use tracing_unwrap::{OptionExt, ResultExt};
+
+/// Limits how fast all hashing in this process reads, across worker threads
+struct Throttle {
+    bytes_per_second: u64,
+    /// When throttling started and how much was read since
+    read: Mutex<(Instant, u64)>,
+}
+
+impl Throttle {
+    /// Counts bytes as read, sleeping while reads are ahead of the allowed rate
+    fn consume(&self, bytes: usize) {
+        let ahead = {
+            let mut read = self.read.lock().expect_or_log("Throttle lock poisoned");
+            read.1 += bytes as u64;
+            let allowed = Duration::from_secs_f64(read.1 as f64 / self.bytes_per_second as f64);
+            allowed.saturating_sub(read.0.elapsed())
+        };
+        if !ahead.is_zero() {
+            std::thread::sleep(ahead);
+        }
+    }
+}
+
+static THROTTLE: OnceLock<Option<Throttle>> = OnceLock::new();
+
+fn throttle() -> Option<&'static Throttle> {
+    THROTTLE
+        .get_or_init(|| {
+            crate::config::get()
+                .io_throttle()
+                .map(|bytes_per_second| Throttle {
+                    bytes_per_second,
+                    read: Mutex::new((Instant::now(), 0)),
+                })
+        })
+        .as_ref()
+}
+
+/// Hashes a file with the configured algorithm, reading it in chunks
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    let config = crate::config::get();
+    let mut file = crate::fileio::open(path)?;
+    let mut hasher = config.hash_algorithm().hasher();
+    let mut buffer = vec![0; config.read_chunk_size()];
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    e.kind(),
+                    format!("Could not read {}: {}", path.display(), e),
+                ))
+            }
+        };
+        hasher.update(&buffer[..read]);
+        if let Some(throttle) = throttle() {
+            throttle.consume(read);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// Hashes files relative to a folder using the configured number of workers.
+/// Results are in the order of `files`.
+pub fn hash_files(dir: &Path, files: &[PathBuf]) -> Vec<std::io::Result<String>> {
+    let workers = crate::config::get().hash_workers().min(files.len());
+    if workers <= 1 {
+        return files
+            .iter()
+            .map(|file| hash_file(&dir.join(file)))
+            .collect();
+    }
+    debug!("Hashing {} files with {} workers", files.len(), workers);
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<std::io::Result<String>>>> =
+        files.iter().map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+                debug!("Generating checksum for {}", file.display());
+                *results[index]
+                    .lock()
+                    .expect_or_log("Hash result lock poisoned") = Some(hash_file(&dir.join(file)));
+            });
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| {
+            result
+                .into_inner()
+                .expect_or_log("Hash result lock poisoned")
+                .expect_or_log("Every file is hashed by a worker")
+        })
+        .collect()
+}
@@ -0,0 +1,419 @@
+use colored::Colorize;
+
+/// A workflow guide shown by `help <topic>`
+struct HelpTopic {
+    name: &'static str,
+    summary: &'static str,
+    body: &'static str,
+}
+
+const TOPICS: &[HelpTopic] = &[
+    HelpTopic {
+        name: "setup",
+        summary: "First-time setup of the database and catalog",
+        body: "\
+1. Create the database:
+     sims4modorganizer initialize
+
+2. If your Mods folder is not under Documents, point the tool at it:
+     sims4modorganizer config set mods_dir \"D:\\Sims 4\\Mods\"
+
+3. Put each mod in its own folder inside Mods, then register them. You are
+   asked for each new mod's name, version, source URL, and tags:
+     sims4modorganizer scan --fix
+
+4. Check that everything was recorded:
+     sims4modorganizer list --details
+
+5. Make a backup once the catalog looks right:
+     sims4modorganizer backup create",
+    },
+    HelpTopic {
+        name: "patch",
+        summary: "Triaging mods after a game patch",
+        body: "\
+1. Take a snapshot before updating anything, so you can see what changed later:
+     sims4modorganizer snapshot create \"before patch\"
+
+   Then get an overview of the script mods to check, optionally disabling them
+   until they are updated:
+     sims4modorganizer postpatch --disable
+
+2. Mark mods that creators reported as broken:
+     sims4modorganizer mark-broken --mod-id 12 --reason \"Crashes CAS\"
+     sims4modorganizer list --broken
+
+3. Download updates into the mods' folders, then record them. Changed mods
+   ask for their new version and source:
+     sims4modorganizer scan --verify --fix
+
+4. Script mods are the usual suspects. To check only them:
+     sims4modorganizer scan --verify --tags Script
+
+5. Clear the broken flag once a mod works again, and review the changes:
+     sims4modorganizer mark-fixed --mod-id 12
+     sims4modorganizer snapshot list
+     sims4modorganizer snapshot diff 1",
+    },
+    HelpTopic {
+        name: "tagging",
+        summary: "Tagging many mods at once",
+        body: "\
+1. See which tags exist and how much they are used:
+     sims4modorganizer tags --stats
+
+2. Add a tag to many mods at once with the interactive editor's
+   \"Bulk tag mods\" view:
+     sims4modorganizer edit --interactive
+
+3. Set the tags of a single mod. This replaces its tags:
+     sims4modorganizer edit --mod-id 12 --tags CAS,Hair
+
+4. Give tags a description and a parent to build a taxonomy:
+     sims4modorganizer tags --set Hair --description \"CAS hair\" --parent CAS
+
+5. Use the interactive editor's cleanup view to find mods without tags:
+     sims4modorganizer edit --interactive",
+    },
+];
+
+/// Worked examples appended to each subcommand's `--help`
+const EXAMPLES: &[(&str, &[&str])] = &[
+    (
+        "initialize",
+        &[
+            "sims4modorganizer initialize",
+            "sims4modorganizer initialize --force",
+        ],
+    ),
+    (
+        "list",
+        &[
+            "sims4modorganizer list --tags CAS,Hair",
+            "sims4modorganizer list --fields version,source --source patreon",
+            "sims4modorganizer list --min-rating 4 --would-reinstall true",
+            "sims4modorganizer list --since-last-play --details",
+            "sims4modorganizer list --tree",
+            "sims4modorganizer list --group-by author",
+            "sims4modorganizer list --group-by tag --tags Hair,CAS --details",
+            "sims4modorganizer list --script-mods --fields composition",
+            "sims4modorganizer list --downloaded-since 2024-06-01 --fields added,version",
+        ],
+    ),
+    (
+        "add",
+        &[
+            "sims4modorganizer add --dir CuteHair",
+            "sims4modorganizer add --dir CuteHair --name \"Cute Hair v2\" --url https://example.com/cute-hair --tags Hair,CAS --mod-version 2.0",
+            "sims4modorganizer add --dir Tools/BetterBuild --root Extras --protected",
+        ],
+    ),
+    (
+        "adopt",
+        &[
+            "sims4modorganizer adopt",
+            "sims4modorganizer adopt --report adopted.json",
+        ],
+    ),
+    (
+        "scan",
+        &[
+            "sims4modorganizer scan",
+            "sims4modorganizer scan --verify --fix",
+            "sims4modorganizer scan --sync-hashes --mod-id 12",
+            "sims4modorganizer scan --sync-hashes --report changes.json",
+            "sims4modorganizer scan --verify --mod-id 12,13",
+            "sims4modorganizer scan --report-extras",
+        ],
+    ),
+    (
+        "info",
+        &[
+            "sims4modorganizer info --mod-id 12",
+            "sims4modorganizer info --mod-id 12 --json",
+        ],
+    ),
+    (
+        "files",
+        &[
+            "sims4modorganizer files --find \"*.ts4script\"",
+            "sims4modorganizer files --find \"wickedwhims*\" --disk",
+        ],
+    ),
+    (
+        "report",
+        &[
+            "sims4modorganizer report --mod-id 12",
+            "sims4modorganizer report --mod-id 12 --template short",
+        ],
+    ),
+    (
+        "verify",
+        &[
+            "sims4modorganizer verify",
+            "sims4modorganizer verify --tags Script --fail-fast",
+        ],
+    ),
+    ("why-failed", &["sims4modorganizer why-failed --mod-id 12"]),
+    (
+        "tags",
+        &[
+            "sims4modorganizer tags",
+            "sims4modorganizer tags --delete Unused",
+            "sims4modorganizer tags --create Hair,Skin,Makeup",
+            "sims4modorganizer tags --related Hair",
+            "sims4modorganizer tags --combine",
+            "sims4modorganizer tags --format json",
+        ],
+    ),
+    (
+        "edit",
+        &[
+            "sims4modorganizer edit --interactive",
+            "sims4modorganizer edit --mod-id 12 --mod-version 1.4 --tags Script",
+            "sims4modorganizer edit --filter-source patreon.com/creator --protected true",
+            "sims4modorganizer edit --mod-id 12 --rating 5 --would-reinstall true",
+            "sims4modorganizer edit --mod-id 14 --parent 12",
+            "sims4modorganizer edit --mod-id 15 --nsfw true",
+        ],
+    ),
+    ("open-mod-dir", &["sims4modorganizer open-mod-dir"]),
+    (
+        "export",
+        &[
+            "sims4modorganizer export --output mods.html --verify",
+            "sims4modorganizer export --format json --fields version,source,tags",
+            "sims4modorganizer export --output mods.html --sfw",
+            "sims4modorganizer export --output mods.html --verify --sign",
+        ],
+    ),
+    (
+        "thumbnail",
+        &[
+            "sims4modorganizer thumbnail --mod-id 12 https://example.com/preview.png --fetch",
+            "sims4modorganizer thumbnail --mod-id 12 --clear",
+        ],
+    ),
+    (
+        "check-links",
+        &[
+            "sims4modorganizer check-links",
+            "sims4modorganizer check-links --fix --concurrency 2 --delay 3000",
+        ],
+    ),
+    (
+        "fetch",
+        &[
+            "sims4modorganizer edit --mod-id 12 --download-url https://simfileshare.net/download/12345/",
+            "sims4modorganizer fetch --mod-id 12 --install",
+            "sims4modorganizer edit --mod-id 12 --update-channel manual",
+            "sims4modorganizer fetch --all --install",
+        ],
+    ),
+    (
+        "reminders",
+        &[
+            "sims4modorganizer reminders",
+            "sims4modorganizer reminders --all",
+        ],
+    ),
+    (
+        "query",
+        &[
+            "sims4modorganizer query save maxis-cas \"--all-tags CAS,Maxis-Match\"",
+            "sims4modorganizer list --query maxis-cas",
+            "sims4modorganizer query save public \"--exclude-flags nsfw --exclude-sources patreon\"",
+            "sims4modorganizer export --output public.html --query public",
+        ],
+    ),
+    (
+        "diff",
+        &[
+            "sims4modorganizer diff old.json new.json",
+            "sims4modorganizer diff --against friend-bundle.json",
+        ],
+    ),
+    (
+        "config",
+        &[
+            "sims4modorganizer config list",
+            "sims4modorganizer config set scan_verify true",
+            "sims4modorganizer config unset mods_dir",
+        ],
+    ),
+    (
+        "snapshot",
+        &[
+            "sims4modorganizer snapshot create \"before patch\"",
+            "sims4modorganizer snapshot diff 1",
+        ],
+    ),
+    (
+        "sync",
+        &[
+            "sims4modorganizer sync export laptop.json",
+            "sims4modorganizer sync export shared.json --exclude-flags nsfw --tags CAS",
+            "sims4modorganizer sync import laptop.json",
+            "sims4modorganizer sync export shared.json --sign",
+            "sims4modorganizer sync key",
+            "sims4modorganizer sync trust Alex q1Yc3l0t0kZ2bR9mD8cX4n7wP5aH6sJ1eV0uT2yG3iA=",
+        ],
+    ),
+    (
+        "backup",
+        &[
+            "sims4modorganizer backup create",
+            "sims4modorganizer backup list",
+        ],
+    ),
+    (
+        "roots",
+        &[
+            "sims4modorganizer roots add \"D:\\Extra CC\" --name extra",
+            "sims4modorganizer roots list",
+        ],
+    ),
+    (
+        "packs",
+        &[
+            "sims4modorganizer packs --add Seasons,\"Cats & Dogs\"",
+            "sims4modorganizer list --missing-packs",
+        ],
+    ),
+    (
+        "probe",
+        &[
+            "sims4modorganizer probe set --mod-id 12 version.txt",
+            "sims4modorganizer probe list",
+        ],
+    ),
+    (
+        "status",
+        &[
+            "sims4modorganizer status",
+            "sims4modorganizer status --verify --write status.json",
+            "sims4modorganizer status --format prometheus --write metrics.prom",
+        ],
+    ),
+    (
+        "schema",
+        &[
+            "sims4modorganizer schema",
+            "sims4modorganizer schema export > mods.schema.json",
+            "sims4modorganizer schema scan-report",
+        ],
+    ),
+    (
+        "postpatch",
+        &[
+            "sims4modorganizer postpatch",
+            "sims4modorganizer postpatch --disable",
+            "sims4modorganizer postpatch --enable",
+        ],
+    ),
+    (
+        "doctor",
+        &[
+            "sims4modorganizer doctor",
+            "sims4modorganizer doctor --max-packages 3000",
+        ],
+    ),
+    (
+        "organize",
+        &[
+            "sims4modorganizer organize --dry-run",
+            "sims4modorganizer organize --template \"{source_type}/{creator}/{folder}\"",
+        ],
+    ),
+    (
+        "mark-broken",
+        &["sims4modorganizer mark-broken --mod-id 12 --reason \"Crashes CAS\""],
+    ),
+    ("mark-fixed", &["sims4modorganizer mark-fixed --mod-id 12"]),
+    (
+        "remove",
+        &[
+            "sims4modorganizer remove --mod-id 12",
+            "sims4modorganizer remove --mod-id 12 --permanent",
+        ],
+    ),
+    ("clear-cache", &["sims4modorganizer clear-cache"]),
+    (
+        "purge-creator",
+        &[
+            "sims4modorganizer purge-creator somecreator",
+            "sims4modorganizer purge-creator somecreator --enable",
+            "sims4modorganizer purge-creator example.com --remove",
+        ],
+    ),
+    (
+        "stale",
+        &[
+            "sims4modorganizer stale --older-than 6m",
+            "sims4modorganizer stale --older-than 1y --review",
+        ],
+    ),
+    (
+        "dedupe",
+        &[
+            "sims4modorganizer dedupe",
+            "sims4modorganizer dedupe --hardlink",
+        ],
+    ),
+    (
+        "ignored",
+        &[
+            "sims4modorganizer ignored list",
+            "sims4modorganizer ignored remove 3",
+        ],
+    ),
+    ("trash", &["sims4modorganizer trash list"]),
+    (
+        "restore-entry",
+        &["sims4modorganizer restore-entry --mod-id 12"],
+    ),
+    (
+        "log",
+        &[
+            "sims4modorganizer log --mod-id 12",
+            "sims4modorganizer log --since 2024-01-01 --action deleted",
+        ],
+    ),
+];
+
+/// Adds the worked examples to each subcommand's long help
+pub fn with_examples(mut command: clap::Command) -> clap::Command {
+    for (name, examples) in EXAMPLES.iter() {
+        command = command.mut_subcommand(*name, |subcommand| {
+            let lines: Vec<String> = examples.iter().map(|e| format!("  {}", e)).collect();
+            subcommand.after_long_help(format!("Examples:\n{}", lines.join("\n")))
+        });
+    }
+    command.after_long_help("Run `help <topic>` for a guide, or `help` to list the guides.")
+}
+
+/// Prints a workflow guide, a subcommand's long help, or the list of guides
+pub fn help(topic: Option<String>, mut command: clap::Command) -> crate::Result<()> {
+    let Some(topic) = topic else {
+        command.print_help()?;
+        println!("\n{}", "Guides:".bold());
+        for topic in TOPICS.iter() {
+            println!("  {:<10} {}", topic.name.bold(), topic.summary);
+        }
+        return Ok(());
+    };
+    if let Some(topic) = TOPICS.iter().find(|t| t.name.eq_ignore_ascii_case(&topic)) {
+        println!("{}\n\n{}", topic.summary.bold(), topic.body);
+        return Ok(());
+    }
+    if let Some(subcommand) = command.find_subcommand_mut(&topic) {
+        subcommand.print_long_help()?;
+        return Ok(());
+    }
+    eprintln!("No guide or subcommand named {} found!", topic);
+    eprintln!(
+        "Guides: {}",
+        TOPICS.iter().map(|t| t.name).collect::<Vec<_>>().join(", ")
+    );
+    Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+}
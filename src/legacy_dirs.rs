@@ -0,0 +1,222 @@
+//! Moving the database and config out of the folders named `com.familiar.sims4modsorganizer`,
+//! which is what they were called before they were named after the binary. Until the user
+//! moves or copies them, the old folders stay in use, so the tool never silently starts
+//! over with an empty database.
+
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use inquire::Select;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use tracing::{debug, info, warn};
+
+use crate::util::{DATABASE_FILE, DATA_DIR_NAME, LEGACY_DATA_DIR_NAME};
+
+/// What to do with data still in the old folders
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LegacyAction {
+    Move,
+    Copy,
+    Keep,
+}
+
+impl std::fmt::Display for LegacyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LegacyAction::Move => write!(f, "Move it to the new folder"),
+            LegacyAction::Copy => write!(f, "Copy it, keeping the old folder as it is"),
+            LegacyAction::Keep => write!(f, "Keep using the old folder for now"),
+        }
+    }
+}
+
+/// The folder named after the binary under `base`, or the old one while only the old
+/// one holds `file`
+pub fn resolve(base: &Path, file: &str) -> PathBuf {
+    let current = base.join(DATA_DIR_NAME);
+    let legacy = base.join(LEGACY_DATA_DIR_NAME);
+    if !current.join(file).exists() && legacy.join(file).is_file() {
+        debug!("Using the old folder {}", legacy.display());
+        legacy
+    } else {
+        current
+    }
+}
+
+/// The old and new folder under `base`, if only the old one holds `file`
+fn pending(base: Option<PathBuf>, file: &str) -> Option<(PathBuf, PathBuf)> {
+    let base = base?;
+    let (legacy, current) = (base.join(LEGACY_DATA_DIR_NAME), base.join(DATA_DIR_NAME));
+    (legacy.join(file).is_file() && !current.join(file).exists()).then_some((legacy, current))
+}
+
+/// Copies a folder's files into another, comparing each copy with its original
+fn copy_verified(from: &Path, to: &Path) -> crate::Result<()> {
+    let mut to_read = vec![PathBuf::new()];
+    while let Some(relative_dir) = to_read.pop() {
+        crate::fileio::create_dir_all(to.join(&relative_dir))?;
+        for entry in crate::fileio::read_dir(from.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                to_read.push(relative_path);
+                continue;
+            }
+            let (source, target) = (from.join(&relative_path), to.join(&relative_path));
+            crate::fileio::copy(&source, &target)?;
+            if crate::util::checksum_file(&source)? != crate::util::checksum_file(&target)? {
+                eprintln!(
+                    "The copy of {} does not match the original!",
+                    source.display()
+                );
+                return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Opens a database file on its own and runs SQLite's consistency check on it
+async fn database_file_is_intact(path: &Path) -> crate::Result<bool> {
+    let pool = SqlitePoolOptions::new()
+        .connect_with(SqliteConnectOptions::new().filename(path))
+        .await?;
+    let db = sea_orm::SqlxSqliteConnector::from_sqlx_sqlite_pool(pool);
+    let intact = crate::util::database_is_intact(&db).await;
+    db.close().await?;
+    Ok(intact)
+}
+
+/// Puts the data folder's contents in the new folder, checking the database once it is
+/// there. A failed check undoes the move or copy, so the old folder stays in use.
+async fn transfer_data_dir(
+    legacy: &Path,
+    current: &Path,
+    action: LegacyAction,
+) -> crate::Result<()> {
+    let renamed = action == LegacyAction::Move
+        && !current.exists()
+        && crate::fileio::rename(legacy, current).is_ok();
+    let created = !renamed && !current.exists();
+    if !renamed {
+        info!("Copying {} to {}", legacy.display(), current.display());
+        if let Err(e) = copy_verified(legacy, current) {
+            undo_copy(current, created);
+            return Err(e);
+        }
+    }
+
+    let database_path = current.join(DATABASE_FILE);
+    if !database_file_is_intact(&database_path).await? {
+        eprintln!(
+            "{}",
+            "The database fails SQLite's integrity check in its new folder.".red()
+        );
+        if renamed {
+            crate::fileio::rename(current, legacy)?;
+        } else {
+            undo_copy(current, created);
+        }
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    }
+    if action == LegacyAction::Move && !renamed {
+        crate::util::remove_path(legacy, false)?;
+    }
+    println!(
+        "{} your data from {} to {}.",
+        if action == LegacyAction::Move {
+            "Moved"
+        } else {
+            "Copied"
+        },
+        legacy.display(),
+        current.display().to_string().blue()
+    );
+    Ok(())
+}
+
+/// Removes what a failed copy left behind: the whole folder if the copy created it,
+/// or else just the database, so the old folder is used again
+fn undo_copy(current: &Path, created: bool) {
+    let result = if created {
+        crate::fileio::remove_dir_all(current)
+    } else {
+        crate::fileio::remove_file(current.join(DATABASE_FILE))
+    };
+    if let Err(e) = result {
+        warn!("Could not clean up {}: {}", current.display(), e);
+    }
+}
+
+/// Puts the config file in the new config folder
+fn transfer_config_file(legacy: &Path, current: &Path, action: LegacyAction) -> crate::Result<()> {
+    let (source, target) = (
+        legacy.join(crate::config::CONFIG_FILE),
+        current.join(crate::config::CONFIG_FILE),
+    );
+    crate::fileio::create_dir_all(current)?;
+    crate::fileio::copy(&source, &target)?;
+    if crate::util::checksum_file(&source)? != crate::util::checksum_file(&target)? {
+        eprintln!(
+            "The copy of {} does not match the original!",
+            source.display()
+        );
+        crate::fileio::remove_file(&target)?;
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+    }
+    if action == LegacyAction::Move {
+        crate::fileio::remove_file(&source)?;
+    }
+    println!(
+        "{} your settings to {}.",
+        if action == LegacyAction::Move {
+            "Moved"
+        } else {
+            "Copied"
+        },
+        target.display().to_string().blue()
+    );
+    Ok(())
+}
+
+/// Offers to move the database and config out of the old folders, once per run before
+/// the config is loaded. Portable setups and data folders set by the environment are
+/// left alone, as they never used the old names.
+pub async fn migrate() -> crate::Result<()> {
+    if crate::util::get_portable_dir().is_some() || crate::fileio::is_read_only() {
+        return Ok(());
+    }
+    let data = pending(
+        dirs::data_dir().filter(|_| !crate::util::has_data_dir_override()),
+        DATABASE_FILE,
+    );
+    let config = pending(dirs::config_dir(), crate::config::CONFIG_FILE);
+    let Some((legacy, current)) = data.as_ref().or(config.as_ref()) else {
+        return Ok(());
+    };
+
+    println!(
+        "sims4modorganizer now keeps its data in {}, but yours is still in {}.",
+        current.display().to_string().bold(),
+        legacy.display().to_string().bold()
+    );
+    let action = Select::new(
+        "What do you want to do with it?",
+        vec![LegacyAction::Move, LegacyAction::Copy, LegacyAction::Keep],
+    )
+    .prompt()
+    .unwrap_or(LegacyAction::Keep);
+    if action == LegacyAction::Keep {
+        println!("Keeping the old folder; you will be asked again next time.");
+        return Ok(());
+    }
+
+    if let Some((legacy, current)) = &data {
+        transfer_data_dir(legacy, current, action).await?;
+    }
+    // Where config and data share a folder, the config file went along with the data
+    if let Some((legacy, current)) = pending(dirs::config_dir(), crate::config::CONFIG_FILE) {
+        transfer_config_file(&legacy, &current, action)?;
+    }
+    Ok(())
+}
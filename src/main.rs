@@ -1,22 +1,100 @@
 mod commands;
+mod config;
+mod datetime;
 mod entities;
+mod fileio;
+mod hasher;
+mod help;
+mod legacy_dirs;
 mod migrator;
+mod render;
+mod s4ignore;
+mod scanner;
+mod signing;
+mod style;
+mod transfer;
 mod util;
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use tracing::info;
 
 /// Program to manage Sims 4 mods
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(disable_help_subcommand = true)]
 struct Args {
+    /// Mods folder to use instead of the configured one
+    #[arg(long, global = true, value_name = "PATH")]
+    mods_dir: Option<String>,
+
+    /// When to color output
+    #[arg(long, global = true, value_enum)]
+    color: Option<config::ColorChoice>,
+
+    /// How statuses are shown
+    #[arg(long, global = true, value_enum)]
+    theme: Option<style::Theme>,
+
+    /// Keep the database and config next to the executable
+    #[arg(long, global = true)]
+    portable: bool,
+
+    /// Hide mods flagged as NSFW, e.g. while screensharing
+    #[arg(long, global = true)]
+    sfw: bool,
+
+    /// Number of files hashed at the same time
+    #[arg(long, global = true, value_name = "COUNT")]
+    hash_workers: Option<String>,
+
+    /// Limit how many MB per second hashing reads, to keep scans from slowing the machine
+    #[arg(long, global = true, value_name = "MB")]
+    io_throttle: Option<String>,
+
+    /// Timezone to show times in: local, utc, or an offset like +02:00
+    #[arg(long, global = true, value_name = "ZONE", allow_hyphen_values = true)]
+    timezone: Option<String>,
+
+    /// Only browse: report changes a command would make instead of making them
+    #[arg(long, global = true)]
+    read_only: bool,
+
     #[command(subcommand)]
     command: Command,
 }
 
+impl Args {
+    /// Config values given as command line flags
+    fn config_flags(&self) -> Vec<(config::ConfigKey, String)> {
+        let mut flags = Vec::new();
+        if let Some(mods_dir) = &self.mods_dir {
+            flags.push((config::ConfigKey::ModsDir, mods_dir.clone()));
+        }
+        if let Some(color) = self.color.and_then(|c| c.to_possible_value()) {
+            flags.push((config::ConfigKey::Color, color.get_name().to_string()));
+        }
+        if let Some(theme) = self.theme.and_then(|t| t.to_possible_value()) {
+            flags.push((config::ConfigKey::Theme, theme.get_name().to_string()));
+        }
+        if self.sfw {
+            flags.push((config::ConfigKey::Sfw, "true".to_string()));
+        }
+        if let Some(hash_workers) = &self.hash_workers {
+            flags.push((config::ConfigKey::HashWorkers, hash_workers.clone()));
+        }
+        if let Some(io_throttle) = &self.io_throttle {
+            flags.push((config::ConfigKey::IoThrottle, io_throttle.clone()));
+        }
+        if let Some(timezone) = &self.timezone {
+            flags.push((config::ConfigKey::Timezone, timezone.clone()));
+        }
+        flags
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Initializes the database
@@ -24,44 +102,87 @@ pub enum Command {
         /// Replace an existing database, if found
         #[arg(short, long)]
         force: bool,
+
+        /// Delete the replaced database instead of moving it to the trash
+        #[arg(long, requires = "force")]
+        permanent: bool,
     },
     /// Lists currently registered mods
     List {
-        /// Only show mods matching the given tags
-        #[arg(short, long, value_delimiter = ',')]
-        tags: Option<Vec<String>>,
+        #[command(flatten)]
+        options: commands::ListOptions,
+    },
+    /// Registers one mod folder without prompts, for scripts
+    Add {
+        #[command(flatten)]
+        options: commands::AddOptions,
+    },
+    /// Registers every unregistered mod folder without prompts, for first-time setup
+    Adopt {
+        /// Write the adopted mods and what they still need to a JSON file
+        #[arg(long, value_name = "PATH")]
+        report: Option<std::path::PathBuf>,
+    },
+    /// Scans for out of date or new mods in the mod directory
+    Scan {
+        #[command(flatten)]
+        options: commands::ScanOptions,
+    },
+    /// Shows everything about a single mod
+    Info {
+        /// Mod ID to show
+        #[arg(short, long)]
+        mod_id: i32,
 
-        /// Verify file data and show results
+        /// Print as JSON
         #[arg(short, long)]
-        verify: bool,
+        json: bool,
+    },
+    /// Finds which mods own files matching a name, such as one mentioned in lastException
+    Files {
+        /// Glob pattern matched against file names, or against paths in the mod's
+        /// folder if it contains a `/`. Case is ignored.
+        #[arg(short, long)]
+        find: String,
 
-        /// Show detailed information
+        /// Also search the mod folders on disk, including files scans don't track
         #[arg(short, long)]
-        details: bool,
+        disk: bool,
     },
-    /// Scans for out of date or new mods in the mod directory
-    Scan {
-        /// Verify file data for existing mods
+    /// Fills in a template with a mod's details, for pasting into bug reports
+    Report {
+        /// Mod ID to report on
         #[arg(short, long)]
-        verify: bool,
+        mod_id: i32,
 
-        /// Interactively update the database for detected changes
+        /// Template to fill in: a built-in one (bugreport, short) or
+        /// `<name>.txt` in the templates folder of the data directory
+        #[arg(short, long, default_value = "bugreport")]
+        template: String,
+    },
+    /// Verifies mod files against stored hashes. Exits with status 1 if any mod fails.
+    Verify {
+        /// Only verify the given mod ID
         #[arg(short, long)]
-        fix: bool,
+        mod_id: Option<i32>,
 
-        /// Update file hash data without changing mod metadata (dangerous)
+        #[command(flatten)]
+        filter: commands::TagFilter,
+
+        /// Stop at the first failed mod
         #[arg(short, long)]
-        sync_hashes: bool,
+        fail_fast: bool,
     },
-    /// View and delete tags
-    Tags {
-        /// Deletes a given tag. Does not delete any mods.
+    /// Explains the likely cause of a mod's failed verification and how to fix it
+    WhyFailed {
+        /// Mod ID to explain
         #[arg(short, long)]
-        delete: Option<String>,
-
-        /// Only show given tags
-        #[arg(short, long, value_delimiter = ',')]
-        tags: Option<Vec<String>>,
+        mod_id: i32,
+    },
+    /// View, describe, delete, export and import tags
+    Tags {
+        #[command(flatten)]
+        options: commands::TagsOptions,
     },
     /// Edit mod information and tags
     Edit {
@@ -69,28 +190,337 @@ pub enum Command {
         #[arg(short, long)]
         interactive: bool,
 
-        /// Mod ID to edit
+        #[command(flatten)]
+        selection: commands::EditSelection,
+
+        #[command(flatten)]
+        fields: commands::EditFields,
+    },
+    // Open the Sims 4 mod directory in a file explorer
+    OpenModDir,
+    /// Exports the mod catalog
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = commands::ExportFormat::Html)]
+        format: commands::ExportFormat,
+
+        /// File to write to. Defaults to standard output.
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Verify file data and include the results
+        #[arg(short, long)]
+        verify: bool,
+
+        /// Only include the given fields (JSON only)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        fields: Option<Vec<commands::ModField>>,
+
+        /// Sign the export with this install's key, writing the signature next to it
+        #[arg(long, requires = "output")]
+        sign: bool,
+
+        #[command(flatten)]
+        filter: commands::TagFilter,
+    },
+    /// Sets, fetches, or clears a mod's thumbnail image
+    Thumbnail {
+        /// Mod ID to update
         #[arg(short, long)]
+        mod_id: i32,
+
+        /// Image file path or URL. Defaults to the current thumbnail.
+        image: Option<String>,
+
+        /// Download a thumbnail URL into the data directory
+        #[arg(short, long)]
+        fetch: bool,
+
+        /// Remove the thumbnail
+        #[arg(short, long)]
+        clear: bool,
+    },
+    /// Checks every mod's source URL for dead links, moved pages, and unreachable hosts
+    CheckLinks {
+        #[command(flatten)]
+        limits: commands::LinkCheckLimits,
+
+        /// Ask to update source URLs that moved permanently
+        #[arg(short, long)]
+        fix: bool,
+    },
+    /// Downloads a mod's file from a direct link and stages it for install, or checks
+    /// every mod for new files as its update channel allows
+    Fetch {
+        /// Mod ID to fetch
+        #[arg(short, long, required_unless_present = "all")]
         mod_id: Option<i32>,
 
-        /// Mod name to edit
+        /// Check every mod with a download URL, following each mod's update channel
+        #[arg(short, long, conflicts_with_all = ["mod_id", "url"])]
+        all: bool,
+
+        /// URL to download instead of the mod's download or source URL
+        #[arg(short, long)]
+        url: Option<String>,
+
+        /// Install a downloaded .package or .ts4script file into the mod's folder.
+        /// With --all, new files of auto-fetch mods are installed without asking.
         #[arg(short, long)]
-        name: Option<String>,
+        install: bool,
 
-        /// Source URL to set
+        /// Install even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists mods whose recheck date has passed
+    Reminders {
+        /// Also show mods with upcoming recheck dates
         #[arg(short, long)]
-        source_url: Option<String>,
+        all: bool,
+    },
+    /// Saves, lists, and deletes named tag queries
+    Query {
+        #[command(subcommand)]
+        action: commands::QueryAction,
+    },
+    /// Shows mods added, removed, or changed between two sync bundles or JSON exports
+    Diff {
+        /// Older and newer sync bundle or JSON export
+        #[arg(
+            num_args = 2,
+            value_names = ["OLD", "NEW"],
+            required_unless_present = "against",
+            conflicts_with = "against"
+        )]
+        exports: Vec<std::path::PathBuf>,
 
-        /// Tags to set
+        /// Compare a sync bundle or JSON export against the current database instead
+        #[arg(short, long, value_name = "FILE")]
+        against: Option<std::path::PathBuf>,
+    },
+    /// Gets, sets, lists, and unsets settings
+    Config {
+        #[command(subcommand)]
+        action: commands::ConfigAction,
+    },
+    /// Creates, lists, and compares point-in-time snapshots of the catalog
+    Snapshot {
+        #[command(subcommand)]
+        action: commands::SnapshotAction,
+    },
+    /// Compares mod folders with another install through export bundles
+    Sync {
+        #[command(subcommand)]
+        action: commands::SyncAction,
+    },
+    /// Creates, lists, and restores checksummed copies of the database
+    Backup {
+        #[command(subcommand)]
+        action: commands::BackupAction,
+    },
+    /// Adds, lists, and removes additional folders holding mods
+    Roots {
+        #[command(subcommand)]
+        action: commands::RootsAction,
+    },
+    /// Manages the list of owned expansion, game, and stuff packs
+    Packs {
+        /// Packs to mark as owned
         #[arg(short, long, value_delimiter = ',')]
-        tags: Option<Vec<String>>,
+        add: Option<Vec<String>>,
 
-        /// Version to set
-        #[arg(short = 'v', long)]
-        mod_version: Option<String>,
+        /// Packs to mark as not owned
+        #[arg(short, long, value_delimiter = ',')]
+        remove: Option<Vec<String>>,
+    },
+    /// Manages version probes that read a mod's installed version from its files
+    Probe {
+        #[command(subcommand)]
+        action: commands::ProbeAction,
+    },
+    /// Prints or writes counts for monitoring: total mods, failing verification, last scan, new folders
+    Status {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = commands::StatusFormat::Json)]
+        format: commands::StatusFormat,
+
+        /// Write the status to a file instead of printing it
+        #[arg(short, long, value_name = "FILE")]
+        write: Option<std::path::PathBuf>,
+
+        /// Also verify every mod's files to count failures
+        #[arg(short, long)]
+        verify: bool,
+    },
+    /// Walks through the checks worth running after a game update
+    Postpatch {
+        /// Offer to disable script mods until they are updated
+        #[arg(short, long)]
+        disable: bool,
+
+        /// Re-enable script mods disabled by an earlier --disable, except broken ones
+        #[arg(short, long, conflicts_with = "disable")]
+        enable: bool,
+
+        /// Rename script files even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Shows the game's cache files with their sizes and deletes them after confirmation
+    ClearCache {
+        /// Delete the caches even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Checks the mod folder against the game's recommended limits. Exits with status 1 on warnings.
+    Doctor {
+        #[command(flatten)]
+        limits: commands::DoctorLimits,
+    },
+    /// Moves mod folders into a new layout and updates the database to match
+    Organize {
+        /// Built-in layout to use
+        #[arg(short, long, value_enum, default_value_t = commands::OrganizeScheme::Creator)]
+        by: commands::OrganizeScheme,
+
+        /// Custom layout, e.g. "{creator}/{folder}". Placeholders: {folder}, {name}, {creator}, {tag}, {source_type}
+        #[arg(short, long, conflicts_with = "by")]
+        template: Option<String>,
+
+        /// Only show the proposed moves
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Move mods even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Marks a mod as broken, e.g. after a game patch
+    MarkBroken {
+        /// Mod ID to mark
+        #[arg(short, long)]
+        mod_id: i32,
+
+        /// Why the mod is broken
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+    /// Clears a mod's broken status
+    MarkFixed {
+        /// Mod ID to mark
+        #[arg(short, long)]
+        mod_id: i32,
+    },
+    /// Moves a mod's folder to the trash and deletes its database entry
+    Remove {
+        /// Mod ID to remove
+        #[arg(short, long)]
+        mod_id: i32,
+
+        /// Delete the folder instead of moving it to the trash
+        #[arg(long)]
+        permanent: bool,
+
+        /// Remove the mod even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Disables or removes every mod by a creator, found by the creator's name or domain
+    /// in the mods' source URLs
+    PurgeCreator {
+        /// Creator name, such as a Patreon or Tumblr name, or a source domain
+        creator: String,
+
+        /// Remove the mods instead of disabling their files
+        #[arg(long)]
+        remove: bool,
+
+        /// Delete the folders instead of moving them to the trash
+        #[arg(long, requires = "remove")]
+        permanent: bool,
+
+        /// Turn the files disabled by an earlier purge back on
+        #[arg(long, conflicts_with = "remove")]
+        enable: bool,
+
+        /// Change mod files even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists mods untouched for a long time, with their sizes
+    Stale {
+        /// Minimum time since a mod was last added, updated, or edited, e.g. 90d, 6m, or 1y
+        #[arg(short, long, value_parser = commands::parse_age, default_value = "1y")]
+        older_than: chrono::Duration,
+
+        /// Also count file modification times as activity
+        #[arg(short, long)]
+        mtimes: bool,
+
+        /// Review each stale mod, choosing to keep, archive, or remove it
+        #[arg(short, long)]
+        review: bool,
+
+        /// Delete removed mods instead of moving them to the trash
+        #[arg(long, requires = "review")]
+        permanent: bool,
+
+        /// Review mods even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists files duplicated across mods, optionally replacing them with hardlinks
+    Dedupe {
+        /// Replace duplicates with hardlinks to their originals, after confirmation
+        #[arg(long)]
+        hardlink: bool,
+
+        /// Link files even while the game is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Manages folders that scans never report as new mods
+    Ignored {
+        #[command(subcommand)]
+        action: commands::IgnoredAction,
+    },
+    /// Shows files and folders moved to the system trash
+    Trash {
+        #[command(subcommand)]
+        action: commands::TrashAction,
+    },
+    /// Restores a deleted mod's database entry
+    RestoreEntry {
+        /// Mod ID to restore
+        #[arg(short, long)]
+        mod_id: i32,
+    },
+    /// Prints the JSON Schema of a JSON format the tool writes, or lists the formats
+    Schema {
+        /// Format to print the schema of
+        #[arg(value_enum)]
+        name: Option<commands::SchemaName>,
+    },
+    /// Shows a workflow guide, or a subcommand's help with examples
+    Help {
+        /// Guide (setup, patch, tagging) or subcommand
+        topic: Option<String>,
+    },
+    /// Shows the journal of mod additions, updates, edits, and deletions
+    Log {
+        /// Only show entries for the given mod ID
+        #[arg(short, long)]
+        mod_id: Option<i32>,
+
+        /// Only show entries on or after the given date (YYYY-MM-DD)
+        #[arg(short, long, value_parser = commands::parse_date)]
+        since: Option<chrono::NaiveDate>,
+
+        /// Only show entries for the given action
+        #[arg(short, long, value_enum)]
+        action: Option<commands::JournalAction>,
     },
-    // Open the Sims 4 mod directory in a file explorer
-    OpenModDir,
 }
 
 #[tokio::main]
@@ -100,55 +530,235 @@ async fn main() -> Result<()> {
         "Starting sims4modorganizer version {}",
         env!("CARGO_PKG_VERSION")
     );
-    let args = Args::parse();
-    match args.command {
-        Command::Initialize { force } => util::init_database(force).await,
-        Command::List {
-            tags,
-            verify,
-            details,
-        } => commands::list(tags, verify, details).await,
-        Command::Scan {
-            verify,
-            fix,
-            sync_hashes,
-        } => {
-            if fix && sync_hashes {
+    let matches = help::with_examples(Args::command()).get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if args.portable {
+        util::enable_portable_mode();
+    }
+    if args.read_only {
+        fileio::enable_read_only();
+    }
+    // The config may still be in the old folder, so this comes before loading it
+    if let Err(e) = legacy_dirs::migrate().await {
+        eprintln!("Could not move your data to its new folder: {}", e);
+    }
+    if let Err(e) = config::init(&args.config_flags()) {
+        eprintln!("{}", e);
+        // The config command stays usable so a broken config can be fixed
+        if !matches!(args.command, Command::Config { .. }) {
+            std::process::exit(1);
+        }
+    }
+    let result = match args.command {
+        Command::Initialize { force, permanent } => util::init_database(force, permanent).await,
+        Command::List { options } => {
+            if options.deleted && !options.filter.is_empty() {
+                eprintln!("Deleted mods cannot be filtered by tag.");
+                std::process::exit(1);
+            }
+            if options.deleted && options.broken {
+                eprintln!("Deleted and broken mod views are mutually exclusive.");
+                std::process::exit(1);
+            }
+            commands::list(options).await
+        }
+        Command::Add { options } => commands::add(options).await,
+        Command::Adopt { report } => commands::adopt(report).await,
+        Command::Scan { mut options } => {
+            let config = config::get();
+            options.verify = options.verify || options.report.is_some() || config.scan_verify();
+            options.fix = options.fix || (!options.sync_hashes && config.scan_fix());
+            if options.fix && options.sync_hashes {
                 eprintln!("Interactive fix and hash sync are mutually exclusive.");
                 std::process::exit(1);
             }
-            commands::scan(None, verify, fix, sync_hashes).await
+            if options.fix || options.sync_hashes {
+                util::ensure_game_not_running(options.force)?;
+            }
+            commands::scan(None, options).await?;
+            Ok(())
+        }
+        Command::Info { mod_id, json } => commands::info(mod_id, json).await,
+        Command::Files { find, disk } => commands::find_mod_files(find, disk).await,
+        Command::Report { mod_id, template } => commands::report(mod_id, template).await,
+        Command::WhyFailed { mod_id } => commands::why_failed(mod_id).await,
+        Command::Verify {
+            mod_id,
+            filter,
+            fail_fast,
+        } => {
+            if mod_id.is_some() && !filter.is_empty() {
+                eprintln!("Mod ID and tag filters are mutually exclusive.");
+                std::process::exit(1);
+            }
+            if !commands::verify(mod_id, filter, fail_fast).await? {
+                std::process::exit(1);
+            }
+            Ok(())
         }
-        Command::Tags { delete, tags } => {
-            if delete.is_some() && tags.is_some() {
-                eprintln!("Delete and show tag options are mutually exclusive.");
+        Command::Tags { options } => {
+            if options.action_count() > 1 {
+                eprintln!(
+                    "Delete, show, create, set, export, import, stats, related and combine tag options are mutually exclusive."
+                );
                 std::process::exit(1);
             }
-            commands::tags(delete, tags).await
+            commands::tags(options).await
         }
         Command::Edit {
             interactive,
-            mod_id,
-            name,
-            source_url,
-            tags,
-            mod_version,
+            selection,
+            fields,
         } => {
             if !interactive {
-                if mod_id.is_none() {
-                    eprintln!("Mod ID required to edit non-interactively");
+                if selection.is_empty() {
+                    eprintln!("Mod ID or filter required to edit non-interactively");
                     std::process::exit(1);
-                } else if name.is_none()
-                    && source_url.is_none()
-                    && tags.is_none()
-                    && mod_version.is_none()
-                {
+                } else if fields.is_empty() {
                     eprintln!("At least one field to edit must be provided");
                     std::process::exit(1);
                 }
             }
-            commands::edit(interactive, mod_id, name, source_url, tags, mod_version).await
+            commands::edit(interactive, selection, fields).await
         }
         Command::OpenModDir => opener::open(util::get_sims_mod_dir()?).map_err(|e| e.into()),
+        Command::Export {
+            format,
+            output,
+            verify,
+            fields,
+            filter,
+            sign,
+        } => commands::export(format, output, verify, fields, filter, sign).await,
+        Command::Thumbnail {
+            mod_id,
+            image,
+            fetch,
+            clear,
+        } => {
+            if clear && (image.is_some() || fetch) {
+                eprintln!("Clearing a thumbnail cannot be combined with setting one.");
+                std::process::exit(1);
+            }
+            commands::thumbnail(mod_id, image, fetch, clear).await
+        }
+        Command::CheckLinks { limits, fix } => commands::check_links(limits, fix).await,
+        Command::Fetch {
+            mod_id,
+            all: _,
+            url,
+            install,
+            force,
+        } => {
+            if install {
+                util::ensure_game_not_running(force)?;
+            }
+            commands::fetch(mod_id, url, install).await
+        }
+        Command::Reminders { all } => commands::reminders(all).await,
+        Command::Status {
+            format,
+            write,
+            verify,
+        } => commands::status(format, write, verify).await,
+        Command::Postpatch {
+            disable,
+            enable,
+            force,
+        } => {
+            if disable || enable {
+                util::ensure_game_not_running(force)?;
+            }
+            commands::postpatch(disable, enable).await
+        }
+        Command::ClearCache { force } => {
+            util::ensure_game_not_running(force)?;
+            commands::clear_cache()
+        }
+        Command::Doctor { limits } => {
+            if !commands::doctor(limits).await? {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Organize {
+            by,
+            template,
+            dry_run,
+            force,
+        } => {
+            if !dry_run {
+                util::ensure_game_not_running(force)?;
+            }
+            commands::organize(by, template, dry_run).await
+        }
+        Command::MarkBroken { mod_id, reason } => commands::mark_broken(mod_id, reason).await,
+        Command::MarkFixed { mod_id } => commands::mark_fixed(mod_id).await,
+        Command::Query { action } => commands::query(action).await,
+        Command::Diff { exports, against } => commands::diff(exports, against).await,
+        Command::Config { action } => commands::config(action).await,
+        Command::Snapshot { action } => commands::snapshot(action).await,
+        Command::Sync { action } => commands::sync(action).await,
+        Command::Roots { action } => commands::roots(action).await,
+        Command::Backup { action } => commands::backup(action).await,
+        Command::Packs { add, remove } => commands::packs(add, remove).await,
+        Command::Probe { action } => commands::probe(action).await,
+        Command::Remove {
+            mod_id,
+            permanent,
+            force,
+        } => {
+            util::ensure_game_not_running(force)?;
+            commands::remove(mod_id, permanent).await
+        }
+        Command::PurgeCreator {
+            creator,
+            remove,
+            permanent,
+            enable,
+            force,
+        } => {
+            util::ensure_game_not_running(force)?;
+            commands::purge_creator(creator, remove, permanent, enable).await
+        }
+        Command::Stale {
+            older_than,
+            mtimes,
+            review,
+            permanent,
+            force,
+        } => {
+            if review {
+                util::ensure_game_not_running(force)?;
+            }
+            commands::stale(older_than, mtimes, review, permanent).await
+        }
+        Command::Dedupe { hardlink, force } => {
+            if hardlink {
+                util::ensure_game_not_running(force)?;
+            }
+            commands::dedupe(hardlink).await
+        }
+        Command::Ignored { action } => commands::ignored(action).await,
+        Command::Trash { action } => commands::trash(action).await,
+        Command::RestoreEntry { mod_id } => commands::restore_entry(mod_id).await,
+        Command::Schema { name } => commands::schema(name),
+        Command::Help { topic } => help::help(topic, help::with_examples(Args::command())),
+        Command::Log {
+            mod_id,
+            since,
+            action,
+        } => commands::log(mod_id, since, action).await,
+    };
+    util::record_database_checksum().await;
+    if let Err(e) = &result {
+        if let Some(refused) = util::read_only_refusal(e.as_ref()) {
+            println!(
+                "Read-only mode: this command {}. Nothing was changed.",
+                refused
+            );
+            return Ok(());
+        }
     }
+    result
 }
@@ -32,6 +32,7 @@ impl MigrationTrait for ModTagTableMigration {
 }
 
 #[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
 pub enum Tag {
     Table,
     Id,
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct ModProtectedMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModProtectedMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(
+                        ColumnDef::new(ModProtected::Protected)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModProtected::Protected)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModProtected {
+    Protected,
+}
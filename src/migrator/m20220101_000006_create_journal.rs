@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct JournalTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for JournalTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Journal::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Journal::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Journal::Timestamp).timestamp().not_null())
+                    .col(ColumnDef::new(Journal::Action).string().not_null())
+                    .col(ColumnDef::new(Journal::ModId).integer().not_null())
+                    .col(ColumnDef::new(Journal::ModName).string().not_null())
+                    .col(ColumnDef::new(Journal::Directory).string().not_null())
+                    .col(ColumnDef::new(Journal::SourceUrl).string().not_null())
+                    .col(ColumnDef::new(Journal::Version).string().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Journal::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Journal {
+    Table,
+    Id,
+    Timestamp,
+    Action,
+    ModId,
+    ModName,
+    Directory,
+    SourceUrl,
+    Version,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct ModDeletedAtMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModDeletedAtMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(ColumnDef::new(ModDeletedAt::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModDeletedAt::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModDeletedAt {
+    DeletedAt,
+}
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+use crate::commands::SourceType;
+
+#[derive(DeriveMigrationName)]
+pub struct ModSourceTypeMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModSourceTypeMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(
+                        ColumnDef::new(ModSourceType::SourceType)
+                            .string()
+                            .not_null()
+                            .default("personal"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing mods are classified by the same host matching as new ones, so a URL
+        // that merely mentions a site elsewhere isn't counted as hosted there
+        let db = manager.get_connection();
+        let rows = db
+            .query_all(
+                db.get_database_backend().build(
+                    Query::select()
+                        .columns([SimsMod::Id, SimsMod::SourceUrl])
+                        .from(SimsMod::Table),
+                ),
+            )
+            .await?;
+        for row in rows {
+            let id: i32 = row.try_get("", &SimsMod::Id.to_string())?;
+            let source_url: String = row.try_get("", &SimsMod::SourceUrl.to_string())?;
+            let source_type = SourceType::name_for_url(&source_url);
+            if source_type == SourceType::Personal.as_str() {
+                continue;
+            }
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(SimsMod::Table)
+                        .value(ModSourceType::SourceType, source_type)
+                        .and_where(Expr::col(SimsMod::Id).eq(id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModSourceType::SourceType)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModSourceType {
+    SourceType,
+}
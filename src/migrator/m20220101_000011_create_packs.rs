@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct PacksTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for PacksTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ModRequiredPack::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ModRequiredPack::ModId).integer().not_null())
+                    .col(ColumnDef::new(ModRequiredPack::Pack).string().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-mod_required_pack-mod_id")
+                            .from(ModRequiredPack::Table, ModRequiredPack::ModId)
+                            .to(SimsMod::Table, SimsMod::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ModRequiredPack::ModId)
+                            .col(ModRequiredPack::Pack),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(OwnedPack::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OwnedPack::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OwnedPack::Pack)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OwnedPack::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ModRequiredPack::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModRequiredPack {
+    Table,
+    ModId,
+    Pack,
+}
+
+#[derive(DeriveIden)]
+enum OwnedPack {
+    Table,
+    Id,
+    Pack,
+}
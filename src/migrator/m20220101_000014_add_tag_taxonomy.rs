@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000002_create_tags::Tag;
+
+#[derive(DeriveMigrationName)]
+pub struct TagTaxonomyMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for TagTaxonomyMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(ColumnDef::new(TagTaxonomy::Description).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(ColumnDef::new(TagTaxonomy::Color).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(ColumnDef::new(TagTaxonomy::ParentId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(
+                        ColumnDef::new(TagTaxonomy::Explicit)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            TagTaxonomy::Explicit,
+            TagTaxonomy::ParentId,
+            TagTaxonomy::Color,
+            TagTaxonomy::Description,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Tag::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TagTaxonomy {
+    Description,
+    Color,
+    ParentId,
+    Explicit,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct SnapshotTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for SnapshotTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Snapshot::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Snapshot::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Snapshot::Name).string().not_null())
+                    .col(ColumnDef::new(Snapshot::Created).timestamp().not_null())
+                    .col(ColumnDef::new(Snapshot::Data).text().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Snapshot::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Snapshot {
+    Table,
+    Id,
+    Name,
+    Created,
+    Data,
+}
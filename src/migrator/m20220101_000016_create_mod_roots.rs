@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct ModRootTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModRootTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ModRoot::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ModRoot::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ModRoot::Name)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ModRoot::Path)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(ColumnDef::new(ModRootColumn::RootId).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModRootColumn::RootId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ModRoot::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModRoot {
+    Table,
+    Id,
+    Name,
+    Path,
+}
+
+#[derive(DeriveIden)]
+enum ModRootColumn {
+    RootId,
+}
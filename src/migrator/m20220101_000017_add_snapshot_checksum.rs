@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000015_create_snapshots::Snapshot;
+
+#[derive(DeriveMigrationName)]
+pub struct SnapshotChecksumMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for SnapshotChecksumMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Snapshot::Table)
+                    .add_column(ColumnDef::new(SnapshotChecksum::Checksum).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Snapshot::Table)
+                    .drop_column(SnapshotChecksum::Checksum)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SnapshotChecksum {
+    Checksum,
+}
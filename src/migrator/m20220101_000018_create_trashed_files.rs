@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct TrashedFileTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for TrashedFileTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TrashedFile::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TrashedFile::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TrashedFile::Path).string().not_null())
+                    .col(ColumnDef::new(TrashedFile::ModId).integer().null())
+                    .col(ColumnDef::new(TrashedFile::ModName).string().null())
+                    .col(
+                        ColumnDef::new(TrashedFile::TrashedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TrashedFile::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TrashedFile {
+    Table,
+    Id,
+    Path,
+    ModId,
+    ModName,
+    TrashedAt,
+}
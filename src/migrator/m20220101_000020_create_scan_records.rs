@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct ScanRecordTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ScanRecordTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScanRecord::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ScanRecord::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ScanRecord::Hostname).string().not_null())
+                    .col(ColumnDef::new(ScanRecord::Username).string().not_null())
+                    .col(ColumnDef::new(ScanRecord::ModsDir).string().not_null())
+                    .col(ColumnDef::new(ScanRecord::ScannedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ScanRecord::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ScanRecord {
+    Table,
+    Id,
+    Hostname,
+    Username,
+    ModsDir,
+    ScannedAt,
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct VersionProbeTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for VersionProbeTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VersionProbe::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VersionProbe::ModId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VersionProbe::File).string().not_null())
+                    .col(ColumnDef::new(VersionProbe::Pattern).string().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-version_probe-mod_id")
+                            .from(VersionProbe::Table, VersionProbe::ModId)
+                            .to(SimsMod::Table, SimsMod::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VersionProbe::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VersionProbe {
+    Table,
+    ModId,
+    File,
+    Pattern,
+}
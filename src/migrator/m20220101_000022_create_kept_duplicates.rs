@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct KeptDuplicateTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for KeptDuplicateTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(KeptDuplicate::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(KeptDuplicate::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(KeptDuplicate::ModId).integer().not_null())
+                    .col(ColumnDef::new(KeptDuplicate::File).string().not_null())
+                    .col(ColumnDef::new(KeptDuplicate::Hash).string().not_null())
+                    .col(ColumnDef::new(KeptDuplicate::KeptAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-kept_duplicate-mod_id")
+                            .from(KeptDuplicate::Table, KeptDuplicate::ModId)
+                            .to(SimsMod::Table, SimsMod::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(KeptDuplicate::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum KeptDuplicate {
+    Table,
+    Id,
+    ModId,
+    File,
+    Hash,
+    KeptAt,
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct PlaySessionTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for PlaySessionTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlaySession::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PlaySession::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PlaySession::PlayedAt)
+                            .timestamp()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(PlaySession::Marker).string().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PlaySession::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlaySession {
+    Table,
+    Id,
+    PlayedAt,
+    Marker,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct GameVersionTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for GameVersionTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GameVersion::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GameVersion::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GameVersion::Version).string().not_null())
+                    .col(
+                        ColumnDef::new(GameVersion::DetectedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GameVersion::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GameVersion {
+    Table,
+    Id,
+    Version,
+    DetectedAt,
+}
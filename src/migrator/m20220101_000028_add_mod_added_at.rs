@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct ModAddedAtMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModAddedAtMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(ColumnDef::new(ModAddedAt::AddedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+        // Existing mods were added when the journal first recorded them, or
+        // at their last update if the journal predates them
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE sims_mod SET added_at = COALESCE(
+                    (SELECT MIN(timestamp) FROM journal
+                        WHERE journal.mod_id = sims_mod.id AND journal.action = 'added'),
+                    updated)",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModAddedAt::AddedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModAddedAt {
+    AddedAt,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000022_create_kept_duplicates::KeptDuplicate;
+
+#[derive(DeriveMigrationName)]
+pub struct KeptDuplicateLinkMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for KeptDuplicateLinkMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(KeptDuplicate::Table)
+                    .add_column(
+                        ColumnDef::new(KeptDuplicateLink::LinkedModId)
+                            .integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(KeptDuplicate::Table)
+                    .add_column(
+                        ColumnDef::new(KeptDuplicateLink::LinkedFile)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(KeptDuplicate::Table)
+                    .add_column(
+                        ColumnDef::new(KeptDuplicateLink::LinkedAt)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            KeptDuplicateLink::LinkedAt,
+            KeptDuplicateLink::LinkedFile,
+            KeptDuplicateLink::LinkedModId,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(KeptDuplicate::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
+enum KeptDuplicateLink {
+    LinkedModId,
+    LinkedFile,
+    LinkedAt,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct ModCompositionMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModCompositionMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(
+                        ColumnDef::new(ModComposition::PackageCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(
+                        ColumnDef::new(ModComposition::ScriptCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // Existing mods are counted from their stored hashes. LIKE ignores ASCII case.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE sims_mod SET
+                    package_count = (SELECT COUNT(*) FROM mod_hash
+                        WHERE mod_hash.mod_id = sims_mod.id AND mod_hash.file LIKE '%.package'),
+                    script_count = (SELECT COUNT(*) FROM mod_hash
+                        WHERE mod_hash.mod_id = sims_mod.id AND mod_hash.file LIKE '%.ts4script')",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModComposition::ScriptCount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModComposition::PackageCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModComposition {
+    PackageCount,
+    ScriptCount,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000006_create_journal::Journal;
+
+#[derive(DeriveMigrationName)]
+pub struct JournalDetailsMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for JournalDetailsMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Journal::Table)
+                    .add_column(ColumnDef::new(JournalDetails::Details).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Journal::Table)
+                    .drop_column(JournalDetails::Details)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JournalDetails {
+    Details,
+}
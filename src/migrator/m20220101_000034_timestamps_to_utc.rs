@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+/// Timestamp columns, which were stored with the offset of the machine that wrote them
+const TIMESTAMP_COLUMNS: &[(&str, &str)] = &[
+    ("sims_mod", "updated"),
+    ("sims_mod", "deleted_at"),
+    ("sims_mod", "broken_at"),
+    ("sims_mod", "added_at"),
+    ("journal", "timestamp"),
+    ("snapshot", "created"),
+    ("trashed_file", "trashed_at"),
+    ("scan_record", "scanned_at"),
+    ("kept_duplicate", "kept_at"),
+    ("kept_duplicate", "linked_at"),
+    ("play_session", "played_at"),
+    ("game_version", "detected_at"),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct TimestampsToUtcMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for TimestampsToUtcMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite's date functions apply the stored offset, so this rewrites every
+        // timestamp as the same instant in UTC. Values it can't read are left alone.
+        for (table, column) in TIMESTAMP_COLUMNS.iter() {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "UPDATE {table} SET {column} = strftime('%Y-%m-%dT%H:%M:%f+00:00', {column})
+                        WHERE strftime('%Y-%m-%dT%H:%M:%f+00:00', {column}) IS NOT NULL"
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // UTC timestamps keep their offset, so older versions read them correctly
+        Ok(())
+    }
+}
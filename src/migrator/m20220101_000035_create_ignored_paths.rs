@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct IgnoredPathTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for IgnoredPathTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IgnoredPath::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IgnoredPath::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(IgnoredPath::RootId).integer().null())
+                    .col(ColumnDef::new(IgnoredPath::Path).string().not_null())
+                    .col(
+                        ColumnDef::new(IgnoredPath::IgnoredAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IgnoredPath::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IgnoredPath {
+    Table,
+    Id,
+    RootId,
+    Path,
+    IgnoredAt,
+}
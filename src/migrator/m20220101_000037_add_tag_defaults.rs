@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000002_create_tags::Tag;
+
+#[derive(DeriveMigrationName)]
+pub struct TagDefaultsMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for TagDefaultsMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(
+                        ColumnDef::new(TagDefaults::DefaultSourceUrl)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(
+                        ColumnDef::new(TagDefaults::DefaultUpdateChannel)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tag::Table)
+                    .add_column(
+                        ColumnDef::new(TagDefaults::DefaultNsfw)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            TagDefaults::DefaultNsfw,
+            TagDefaults::DefaultUpdateChannel,
+            TagDefaults::DefaultSourceUrl,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Tag::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
+enum TagDefaults {
+    DefaultSourceUrl,
+    DefaultUpdateChannel,
+    DefaultNsfw,
+}
@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_create_sims_mods::SimsMod;
+
+#[derive(DeriveMigrationName)]
+pub struct ModFileCountMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for ModFileCountMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .add_column(
+                        ColumnDef::new(ModFileCount::FileCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // Existing mods expect as many files as they have stored hashes
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE sims_mod SET file_count = (SELECT COUNT(*) FROM mod_hash
+                    WHERE mod_hash.mod_id = sims_mod.id)",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SimsMod::Table)
+                    .drop_column(ModFileCount::FileCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModFileCount {
+    FileCount,
+}
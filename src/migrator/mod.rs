@@ -4,6 +4,40 @@ mod m20220101_000001_create_sims_mods;
 mod m20220101_000002_create_tags;
 mod m20220101_000003_create_hashes;
 mod m20220101_000004_create_mod_tag_relations;
+mod m20220101_000005_add_mod_protected;
+mod m20220101_000006_create_journal;
+mod m20220101_000007_add_mod_deleted_at;
+mod m20220101_000008_add_mod_thumbnail;
+mod m20220101_000009_add_mod_source_type;
+mod m20220101_000010_add_mod_recheck_after;
+mod m20220101_000011_create_packs;
+mod m20220101_000012_create_saved_queries;
+mod m20220101_000013_add_mod_broken;
+mod m20220101_000014_add_tag_taxonomy;
+mod m20220101_000015_create_snapshots;
+mod m20220101_000016_create_mod_roots;
+mod m20220101_000017_add_snapshot_checksum;
+mod m20220101_000018_create_trashed_files;
+mod m20220101_000019_add_mod_frozen;
+mod m20220101_000020_create_scan_records;
+mod m20220101_000021_create_version_probes;
+mod m20220101_000022_create_kept_duplicates;
+mod m20220101_000023_add_mod_rating;
+mod m20220101_000024_create_play_sessions;
+mod m20220101_000025_create_game_versions;
+mod m20220101_000026_add_mod_download_url;
+mod m20220101_000027_add_mod_parent;
+mod m20220101_000028_add_mod_added_at;
+mod m20220101_000029_add_kept_duplicate_link;
+mod m20220101_000030_add_mod_nsfw;
+mod m20220101_000031_add_mod_update_channel;
+mod m20220101_000032_add_mod_composition;
+mod m20220101_000033_add_journal_details;
+mod m20220101_000034_timestamps_to_utc;
+mod m20220101_000035_create_ignored_paths;
+mod m20220101_000036_add_mod_archive_hash;
+mod m20220101_000037_add_tag_defaults;
+mod m20220101_000038_add_mod_file_count;
 
 pub struct Migrator;
 
@@ -15,6 +49,40 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000002_create_tags::ModTagTableMigration),
             Box::new(m20220101_000003_create_hashes::ModHashesTableMigration),
             Box::new(m20220101_000004_create_mod_tag_relations::ModTagRelationsTableMigration),
+            Box::new(m20220101_000005_add_mod_protected::ModProtectedMigration),
+            Box::new(m20220101_000006_create_journal::JournalTableMigration),
+            Box::new(m20220101_000007_add_mod_deleted_at::ModDeletedAtMigration),
+            Box::new(m20220101_000008_add_mod_thumbnail::ModThumbnailMigration),
+            Box::new(m20220101_000009_add_mod_source_type::ModSourceTypeMigration),
+            Box::new(m20220101_000010_add_mod_recheck_after::ModRecheckAfterMigration),
+            Box::new(m20220101_000011_create_packs::PacksTableMigration),
+            Box::new(m20220101_000012_create_saved_queries::SavedQueryTableMigration),
+            Box::new(m20220101_000013_add_mod_broken::ModBrokenMigration),
+            Box::new(m20220101_000014_add_tag_taxonomy::TagTaxonomyMigration),
+            Box::new(m20220101_000015_create_snapshots::SnapshotTableMigration),
+            Box::new(m20220101_000016_create_mod_roots::ModRootTableMigration),
+            Box::new(m20220101_000017_add_snapshot_checksum::SnapshotChecksumMigration),
+            Box::new(m20220101_000018_create_trashed_files::TrashedFileTableMigration),
+            Box::new(m20220101_000019_add_mod_frozen::ModFrozenMigration),
+            Box::new(m20220101_000020_create_scan_records::ScanRecordTableMigration),
+            Box::new(m20220101_000021_create_version_probes::VersionProbeTableMigration),
+            Box::new(m20220101_000022_create_kept_duplicates::KeptDuplicateTableMigration),
+            Box::new(m20220101_000023_add_mod_rating::ModRatingMigration),
+            Box::new(m20220101_000024_create_play_sessions::PlaySessionTableMigration),
+            Box::new(m20220101_000025_create_game_versions::GameVersionTableMigration),
+            Box::new(m20220101_000026_add_mod_download_url::ModDownloadUrlMigration),
+            Box::new(m20220101_000027_add_mod_parent::ModParentMigration),
+            Box::new(m20220101_000028_add_mod_added_at::ModAddedAtMigration),
+            Box::new(m20220101_000029_add_kept_duplicate_link::KeptDuplicateLinkMigration),
+            Box::new(m20220101_000030_add_mod_nsfw::ModNsfwMigration),
+            Box::new(m20220101_000031_add_mod_update_channel::ModUpdateChannelMigration),
+            Box::new(m20220101_000032_add_mod_composition::ModCompositionMigration),
+            Box::new(m20220101_000033_add_journal_details::JournalDetailsMigration),
+            Box::new(m20220101_000034_timestamps_to_utc::TimestampsToUtcMigration),
+            Box::new(m20220101_000035_create_ignored_paths::IgnoredPathTableMigration),
+            Box::new(m20220101_000036_add_mod_archive_hash::ModArchiveHashMigration),
+            Box::new(m20220101_000037_add_tag_defaults::TagDefaultsMigration),
+            Box::new(m20220101_000038_add_mod_file_count::ModFileCountMigration),
         ]
     }
 }
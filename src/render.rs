@@ -0,0 +1,414 @@
+//! Box-drawing layout for detailed output, fitted to the terminal's width.
+//! Output is built as a [`Tree`] of [`Node`]s and drawn in one go.
+//! Values too long for the terminal are wrapped below their label, titles are
+//! cut short with an ellipsis, and labels of sibling entries are aligned.
+
+use std::fmt::Write;
+
+use colored::{ColoredString, Colorize};
+use terminal_size::{terminal_size, Width};
+
+use crate::style::{self, Status};
+
+/// Narrowest space worth wrapping a value into; anything less only makes a column of letters
+const MIN_VALUE_WIDTH: usize = 16;
+
+/// Width of the terminal, or `None` when output is not a terminal
+pub fn terminal_width() -> Option<usize> {
+    terminal_size().map(|(Width(width), _)| width as usize)
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Cuts text to at most `width` characters, ending in an ellipsis when shortened
+pub fn truncate(text: &str, width: usize) -> String {
+    if text_width(text) <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Splits text into lines of at most `width` characters, breaking at spaces
+/// where possible and inside long words such as URLs where not
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let mut word = word;
+        loop {
+            let separator = usize::from(!line.is_empty());
+            if text_width(&line) + separator + text_width(word) <= width {
+                if separator > 0 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                break;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                continue;
+            }
+            let split = word
+                .char_indices()
+                .nth(width)
+                .map(|(i, _)| i)
+                .unwrap_or(word.len());
+            lines.push(word[..split].to_string());
+            word = &word[split..];
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// One entry of a tree: a label, a value, or both, with nested entries below it
+#[derive(Clone, Debug, Default)]
+pub struct Node {
+    label: String,
+    label_width: usize,
+    value: Option<String>,
+    status: Option<Status>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    /// An entry with a bold label, such as `Version:`
+    pub fn field(label: &str) -> Node {
+        Node::styled(label.bold())
+    }
+
+    /// An entry with an already styled label
+    pub fn styled(label: ColoredString) -> Node {
+        Node {
+            label_width: text_width(&label),
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// An unlabelled entry, such as a tag or a file name
+    pub fn item(text: impl Into<String>) -> Node {
+        Node {
+            value: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn value(mut self, value: impl Into<String>) -> Node {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Colors the value for a status
+    pub fn status(mut self, status: Status) -> Node {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn children(mut self, children: Vec<Node>) -> Node {
+        self.children = children;
+        self
+    }
+
+    fn render_value(&self, line: &str) -> String {
+        match self.status {
+            Some(status) => style::paint(status, line).to_string(),
+            None => line.to_string(),
+        }
+    }
+}
+
+/// Title shown in a thick box above a tree, and how to color each of its lines
+type TitleStyle<'a> = Box<dyn Fn(usize, &str) -> ColoredString + 'a>;
+
+/// A titled tree of entries. Sections are added as nodes without any knowledge of
+/// the drawing, which happens in one go when the tree is rendered.
+pub struct Tree<'a> {
+    title: Vec<String>,
+    title_style: TitleStyle<'a>,
+    nodes: Vec<Node>,
+}
+
+impl<'a> Tree<'a> {
+    /// A tree with a bold title
+    pub fn new(title: impl Into<String>) -> Tree<'a> {
+        Tree {
+            title: vec![title.into()],
+            title_style: Box::new(|_, line| line.bold()),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Adds a line below the title
+    pub fn subtitle(mut self, line: impl Into<String>) -> Tree<'a> {
+        self.title.push(line.into());
+        self
+    }
+
+    /// Colors the title lines, given each line's index and its possibly shortened text
+    pub fn title_style<F>(mut self, style: F) -> Tree<'a>
+    where
+        F: Fn(usize, &str) -> ColoredString + 'a,
+    {
+        self.title_style = Box::new(style);
+        self
+    }
+
+    pub fn push(&mut self, node: Node) {
+        self.nodes.push(node);
+    }
+
+    /// Adds a labelled section listing items below it, unless there are no items
+    pub fn section<I, T>(&mut self, label: &str, items: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let children: Vec<Node> = items.into_iter().map(Node::item).collect();
+        if !children.is_empty() {
+            self.push(Node::field(label).children(children));
+        }
+    }
+
+    /// Draws the tree for a terminal of the given width, or without wrapping
+    /// or shortening anything if there is no width
+    pub fn render(&self, width: Option<usize>) -> String {
+        let mut output = String::new();
+        render_title(&mut output, &self.title, &self.title_style, width);
+        render_nodes(&mut output, &self.nodes, "", width);
+        output
+    }
+
+    /// Prints the tree, fitted to the terminal
+    pub fn print(&self) {
+        print!("{}", self.render(terminal_width()));
+    }
+}
+
+/// Draws a thick box around the title, opening into the tree drawn below it
+fn render_title(
+    output: &mut String,
+    lines: &[String],
+    style: &TitleStyle<'_>,
+    width: Option<usize>,
+) {
+    let corner = boxy::Char::upper_left(boxy::Weight::Thick);
+    let side_h = boxy::Char::horizontal(boxy::Weight::Thick).to_string();
+    let side_v = boxy::Char::vertical(boxy::Weight::Thick);
+    let branch = boxy::Char::right_tee(boxy::Weight::Thick).down(boxy::Weight::Normal);
+
+    let max_width = width
+        .map(|width| width.saturating_sub(4).max(MIN_VALUE_WIDTH))
+        .unwrap_or(usize::MAX);
+    let lines: Vec<String> = lines.iter().map(|line| truncate(line, max_width)).collect();
+    let box_width = lines
+        .iter()
+        .map(|line| text_width(line))
+        .max()
+        .unwrap_or_default();
+
+    let _ = writeln!(
+        output,
+        "{}{}{}",
+        corner,
+        side_h.repeat(box_width + 2),
+        corner.rotate_cw(1)
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let _ = writeln!(
+            output,
+            "{} {}{} {}",
+            side_v,
+            style(i, line),
+            " ".repeat(box_width - text_width(line)),
+            side_v
+        );
+    }
+    let _ = writeln!(
+        output,
+        "{}{}{}",
+        branch,
+        side_h.repeat(box_width + 2),
+        corner.rotate_cw(2)
+    );
+}
+
+fn render_nodes(output: &mut String, nodes: &[Node], prefix: &str, width: Option<usize>) {
+    let branch_more = boxy::Char::right_tee(boxy::Weight::Normal);
+    let branch_done = boxy::Char::lower_left(boxy::Weight::Normal);
+    let branch_down = boxy::Char::down_tee(boxy::Weight::Normal);
+    let branch_v = boxy::Char::vertical(boxy::Weight::Normal).to_string();
+    let node_end = boxy::Char::left_half(boxy::Weight::Normal);
+
+    // Labels followed by a value are padded so the values line up
+    let label_width = nodes
+        .iter()
+        .filter(|node| node.value.is_some())
+        .map(|node| node.label_width)
+        .max()
+        .unwrap_or_default();
+    let prefix_width = text_width(prefix);
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let has_children = !node.children.is_empty();
+        let head = format!(
+            "{}{}{}{}",
+            prefix,
+            if last { branch_done } else { branch_more },
+            if has_children {
+                branch_down.to_string()
+            } else {
+                String::new()
+            },
+            node_end
+        );
+        let head_width = prefix_width + 2 + usize::from(has_children);
+        let child_prefix = format!("{}{}", prefix, if last { " " } else { &branch_v });
+
+        match &node.value {
+            None => {
+                let _ = writeln!(output, "{}{}", head, node.label);
+            }
+            Some(value) => {
+                let label = if node.label_width > 0 {
+                    format!(
+                        "{}{} ",
+                        node.label,
+                        " ".repeat(label_width - node.label_width)
+                    )
+                } else {
+                    String::new()
+                };
+                let value_column = head_width
+                    + if node.label_width > 0 {
+                        label_width + 1
+                    } else {
+                        0
+                    };
+                let lines = match width {
+                    Some(width) if width >= value_column + MIN_VALUE_WIDTH => {
+                        wrap(value, width - value_column)
+                    }
+                    _ => vec![value.clone()],
+                };
+                // Wrapped lines keep the tree's lines going on their left
+                let continuation = format!(
+                    "{}{}{}",
+                    child_prefix,
+                    if has_children { branch_v.as_str() } else { "" },
+                    " ".repeat(value_column - prefix_width - 1 - usize::from(has_children))
+                );
+                for (j, line) in lines.iter().enumerate() {
+                    if j == 0 {
+                        let _ = writeln!(output, "{}{}{}", head, label, node.render_value(line));
+                    } else {
+                        let _ = writeln!(output, "{}{}", continuation, node.render_value(line));
+                    }
+                }
+            }
+        }
+        render_nodes(output, &node.children, &child_prefix, width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tree as printed without colors
+    fn render(tree: &Tree, width: Option<usize>) -> String {
+        colored::control::set_override(false);
+        tree.render(width)
+    }
+
+    fn sample_tree() -> Tree<'static> {
+        let mut tree = Tree::new("Cute Hair").subtitle("ID 12");
+        tree.push(Node::field("Version:").value("1.0"));
+        tree.push(
+            Node::field("Source:").value("https://example.com/a/very/long/path/to/cute-hair"),
+        );
+        tree.section("Tags:", ["CAS", "Hair"]);
+        tree.section("Packs:", Vec::<String>::new());
+        tree.push(Node::field("Files:").children(vec![
+            Node::item("hair.package").children(vec![Node::item("nested")]),
+        ]));
+        tree
+    }
+
+    #[test]
+    fn truncates_with_an_ellipsis() {
+        assert_eq!(truncate("Cute Hair", 20), "Cute Hair");
+        assert_eq!(truncate("Cute Hair", 9), "Cute Hair");
+        assert_eq!(truncate("Cute Hair", 5), "Cute…");
+    }
+
+    #[test]
+    fn wraps_at_spaces_and_inside_long_words() {
+        assert_eq!(wrap("one two three", 7), ["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), [""]);
+    }
+
+    #[test]
+    fn draws_nested_entries_with_aligned_values() {
+        assert_eq!(
+            render(&sample_tree(), None),
+            "\
+┎━━━━━━━━━━━┓
+┃ Cute Hair ┃
+┃ ID 12     ┃
+┡━━━━━━━━━━━┛
+├╴Version: 1.0
+├╴Source:  https://example.com/a/very/long/path/to/cute-hair
+├┬╴Tags:
+│├╴CAS
+│└╴Hair
+└┬╴Files:
+ └┬╴hair.package
+  └╴nested
+"
+        );
+    }
+
+    #[test]
+    fn wraps_values_to_the_terminal_width() {
+        assert_eq!(
+            render(&sample_tree(), Some(40)),
+            "\
+┎━━━━━━━━━━━┓
+┃ Cute Hair ┃
+┃ ID 12     ┃
+┡━━━━━━━━━━━┛
+├╴Version: 1.0
+├╴Source:  https://example.com/a/very/lo
+│          ng/path/to/cute-hair
+├┬╴Tags:
+│├╴CAS
+│└╴Hair
+└┬╴Files:
+ └┬╴hair.package
+  └╴nested
+"
+        );
+    }
+
+    #[test]
+    fn shortens_titles_wider_than_the_terminal() {
+        let tree = Tree::new("A mod with a very long name indeed");
+        assert_eq!(
+            render(&tree, Some(20)),
+            "\
+┎━━━━━━━━━━━━━━━━━━┓
+┃ A mod with a ve… ┃
+┡━━━━━━━━━━━━━━━━━━┛
+"
+        );
+    }
+}
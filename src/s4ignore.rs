@@ -0,0 +1,102 @@
+//! `.s4ignore` files, which list files inside a mod's folder that scans skip,
+//! such as files a mod writes while the game runs. They use gitignore syntax.
+
+use std::path::Path;
+
+use tracing::{debug, warn};
+
+/// Name of the ignore file inside a mod's folder
+pub const IGNORE_FILE: &str = ".s4ignore";
+
+struct Rule {
+    pattern: glob::Pattern,
+    negated: bool,
+    directory_only: bool,
+}
+
+/// The rules of a mod's `.s4ignore`. A mod without one ignores nothing.
+#[derive(Default)]
+pub struct IgnoreFile {
+    rules: Vec<Rule>,
+}
+
+/// Turns one line of gitignore syntax into a rule
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (directory_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    // Patterns without a slash match at any depth, others from the mod's folder
+    let glob = match line.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if line.contains('/') => line.to_string(),
+        None => format!("**/{}", line),
+    };
+    match glob::Pattern::new(&glob) {
+        Ok(pattern) => Some(Rule {
+            pattern,
+            negated,
+            directory_only,
+        }),
+        Err(e) => {
+            warn!("Skipping invalid {} pattern {}: {}", IGNORE_FILE, line, e);
+            None
+        }
+    }
+}
+
+impl IgnoreFile {
+    /// Reads the `.s4ignore` in a mod's folder, if there is one
+    pub fn load(mod_dir: &Path) -> crate::Result<IgnoreFile> {
+        let path = mod_dir.join(IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(IgnoreFile::default());
+        }
+        debug!("Reading {}", path.display());
+        Ok(IgnoreFile {
+            rules: crate::fileio::read_to_string(&path)?
+                .lines()
+                .filter_map(parse_rule)
+                .collect(),
+        })
+    }
+
+    /// Whether a path matches the rules, the last matching rule winning
+    fn matches(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.directory_only)
+                    && rule.pattern.matches_path_with(relative_path, options)
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Whether a path relative to the mod's folder is ignored. As in git, files in
+    /// an ignored folder stay ignored.
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        relative_path
+            .ancestors()
+            .skip(1)
+            .filter(|path| !path.as_os_str().is_empty())
+            .any(|folder| self.matches(folder, true))
+            || self.matches(relative_path, is_dir)
+    }
+}
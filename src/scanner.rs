@@ -0,0 +1,521 @@
+//! Finding mod folders and the files in them. Scans, verification, installs, and
+//! reports all discover files here, so they agree on which files count: the
+//! extensions looked for, how deep folders are searched, and which paths the
+//! `ignore_globs` setting and `.s4ignore` files exclude. Entries that can't be
+//! tracked, such as broken links or names Windows can't open, are skipped with a
+//! warning instead of failing the whole scan.
+
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, warn};
+
+use crate::s4ignore::{IgnoreFile, IGNORE_FILE};
+
+/// Extensions of the files the game loads
+pub const MOD_EXTENSIONS: &[&str] = &["package", "ts4script"];
+
+/// Extensions of the files tracked directly in the Mods folder: loose game files and
+/// Resource.cfg, which tells the game how deep to look for mods
+const ROOT_EXTENSIONS: &[&str] = &["package", "ts4script", "cfg"];
+
+/// Folder script mods keep their settings and runtime data in
+const MOD_DATA_DIR: &str = "mod_data";
+
+/// Device names Windows reserves in every folder, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Why a file or folder can't be tracked by its name, if it can't. Paths are stored
+/// as text and must stay usable when the Mods folder is shared with Windows.
+fn name_problem(name: &OsStr) -> Option<&'static str> {
+    let Some(name) = name.to_str() else {
+        return Some("its name is not valid UTF-8");
+    };
+    if name.ends_with(' ') || name.ends_with('.') {
+        return Some("its name ends with a space or dot, which Windows drops");
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES.iter().any(|r| stem.eq_ignore_ascii_case(r)) {
+        return Some("its name is reserved on Windows");
+    }
+    None
+}
+
+/// Whether a file is one the game loads, ignoring the extension's case
+pub fn is_mod_file(path: &Path) -> bool {
+    has_extension(path, MOD_EXTENSIONS)
+}
+
+/// Whether a file is a game file or has one of the further extensions tracked for its mod
+pub fn is_tracked_file(path: &Path, extra_extensions: &[String]) -> bool {
+    is_mod_file(path) || has_extension(path, extra_extensions)
+}
+
+fn has_extension<S: AsRef<str>>(path: &Path, extensions: &[S]) -> bool {
+    path.extension().is_some_and(|e| {
+        extensions
+            .iter()
+            .any(|x| e.eq_ignore_ascii_case(x.as_ref()))
+    })
+}
+
+/// Which files [`find_files`] looks for
+#[derive(Clone, Debug)]
+pub struct ScanOptions {
+    /// Extensions of the files to find. Empty finds every file.
+    pub extensions: &'static [&'static str],
+    /// Further extensions to find, such as those the `tag_extensions` setting tracks for
+    /// a mod's tags
+    pub extra_extensions: Vec<String>,
+    /// How many folders deep to search, where 0 only searches the mod's folder itself
+    pub max_depth: Option<usize>,
+    /// Skip paths excluded by the `ignore_globs` setting or the mod's `.s4ignore`
+    pub respect_ignores: bool,
+    /// Skip script mods' `mod_data` folders
+    pub skip_mod_data: bool,
+}
+
+impl ScanOptions {
+    /// Files that scans hash and verify: game files directly in the mod's folder
+    pub fn tracked() -> ScanOptions {
+        ScanOptions {
+            extensions: MOD_EXTENSIONS,
+            extra_extensions: Vec::new(),
+            max_depth: Some(0),
+            respect_ignores: true,
+            skip_mod_data: true,
+        }
+    }
+
+    /// Files the Mods folder itself holds outside of any mod folder
+    pub fn root_files() -> ScanOptions {
+        ScanOptions {
+            extensions: ROOT_EXTENSIONS,
+            extra_extensions: Vec::new(),
+            max_depth: Some(0),
+            respect_ignores: true,
+            skip_mod_data: true,
+        }
+    }
+
+    /// Every file under the mod's folder that is not ignored
+    pub fn all_files() -> ScanOptions {
+        ScanOptions {
+            extensions: &[],
+            extra_extensions: Vec::new(),
+            max_depth: None,
+            respect_ignores: true,
+            skip_mod_data: true,
+        }
+    }
+
+    /// Every game file at any depth, as the game itself finds them
+    pub fn game_files() -> ScanOptions {
+        ScanOptions {
+            extensions: MOD_EXTENSIONS,
+            extra_extensions: Vec::new(),
+            max_depth: None,
+            respect_ignores: false,
+            skip_mod_data: false,
+        }
+    }
+
+    /// Also finds files with the given extensions
+    pub fn with_extensions(mut self, extensions: &[String]) -> ScanOptions {
+        self.extra_extensions.extend(extensions.iter().cloned());
+        self
+    }
+}
+
+/// A file found under a mod's folder
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FoundFile {
+    /// Path relative to the mod's folder
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Lists the files under `root`/`mod_path` matching the options, sorted by path
+pub fn find_files(
+    root: &Path,
+    mod_path: &Path,
+    options: &ScanOptions,
+) -> crate::Result<Vec<FoundFile>> {
+    let mod_dir = root.join(mod_path);
+    debug!("Finding files in {}", mod_dir.display());
+    let config = crate::config::get();
+    let ignore_file = if options.respect_ignores {
+        IgnoreFile::load(&mod_dir)?
+    } else {
+        IgnoreFile::default()
+    };
+    let is_ignored = |relative_path: &Path, is_dir: bool| {
+        options.respect_ignores
+            && (relative_path.as_os_str() == IGNORE_FILE
+                || config.is_ignored(&mod_path.join(relative_path))
+                || ignore_file.is_ignored(relative_path, is_dir))
+    };
+
+    let mut files = Vec::new();
+    // Folders already searched, so links back into the tree are not followed forever
+    let mut searched: HashSet<PathBuf> = mod_dir.canonicalize().into_iter().collect();
+    let mut to_read = vec![(PathBuf::new(), 0)];
+    while let Some((relative_dir, depth)) = to_read.pop() {
+        let entries = match crate::fileio::read_dir(mod_dir.join(&relative_dir)) {
+            Ok(entries) => entries,
+            Err(e) if depth > 0 => {
+                warn!(
+                    "Skipping unreadable folder {}: {}",
+                    relative_dir.display(),
+                    e
+                );
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Skipping unreadable entry in {}: {}", mod_dir.display(), e);
+                    continue;
+                }
+            };
+            let relative_path = relative_dir.join(entry.file_name());
+            // Follows symlinks and junctions, as the game does
+            let metadata = match std::fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    warn!("Skipping {}: {}", relative_path.display(), e);
+                    continue;
+                }
+            };
+            if is_ignored(&relative_path, metadata.is_dir()) {
+                debug!("Ignoring {}", relative_path.display());
+                continue;
+            }
+            if let Some(problem) = name_problem(&entry.file_name()) {
+                warn!("Skipping {}: {}", relative_path.display(), problem);
+                continue;
+            }
+            if metadata.is_dir() {
+                let descend = options.max_depth.is_none_or(|max| depth < max)
+                    && !(options.skip_mod_data && entry.file_name() == MOD_DATA_DIR);
+                if !descend {
+                    continue;
+                }
+                let already_searched = entry
+                    .path()
+                    .canonicalize()
+                    .is_ok_and(|resolved| !searched.insert(resolved));
+                if already_searched {
+                    warn!(
+                        "Skipping {}: it links to a folder that was already searched",
+                        relative_path.display()
+                    );
+                } else {
+                    to_read.push((relative_path, depth + 1));
+                }
+                continue;
+            }
+            if options.extensions.is_empty()
+                || has_extension(&relative_path, options.extensions)
+                || has_extension(&relative_path, &options.extra_extensions)
+            {
+                if metadata.len() == 0 && !options.extensions.is_empty() {
+                    warn!("Skipping {}: the file is empty", relative_path.display());
+                    continue;
+                }
+                files.push(FoundFile {
+                    path: relative_path,
+                    size: metadata.len(),
+                });
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Finds mod directories under a mod root. Top-level directories are mods,
+/// except for group folders (such as those made by `organize`) containing known mods,
+/// whose subdirectories are searched instead. Directories that resolve into one of
+/// `other_roots`, such as symlinks to another root, are skipped.
+pub fn find_mod_directories(
+    mod_dir: &Path,
+    known_dirs: &[PathBuf],
+    other_roots: &[PathBuf],
+) -> crate::Result<HashSet<PathBuf>> {
+    let group_dirs: HashSet<&Path> = known_dirs
+        .iter()
+        .flat_map(|dir| dir.ancestors().skip(1))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect();
+    let mut mod_dirs = HashSet::new();
+    let mut to_read = vec![PathBuf::new()];
+    while let Some(relative_dir) = to_read.pop() {
+        for entry in crate::fileio::read_dir(mod_dir.join(&relative_dir))? {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Skipping unreadable entry in {}: {}", mod_dir.display(), e);
+                    continue;
+                }
+            };
+            if !entry.path().is_dir() || entry.file_name() == MOD_DATA_DIR {
+                continue;
+            }
+            let relative_path = relative_dir.join(entry.file_name());
+            if let Some(problem) = name_problem(&entry.file_name()) {
+                warn!("Skipping {}: {}", relative_path.display(), problem);
+                continue;
+            }
+            if crate::config::get().is_ignored(&relative_path) {
+                debug!("Ignoring {}", relative_path.display());
+                continue;
+            }
+            if let Ok(resolved) = entry.path().canonicalize() {
+                if other_roots.iter().any(|root| resolved.starts_with(root)) {
+                    debug!("Skipping {}, it is another root", relative_path.display());
+                    continue;
+                }
+            }
+            if group_dirs.contains(relative_path.as_path()) {
+                debug!("Searching group folder {}", relative_path.display());
+                to_read.push(relative_path);
+            } else {
+                mod_dirs.insert(relative_path);
+            }
+        }
+    }
+    Ok(mod_dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes files with the given contents under a new temporary folder
+    fn fixture(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    /// Paths found under the fixture's `Mod` folder
+    fn found(root: &Path, options: &ScanOptions) -> Vec<String> {
+        find_files(root, Path::new("Mod"), options)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path.to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn tracked_finds_game_files_directly_in_the_folder() {
+        let dir = fixture(&[
+            ("Mod/hair.package", "data"),
+            ("Mod/script.TS4SCRIPT", "data"),
+            ("Mod/readme.txt", "data"),
+            ("Mod/Sub/nested.package", "data"),
+        ]);
+        assert_eq!(
+            found(dir.path(), &ScanOptions::tracked()),
+            ["hair.package", "script.TS4SCRIPT"]
+        );
+    }
+
+    #[test]
+    fn finds_further_extensions() {
+        let dir = fixture(&[("Mod/hair.package", "data"), ("Mod/preset.cfg", "data")]);
+        let options = ScanOptions::tracked().with_extensions(&["CFG".to_string()]);
+        assert_eq!(found(dir.path(), &options), ["hair.package", "preset.cfg"]);
+    }
+
+    #[test]
+    fn stops_at_the_depth_limit() {
+        let dir = fixture(&[
+            ("Mod/A/one.package", "data"),
+            ("Mod/A/B/two.package", "data"),
+        ]);
+        let options = ScanOptions {
+            max_depth: Some(1),
+            ..ScanOptions::game_files()
+        };
+        assert_eq!(found(dir.path(), &options), ["A/one.package"]);
+    }
+
+    #[test]
+    fn all_files_respects_ignores_and_skips_mod_data() {
+        let dir = fixture(&[
+            ("Mod/.s4ignore", "*.log\n"),
+            ("Mod/cache.log", "data"),
+            ("Mod/readme.txt", "data"),
+            ("Mod/Sub/nested.package", "data"),
+            ("Mod/mod_data/settings.cfg", "data"),
+        ]);
+        assert_eq!(
+            found(dir.path(), &ScanOptions::all_files()),
+            ["Sub/nested.package", "readme.txt"]
+        );
+    }
+
+    #[test]
+    fn game_files_ignore_nothing_the_game_loads() {
+        let dir = fixture(&[
+            ("Mod/.s4ignore", "*.package\n"),
+            ("Mod/hair.package", "data"),
+            ("Mod/mod_data/cache.package", "data"),
+        ]);
+        assert_eq!(
+            found(dir.path(), &ScanOptions::game_files()),
+            ["hair.package", "mod_data/cache.package"]
+        );
+    }
+
+    #[test]
+    fn root_files_include_resource_cfg() {
+        let dir = fixture(&[
+            ("Resource.cfg", "data"),
+            ("loose.package", "data"),
+            ("notes.txt", "data"),
+            ("Mod/hair.package", "data"),
+        ]);
+        let files: Vec<PathBuf> = find_files(dir.path(), Path::new(""), &ScanOptions::root_files())
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(
+            files,
+            [
+                PathBuf::from("Resource.cfg"),
+                PathBuf::from("loose.package")
+            ]
+        );
+    }
+
+    #[test]
+    fn searches_group_folders_of_known_mods() {
+        let dir = fixture(&[
+            ("Mods/Hair/hair.package", "data"),
+            ("Mods/Creator/Dress/dress.package", "data"),
+            ("Mods/Creator/Shoes/shoes.package", "data"),
+        ]);
+        let mod_dirs = find_mod_directories(
+            &dir.path().join("Mods"),
+            &[PathBuf::from("Creator/Dress")],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            mod_dirs,
+            HashSet::from([
+                PathBuf::from("Hair"),
+                PathBuf::from("Creator/Dress"),
+                PathBuf::from("Creator/Shoes"),
+            ])
+        );
+    }
+
+    #[test]
+    fn flags_names_windows_cannot_open() {
+        assert!(name_problem(OsStr::new("Hair ")).is_some());
+        assert!(name_problem(OsStr::new("Hair.")).is_some());
+        assert!(name_problem(OsStr::new("CON.package")).is_some());
+        assert!(name_problem(OsStr::new("lpt1")).is_some());
+        assert!(name_problem(OsStr::new("Console.package")).is_none());
+        assert!(name_problem(OsStr::new("Hair.package")).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn flags_names_that_are_not_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        assert!(name_problem(OsStr::from_bytes(b"\xff.package")).is_some());
+    }
+
+    #[test]
+    fn skips_empty_files_and_odd_names() {
+        let dir = fixture(&[
+            ("Mod/good.package", "data"),
+            ("Mod/empty.package", ""),
+            ("Mod/CON.package", "data"),
+            ("Mod/nul.ts4script", "data"),
+            ("Mod/Trailing /inner.package", "data"),
+            ("Mod/Dotted./inner.package", "data"),
+        ]);
+        assert_eq!(
+            found(dir.path(), &ScanOptions::game_files()),
+            ["good.package"]
+        );
+    }
+
+    #[test]
+    fn keeps_empty_files_when_finding_every_file() {
+        let dir = fixture(&[("Mod/empty.txt", "")]);
+        assert_eq!(found(dir.path(), &ScanOptions::all_files()), ["empty.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_broken_links_and_folder_loops() {
+        let dir = fixture(&[
+            ("Mod/good.package", "data"),
+            ("Mod/Sub/inner.package", "data"),
+        ]);
+        let mod_dir = dir.path().join("Mod");
+        std::os::unix::fs::symlink(
+            mod_dir.join("missing.package"),
+            mod_dir.join("dangling.package"),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(&mod_dir, mod_dir.join("Sub/loop")).unwrap();
+        assert_eq!(
+            found(dir.path(), &ScanOptions::game_files()),
+            ["Sub/inner.package", "good.package"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_links_to_folders_elsewhere() {
+        let dir = fixture(&[
+            ("Mod/good.package", "data"),
+            ("Elsewhere/linked.package", "data"),
+        ]);
+        std::os::unix::fs::symlink(dir.path().join("Elsewhere"), dir.path().join("Mod/Linked"))
+            .unwrap();
+        assert_eq!(
+            found(dir.path(), &ScanOptions::game_files()),
+            ["Linked/linked.package", "good.package"]
+        );
+    }
+
+    #[test]
+    fn fails_only_when_the_mod_folder_is_unreadable() {
+        let dir = fixture(&[]);
+        assert!(find_files(dir.path(), Path::new("Missing"), &ScanOptions::tracked()).is_err());
+    }
+
+    #[test]
+    fn skips_odd_mod_folders() {
+        let dir = fixture(&[
+            ("Mods/Hair/hair.package", "data"),
+            ("Mods/CON/con.package", "data"),
+            ("Mods/Trailing /inner.package", "data"),
+            ("Mods/mod_data/settings.cfg", "data"),
+            ("Mods/loose.package", "data"),
+        ]);
+        let mod_dirs = find_mod_directories(&dir.path().join("Mods"), &[], &[]).unwrap();
+        assert_eq!(mod_dirs, HashSet::from([PathBuf::from("Hair")]));
+    }
+}
@@ -0,0 +1,190 @@
+//! Signing exported files with a local ed25519 key, so that friends receiving a
+//! bundle can tell whether it changed in transit. Signatures are written next to
+//! the file as `<file>.sig`. The key, and the public keys of people whose files
+//! are trusted, live in the `keys` folder of the data directory.
+
+use std::io::{Error as IOError, ErrorKind as IOErrorKind};
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde_json::{json, Map, Value};
+use tracing::{debug, info};
+
+/// Identifies signature files
+const SIGNATURE_FORMAT: &str = "sims4modorganizer-signature";
+const SIGNATURE_VERSION: u64 = 1;
+
+const SIGNING_KEY_FILE: &str = "signing.key";
+const TRUSTED_KEYS_FILE: &str = "trusted.json";
+
+/// What the signature next to a file says about it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// There is no signature file
+    Unsigned,
+    /// Signed by a trusted key, with the name it was trusted under
+    Trusted(String),
+    /// Signed by an unknown key, with the key's fingerprint
+    Untrusted(String),
+    /// The file changed after it was signed, or the signature is damaged
+    Invalid,
+}
+
+fn keys_dir() -> crate::Result<PathBuf> {
+    let keys_dir = crate::util::get_data_dir()?.join("keys");
+    if !keys_dir.is_dir() {
+        info!("Creating key directory");
+        crate::fileio::create_dir_all(&keys_dir)?;
+    }
+    Ok(keys_dir)
+}
+
+/// Where a file's signature is written
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+fn invalid_data(message: String) -> Box<dyn std::error::Error> {
+    IOError::new(IOErrorKind::InvalidData, message).into()
+}
+
+fn decode_public_key(text: &str) -> crate::Result<VerifyingKey> {
+    let bytes: [u8; 32] = BASE64
+        .decode(text.trim())?
+        .try_into()
+        .map_err(|_| invalid_data(format!("{} is not an ed25519 public key", text)))?;
+    Ok(VerifyingKey::from_bytes(&bytes)?)
+}
+
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    BASE64.encode(key.to_bytes())
+}
+
+/// Short form of a public key for comparing by eye, e.g. `3F2A 91C0 7B1E 44D9`
+pub fn fingerprint(key: &VerifyingKey) -> String {
+    key.to_bytes()[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02X}{:02X}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// This install's signing key, created the first time it is needed
+fn signing_key() -> crate::Result<SigningKey> {
+    let path = keys_dir()?.join(SIGNING_KEY_FILE);
+    if path.is_file() {
+        let seed: [u8; 32] = BASE64
+            .decode(crate::fileio::read_to_string(&path)?.trim())?
+            .try_into()
+            .map_err(|_| invalid_data(format!("{} is not a signing key", path.display())))?;
+        return Ok(SigningKey::from_bytes(&seed));
+    }
+    info!("Creating signing key {}", path.display());
+    let mut seed = [0; 32];
+    getrandom::getrandom(&mut seed)?;
+    crate::fileio::write(&path, BASE64.encode(seed) + "\n")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// This install's public key, for friends to trust
+pub fn own_public_key() -> crate::Result<VerifyingKey> {
+    Ok(signing_key()?.verifying_key())
+}
+
+fn read_trusted_keys() -> crate::Result<Map<String, Value>> {
+    let path = keys_dir()?.join(TRUSTED_KEYS_FILE);
+    if !path.is_file() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_str(&crate::fileio::read_to_string(&path)?)? {
+        Value::Object(keys) => Ok(keys),
+        _ => Err(invalid_data(format!(
+            "{} does not contain a JSON object",
+            path.display()
+        ))),
+    }
+}
+
+/// Trusts files signed with a public key, remembering it under a name
+pub fn trust_key(name: &str, public_key: &str) -> crate::Result<VerifyingKey> {
+    let key = decode_public_key(public_key)?;
+    let mut keys = read_trusted_keys()?;
+    keys.insert(name.to_string(), Value::from(encode_public_key(&key)));
+    crate::fileio::write(
+        keys_dir()?.join(TRUSTED_KEYS_FILE),
+        serde_json::to_string_pretty(&Value::Object(keys))? + "\n",
+    )?;
+    Ok(key)
+}
+
+/// Signs a file, writing the signature next to it. Returns the key's fingerprint.
+pub fn sign_file(path: &Path) -> crate::Result<String> {
+    let key = signing_key()?;
+    let signature = key.sign(&crate::fileio::read(path)?);
+    let value = json!({
+        "format": SIGNATURE_FORMAT,
+        "version": SIGNATURE_VERSION,
+        "public_key": encode_public_key(&key.verifying_key()),
+        "signature": BASE64.encode(signature.to_bytes()),
+    });
+    let signature_path = signature_path(path);
+    debug!("Writing signature {}", signature_path.display());
+    crate::fileio::write(
+        &signature_path,
+        serde_json::to_string_pretty(&value)? + "\n",
+    )?;
+    Ok(fingerprint(&key.verifying_key()))
+}
+
+/// Checks a file against the signature next to it, if there is one
+pub fn check_file(path: &Path) -> crate::Result<SignatureStatus> {
+    let signature_path = signature_path(path);
+    if !signature_path.is_file() {
+        return Ok(SignatureStatus::Unsigned);
+    }
+    let value: Value = serde_json::from_str(&crate::fileio::read_to_string(&signature_path)?)?;
+    if value.get("format").and_then(Value::as_str) != Some(SIGNATURE_FORMAT)
+        || value
+            .get("version")
+            .and_then(Value::as_u64)
+            .is_none_or(|version| version > SIGNATURE_VERSION)
+    {
+        return Err(invalid_data(format!(
+            "{} is not a signature file",
+            signature_path.display()
+        )));
+    }
+    let (Some(public_key), Some(signature)) = (
+        value.get("public_key").and_then(Value::as_str),
+        value.get("signature").and_then(Value::as_str),
+    ) else {
+        return Ok(SignatureStatus::Invalid);
+    };
+    let Ok(key) = decode_public_key(public_key) else {
+        return Ok(SignatureStatus::Invalid);
+    };
+    let signature = match BASE64.decode(signature.trim()).map(<[u8; 64]>::try_from) {
+        Ok(Ok(bytes)) => Signature::from_bytes(&bytes),
+        _ => return Ok(SignatureStatus::Invalid),
+    };
+    if key.verify(&crate::fileio::read(path)?, &signature).is_err() {
+        return Ok(SignatureStatus::Invalid);
+    }
+    let encoded = encode_public_key(&key);
+    Ok(read_trusted_keys()?
+        .into_iter()
+        .find(|(_, trusted)| trusted.as_str() == Some(encoded.as_str()))
+        .map_or_else(
+            || SignatureStatus::Untrusted(fingerprint(&key)),
+            |(name, _)| SignatureStatus::Trusted(name),
+        ))
+}
@@ -0,0 +1,85 @@
+use colored::{Color, ColoredString, Colorize};
+
+/// How statuses are shown in output
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Theme {
+    /// Green for passing, red for failing
+    Default,
+    /// A palette that stays distinct with color vision deficiencies, plus ✔/✖/Δ symbols
+    Colorblind,
+    /// The default colors with emoji symbols
+    Emoji,
+}
+
+/// Kinds of status that output is styled for
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Passed,
+    Failed,
+    Changed,
+    New,
+    Missing,
+    Warning,
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::TrueColor { r, g, b }
+}
+
+impl Status {
+    fn color(&self, theme: Theme) -> Color {
+        match theme {
+            Theme::Default | Theme::Emoji => match self {
+                Status::Passed => Color::Green,
+                Status::Failed | Status::Missing => Color::Red,
+                Status::Changed | Status::Warning => Color::Yellow,
+                Status::New => Color::Blue,
+            },
+            // Okabe-Ito colors
+            Theme::Colorblind => match self {
+                Status::Passed => rgb(0, 114, 178),
+                Status::Failed => rgb(213, 94, 0),
+                Status::Changed => rgb(230, 159, 0),
+                Status::New => rgb(86, 180, 233),
+                Status::Missing => rgb(204, 121, 167),
+                Status::Warning => rgb(240, 228, 66),
+            },
+        }
+    }
+
+    fn symbol(&self, theme: Theme) -> Option<&'static str> {
+        match theme {
+            Theme::Default => None,
+            Theme::Colorblind => Some(match self {
+                Status::Passed => "✔",
+                Status::Failed => "✖",
+                Status::Changed => "Δ",
+                Status::New => "+",
+                Status::Missing => "−",
+                Status::Warning => "!",
+            }),
+            Theme::Emoji => Some(match self {
+                Status::Passed => "✅",
+                Status::Failed => "❌",
+                Status::Changed => "🔄",
+                Status::New => "🆕",
+                Status::Missing => "❓",
+                Status::Warning => "⚠️",
+            }),
+        }
+    }
+}
+
+/// Colors text for a status
+pub fn paint(status: Status, text: &str) -> ColoredString {
+    text.color(status.color(crate::config::get().theme()))
+}
+
+/// Colors text for a status, prefixed with the theme's symbol for it
+pub fn mark(status: Status, text: &str) -> ColoredString {
+    let theme = crate::config::get().theme();
+    match status.symbol(theme) {
+        Some(symbol) => format!("{} {}", symbol, text).color(status.color(theme)),
+        None => text.color(status.color(theme)),
+    }
+}
@@ -0,0 +1,360 @@
+//! Moves and copies of mod files that are checked after the fact. Each file's hash is
+//! taken before it is moved and compared once the operation is done, and a failed check
+//! undoes the whole operation. Every step is written to a journal in the data directory
+//! and flushed to disk before it is carried out, so an operation interrupted by a crash
+//! leaves a record of exactly which files were moved where.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum StepKind {
+    Move,
+    Copy,
+}
+
+impl StepKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            StepKind::Move => "move",
+            StepKind::Copy => "copy",
+        }
+    }
+}
+
+struct Step {
+    kind: StepKind,
+    from: PathBuf,
+    to: PathBuf,
+    /// Where a file overwritten by a copy was set aside until the operation finishes
+    replaced: Option<PathBuf>,
+    /// Hashes of the moved files, relative to `from`. A single file has an empty path.
+    hashes: Vec<(PathBuf, String)>,
+}
+
+impl Step {
+    fn to_json(&self, done: bool) -> Value {
+        json!({
+            "action": self.kind.as_str(),
+            "from": self.from.to_string_lossy(),
+            "to": self.to.to_string_lossy(),
+            "replaced": self.replaced.as_ref().map(|p| p.to_string_lossy().to_string()),
+            "done": done,
+        })
+    }
+}
+
+/// Folder holding the journals of file operations in progress
+fn journal_dir() -> crate::Result<PathBuf> {
+    let journal_dir = crate::util::get_data_dir()?.join("transfers");
+    if !journal_dir.is_dir() {
+        info!("Creating transfer journal directory");
+        crate::fileio::create_dir_all(&journal_dir)?;
+    }
+    Ok(journal_dir)
+}
+
+/// Hashes of the files at a path, relative to it
+fn hash_tree(path: &Path) -> crate::Result<Vec<(PathBuf, String)>> {
+    if !path.is_dir() {
+        return Ok(vec![(PathBuf::new(), crate::hasher::hash_file(path)?)]);
+    }
+    let mut hashes = Vec::new();
+    let mut to_read = vec![path.to_path_buf()];
+    while let Some(dir) = to_read.pop() {
+        for entry in crate::fileio::read_dir(&dir)? {
+            let entry_path = entry?.path();
+            if entry_path.is_dir() {
+                to_read.push(entry_path);
+                continue;
+            }
+            let relative = entry_path.strip_prefix(path)?.to_path_buf();
+            hashes.push((relative, crate::hasher::hash_file(&entry_path)?));
+        }
+    }
+    Ok(hashes)
+}
+
+/// File name of `path` with a suffix appended
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// A group of file moves and copies that succeed or are undone together
+pub struct Transfer {
+    operation: String,
+    started: String,
+    journal_path: PathBuf,
+    steps: Vec<Step>,
+}
+
+impl Transfer {
+    /// Starts an operation. Nothing is written until the first file is moved.
+    pub fn begin(operation: &str) -> crate::Result<Self> {
+        let started = chrono::Local::now();
+        let journal_path = journal_dir()?.join(format!(
+            "{}-{}-{}.json",
+            operation,
+            started.format("%Y%m%d-%H%M%S"),
+            std::process::id()
+        ));
+        Ok(Self {
+            operation: operation.to_string(),
+            started: started.to_rfc3339(),
+            journal_path,
+            steps: Vec::new(),
+        })
+    }
+
+    /// Writes the finished steps and the step about to be carried out to the journal,
+    /// or removes the journal when there is nothing to record
+    fn save(&self, pending: Option<&Step>) -> crate::Result<()> {
+        // Read-only mode refuses the file changes themselves with a clearer message
+        if crate::fileio::is_read_only() {
+            return Ok(());
+        }
+        if self.steps.is_empty() && pending.is_none() {
+            if self.journal_path.exists() {
+                crate::fileio::remove_file(&self.journal_path)?;
+            }
+            return Ok(());
+        }
+        let steps: Vec<Value> = self
+            .steps
+            .iter()
+            .map(|step| step.to_json(true))
+            .chain(pending.map(|step| step.to_json(false)))
+            .collect();
+        let journal = json!({
+            "operation": self.operation,
+            "started": self.started,
+            "steps": steps,
+        });
+        crate::fileio::write_synced(&self.journal_path, serde_json::to_string_pretty(&journal)?)?;
+        Ok(())
+    }
+
+    /// Moves a file or folder, creating the folders above its new location
+    pub fn move_path<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        from: P,
+        to: Q,
+    ) -> crate::Result<()> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        let step = Step {
+            kind: StepKind::Move,
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            replaced: None,
+            hashes: hash_tree(from)?,
+        };
+        debug!("Moving {} to {}", from.display(), to.display());
+        self.save(Some(&step))?;
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = to.parent() {
+                crate::fileio::create_dir_all(parent)?;
+            }
+            crate::fileio::rename(from, to)?;
+            if let Some(parent) = to.parent() {
+                crate::fileio::sync(parent)?;
+            }
+            Ok(())
+        })();
+        if let Err(e) = result {
+            self.save(None)?;
+            return Err(e.into());
+        }
+        self.steps.push(step);
+        self.save(None)
+    }
+
+    /// Copies a file. A file it overwrites is kept until the operation finishes.
+    pub fn copy_file<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        from: P,
+        to: Q,
+    ) -> crate::Result<()> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        let step = Step {
+            kind: StepKind::Copy,
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            replaced: to.exists().then(|| with_suffix(to, ".replaced")),
+            hashes: vec![(PathBuf::new(), crate::hasher::hash_file(from)?)],
+        };
+        debug!("Copying {} to {}", from.display(), to.display());
+        self.save(Some(&step))?;
+        let result = (|| -> std::io::Result<()> {
+            if let Some(replaced) = &step.replaced {
+                crate::fileio::rename(to, replaced)?;
+            }
+            if let Some(parent) = to.parent() {
+                crate::fileio::create_dir_all(parent)?;
+            }
+            crate::fileio::copy(from, to)?;
+            crate::fileio::sync(to)
+        })();
+        if let Err(e) = result {
+            if let Some(replaced) = step.replaced.as_ref().filter(|r| r.exists()) {
+                if to.exists() {
+                    crate::fileio::remove_file(to)?;
+                }
+                crate::fileio::rename(replaced, to)?;
+            }
+            self.save(None)?;
+            return Err(e.into());
+        }
+        self.steps.push(step);
+        self.save(None)
+    }
+
+    /// Files whose hash at their new location differs from before the operation
+    fn verify(&self) -> Vec<String> {
+        let mut failures = Vec::new();
+        for step in self.steps.iter() {
+            for (relative, hash) in step.hashes.iter() {
+                let path = if relative.as_os_str().is_empty() {
+                    step.to.clone()
+                } else {
+                    step.to.join(relative)
+                };
+                match crate::hasher::hash_file(&path) {
+                    Ok(new_hash) if new_hash == *hash => {}
+                    Ok(_) => failures.push(format!("{} changed while moving", path.display())),
+                    Err(e) => failures.push(format!("{} is unreadable: {}", path.display(), e)),
+                }
+            }
+        }
+        failures
+    }
+
+    /// Checks every moved file and finishes the operation, or undoes it when a file
+    /// did not arrive intact
+    pub fn finish(mut self) -> crate::Result<()> {
+        let failures = self.verify();
+        if !failures.is_empty() {
+            eprintln!("Verification failed after {}, undoing it:", self.operation);
+            for failure in failures.iter() {
+                eprintln!("  {}", failure);
+            }
+            self.rollback();
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+        }
+        for replaced in self.steps.iter().filter_map(|step| step.replaced.as_ref()) {
+            crate::fileio::remove_file(replaced)?;
+        }
+        debug!(
+            "Verified {} file operations of {}",
+            self.steps.len(),
+            self.operation
+        );
+        self.steps.clear();
+        self.save(None)
+    }
+
+    /// Undoes every step, newest first. Steps that cannot be undone are reported and
+    /// stay in the journal.
+    pub fn rollback(mut self) {
+        if self.steps.is_empty() {
+            return;
+        }
+        warn!(
+            "Reverting {} file operations of {}",
+            self.steps.len(),
+            self.operation
+        );
+        let mut failed = Vec::new();
+        while let Some(step) = self.steps.pop() {
+            let result = (|| -> std::io::Result<()> {
+                match step.kind {
+                    StepKind::Move => {
+                        if let Some(parent) = step.from.parent() {
+                            crate::fileio::create_dir_all(parent)?;
+                        }
+                        crate::fileio::rename(&step.to, &step.from)
+                    }
+                    StepKind::Copy => {
+                        crate::fileio::remove_file(&step.to)?;
+                        match &step.replaced {
+                            Some(replaced) => crate::fileio::rename(replaced, &step.to),
+                            None => Ok(()),
+                        }
+                    }
+                }
+            })();
+            if let Err(e) = result {
+                eprintln!(
+                    "Failed to undo the {} of {} to {}: {}",
+                    step.kind.as_str(),
+                    step.from.display(),
+                    step.to.display(),
+                    e
+                );
+                failed.push(step);
+            }
+        }
+        self.steps = failed;
+        if let Err(e) = self.save(None) {
+            eprintln!("Failed to update {}: {}", self.journal_path.display(), e);
+        } else if !self.steps.is_empty() {
+            eprintln!(
+                "The steps left to undo are listed in {}",
+                self.journal_path.display()
+            );
+        }
+    }
+}
+
+/// Runs the file operations in `operation`, undoing them when one fails and checking
+/// them when all succeed
+pub fn run<F>(name: &str, operation: F) -> crate::Result<()>
+where
+    F: FnOnce(&mut Transfer) -> crate::Result<()>,
+{
+    let mut transfer = Transfer::begin(name)?;
+    if let Err(e) = operation(&mut transfer) {
+        transfer.rollback();
+        return Err(e);
+    }
+    transfer.finish()
+}
+
+/// Journals of operations that were interrupted, with a description of each step
+pub fn interrupted() -> crate::Result<Vec<(PathBuf, Vec<String>)>> {
+    let mut journals = Vec::new();
+    for entry in crate::fileio::read_dir(journal_dir()?)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        let journal: Value = serde_json::from_str(&crate::fileio::read_to_string(&path)?)?;
+        let steps = journal["steps"]
+            .as_array()
+            .map(|steps| {
+                steps
+                    .iter()
+                    .map(|step| {
+                        format!(
+                            "{} {} -> {}{}",
+                            step["action"].as_str().unwrap_or_default(),
+                            step["from"].as_str().unwrap_or_default(),
+                            step["to"].as_str().unwrap_or_default(),
+                            if step["done"].as_bool().unwrap_or_default() {
+                                ""
+                            } else {
+                                " (may be incomplete)"
+                            }
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        journals.push((path, steps));
+    }
+    journals.sort();
+    Ok(journals)
+}
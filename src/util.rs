@@ -1,15 +1,29 @@
-use crate::{migrator::Migrator, Result};
+use crate::{
+    config::{HashAlgorithm, JournalMode, Synchronous},
+    migrator::Migrator,
+    Result,
+};
+use colored::Colorize;
+use sea_orm::ConnectionTrait;
 use sea_orm_migration::prelude::*;
-use std::io::{Error as IOError, ErrorKind as IOErrorKind};
-use tracing::{debug, error, info};
-use tracing_unwrap::OptionExt;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use std::{
+    io::{Error as IOError, ErrorKind as IOErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tracing::{debug, error, info, warn};
+use tracing_unwrap::{OptionExt, ResultExt};
 
 pub fn get_sims_mod_dir() -> Result<std::path::PathBuf> {
-    let sims_mod_dir = dirs::document_dir()
-        .expect_or_log("Failed to get Documents directory")
-        .join("Electronic Arts")
-        .join("The Sims 4")
-        .join("Mods");
+    let sims_mod_dir = crate::config::get().mods_dir().unwrap_or_else(|| {
+        dirs::document_dir()
+            .expect_or_log("Failed to get Documents directory")
+            .join("Electronic Arts")
+            .join("The Sims 4")
+            .join("Mods")
+    });
     if !sims_mod_dir.is_dir() {
         use std::io::*;
         error!("Could not locate Sims 4 mods folder. ");
@@ -19,53 +33,520 @@ pub fn get_sims_mod_dir() -> Result<std::path::PathBuf> {
     }
 }
 
-fn get_db_path() -> Result<std::path::PathBuf> {
-    let data_dir = dirs::data_dir()
-        .expect_or_log("Failed to get user data directory")
-        .join("com.familiar.sims4modsorganizer");
+/// The game's user folder holding Mods, saves, caches, and logs.
+/// Assumes the Mods folder is in its default place inside it.
+pub fn get_game_dir() -> Option<PathBuf> {
+    get_sims_mod_dir()
+        .ok()
+        .and_then(|mods_dir| mods_dir.parent().map(Path::to_path_buf))
+}
+
+/// Process names used by The Sims 4 on Windows and macOS
+const GAME_PROCESS_NAMES: &[&str] = &["ts4_x64.exe", "ts4_dx9_x64.exe", "ts4.exe", "the sims 4"];
+
+pub fn is_game_running() -> bool {
+    let mut system = sysinfo::System::new();
+    system.refresh_processes();
+    let running = system
+        .processes()
+        .values()
+        .any(|process| GAME_PROCESS_NAMES.contains(&process.name().to_lowercase().as_str()));
+    debug!("Game running: {}", running);
+    running
+}
+
+/// Refuses to continue while the game is running, unless forced
+pub fn ensure_game_not_running(force: bool) -> Result<()> {
+    if !is_game_running() {
+        return Ok(());
+    }
+    if force {
+        warn!("Continuing while the game is running");
+        eprintln!("The Sims 4 is running; continuing anyway.");
+        return Ok(());
+    }
+    eprintln!("The Sims 4 is running. Close the game first, or pass --force to continue anyway.");
+    Err(IOError::new(IOErrorKind::ResourceBusy, "The Sims 4 is running").into())
+}
+
+/// Moves a file or folder to the system trash, or deletes it outright when `permanent` is set
+pub fn remove_path(path: &Path, permanent: bool) -> Result<()> {
+    crate::fileio::ensure_writable(
+        if permanent {
+            "delete"
+        } else {
+            "move to the trash"
+        },
+        path,
+    )?;
+    if !permanent {
+        info!("Moving {} to the trash", path.display());
+        return trash::delete(path).map_err(|e| e.into());
+    }
+    info!("Permanently deleting {}", path.display());
+    if path.is_dir() {
+        crate::fileio::remove_dir_all(path)?;
+    } else {
+        crate::fileio::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Overrides the data directory holding the database and downloaded files
+const DATA_DIR_ENV: &str = "S4MO_DATA_DIR";
+/// Overrides the database location entirely, e.g. `sqlite:///mnt/usb/mods.sqlite`
+const DATABASE_URL_ENV: &str = "S4MO_DATABASE_URL";
+
+/// Name of the per-user data and config folders
+pub const DATA_DIR_NAME: &str = "sims4modorganizer";
+/// Name the per-user folders had before they were named after the binary
+pub const LEGACY_DATA_DIR_NAME: &str = "com.familiar.sims4modsorganizer";
+/// The mod database in the data folder
+pub const DATABASE_FILE: &str = "mods.sqlite";
+
+/// File next to the executable that turns on portable mode
+const PORTABLE_MARKER: &str = "sims4modorganizer.portable";
+/// Folder next to the executable holding data in portable mode
+const PORTABLE_DATA_DIR: &str = "sims4modorganizer-data";
+
+static PORTABLE_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Turns on portable mode for this run, as with `--portable`
+pub fn enable_portable_mode() {
+    PORTABLE_FLAG.store(true, Ordering::Relaxed);
+}
+
+/// The executable's folder, if running in portable mode
+pub fn get_portable_dir() -> Option<std::path::PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    if PORTABLE_FLAG.load(Ordering::Relaxed) || exe_dir.join(PORTABLE_MARKER).is_file() {
+        Some(exe_dir)
+    } else {
+        None
+    }
+}
+
+/// Where the database and config live, unless overridden by the environment
+pub fn get_default_data_dir() -> std::path::PathBuf {
+    match get_portable_dir() {
+        Some(portable_dir) => portable_dir.join(PORTABLE_DATA_DIR),
+        None => crate::legacy_dirs::resolve(
+            &dirs::data_dir().expect_or_log("Failed to get user data directory"),
+            DATABASE_FILE,
+        ),
+    }
+}
+
+/// Whether the environment sets the data directory
+pub fn has_data_dir_override() -> bool {
+    std::env::var_os(DATA_DIR_ENV).is_some()
+}
+
+pub fn get_data_dir() -> Result<std::path::PathBuf> {
+    let data_dir = match std::env::var_os(DATA_DIR_ENV) {
+        Some(data_dir) => std::path::PathBuf::from(data_dir),
+        None => get_default_data_dir(),
+    };
     if !data_dir.is_dir() {
         info!("Creating data directory");
-        std::fs::create_dir(&data_dir)?;
+        crate::fileio::create_dir_all(&data_dir)?;
+    }
+    Ok(data_dir)
+}
+
+/// SQLite open mode. Read-only mode opens databases read-only, so SQLite itself
+/// refuses any write.
+fn get_open_mode(create: bool) -> &'static str {
+    if crate::fileio::is_read_only() {
+        "ro"
+    } else if create {
+        "rwc"
+    } else {
+        "rw"
     }
-    Ok(data_dir.join("mods.sqlite"))
+}
+
+/// Database URL from the environment, with the SQLite open mode added if it is missing
+fn get_database_url_override(create: bool) -> Option<String> {
+    let url = std::env::var(DATABASE_URL_ENV).ok()?;
+    if !url.starts_with("sqlite:") {
+        return Some(url);
+    }
+    if url.contains("mode=") {
+        if !crate::fileio::is_read_only() {
+            return Some(url);
+        }
+        let mode = regex::Regex::new(r"mode=\w+").expect_or_log("Invalid open mode pattern");
+        return Some(mode.replace(&url, "mode=ro").into_owned());
+    }
+    let separator = if url.contains('?') { '&' } else { '?' };
+    Some(format!(
+        "{}{}mode={}",
+        url,
+        separator,
+        get_open_mode(create)
+    ))
+}
+
+fn get_db_path() -> Result<std::path::PathBuf> {
+    Ok(get_data_dir()?.join(DATABASE_FILE))
+}
+
+/// SQLite connection options with the configured pragmas. The journal mode is
+/// stored in the database file, so it is left alone in read-only mode.
+fn get_connect_options(url: &str) -> Result<SqliteConnectOptions> {
+    let config = crate::config::get();
+    let mut options = SqliteConnectOptions::from_str(url)?
+        .busy_timeout(config.sqlite_busy_timeout())
+        .synchronous(match config.sqlite_synchronous() {
+            Synchronous::Off => SqliteSynchronous::Off,
+            Synchronous::Normal => SqliteSynchronous::Normal,
+            Synchronous::Full => SqliteSynchronous::Full,
+            Synchronous::Extra => SqliteSynchronous::Extra,
+        });
+    if !crate::fileio::is_read_only() {
+        options = options.journal_mode(match config.sqlite_journal_mode() {
+            JournalMode::Wal => SqliteJournalMode::Wal,
+            JournalMode::Delete => SqliteJournalMode::Delete,
+            JournalMode::Truncate => SqliteJournalMode::Truncate,
+        });
+    }
+    Ok(options)
 }
 
 async fn open_db_internal(create: bool) -> Result<sea_orm::DbConn> {
     debug!("Opening database with create = {}", create);
+    let url = match get_database_url_override(create) {
+        Some(url) => {
+            debug!("Database URL from {}", DATABASE_URL_ENV);
+            url
+        }
+        None => {
+            let database_path = get_db_path()?;
+            debug!("Database path: {}", database_path.display());
+            format!(
+                "sqlite://{}?mode={}",
+                database_path
+                    .to_str()
+                    .expect_or_log("Failed to parse path as UTF-8"),
+                get_open_mode(create)
+            )
+        }
+    };
+    let pool = SqlitePoolOptions::new()
+        .connect_with(get_connect_options(&url)?)
+        .await?;
+    Ok(sea_orm::SqlxSqliteConnector::from_sqlx_sqlite_pool(pool))
+}
+
+/// Set once this run has opened the default database file
+static DATABASE_OPENED: AtomicBool = AtomicBool::new(false);
+/// Set when the database failed its integrity check and was kept anyway
+static DATABASE_SUSPECT: AtomicBool = AtomicBool::new(false);
+
+/// Whether a database file or backup matches its stored checksum
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    Valid,
+    Mismatch,
+    Missing,
+}
+
+/// Sidecar file holding the checksum of a database file
+fn get_checksum_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".checksum");
+    path.with_file_name(file_name)
+}
+
+/// Checksum of a database file. Always xxh3, regardless of the configured
+/// mod file hash, so stored checksums stay comparable.
+pub fn checksum_file(path: &Path) -> Result<String> {
+    Ok(HashAlgorithm::Xxh3.hash(&crate::fileio::read(path)?))
+}
+
+pub fn write_checksum(path: &Path) -> Result<()> {
+    crate::fileio::write(get_checksum_path(path), checksum_file(path)?)?;
+    Ok(())
+}
+
+pub fn verify_checksum(path: &Path) -> Result<ChecksumStatus> {
+    let Ok(expected) = std::fs::read_to_string(get_checksum_path(path)) else {
+        return Ok(ChecksumStatus::Missing);
+    };
+    if expected.trim() == checksum_file(path)? {
+        Ok(ChecksumStatus::Valid)
+    } else {
+        Ok(ChecksumStatus::Mismatch)
+    }
+}
+
+pub fn get_backup_dir() -> Result<PathBuf> {
+    let backup_dir = get_data_dir()?.join("backups");
+    if !backup_dir.is_dir() {
+        info!("Creating backup directory");
+        crate::fileio::create_dir_all(&backup_dir)?;
+    }
+    Ok(backup_dir)
+}
+
+/// Folder holding mods set aside with `stale --review`
+pub fn get_archive_dir() -> Result<PathBuf> {
+    let archive_dir = get_data_dir()?.join("archive");
+    if !archive_dir.is_dir() {
+        info!("Creating archive directory");
+        crate::fileio::create_dir_all(&archive_dir)?;
+    }
+    Ok(archive_dir)
+}
+
+/// Database backups, oldest first
+pub fn list_backups() -> Result<Vec<PathBuf>> {
+    let mut backups: Vec<PathBuf> = crate::fileio::read_dir(get_backup_dir()?)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sqlite"))
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Backups only cover the default database file, not one given by URL
+pub fn ensure_default_database() -> Result<()> {
+    if std::env::var_os(DATABASE_URL_ENV).is_some() {
+        eprintln!(
+            "Backups are not available while {} is set.",
+            DATABASE_URL_ENV
+        );
+        return Err(IOError::from(IOErrorKind::Unsupported).into());
+    }
+    Ok(())
+}
+
+/// Writes a consistent copy of the database to the backup folder
+pub async fn create_backup(db: &sea_orm::DbConn) -> Result<PathBuf> {
+    ensure_default_database()?;
+    let backup_path = get_backup_dir()?.join(format!(
+        "mods-{}.sqlite",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    if backup_path.exists() {
+        return Err(IOError::new(IOErrorKind::AlreadyExists, "Backup file exists!").into());
+    }
+    info!("Writing backup to {}", backup_path.display());
+    let quoted_path = backup_path.to_string_lossy().replace('\'', "''");
+    db.execute_unprepared(&format!("VACUUM INTO '{}'", quoted_path))
+        .await?;
+    write_checksum(&backup_path)?;
+    Ok(backup_path)
+}
+
+/// Replaces the database file with a backup after verifying its checksum.
+/// No connection to the database may be open.
+pub fn restore_backup(backup_path: &Path) -> Result<()> {
+    ensure_default_database()?;
+    if verify_checksum(backup_path)? != ChecksumStatus::Valid {
+        eprintln!(
+            "Backup {} does not match its checksum and may be corrupted.",
+            backup_path.display()
+        );
+        return Err(IOError::from(IOErrorKind::InvalidData).into());
+    }
+    let database_path = get_db_path()?;
+    info!("Restoring {} from backup", database_path.display());
+    // A log left by WAL mode would be replayed onto the restored file
+    for suffix in ["-wal", "-shm"] {
+        let mut file_name = database_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(suffix);
+        let path = database_path.with_file_name(file_name);
+        if path.exists() {
+            debug!("Removing {}", path.display());
+            crate::fileio::remove_file(&path)?;
+        }
+    }
+    crate::transfer::run("restore", |transfer| {
+        transfer.copy_file(backup_path, &database_path)
+    })?;
+    write_checksum(&database_path)?;
+    DATABASE_SUSPECT.store(false, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Runs SQLite's own consistency check
+pub async fn database_is_intact(db: &sea_orm::DbConn) -> bool {
+    let result = db
+        .query_one(sea_orm::Statement::from_string(
+            sea_orm::DatabaseBackend::Sqlite,
+            "PRAGMA integrity_check",
+        ))
+        .await;
+    match result {
+        Ok(Some(row)) => row
+            .try_get::<String>("", "integrity_check")
+            .is_ok_and(|status| status == "ok"),
+        _ => false,
+    }
+}
+
+/// Compares the database file against the checksum stored when the last run
+/// finished, offering to restore the newest valid backup on a mismatch
+async fn check_database_file() -> Result<()> {
     let database_path = get_db_path()?;
-    debug!("Database path: {}", database_path.display());
-    let dbpath = format!(
-        "sqlite://{}?mode={}",
-        database_path
-            .to_str()
-            .expect_or_log("Failed to parse path as UTF-8"),
-        if create { "rwc" } else { "rw" }
-    );
-    let connect_options = sea_orm::ConnectOptions::new(dbpath);
-    sea_orm::SqlxSqliteConnector::connect(connect_options)
-        .await
-        .map_err(|e| e.into())
+    if verify_checksum(&database_path)? != ChecksumStatus::Mismatch {
+        return Ok(());
+    }
+    warn!("Database does not match the checksum from the last run");
+    let db = open_db_internal(false).await?;
+    let intact = database_is_intact(&db).await;
+    db.close().await?;
+    if intact {
+        eprintln!(
+            "{}",
+            "The database changed since sims4modorganizer last closed it. It passes SQLite's integrity check, but may have been modified by another program or an interrupted run.".yellow()
+        );
+    } else {
+        eprintln!(
+            "{}",
+            "The database is corrupted: it changed since sims4modorganizer last closed it and fails SQLite's integrity check.".red()
+        );
+    }
+    let backup = list_backups()?
+        .into_iter()
+        .rev()
+        .find(|backup| matches!(verify_checksum(backup), Ok(ChecksumStatus::Valid)));
+    let restore = match &backup {
+        Some(backup) => inquire::Confirm::new(&format!(
+            "Restore the database from backup {}?",
+            backup.file_name().unwrap_or_default().to_string_lossy()
+        ))
+        .with_default(!intact)
+        .prompt()
+        .unwrap_or(false),
+        None => {
+            eprintln!("No valid backup is available to restore.");
+            false
+        }
+    };
+    match backup {
+        Some(backup) if restore => {
+            restore_backup(&backup)?;
+            println!("Restored the database from backup.");
+        }
+        _ if intact => write_checksum(&database_path)?,
+        _ => DATABASE_SUSPECT.store(true, Ordering::Relaxed),
+    }
+    Ok(())
+}
+
+/// Moves changes from the write-ahead log into the database file, so the file
+/// alone holds the whole database
+async fn checkpoint_database() -> Result<()> {
+    let db = open_db_internal(false).await?;
+    db.execute_unprepared("PRAGMA wal_checkpoint(TRUNCATE)")
+        .await?;
+    db.close().await?;
+    Ok(())
+}
+
+/// Stores the database checksum at the end of a run, so corruption that
+/// happens while the tool is not running is noticed on the next start
+pub async fn record_database_checksum() {
+    if !DATABASE_OPENED.load(Ordering::Relaxed)
+        || DATABASE_SUSPECT.load(Ordering::Relaxed)
+        || crate::fileio::is_read_only()
+    {
+        return;
+    }
+    let mut result = Ok(());
+    if crate::config::get().sqlite_journal_mode() == JournalMode::Wal {
+        result = checkpoint_database().await;
+    }
+    let result = result
+        .and_then(|_| get_db_path())
+        .and_then(|database_path| write_checksum(&database_path));
+    if let Err(e) = result {
+        warn!("Could not store database checksum: {}", e);
+    }
 }
 
 pub async fn open_database() -> Result<sea_orm::DbConn> {
     debug!("Opening existing mod database");
-    open_db_internal(false).await
+    if std::env::var_os(DATABASE_URL_ENV).is_none()
+        && !crate::fileio::is_read_only()
+        && !DATABASE_OPENED.swap(true, Ordering::Relaxed)
+    {
+        check_database_file().await?;
+    }
+    let db = open_db_internal(false).await?;
+    if crate::fileio::is_read_only() {
+        // Checking the applied migrations directly, as the migrator would create its table
+        use sea_orm::{EntityTrait, PaginatorTrait};
+        let applied = sea_orm_migration::seaql_migrations::Entity::find()
+            .count(&db)
+            .await?;
+        if applied < Migrator::migrations().len() as u64 {
+            return Err(crate::fileio::read_only_error(
+                "upgrade the mod database to this version".to_string(),
+            )
+            .into());
+        }
+        return Ok(db);
+    }
+    debug!("Applying pending migrations");
+    Migrator::up(&db, None).await?;
+    Ok(db)
 }
 
-pub async fn init_database(force: bool) -> Result<()> {
+pub async fn init_database(force: bool, permanent: bool) -> Result<()> {
     debug!("Initializing database");
+    if crate::fileio::is_read_only() {
+        return Err(
+            crate::fileio::read_only_error("initialize the mod database".to_string()).into(),
+        );
+    }
+    if std::env::var_os(DATABASE_URL_ENV).is_some() {
+        let db = open_db_internal(true).await?;
+        if !force && SchemaManager::new(&db).has_table("sims_mod").await? {
+            return Err(IOError::new(IOErrorKind::AlreadyExists, "Database exists!").into());
+        }
+        info!("Initializing...");
+        Migrator::refresh(&db).await?;
+        return Ok(());
+    }
     let database_path = get_db_path()?;
+    let mut trashed = false;
     if database_path.is_file() {
         if force {
-            info!("Deleting existing database...");
-            tokio::fs::remove_file(database_path).await?;
+            info!("Removing existing database...");
+            remove_path(&database_path, permanent)?;
+            trashed = !permanent;
         } else {
             return Err(IOError::new(IOErrorKind::AlreadyExists, "Database file exists!").into());
         }
     }
     info!("Opening database...");
+    DATABASE_OPENED.store(true, Ordering::Relaxed);
     let db = open_db_internal(true).await?;
     info!("Initializing...");
     Migrator::refresh(&db).await?;
+    if trashed {
+        crate::commands::record_trashed(&db, &database_path, None).await?;
+    }
     Ok(())
 }
+
+/// What a command would have changed, if it failed because read-only mode refused
+/// a change, either in the file layer or by SQLite refusing to write
+pub fn read_only_refusal(error: &(dyn std::error::Error + 'static)) -> Option<String> {
+    if let Some(refused) = error
+        .downcast_ref::<IOError>()
+        .and_then(|e| e.get_ref())
+        .and_then(|e| e.downcast_ref::<crate::fileio::ReadOnlyError>())
+    {
+        return Some(refused.to_string());
+    }
+    if crate::fileio::is_read_only() && error.to_string().contains("readonly database") {
+        return Some("would change the mod database".to_string());
+    }
+    None
+}